@@ -0,0 +1,80 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use indexmap::IndexMap;
+use istanbul_oxide::{Branch, BranchType, FileCoverage, Function, Range};
+
+/// Creates a coverage with given number of statements / functions / branches,
+/// hits are offset by `seed` to make each shard distinct.
+fn create_coverage(size: u32, seed: u32) -> FileCoverage {
+    let mut coverage = FileCoverage::from_file_path("/path/to/file.js".to_string(), true);
+
+    for idx in 0..size {
+        let range = Range::new(idx + 1, 0, idx + 1, 80);
+        coverage.statement_map.insert(idx, range);
+        coverage.s.insert(idx, (idx + seed) % 3);
+
+        coverage.fn_map.insert(
+            idx,
+            Function {
+                name: format!("fn_{}", idx),
+                decl: range,
+                loc: range,
+                line: idx + 1,
+                export_name: None,
+                metadata: None,
+            },
+        );
+        coverage.f.insert(idx, (idx + seed) % 2);
+
+        coverage.branch_map.insert(
+            idx,
+            Branch::from_line(
+                BranchType::If,
+                idx + 1,
+                vec![
+                    Range::new(idx + 1, 0, idx + 1, 40),
+                    Range::new(idx + 1, 41, idx + 1, 80),
+                ],
+            ),
+        );
+        coverage.b.insert(idx, vec![seed % 2, (seed + 1) % 2]);
+    }
+
+    coverage.b_t = Some(IndexMap::from_iter(
+        coverage.b.iter().map(|(k, v)| (*k, v.clone())),
+    ));
+
+    coverage
+}
+
+fn bench_merge(c: &mut Criterion) {
+    let shards: Vec<FileCoverage> = (0..30).map(|seed| create_coverage(1000, seed)).collect();
+
+    c.bench_function("merge 30 shards", |b| {
+        b.iter_batched(
+            || (create_coverage(1000, 0), shards.clone()),
+            |(mut base, shards)| {
+                for shard in shards.iter() {
+                    base.merge(black_box(shard));
+                }
+                base
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    c.bench_function("merge_owned 30 shards", |b| {
+        b.iter_batched(
+            || (create_coverage(1000, 0), shards.clone()),
+            |(mut base, shards)| {
+                for shard in shards.into_iter() {
+                    base.merge_owned(black_box(shard));
+                }
+                base
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(benches, bench_merge);
+criterion_main!(benches);
@@ -0,0 +1,138 @@
+//! Canonicalization of the file paths, so coverage of the same file gets the same path across
+//! the runs regardless of how the file is reached (symlinks, drive letter case, pnpm's virtual store).
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+/// Built-in canonicalizations of the file paths.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PathCanonicalizer {
+    /// Resolve symlinks to the real path of the file. Path is kept as-is if it can't be resolved
+    /// (i.e the file doesn't exist, or no access to the file system as the plugin).
+    Realpath,
+    /// Lowercase the drive letter of the windows paths (`C:\src\a.js` to `c:\src\a.js`).
+    LowercaseDrive,
+    /// Unwrap the path into pnpm's virtual store into the path of the package linked from
+    /// `node_modules` (`node_modules/.pnpm/foo@1.0.0/node_modules/foo/a.js` to `node_modules/foo/a.js`).
+    PnpmVirtualStore,
+}
+
+impl PathCanonicalizer {
+    pub fn canonicalize(&self, path: &str) -> String {
+        match self {
+            PathCanonicalizer::Realpath => realpath(path),
+            PathCanonicalizer::LowercaseDrive => lowercase_drive(path),
+            PathCanonicalizer::PnpmVirtualStore => unwrap_pnpm_virtual_store(path),
+        }
+    }
+}
+
+fn realpath(path: &str) -> String {
+    match fs::canonicalize(path) {
+        // Windows returns the verbatim path (`\\?\C:\src`), which is never spelled that way otherwise.
+        Ok(resolved) => {
+            let resolved = resolved.to_string_lossy();
+            resolved
+                .strip_prefix(r"\\?\")
+                .unwrap_or(&resolved)
+                .to_string()
+        }
+        Err(_) => path.to_string(),
+    }
+}
+
+fn lowercase_drive(path: &str) -> String {
+    match path.as_bytes() {
+        [drive, b':', rest @ ..]
+            if drive.is_ascii_uppercase() && matches!(rest.first(), None | Some(b'\\' | b'/')) =>
+        {
+            format!("{}{}", drive.to_ascii_lowercase() as char, &path[1..])
+        }
+        _ => path.to_string(),
+    }
+}
+
+fn unwrap_pnpm_virtual_store(path: &str) -> String {
+    for separator in ['/', '\\'] {
+        let store = format!("node_modules{0}.pnpm{0}", separator);
+        let linked = format!("{0}node_modules{0}", separator);
+
+        // `node_modules/.pnpm/<name>@<version>/node_modules/<name>/...`
+        if let Some(start) = path.find(&store) {
+            let rest = &path[start + store.len()..];
+            if let Some(end) = rest.find(&linked) {
+                return format!(
+                    "{}{}",
+                    &path[..start + "node_modules".len() + 1],
+                    &rest[end + linked.len()..]
+                );
+            }
+        }
+    }
+
+    path.to_string()
+}
+
+/// Canonicalize the path with each of the canonicalizers in the given order. Node resolves symlinks
+/// by default, `Realpath` followed by `PnpmVirtualStore` gives the same path for the file of
+/// a package however it's imported.
+pub fn canonicalize_path(path: &str, canonicalizers: &[PathCanonicalizer]) -> String {
+    canonicalizers
+        .iter()
+        .fold(path.to_string(), |path, canonicalizer| {
+            canonicalizer.canonicalize(&path)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{canonicalize_path, PathCanonicalizer};
+
+    #[test]
+    fn should_canonicalize_paths() {
+        let lowercase_drive = |path| canonicalize_path(path, &[PathCanonicalizer::LowercaseDrive]);
+        assert_eq!(lowercase_drive(r"C:\src\a.js"), r"c:\src\a.js");
+        assert_eq!(lowercase_drive("D:/src/a.js"), "d:/src/a.js");
+        assert_eq!(lowercase_drive(r"c:\src\a.js"), r"c:\src\a.js");
+        assert_eq!(lowercase_drive("/src/C:/a.js"), "/src/C:/a.js");
+        assert_eq!(lowercase_drive("Ab:/a.js"), "Ab:/a.js");
+
+        let unwrap_pnpm = |path| canonicalize_path(path, &[PathCanonicalizer::PnpmVirtualStore]);
+        assert_eq!(
+            unwrap_pnpm("/repo/node_modules/.pnpm/lodash@4.17.21/node_modules/lodash/index.js"),
+            "/repo/node_modules/lodash/index.js"
+        );
+        assert_eq!(
+            unwrap_pnpm(
+                "/repo/node_modules/.pnpm/@babel+core@7.0.0_foo@1.0.0/node_modules/@babel/core/lib/index.js"
+            ),
+            "/repo/node_modules/@babel/core/lib/index.js"
+        );
+        assert_eq!(
+            unwrap_pnpm(r"C:\repo\node_modules\.pnpm\a@1.0.0\node_modules\a\index.js"),
+            r"C:\repo\node_modules\a\index.js"
+        );
+        assert_eq!(
+            unwrap_pnpm("/repo/node_modules/lodash/index.js"),
+            "/repo/node_modules/lodash/index.js"
+        );
+
+        // Missing files are kept as-is
+        assert_eq!(
+            canonicalize_path("/does/not/exist.js", &[PathCanonicalizer::Realpath]),
+            "/does/not/exist.js"
+        );
+
+        assert_eq!(
+            canonicalize_path(
+                r"C:\repo\node_modules\.pnpm\a@1.0.0\node_modules\a\index.js",
+                &[
+                    PathCanonicalizer::PnpmVirtualStore,
+                    PathCanonicalizer::LowercaseDrive
+                ]
+            ),
+            r"c:\repo\node_modules\a\index.js"
+        );
+    }
+}
@@ -1,6 +1,25 @@
+use std::path::Path;
+
 use indexmap::IndexMap;
 
-use crate::{CoverageSummary, FileCoverage};
+use crate::{
+    is_glob_match, CoverageSummary, FileCoverage, MergeConflict, MergeKey, UnusedExport,
+    ValidationError,
+};
+
+/// Merges coverages for the same file path in given order.
+fn merge_file_coverages(coverages: Vec<FileCoverage>, key: MergeKey) -> FileCoverage {
+    let mut coverages = coverages.into_iter();
+    let mut ret = coverages
+        .next()
+        .expect("Should have at least one coverage to merge");
+
+    for coverage in coverages {
+        ret.merge_owned_by(coverage, key);
+    }
+
+    ret
+}
 
 /// a map of `FileCoverage` objects keyed by file paths
 #[derive(Clone, PartialEq, Default)]
@@ -35,8 +54,92 @@ impl CoverageMap {
 
     /// Merges a second coverage map into this one
     pub fn merge(&mut self, map: &CoverageMap) {
-        for (_, coverage) in map.inner.iter() {
-            self.add_coverage_for_file(coverage);
+        self.merge_by(map, MergeKey::Location);
+    }
+
+    /// Variant of `merge` lines up the entries of the coverages by given key,
+    /// see `FileCoverage::merge_by`.
+    pub fn merge_by(&mut self, map: &CoverageMap, key: MergeKey) {
+        for (path, coverage) in map.inner.iter() {
+            if let Some(value) = self.inner.get_mut(path) {
+                value.merge_by(coverage, key);
+            } else {
+                self.inner.insert(path.clone(), coverage.clone());
+            }
+        }
+    }
+
+    /// Strict variant of `merge`, returns the first conflict without merging anything
+    /// if coverages of any path are structurally different.
+    pub fn try_merge(&mut self, map: &CoverageMap) -> Result<(), MergeConflict> {
+        for (path, coverage) in map.inner.iter() {
+            if let Some(conflict) = self
+                .inner
+                .get(path)
+                .and_then(|value| value.find_merge_conflict(coverage))
+            {
+                return Err(conflict);
+            }
+        }
+
+        self.merge(map);
+        Ok(())
+    }
+
+    /// Validates the coverage of each file, returns errors of all of the files.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let errors: Vec<ValidationError> = self
+            .inner
+            .values()
+            .filter_map(|coverage| coverage.validate().err())
+            .flatten()
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Merges given coverage maps into a single map.
+    ///
+    /// Coverages for the same file path are merged in the order of given maps, and
+    /// files are ordered by its first appearance. Each file path is merged independently,
+    /// in parallel if `parallel` feature is enabled. Result is identical regardless of the feature.
+    pub fn merge_all(maps: impl IntoIterator<Item = CoverageMap>) -> CoverageMap {
+        CoverageMap::merge_all_by(maps, MergeKey::Location)
+    }
+
+    /// Variant of `merge_all` lines up the entries of the coverages by given key.
+    pub fn merge_all_by(maps: impl IntoIterator<Item = CoverageMap>, key: MergeKey) -> CoverageMap {
+        let mut grouped: IndexMap<String, Vec<FileCoverage>> = Default::default();
+
+        for map in maps {
+            for (path, coverage) in map.inner {
+                grouped.entry(path).or_default().push(coverage);
+            }
+        }
+
+        let grouped: Vec<(String, Vec<FileCoverage>)> = grouped.into_iter().collect();
+
+        #[cfg(feature = "parallel")]
+        let merged: Vec<(String, FileCoverage)> = {
+            use rayon::prelude::*;
+            grouped
+                .into_par_iter()
+                .map(|(path, coverages)| (path, merge_file_coverages(coverages, key)))
+                .collect()
+        };
+
+        #[cfg(not(feature = "parallel"))]
+        let merged: Vec<(String, FileCoverage)> = grouped
+            .into_iter()
+            .map(|(path, coverages)| (path, merge_file_coverages(coverages, key)))
+            .collect();
+
+        CoverageMap {
+            inner: merged.into_iter().collect(),
         }
     }
 
@@ -54,8 +157,106 @@ impl CoverageMap {
         self.inner = filtered;
     }
 
-    pub fn to_json() {
-        unimplemented!()
+    /// Removes the files of the paths not matching `globs`. Globs prefixed with `!` exclude the files,
+    /// the others include: a file is kept if it matches any of the include globs (or there are none)
+    /// and none of the exclude globs, same as nyc's `include` / `exclude`. Globs match the same as the
+    /// instrumenter's, relative globs match the end of the path at the path separator.
+    pub fn filter_paths<S: AsRef<str>>(&mut self, globs: &[S]) {
+        let (exclude, include): (Vec<&str>, Vec<&str>) = globs
+            .iter()
+            .map(|glob| glob.as_ref())
+            .partition(|glob| glob.starts_with('!'));
+
+        self.filter(|coverage| {
+            (include.is_empty()
+                || include
+                    .iter()
+                    .any(|glob| is_glob_match(glob, &coverage.path)))
+                && !exclude
+                    .iter()
+                    .any(|glob| is_glob_match(&glob[1..], &coverage.path))
+        });
+    }
+
+    /// Replaces the path of each file with the one `canonicalize` returns (i.e `canonicalize_path`
+    /// with the built-in canonicalizers), merging the coverages of the same canonical path,
+    /// like the same file reached through a symlink in the other run.
+    pub fn canonicalize_paths(&mut self, canonicalize: impl Fn(&str) -> String) {
+        let mut ret = CoverageMap::new();
+
+        for (_, mut coverage) in self.inner.drain(..) {
+            coverage.path = canonicalize(&coverage.path);
+            ret.add_coverage_for_file(&coverage);
+        }
+
+        *self = ret;
+    }
+
+    /// Remaps the coverage same as `remap_with_input_source_maps`, keeping the files matching `globs`
+    /// same as `filter_paths`. With `exclude_after_remap` (nyc's default), globs match the paths of the
+    /// original sources. Otherwise globs match the generated files before remapping, and all of the
+    /// sources of the generated files kept are kept.
+    pub fn remap_and_filter_paths<S: AsRef<str>>(
+        &self,
+        globs: &[S],
+        exclude_after_remap: bool,
+    ) -> CoverageMap {
+        if exclude_after_remap {
+            let mut ret = self.remap_with_input_source_maps();
+            ret.filter_paths(globs);
+            ret
+        } else {
+            let mut filtered = self.clone();
+            filtered.filter_paths(globs);
+            filtered.remap_with_input_source_maps()
+        }
+    }
+
+    /// Maps the coverage of the files carrying `inputSourceMap` to its original sources, same as
+    /// istanbul-lib-source-maps' `transformCoverage`: coverage of a generated file is split into each
+    /// original source, entries can't be mapped are dropped. Files without `inputSourceMap` are kept as-is,
+    /// coverages of the same path are merged.
+    pub fn remap_with_input_source_maps(&self) -> CoverageMap {
+        let mut ret = CoverageMap::new();
+
+        for coverage in self.inner.values() {
+            match &coverage.input_source_map {
+                Some(source_map) => {
+                    for mapped in crate::remap::remap_file_coverage(coverage, source_map).values() {
+                        ret.add_coverage_for_file(mapped);
+                    }
+                }
+                None => ret.add_coverage_for_file(coverage),
+            }
+        }
+
+        ret
+    }
+
+    /// Reads the coverage map in the shape of istanbul's json (coverage-final.json).
+    pub fn from_json(value: &str) -> serde_json::Result<CoverageMap> {
+        Ok(CoverageMap {
+            inner: serde_json::from_str(value)?,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.inner).expect("Should able to serialize coverage map")
+    }
+
+    /// Reads the coverage map from the binary format `to_binary` writes.
+    #[cfg(feature = "binary")]
+    pub fn from_binary(value: &[u8]) -> serde_cbor::Result<CoverageMap> {
+        Ok(CoverageMap {
+            inner: serde_cbor::from_slice(value)?,
+        })
+    }
+
+    /// Serialize the coverage map into CBOR, the same structure as istanbul's json
+    /// with integer keys for the entries. Smaller and faster to parse than json.
+    #[cfg(feature = "binary")]
+    pub fn to_binary(&self) -> Vec<u8> {
+        serde_cbor::to_vec(&self.inner).expect("Should able to serialize coverage map")
     }
 
     pub fn get_files(&self) -> Vec<&String> {
@@ -83,11 +284,52 @@ impl CoverageMap {
 
         ret
     }
+
+    /// Summaries of the coverages grouped by the key given classifier returns for each file
+    /// (i.e language of the file), ordered by its first appearance.
+    pub fn get_coverage_summary_by(
+        &self,
+        classifier: impl Fn(&FileCoverage) -> String,
+    ) -> IndexMap<String, CoverageSummary> {
+        let mut ret: IndexMap<String, CoverageSummary> = Default::default();
+
+        for coverage in self.inner.values() {
+            ret.entry(classifier(coverage))
+                .or_default()
+                .merge(&coverage.to_summary());
+        }
+
+        ret
+    }
+
+    /// Exported functions never hit in any of the files, keyed by the path of the file
+    /// (i.e of the coverage merged from the whole test suite). Files without any are not included.
+    pub fn find_unused_exports(&self) -> IndexMap<String, Vec<UnusedExport>> {
+        self.inner
+            .iter()
+            .map(|(path, coverage)| (path.clone(), coverage.find_unused_exports()))
+            .filter(|(_, unused)| !unused.is_empty())
+            .collect()
+    }
+
+    /// Summaries of the coverages grouped by the extension of the file path without
+    /// the leading dot (`ts`, `tsx`, `js`). Files without extension are grouped under an empty key.
+    pub fn get_coverage_summary_by_extension(&self) -> IndexMap<String, CoverageSummary> {
+        self.get_coverage_summary_by(|coverage| {
+            Path::new(&coverage.path)
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{CoverageMap, FileCoverage};
+    use crate::{
+        canonicalize_path, CoverageMap, CoveragePercentage, FileCoverage, Function, MergeConflict,
+        PathCanonicalizer, Range,
+    };
 
     #[test]
     fn should_able_to_merge_another_coverage_map() {
@@ -111,6 +353,78 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_not_merge_any_file_on_conflict_in_strict_mode() {
+        let create_coverage = |path: &str, line: u32| {
+            let mut coverage = FileCoverage::from_file_path(path.to_string(), false);
+            coverage
+                .statement_map
+                .insert(0, Range::new(line, 1, line, 10));
+            coverage.s.insert(0, 1);
+            coverage
+        };
+
+        let mut base = CoverageMap::from_iter(vec![
+            &create_coverage("foo.js", 1),
+            &create_coverage("bar.js", 1),
+        ]);
+        let second = CoverageMap::from_iter(vec![
+            &create_coverage("foo.js", 1),
+            &create_coverage("bar.js", 2),
+        ]);
+
+        assert_eq!(
+            base.try_merge(&second),
+            Err(MergeConflict::Statement {
+                path: "bar.js".to_string(),
+                id: 0
+            })
+        );
+        assert_eq!(
+            base.get_coverage_for_file("foo.js").unwrap().s.get(&0),
+            Some(&1)
+        );
+    }
+
+    #[test]
+    fn should_merge_all_coverage_maps_in_order() {
+        let create_coverage = |path: &str, hits: u32| {
+            let mut coverage = FileCoverage::from_file_path(path.to_string(), false);
+            coverage.statement_map.insert(0, Range::new(1, 1, 1, 10));
+            coverage.s.insert(0, hits);
+            coverage
+        };
+
+        let maps = vec![
+            CoverageMap::from_iter(vec![&create_coverage("foo.js", 1)]),
+            CoverageMap::from_iter(vec![
+                &create_coverage("bar.js", 2),
+                &create_coverage("foo.js", 3),
+            ]),
+            CoverageMap::from_iter(vec![&create_coverage("baz.js", 0)]),
+        ];
+
+        let mut expected = CoverageMap::new();
+        for map in maps.iter() {
+            expected.merge(map);
+        }
+
+        let merged = CoverageMap::merge_all(maps);
+        assert_eq!(
+            merged.get_files(),
+            vec![
+                &"foo.js".to_string(),
+                &"bar.js".to_string(),
+                &"baz.js".to_string()
+            ]
+        );
+        assert_eq!(
+            merged.get_coverage_for_file("foo.js").unwrap().s.get(&0),
+            Some(&4)
+        );
+        assert!(merged == expected);
+    }
+
     #[test]
     fn should_able_to_return_file_coverage() {
         let base = CoverageMap::from_iter(vec![
@@ -140,6 +454,88 @@ mod tests {
         assert_eq!(base.get_files(), vec![&"foo.js".to_string()]);
     }
 
+    #[test]
+    fn should_merge_coverages_of_canonical_paths() {
+        let create_coverage = |path: &str, hit: u32| {
+            let mut coverage = FileCoverage::from_file_path(path.to_string(), false);
+            coverage.statement_map.insert(0, Range::new(1, 0, 1, 10));
+            coverage.s.insert(0, hit);
+            coverage
+        };
+
+        let mut map = CoverageMap::from_iter(vec![
+            &create_coverage(
+                r"C:\repo\node_modules\.pnpm\a@1.0.0\node_modules\a\index.js",
+                1,
+            ),
+            &create_coverage(r"c:\repo\src\b.js", 1),
+            &create_coverage(r"c:\repo\node_modules\a\index.js", 2),
+        ]);
+
+        map.canonicalize_paths(|path| {
+            canonicalize_path(
+                path,
+                &[
+                    PathCanonicalizer::LowercaseDrive,
+                    PathCanonicalizer::PnpmVirtualStore,
+                ],
+            )
+        });
+
+        assert_eq!(
+            map.get_files(),
+            vec![r"c:\repo\node_modules\a\index.js", r"c:\repo\src\b.js"]
+        );
+        let coverage = map
+            .get_coverage_for_file(r"c:\repo\node_modules\a\index.js")
+            .unwrap();
+        assert_eq!(coverage.path, r"c:\repo\node_modules\a\index.js");
+        assert_eq!(coverage.s.get(&0), Some(&3));
+    }
+
+    #[test]
+    fn should_filter_paths_by_globs() {
+        let files = || {
+            CoverageMap::from_iter(vec![
+                &FileCoverage::from_file_path("/repo/src/a.ts".to_string(), false),
+                &FileCoverage::from_file_path("/repo/src/a.test.ts".to_string(), false),
+                &FileCoverage::from_file_path("/repo/src/generated/b.ts".to_string(), false),
+                &FileCoverage::from_file_path("/repo/lib/c.js".to_string(), false),
+            ])
+        };
+
+        let mut map = files();
+        map.filter_paths(&["src/**"]);
+        assert_eq!(
+            map.get_files(),
+            vec![
+                "/repo/src/a.ts",
+                "/repo/src/a.test.ts",
+                "/repo/src/generated/b.ts"
+            ]
+        );
+
+        let mut map = files();
+        map.filter_paths(&["src/**", "!**/*.test.ts", "!**/generated/**"]);
+        assert_eq!(map.get_files(), vec!["/repo/src/a.ts"]);
+
+        // Exclude only
+        let mut map = files();
+        map.filter_paths(&["!*.test.ts"]);
+        assert_eq!(
+            map.get_files(),
+            vec![
+                "/repo/src/a.ts",
+                "/repo/src/generated/b.ts",
+                "/repo/lib/c.js"
+            ]
+        );
+
+        let mut map = files();
+        map.filter_paths::<&str>(&[]);
+        assert_eq!(map.get_files().len(), 4);
+    }
+
     #[test]
     fn should_return_coverage_summary_for_all_files() {
         let mut base = CoverageMap::from_iter(vec![
@@ -153,4 +549,173 @@ mod tests {
         let summary = base.get_coverage_summary();
         assert_eq!(summary.statements.total, 0);
     }
+
+    #[test]
+    fn should_summarize_empty_files_same_as_istanbul() {
+        // Nothing merged yet
+        let summary = CoverageMap::new().get_coverage_summary();
+        assert_eq!(summary.statements.pct, CoveragePercentage::Unknown);
+
+        // Files without any statements (empty, directive or type only) are fully covered.
+        let map = CoverageMap::from_iter(vec![&FileCoverage::from_file_path(
+            "types.ts".to_string(),
+            false,
+        )]);
+        let summary = map.get_coverage_summary();
+        for totals in [
+            summary.lines,
+            summary.statements,
+            summary.functions,
+            summary.branches,
+        ] {
+            assert_eq!(totals.total, 0);
+            assert_eq!(totals.pct, CoveragePercentage::Value(100.0));
+        }
+    }
+
+    #[test]
+    fn should_return_coverage_summary_grouped_by_extension() {
+        let create_coverage = |path: &str, hits: &[u32]| {
+            let mut coverage = FileCoverage::from_file_path(path.to_string(), false);
+            for (idx, hit) in hits.iter().enumerate() {
+                let line = idx as u32 + 1;
+                coverage
+                    .statement_map
+                    .insert(idx as u32, Range::new(line, 1, line, 10));
+                coverage.s.insert(idx as u32, *hit);
+            }
+            coverage
+        };
+
+        let base = CoverageMap::from_iter(vec![
+            &create_coverage("src/foo.ts", &[1, 0]),
+            &create_coverage("src/bar.js", &[1]),
+            &create_coverage("src/baz.tsx", &[0]),
+            &create_coverage("src/qux.ts", &[1, 1]),
+            &create_coverage("bin/cli", &[0]),
+        ]);
+
+        let summaries = base.get_coverage_summary_by_extension();
+        assert_eq!(
+            summaries.keys().collect::<Vec<_>>(),
+            vec!["ts", "js", "tsx", ""]
+        );
+
+        let ts = summaries.get("ts").unwrap();
+        assert_eq!((ts.statements.total, ts.statements.covered), (4, 3));
+        let js = summaries.get("js").unwrap();
+        assert_eq!((js.statements.total, js.statements.covered), (1, 1));
+        let tsx = summaries.get("tsx").unwrap();
+        assert_eq!((tsx.statements.total, tsx.statements.covered), (1, 0));
+        let none = summaries.get("").unwrap();
+        assert_eq!((none.statements.total, none.statements.covered), (1, 0));
+    }
+
+    #[test]
+    fn should_return_coverage_summary_grouped_by_classifier() {
+        let base = CoverageMap::from_iter(vec![
+            &FileCoverage::from_file_path("foo.ts".to_string(), false),
+            &FileCoverage::from_file_path("foo.tsx".to_string(), false),
+            &FileCoverage::from_file_path("bar.js".to_string(), false),
+        ]);
+
+        let summaries = base.get_coverage_summary_by(|coverage| {
+            if coverage.path.ends_with(".js") {
+                "js".to_string()
+            } else {
+                "ts".to_string()
+            }
+        });
+
+        assert_eq!(summaries.keys().collect::<Vec<_>>(), vec!["ts", "js"]);
+    }
+
+    #[test]
+    fn should_find_unused_exports() {
+        let create_coverage = |path: &str, functions: &[(&str, Option<&str>, u32)]| {
+            let mut coverage = FileCoverage::from_file_path(path.to_string(), false);
+            for (idx, (name, export_name, hits)) in functions.iter().enumerate() {
+                let line = idx as u32 + 1;
+                coverage.fn_map.insert(
+                    idx as u32,
+                    Function {
+                        name: name.to_string(),
+                        decl: Range::new(line, 9, line, 12),
+                        loc: Range::new(line, 15, line, 20),
+                        line,
+                        export_name: export_name.map(|name| name.to_string()),
+                        metadata: None,
+                    },
+                );
+                coverage.f.insert(idx as u32, *hits);
+            }
+            coverage
+        };
+
+        let first = CoverageMap::from_iter(vec![
+            &create_coverage(
+                "a.js",
+                &[
+                    ("foo", Some("foo"), 0),
+                    ("bar", None, 0),
+                    ("baz", Some("default"), 0),
+                ],
+            ),
+            &create_coverage("b.js", &[("qux", Some("qux"), 1)]),
+        ]);
+        // Hits of the other runs are counted.
+        let second = CoverageMap::from_iter(vec![&create_coverage(
+            "a.js",
+            &[
+                ("foo", Some("foo"), 1),
+                ("bar", None, 0),
+                ("baz", Some("default"), 0),
+            ],
+        )]);
+        let merged = CoverageMap::merge_all(vec![first, second]);
+
+        let unused = merged.find_unused_exports();
+        assert_eq!(unused.keys().collect::<Vec<_>>(), vec!["a.js"]);
+        assert_eq!(
+            unused["a.js"]
+                .iter()
+                .map(|unused| (unused.export_name.as_str(), unused.id, unused.name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("default", 2, "baz")]
+        );
+        assert_eq!(unused["a.js"][0].decl, Range::new(3, 9, 3, 12));
+    }
+
+    fn coverage_map_for_serialization() -> CoverageMap {
+        let mut coverage = FileCoverage::from_file_path("foo.js".to_string(), true);
+        coverage.statement_map.insert(0, Range::new(1, 0, 1, 10));
+        coverage.s.insert(0, 3);
+        coverage.extras.insert(
+            "contentHash".to_string(),
+            serde_json::Value::String("abc".to_string()),
+        );
+
+        CoverageMap::from_iter(vec![
+            &coverage,
+            &FileCoverage::from_file_path("bar.js".to_string(), false),
+        ])
+    }
+
+    #[test]
+    fn should_roundtrip_json() {
+        let coverage_map = coverage_map_for_serialization();
+        let json = coverage_map.to_json();
+
+        assert!(CoverageMap::from_json(&json).unwrap() == coverage_map);
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn should_roundtrip_binary() {
+        let coverage_map = coverage_map_for_serialization();
+        let binary = coverage_map.to_binary();
+
+        assert!(binary.len() < coverage_map.to_json().len());
+        assert!(CoverageMap::from_binary(&binary).unwrap() == coverage_map);
+    }
 }
@@ -0,0 +1,144 @@
+use std::fmt;
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{CoverageMap, FileCoverage, ValidationError};
+
+/// Errors of the invalid coverage of the session, with the instrumenter produced it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvalidSession {
+    pub tool: String,
+    pub version: String,
+    pub errors: Vec<ValidationError>,
+}
+
+impl fmt::Display for InvalidSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid coverage from {}@{}", self.tool, self.version)?;
+        for error in &self.errors {
+            write!(f, "\n  {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for InvalidSession {}
+
+/// Coverage of the files with the metadata of the session produced it, to track which
+/// instrumenter produced the coverage and when (i.e debugging mixed babel / swc pipelines).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageSession {
+    /// Unix timestamp in milliseconds when the coverage is collected.
+    pub timestamp: u64,
+    /// Name of the instrumenter, i.e `swc-coverage-instrument`, `babel-plugin-istanbul`.
+    pub tool: String,
+    /// Version of the instrumenter.
+    pub version: String,
+    /// Coverage of the files keyed by the path, same as `coverage-final.json`.
+    pub files: IndexMap<String, FileCoverage>,
+}
+
+impl CoverageSession {
+    pub fn new(
+        timestamp: u64,
+        tool: &str,
+        version: &str,
+        coverage_map: &CoverageMap,
+    ) -> CoverageSession {
+        CoverageSession {
+            timestamp,
+            tool: tool.to_string(),
+            version: version.to_string(),
+            files: coverage_map
+                .get_files()
+                .into_iter()
+                .map(|path| {
+                    let coverage = coverage_map
+                        .get_coverage_for_file(path)
+                        .expect("Coverage for the file should exist");
+                    (path.clone(), coverage.clone())
+                })
+                .collect(),
+        }
+    }
+
+    pub fn from_json(value: &str) -> serde_json::Result<CoverageSession> {
+        serde_json::from_str(value)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Should able to serialize coverage session")
+    }
+
+    /// Validates the coverage of the files, to find out which instrumenter produced
+    /// the invalid coverage before merging the sessions.
+    pub fn validate(&self) -> Result<(), InvalidSession> {
+        let errors: Vec<ValidationError> = self
+            .files
+            .values()
+            .filter_map(|coverage| coverage.validate().err())
+            .flatten()
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidSession {
+                tool: self.tool.clone(),
+                version: self.version.clone(),
+                errors,
+            })
+        }
+    }
+
+    /// Coverage map of the files, to merge with the coverage from the other sessions.
+    pub fn to_coverage_map(&self) -> CoverageMap {
+        CoverageMap::from_iter(self.files.values())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CoverageMap, FileCoverage, Range};
+
+    use super::CoverageSession;
+
+    #[test]
+    fn should_roundtrip_session() {
+        let mut coverage = FileCoverage::from_file_path("foo.js".to_string(), false);
+        coverage.statement_map.insert(0, Range::new(1, 0, 1, 10));
+        coverage.s.insert(0, 1);
+        let coverage_map = CoverageMap::from_iter(vec![&coverage]);
+
+        let session = CoverageSession::new(
+            1660000000000,
+            "swc-coverage-instrument",
+            "0.0.7",
+            &coverage_map,
+        );
+        let json = session.to_json();
+        assert!(json.starts_with(
+            r#"{"timestamp":1660000000000,"tool":"swc-coverage-instrument","version":"0.0.7","files":{"foo.js":"#
+        ));
+
+        let deserialized = CoverageSession::from_json(&json).expect("Should parse session");
+        assert_eq!(deserialized, session);
+        assert!(deserialized.to_coverage_map() == coverage_map);
+    }
+
+    #[test]
+    fn should_validate_with_producer() {
+        let mut coverage = FileCoverage::from_file_path("foo.js".to_string(), false);
+        coverage.s.insert(0, 1);
+        let coverage_map = CoverageMap::from_iter(vec![&coverage]);
+
+        let session = CoverageSession::new(0, "babel-plugin-istanbul", "6.1.1", &coverage_map);
+        let error = session.validate().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid coverage from babel-plugin-istanbul@6.1.1\n  Statement 0 has hits without the entry for foo.js"
+        );
+    }
+}
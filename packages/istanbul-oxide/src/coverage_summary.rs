@@ -1,5 +1,11 @@
+use std::fmt;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
 use crate::percent;
 
+/// Percentage of the covered items. Summaries not merged with any coverage yet
+/// are `Unknown`, serialized as `"Unknown"` same as istanbul. Once merged, zero total is 100.
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum CoveragePercentage {
     Unknown,
@@ -12,7 +18,55 @@ impl Default for CoveragePercentage {
     }
 }
 
-#[derive(Default, Copy, Clone, PartialEq, Debug)]
+impl Serialize for CoveragePercentage {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            CoveragePercentage::Unknown => serializer.serialize_str("Unknown"),
+            // Write integers without fraction as js does (`100`, not `100.0`)
+            CoveragePercentage::Value(value) if value.fract() == 0.0 => {
+                serializer.serialize_i64(*value as i64)
+            }
+            CoveragePercentage::Value(value) => serializer.serialize_f32(*value),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for CoveragePercentage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PercentageVisitor;
+
+        impl<'de> de::Visitor<'de> for PercentageVisitor {
+            type Value = CoveragePercentage;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a number or \"Unknown\"")
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+                Ok(CoveragePercentage::Value(value as f32))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                Ok(CoveragePercentage::Value(value as f32))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                Ok(CoveragePercentage::Value(value as f32))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                match value {
+                    "Unknown" => Ok(CoveragePercentage::Unknown),
+                    _ => Err(E::invalid_value(de::Unexpected::Str(value), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(PercentageVisitor)
+    }
+}
+
+#[derive(Default, Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Totals {
     pub total: u32,
     pub covered: u32,
@@ -38,15 +92,26 @@ impl Totals {
             pct: CoveragePercentage::Unknown,
         }
     }
+
+    /// Merges a second totals into this one. Percentage is always recomputed,
+    /// so it is no longer `Unknown` even if both are empty.
+    pub fn merge(&mut self, totals: &Totals) {
+        self.total += totals.total;
+        self.covered += totals.covered;
+        self.skipped += totals.skipped;
+        self.pct = CoveragePercentage::Value(percent(self.covered, self.total));
+    }
 }
 
-#[derive(Default, Copy, Clone)]
+#[derive(Default, Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CoverageSummary {
-    pub(crate) lines: Totals,
-    pub(crate) statements: Totals,
-    pub(crate) functions: Totals,
-    pub(crate) branches: Totals,
-    pub(crate) branches_true: Option<Totals>,
+    pub lines: Totals,
+    pub statements: Totals,
+    pub functions: Totals,
+    pub branches: Totals,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branches_true: Option<Totals>,
 }
 
 impl CoverageSummary {
@@ -88,50 +153,22 @@ impl CoverageSummary {
 
     /// Merges a second summary coverage object into this one
     pub fn merge(&mut self, summary: &CoverageSummary) {
-        self.lines.total += summary.lines.total;
-        self.lines.covered += summary.lines.covered;
-        self.lines.skipped += summary.lines.skipped;
-        self.lines.pct = CoveragePercentage::Value(percent(self.lines.covered, self.lines.total));
-
-        self.statements.total += summary.statements.total;
-        self.statements.covered += summary.statements.covered;
-        self.statements.skipped += summary.statements.skipped;
-        self.statements.pct =
-            CoveragePercentage::Value(percent(self.statements.covered, self.statements.total));
-
-        self.functions.total += summary.functions.total;
-        self.functions.covered += summary.functions.covered;
-        self.functions.skipped += summary.functions.skipped;
-        self.functions.pct =
-            CoveragePercentage::Value(percent(self.functions.covered, self.functions.total));
-
-        self.branches.total += summary.branches.total;
-        self.branches.covered += summary.branches.covered;
-        self.branches.skipped += summary.branches.skipped;
-        self.branches.pct =
-            CoveragePercentage::Value(percent(self.branches.covered, self.branches.total));
-
-        if let Some(branches_true) = summary.branches_true {
-            let mut self_branches_true = if let Some(self_value) = self.branches_true {
-                self_value
-            } else {
-                Default::default()
-            };
-
-            self_branches_true.total += branches_true.total;
-            self_branches_true.covered += branches_true.covered;
-            self_branches_true.skipped += branches_true.skipped;
-            self_branches_true.pct = CoveragePercentage::Value(percent(
-                self_branches_true.covered,
-                self_branches_true.total,
-            ));
-
-            self.branches_true = Some(self_branches_true);
+        self.lines.merge(&summary.lines);
+        self.statements.merge(&summary.statements);
+        self.functions.merge(&summary.functions);
+        self.branches.merge(&summary.branches);
+
+        if let Some(branches_true) = &summary.branches_true {
+            self.branches_true
+                .get_or_insert_with(Default::default)
+                .merge(branches_true);
         }
     }
 
-    pub fn to_json(&self) {
-        unimplemented!("Not implemented yet")
+    /// Serialize the summary in the shape of istanbul's json summary, `Unknown` percentage
+    /// is written as `"Unknown"`.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Should able to serialize summary")
     }
 
     pub fn is_empty(&self) -> bool {
@@ -170,4 +207,53 @@ mod tests {
         let branches_true = first.branches_true.expect("Should exist");
         assert_eq!(branches_true.pct, CoveragePercentage::Value(100.0));
     }
+
+    #[test]
+    fn should_keep_unknown_until_merged() {
+        let mut summary = CoverageSummary::default();
+        assert_eq!(summary.lines.pct, CoveragePercentage::Unknown);
+
+        summary.merge(&CoverageSummary::new(
+            Totals::default(),
+            Totals::default(),
+            Totals::default(),
+            Totals::default(),
+            None,
+        ));
+        assert_eq!(summary.lines.pct, CoveragePercentage::Value(100.0));
+        // Not merged with branches_true
+        assert_eq!(
+            summary.branches_true.expect("Should exist").pct,
+            CoveragePercentage::Unknown
+        );
+    }
+
+    #[test]
+    fn should_serialize_percentage_same_as_istanbul() {
+        let summary = CoverageSummary::new(
+            Totals::new(3, 2, 0, CoveragePercentage::Value(66.66)),
+            Totals::new(1, 1, 0, CoveragePercentage::Value(100.0)),
+            Totals::default(),
+            Totals::new(0, 0, 0, CoveragePercentage::Value(100.0)),
+            None,
+        );
+
+        let json = summary.to_json();
+        assert_eq!(
+            json,
+            r#"{"lines":{"total":3,"covered":2,"skipped":0,"pct":66.66},"statements":{"total":1,"covered":1,"skipped":0,"pct":100},"functions":{"total":0,"covered":0,"skipped":0,"pct":"Unknown"},"branches":{"total":0,"covered":0,"skipped":0,"pct":100}}"#
+        );
+
+        let deserialized: CoverageSummary = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, summary);
+    }
+
+    #[test]
+    fn should_not_deserialize_unexpected_percentage() {
+        assert!(serde_json::from_str::<CoveragePercentage>(r#""N/A""#).is_err());
+        assert_eq!(
+            serde_json::from_str::<CoveragePercentage>("50.5").unwrap(),
+            CoveragePercentage::Value(50.5)
+        );
+    }
 }
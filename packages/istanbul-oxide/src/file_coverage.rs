@@ -1,111 +1,190 @@
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
+use crate::merge_key::{EntryKey, EntryKeys};
 use crate::{
     coverage::Coverage,
     percent,
     types::{Branch, BranchCoverageMap, BranchHitMap, BranchMap, Function, FunctionMap},
-    CoveragePercentage, CoverageSummary, LineHitMap, Range, SourceMap, StatementMap, Totals,
+    BranchDetail, CoveragePercentage, CoverageSummary, EntryKind, FrozenFileCoverage,
+    FunctionDetail, LineDetail, LineHitMap, MergeConflict, MergeKey, Range, SourceMap,
+    StatementDetail, StatementMap, Totals, UncoveredKind, UncoveredRange, UnusedExport,
+    ValidationError,
 };
-use std::fmt::Debug;
+use std::{borrow::Cow, mem::take};
 
-fn key_from_loc(range: &Range) -> String {
-    format!(
-        "{}|{}|{}|{}",
-        range.start.line, range.start.column, range.end.line, range.end.column
-    )
-}
+/// Key of the integrity of the maps in `extras`, stamped by the instrumenter's `freezeCoverage`.
+pub const INTEGRITY_KEY: &str = "integrity";
 
-fn merge_properties_hits_vec(
-    first_hits: &BranchHitMap,
-    first_map: &BranchMap,
-    second_hits: &BranchHitMap,
-    second_map: &BranchMap,
-    get_item_key_fn: for<'r> fn(&'r Branch) -> String,
-) -> (BranchHitMap, IndexMap<u32, Branch>) {
-    let mut items: IndexMap<String, (Vec<u32>, Branch)> = Default::default();
-
-    for (key, item_hits) in first_hits {
-        let item = first_map
-            .get(key)
-            .expect("Corresponding map value should exist");
-        let item_key = get_item_key_fn(item);
+/// 64bit FNV-1a, stable across the platforms and the versions unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
 
-        items.insert(item_key, (item_hits.clone(), item.clone()));
+fn key_from_loc(range: &Range) -> Range {
+    Range {
+        skip: false,
+        ..*range
     }
+}
+
+fn branch_range_from_map(branch_map: &BranchMap, key: &u32) -> Range {
+    branch_map
+        .get(key)
+        .expect("Corresponding map value should exist")
+        .locations[0]
+}
 
-    for (key, item_hits) in second_hits {
-        let item = second_map
-            .get(key)
+/// Pairs hit counts with its corresponding map value by moving values out of the maps.
+fn into_pairs<H, T>(
+    hits: IndexMap<u32, H>,
+    mut map: IndexMap<u32, T>,
+) -> impl Iterator<Item = (H, T)> {
+    hits.into_iter().map(move |(key, item_hits)| {
+        let item = map
+            .swap_remove(&key)
             .expect("Corresponding map value should exist");
-        let item_key = get_item_key_fn(item);
+        (item_hits, item)
+    })
+}
 
-        items
-            .entry(item_key)
-            .and_modify(|pair| {
-                if pair.0.len() < item_hits.len() {
-                    pair.0.resize(item_hits.len(), 0);
-                }
+/// Pairs hit counts with its corresponding map value without cloning.
+fn borrowed_pairs<'a, H: Clone, T: Clone>(
+    hits: &'a IndexMap<u32, H>,
+    map: &'a IndexMap<u32, T>,
+) -> impl Iterator<Item = (Cow<'a, H>, Cow<'a, T>)> {
+    hits.iter().map(move |(key, item_hits)| {
+        let item = map.get(key).expect("Corresponding map value should exist");
+        (Cow::Borrowed(item_hits), Cow::Borrowed(item))
+    })
+}
 
-                for (h, hits) in item_hits.iter().enumerate() {
-                    pair.0[h] += hits;
-                }
-            })
-            .or_insert((item_hits.clone(), item.clone()));
+fn merge_line_hits(first: &mut u32, second: &u32) {
+    *first += *second;
+}
+
+// Signature should match with `merge_line_hits` to be used as `merge_hits_fn`.
+#[allow(clippy::ptr_arg)]
+fn merge_branch_hits(first: &mut Vec<u32>, second: &Vec<u32>) {
+    if first.len() < second.len() {
+        first.resize(second.len(), 0);
+    }
+
+    for (h, hits) in second.iter().enumerate() {
+        first[h] += hits;
     }
+}
+
+/// Returns the id of the first entry differs between given maps, or exists only in one of them.
+fn find_conflicting_id<T>(
+    first: &IndexMap<u32, T>,
+    second: &IndexMap<u32, T>,
+    is_same: fn(&T, &T) -> bool,
+) -> Option<u32> {
+    first
+        .iter()
+        .find_map(|(id, item)| match second.get(id) {
+            Some(other) if is_same(item, other) => None,
+            _ => Some(*id),
+        })
+        .or_else(|| second.keys().find(|id| !first.contains_key(*id)).copied())
+}
 
-    let mut hits: BranchHitMap = Default::default();
-    let mut map: BranchMap = Default::default();
+fn is_ordered(range: &Range) -> bool {
+    (range.start.line, range.start.column) <= (range.end.line, range.end.column)
+}
 
-    for (idx, (hit, item)) in items.values().enumerate() {
-        hits.insert(idx as u32, hit.clone());
-        map.insert(idx as u32, item.clone());
+/// Collects the ids exist only in one of the hits and the map. Ids of the map without hits
+/// are allowed if `optional_hits` is set (i.e `bT` exists for the logical branches only).
+fn find_missing_ids<H, T>(
+    path: &str,
+    kind: EntryKind,
+    hits: &IndexMap<u32, H>,
+    map: &IndexMap<u32, T>,
+    optional_hits: bool,
+    errors: &mut Vec<ValidationError>,
+) {
+    errors.extend(hits.keys().filter(|id| !map.contains_key(*id)).map(|id| {
+        ValidationError::MissingEntry {
+            path: path.to_string(),
+            kind,
+            id: *id,
+        }
+    }));
+
+    if !optional_hits {
+        errors.extend(map.keys().filter(|id| !hits.contains_key(*id)).map(|id| {
+            ValidationError::MissingHits {
+                path: path.to_string(),
+                kind,
+                id: *id,
+            }
+        }));
     }
+}
 
-    (hits, map)
+/// Collects the branches have different number of hits from its paths.
+fn find_branch_length_mismatch(
+    path: &str,
+    kind: EntryKind,
+    hits: &BranchHitMap,
+    branch_map: &BranchMap,
+    errors: &mut Vec<ValidationError>,
+) {
+    errors.extend(hits.iter().filter_map(|(id, hits)| {
+        let locations = branch_map.get(id)?.locations.len();
+        (locations != hits.len()).then(|| ValidationError::BranchLength {
+            path: path.to_string(),
+            kind,
+            id: *id,
+            locations,
+            hits: hits.len(),
+        })
+    }));
 }
 
-fn merge_properties<T>(
-    first_hits: &LineHitMap,
-    first_map: &IndexMap<u32, T>,
-    second_hits: &LineHitMap,
-    second_map: &IndexMap<u32, T>,
-    get_item_key_fn: for<'r> fn(&'r T) -> String,
-) -> (LineHitMap, IndexMap<u32, T>)
+/// Merges two sets of (hits, item) pairs keyed by the range `get_item_range_fn` returns,
+/// or by its order depending on `key`.
+/// Items from the first set are moved, and items from the second set are only
+/// cloned when they do not exist in the first set yet.
+/// Returned maps are re-indexed from 0 in order of first appearance.
+fn merge_properties<'a, H, T>(
+    first: impl Iterator<Item = (H, T)>,
+    second: impl Iterator<Item = (Cow<'a, H>, Cow<'a, T>)>,
+    get_item_range_fn: fn(&T) -> Range,
+    key: MergeKey,
+    merge_hits_fn: fn(&mut H, &H),
+) -> (IndexMap<u32, H>, IndexMap<u32, T>)
 where
-    T: Clone + Debug,
+    H: Clone + 'a,
+    T: Clone + 'a,
 {
-    let mut items: IndexMap<String, (u32, T)> = Default::default();
-
-    for (key, item_hits) in first_hits {
-        let item = first_map
-            .get(key)
-            .expect("Corresponding map value should exist");
-        let item_key = get_item_key_fn(item);
+    let mut items: IndexMap<EntryKey, (H, T)> = Default::default();
 
-        items.insert(item_key, (*item_hits, item.clone()));
+    let mut keys = EntryKeys::new(key);
+    for (item_hits, item) in first {
+        items.insert(keys.next(&get_item_range_fn(&item)), (item_hits, item));
     }
 
-    for (key, item_hits) in second_hits {
-        let item = second_map
-            .get(key)
-            .expect("Corresponding map value should exist");
-        let item_key = get_item_key_fn(item);
+    let mut keys = EntryKeys::new(key);
+    for (item_hits, item) in second {
+        let item_key = keys.next(&get_item_range_fn(&item));
 
-        items
-            .entry(item_key)
-            .and_modify(|pair| {
-                pair.0 += *item_hits;
-            })
-            .or_insert((*item_hits, item.clone()));
+        if let Some(pair) = items.get_mut(&item_key) {
+            merge_hits_fn(&mut pair.0, &item_hits);
+        } else {
+            items.insert(item_key, (item_hits.into_owned(), item.into_owned()));
+        }
     }
 
-    let mut hits: LineHitMap = Default::default();
-    let mut map: IndexMap<u32, T> = Default::default();
+    let mut hits: IndexMap<u32, H> = IndexMap::with_capacity(items.len());
+    let mut map: IndexMap<u32, T> = IndexMap::with_capacity(items.len());
 
-    for (idx, (hit, item)) in items.values().enumerate() {
-        hits.insert(idx as u32, *hit);
-        map.insert(idx as u32, item.clone());
+    for (idx, (hit, item)) in items.into_values().enumerate() {
+        hits.insert(idx as u32, hit);
+        map.insert(idx as u32, item);
     }
 
     (hits, map)
@@ -140,6 +219,19 @@ pub struct FileCoverage {
     pub b_t: Option<BranchHitMap>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub input_source_map: Option<SourceMap>,
+    /// Schema hash istanbul-lib-instrument stamps to the coverage object.
+    #[serde(
+        rename = "_coverageSchema",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub coverage_schema: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    /// Fields not modeled here (i.e nyc's `contentHash`, legacy `l` line map), preserved
+    /// as-is to round-trip coverage produced by the other tools.
+    #[serde(flatten)]
+    pub extras: IndexMap<String, serde_json::Value>,
 }
 
 impl FileCoverage {
@@ -159,6 +251,9 @@ impl FileCoverage {
                 None
             },
             input_source_map: Default::default(),
+            coverage_schema: Default::default(),
+            hash: Default::default(),
+            extras: Default::default(),
         }
     }
 
@@ -179,11 +274,12 @@ impl FileCoverage {
         let mut line_map: LineHitMap = Default::default();
 
         for (st, count) in statements {
-            let line = statements_map
-                .get(st)
-                .expect("statement not found")
-                .start
-                .line;
+            let range = statements_map.get(st).expect("statement not found");
+            if range.skip {
+                continue;
+            }
+
+            let line = range.start.line;
             let pre_val = line_map.get(&line);
 
             match pre_val {
@@ -204,12 +300,15 @@ impl FileCoverage {
 
     /// Returns an array of uncovered line numbers.
     pub fn get_uncovered_lines(&self) -> Vec<u32> {
-        let lc = self.get_line_coverage();
+        FileCoverage::uncovered_lines_from(&self.get_line_coverage())
+    }
+
+    pub(crate) fn uncovered_lines_from(line_coverage: &LineHitMap) -> Vec<u32> {
         let mut ret: Vec<u32> = Default::default();
 
-        for (l, hits) in lc {
-            if hits == 0 {
-                ret.push(l);
+        for (l, hits) in line_coverage {
+            if *hits == 0 {
+                ret.push(*l);
             }
         }
 
@@ -224,11 +323,7 @@ impl FileCoverage {
         let mut ret: BranchCoverageMap = Default::default();
 
         for (k, map) in branch_map {
-            let line = if let Some(line) = map.line {
-                line
-            } else {
-                map.loc.expect("Either line or loc should exist").start.line
-            };
+            let line = map.get_line();
 
             let branch_data = branches.get(k).expect("branch data not found");
 
@@ -252,11 +347,150 @@ impl FileCoverage {
         ret
     }
 
+    /// Returns functions keyed by the line number of its declaration.
+    pub fn get_function_coverage_by_line(&self) -> IndexMap<u32, Vec<FunctionDetail>> {
+        let mut ret: IndexMap<u32, Vec<FunctionDetail>> = Default::default();
+
+        for (k, function) in &self.fn_map {
+            let detail = FunctionDetail {
+                id: *k,
+                name: function.name.clone(),
+                hits: *self.f.get(k).expect("function data not found"),
+            };
+            ret.entry(function.line).or_default().push(detail);
+        }
+
+        ret
+    }
+
+    /// Returns per-line annotations of statements, branches and functions starting
+    /// on the line, sorted by line number.
+    pub fn get_line_details(&self) -> IndexMap<u32, LineDetail> {
+        let mut ret: IndexMap<u32, LineDetail> = Default::default();
+
+        for (k, range) in &self.statement_map {
+            let line = range.start.line;
+            let hits = *self.s.get(k).expect("statement not found");
+            let detail = ret.entry(line).or_insert_with(|| LineDetail::new(line));
+
+            detail.hits = Some(detail.hits.map_or(hits, |prev| prev.max(hits)));
+            detail.statements.push(StatementDetail { id: *k, hits });
+        }
+
+        for (k, branch) in &self.branch_map {
+            let line = branch.get_line();
+            let hits = self.b.get(k).expect("branch data not found");
+            let (taken, not_taken) = (0..hits.len()).partition(|idx| hits[*idx] > 0);
+
+            ret.entry(line)
+                .or_insert_with(|| LineDetail::new(line))
+                .branches
+                .push(BranchDetail {
+                    id: *k,
+                    branch_type: branch.branch_type,
+                    taken,
+                    not_taken,
+                });
+        }
+
+        for (line, functions) in self.get_function_coverage_by_line() {
+            ret.entry(line)
+                .or_insert_with(|| LineDetail::new(line))
+                .functions = functions;
+        }
+
+        ret.sort_keys();
+        ret
+    }
+
+    /// Returns ranges of the statements, functions and branch locations never hit, sorted by
+    /// the start of the range. Skipped entries are not included.
+    pub fn uncovered_ranges(&self) -> Vec<UncoveredRange> {
+        let mut ret = vec![];
+
+        for (k, range) in &self.statement_map {
+            if !range.skip && self.s.get(k).copied().unwrap_or_default() == 0 {
+                ret.push(UncoveredRange {
+                    kind: UncoveredKind::Statement { id: *k },
+                    range: *range,
+                });
+            }
+        }
+
+        for (k, function) in &self.fn_map {
+            if !function.loc.skip && self.f.get(k).copied().unwrap_or_default() == 0 {
+                ret.push(UncoveredRange {
+                    kind: UncoveredKind::Function {
+                        id: *k,
+                        name: function.name.clone(),
+                    },
+                    range: function.decl,
+                });
+            }
+        }
+
+        for (k, branch) in &self.branch_map {
+            if branch.loc.is_some_and(|loc| loc.skip) {
+                continue;
+            }
+
+            let hits = self
+                .b
+                .get(k)
+                .map(|hits| hits.as_slice())
+                .unwrap_or_default();
+            for (idx, location) in branch.locations.iter().enumerate() {
+                if !location.skip && hits.get(idx).copied().unwrap_or_default() == 0 {
+                    ret.push(UncoveredRange {
+                        kind: UncoveredKind::Branch {
+                            id: *k,
+                            branch_type: branch.branch_type,
+                            location: idx,
+                        },
+                        range: *location,
+                    });
+                }
+            }
+        }
+
+        ret.sort_by_key(|uncovered| (uncovered.range.start.line, uncovered.range.start.column));
+        ret
+    }
+
+    /// Returns the exported functions never hit, in the order of the ids. Functions without
+    /// `export_name` and skipped functions are not included.
+    pub fn find_unused_exports(&self) -> Vec<UnusedExport> {
+        self.fn_map
+            .iter()
+            .filter(|(k, function)| {
+                !function.loc.skip && self.f.get(*k).copied().unwrap_or_default() == 0
+            })
+            .filter_map(|(k, function)| {
+                function
+                    .export_name
+                    .as_ref()
+                    .map(|export_name| UnusedExport {
+                        export_name: export_name.clone(),
+                        id: *k,
+                        name: function.name.clone(),
+                        decl: function.decl,
+                    })
+            })
+            .collect()
+    }
+
     pub fn to_json() {
         unimplemented!()
     }
-    /// Merges a second coverage object into this one, updating hit counts
+    /// Merges a second coverage object into this one, updating hit counts.
+    /// Only the entries of the second coverage not existing in this one are cloned.
     pub fn merge(&mut self, coverage: &FileCoverage) {
+        self.merge_by(coverage, MergeKey::Location);
+    }
+
+    /// Variant of `merge` lines up the entries of the coverages by given key instead of
+    /// its exact range, to merge the coverages of near-identical builds.
+    pub fn merge_by(&mut self, coverage: &FileCoverage, key: MergeKey) {
         if coverage.all {
             return;
         }
@@ -266,53 +500,334 @@ impl FileCoverage {
             return;
         }
 
+        // Fields not modeled can't be merged, keep the first one seen.
+        for (key, value) in coverage.extras.iter() {
+            if !self.extras.contains_key(key) {
+                self.extras.insert(key.clone(), value.clone());
+            }
+        }
+
+        // Tracking additional information about branch truthiness
+        // can be optionally enabled:
+        if let Some(coverage_branches_true) = &coverage.b_t {
+            if let Some(branches_true) = self.b_t.take() {
+                let first = branches_true
+                    .into_iter()
+                    .map(|(key, hits)| (hits, branch_range_from_map(&self.branch_map, &key)));
+                let second = coverage_branches_true.iter().map(|(key, hits)| {
+                    (
+                        Cow::Borrowed(hits),
+                        Cow::Owned(branch_range_from_map(&coverage.branch_map, key)),
+                    )
+                });
+                let (branches_true_hits_merged, _) = merge_properties(
+                    first,
+                    second,
+                    |range: &Range| *range,
+                    key,
+                    merge_branch_hits,
+                );
+
+                self.b_t = Some(branches_true_hits_merged);
+            }
+        }
+
         let (statement_hits_merged, statement_map_merged) = merge_properties(
-            &self.s,
-            &self.statement_map,
-            &coverage.s,
-            &coverage.statement_map,
-            |range: &Range| key_from_loc(range),
+            into_pairs(take(&mut self.s), take(&mut self.statement_map)),
+            borrowed_pairs(&coverage.s, &coverage.statement_map),
+            |range: &Range| *range,
+            key,
+            merge_line_hits,
         );
 
         self.s = statement_hits_merged;
         self.statement_map = statement_map_merged;
 
         let (fn_hits_merged, fn_map_merged) = merge_properties(
-            &self.f,
-            &self.fn_map,
-            &coverage.f,
-            &coverage.fn_map,
-            |map: &Function| key_from_loc(&map.loc),
+            into_pairs(take(&mut self.f), take(&mut self.fn_map)),
+            borrowed_pairs(&coverage.f, &coverage.fn_map),
+            |map: &Function| map.loc,
+            key,
+            merge_line_hits,
         );
 
         self.f = fn_hits_merged;
         self.fn_map = fn_map_merged;
 
-        let (branches_hits_merged, branches_map_merged) = merge_properties_hits_vec(
-            &self.b,
-            &self.branch_map,
-            &coverage.b,
-            &coverage.branch_map,
-            |branch: &Branch| key_from_loc(&branch.locations[0]),
+        let (branches_hits_merged, branches_map_merged) = merge_properties(
+            into_pairs(take(&mut self.b), take(&mut self.branch_map)),
+            borrowed_pairs(&coverage.b, &coverage.branch_map),
+            |branch: &Branch| branch.locations[0],
+            key,
+            merge_branch_hits,
         );
         self.b = branches_hits_merged;
         self.branch_map = branches_map_merged;
+    }
 
-        // Tracking additional information about branch truthiness
-        // can be optionally enabled:
-        if let Some(branches_true) = &self.b_t {
-            if let Some(coverage_branches_true) = &coverage.b_t {
-                let (branches_true_hits_merged, _) = merge_properties_hits_vec(
-                    branches_true,
-                    &self.branch_map,
-                    coverage_branches_true,
-                    &coverage.branch_map,
-                    |branch: &Branch| key_from_loc(&branch.locations[0]),
+    /// Returns the first structural mismatch between this coverage and the second one,
+    /// if those do not have the same hash, or the same ranges for the entries of the same id.
+    pub fn find_merge_conflict(&self, coverage: &FileCoverage) -> Option<MergeConflict> {
+        let path = || self.path.clone();
+
+        if let (Some(hash), Some(other)) = (&self.hash, &coverage.hash) {
+            if hash != other {
+                return Some(MergeConflict::Hash { path: path() });
+            }
+        }
+
+        if let Some(id) =
+            find_conflicting_id(&self.statement_map, &coverage.statement_map, |a, b| {
+                key_from_loc(a) == key_from_loc(b)
+            })
+        {
+            return Some(MergeConflict::Statement { path: path(), id });
+        }
+
+        if let Some(id) = find_conflicting_id(&self.fn_map, &coverage.fn_map, |a, b| {
+            key_from_loc(&a.loc) == key_from_loc(&b.loc)
+                && key_from_loc(&a.decl) == key_from_loc(&b.decl)
+        }) {
+            return Some(MergeConflict::Function { path: path(), id });
+        }
+
+        if let Some(id) = find_conflicting_id(&self.branch_map, &coverage.branch_map, |a, b| {
+            a.branch_type == b.branch_type
+                && a.locations.len() == b.locations.len()
+                && a.locations
+                    .iter()
+                    .zip(b.locations.iter())
+                    .all(|(a, b)| key_from_loc(a) == key_from_loc(b))
+        }) {
+            return Some(MergeConflict::Branch { path: path(), id });
+        }
+
+        None
+    }
+
+    /// Strict variant of `merge`, returns an error without merging if the coverages
+    /// are structurally different instead of merging the entries by its range.
+    pub fn try_merge(&mut self, coverage: &FileCoverage) -> Result<(), MergeConflict> {
+        if let Some(conflict) = self.find_merge_conflict(coverage) {
+            return Err(conflict);
+        }
+
+        self.merge(coverage);
+        Ok(())
+    }
+
+    /// Hash of the maps (`statementMap`, `fnMap`, `branchMap`) of the coverage, not including the
+    /// hit counts. Entries are hashed in the order of the ids, as the runtime object doesn't
+    /// keep the order of the keys.
+    pub fn compute_integrity(&self) -> String {
+        fn sorted<T>(map: &IndexMap<u32, T>) -> Vec<(&u32, &T)> {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(id, _)| **id);
+            entries
+        }
+
+        let maps = (
+            sorted(&self.statement_map),
+            sorted(&self.fn_map),
+            sorted(&self.branch_map),
+        );
+        let value = serde_json::to_vec(&maps).expect("Should able to serialize maps");
+        format!("{:016x}", fnv1a(&value))
+    }
+
+    /// Checks the internal consistency of the coverage: every hit count has its entry in the map
+    /// and vice versa, branches have the hits for each path, and ranges are ordered.
+    /// If the coverage has the integrity stamped by the instrumenter, maps are checked against it.
+    /// Returns all of the errors found, to validate the coverage from the other tools before merging.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let path = self.path.as_str();
+        let mut errors = vec![];
+
+        find_missing_ids(
+            path,
+            EntryKind::Statement,
+            &self.s,
+            &self.statement_map,
+            false,
+            &mut errors,
+        );
+        find_missing_ids(
+            path,
+            EntryKind::Function,
+            &self.f,
+            &self.fn_map,
+            false,
+            &mut errors,
+        );
+        find_missing_ids(
+            path,
+            EntryKind::Branch,
+            &self.b,
+            &self.branch_map,
+            false,
+            &mut errors,
+        );
+        find_branch_length_mismatch(
+            path,
+            EntryKind::Branch,
+            &self.b,
+            &self.branch_map,
+            &mut errors,
+        );
+        if let Some(b_t) = &self.b_t {
+            find_missing_ids(
+                path,
+                EntryKind::BranchTrue,
+                b_t,
+                &self.branch_map,
+                true,
+                &mut errors,
+            );
+            find_branch_length_mismatch(
+                path,
+                EntryKind::BranchTrue,
+                b_t,
+                &self.branch_map,
+                &mut errors,
+            );
+        }
+
+        let invalid_range = |kind: EntryKind, id: &u32| ValidationError::InvalidRange {
+            path: path.to_string(),
+            kind,
+            id: *id,
+        };
+        errors.extend(
+            self.statement_map
+                .iter()
+                .filter(|(_, range)| !is_ordered(range))
+                .map(|(id, _)| invalid_range(EntryKind::Statement, id)),
+        );
+        errors.extend(
+            self.fn_map
+                .iter()
+                .filter(|(_, function)| !is_ordered(&function.loc) || !is_ordered(&function.decl))
+                .map(|(id, _)| invalid_range(EntryKind::Function, id)),
+        );
+        errors.extend(
+            self.branch_map
+                .iter()
+                .filter(|(_, branch)| {
+                    branch.loc.as_ref().is_some_and(|loc| !is_ordered(loc))
+                        || !branch.locations.iter().all(is_ordered)
+                })
+                .map(|(id, _)| invalid_range(EntryKind::Branch, id)),
+        );
+
+        if let Some(expected) = self
+            .extras
+            .get(INTEGRITY_KEY)
+            .and_then(|integrity| integrity.as_str())
+        {
+            let actual = self.compute_integrity();
+            if expected != actual {
+                errors.push(ValidationError::Integrity {
+                    path: path.to_string(),
+                    expected: expected.to_string(),
+                    actual,
+                });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Merges a second coverage object into this one by taking its ownership.
+    /// Unlike `merge`, this does not clone any entries of the second coverage.
+    pub fn merge_owned(&mut self, coverage: FileCoverage) {
+        self.merge_owned_by(coverage, MergeKey::Location);
+    }
+
+    /// Variant of `merge_owned` lines up the entries by given key, same as `merge_by`.
+    pub fn merge_owned_by(&mut self, coverage: FileCoverage, key: MergeKey) {
+        if coverage.all {
+            return;
+        }
+
+        if self.all {
+            *self = coverage;
+            return;
+        }
+
+        let FileCoverage {
+            statement_map,
+            fn_map,
+            branch_map,
+            s,
+            f,
+            b,
+            b_t,
+            extras,
+            ..
+        } = coverage;
+
+        for (key, value) in extras {
+            self.extras.entry(key).or_insert(value);
+        }
+
+        if let Some(coverage_branches_true) = b_t {
+            if let Some(branches_true) = self.b_t.take() {
+                let first = branches_true
+                    .into_iter()
+                    .map(|(key, hits)| (hits, branch_range_from_map(&self.branch_map, &key)));
+                let second = coverage_branches_true.into_iter().map(|(key, hits)| {
+                    (
+                        Cow::Owned(hits),
+                        Cow::Owned(branch_range_from_map(&branch_map, &key)),
+                    )
+                });
+                let (branches_true_hits_merged, _) = merge_properties(
+                    first,
+                    second,
+                    |range: &Range| *range,
+                    key,
+                    merge_branch_hits,
                 );
 
                 self.b_t = Some(branches_true_hits_merged);
             }
         }
+
+        let (statement_hits_merged, statement_map_merged) = merge_properties(
+            into_pairs(take(&mut self.s), take(&mut self.statement_map)),
+            into_pairs(s, statement_map).map(|(h, t)| (Cow::Owned(h), Cow::Owned(t))),
+            |range: &Range| *range,
+            key,
+            merge_line_hits,
+        );
+
+        self.s = statement_hits_merged;
+        self.statement_map = statement_map_merged;
+
+        let (fn_hits_merged, fn_map_merged) = merge_properties(
+            into_pairs(take(&mut self.f), take(&mut self.fn_map)),
+            into_pairs(f, fn_map).map(|(h, t)| (Cow::Owned(h), Cow::Owned(t))),
+            |map: &Function| map.loc,
+            key,
+            merge_line_hits,
+        );
+
+        self.f = fn_hits_merged;
+        self.fn_map = fn_map_merged;
+
+        let (branches_hits_merged, branches_map_merged) = merge_properties(
+            into_pairs(take(&mut self.b), take(&mut self.branch_map)),
+            into_pairs(b, branch_map).map(|(h, t)| (Cow::Owned(h), Cow::Owned(t))),
+            |branch: &Branch| branch.locations[0],
+            key,
+            merge_branch_hits,
+        );
+        self.b = branches_hits_merged;
+        self.branch_map = branches_map_merged;
     }
 
     pub fn compute_simple_totals<T>(line_map: &IndexMap<T, u32>) -> Totals {
@@ -326,12 +841,44 @@ impl FileCoverage {
         }
     }
 
-    fn compute_branch_totals(branch_map: &BranchHitMap) -> Totals {
+    /// Computes totals of the hits same as `compute_simple_totals`, except entries
+    /// given fn determines skipped are counted as `skipped` only.
+    fn compute_totals_with_skip<F: Fn(&u32) -> bool>(
+        hits: &IndexMap<u32, u32>,
+        is_skipped: F,
+    ) -> Totals {
         let mut ret: Totals = Default::default();
 
-        branch_map.values().for_each(|branches| {
-            ret.covered += branches.iter().filter(|hits| **hits > 0).count() as u32;
-            ret.total += branches.len() as u32;
+        for (key, hits) in hits {
+            if is_skipped(key) {
+                ret.skipped += 1;
+            } else {
+                ret.total += 1;
+                if *hits > 0 {
+                    ret.covered += 1;
+                }
+            }
+        }
+
+        ret.pct = CoveragePercentage::Value(percent(ret.covered, ret.total));
+        ret
+    }
+
+    fn compute_branch_totals(branch_hits: &BranchHitMap, branch_map: &BranchMap) -> Totals {
+        let mut ret: Totals = Default::default();
+
+        branch_hits.iter().for_each(|(key, branches)| {
+            let skip = branch_map.get(key).is_some_and(|branch| {
+                branch.loc.is_some_and(|loc| loc.skip)
+                    || branch.locations.iter().any(|location| location.skip)
+            });
+
+            if skip {
+                ret.skipped += branches.len() as u32;
+            } else {
+                ret.covered += branches.iter().filter(|hits| **hits > 0).count() as u32;
+                ret.total += branches.len() as u32;
+            }
         });
 
         ret.pct = CoveragePercentage::Value(percent(ret.covered, ret.total));
@@ -359,15 +906,45 @@ impl FileCoverage {
     }
 
     pub fn to_summary(&self) -> CoverageSummary {
-        let line_coverage = self.get_line_coverage();
+        self.to_summary_with_line_coverage(&self.get_line_coverage())
+    }
 
-        let line = FileCoverage::compute_simple_totals(&line_coverage);
-        let function = FileCoverage::compute_simple_totals(&self.f);
-        let statement = FileCoverage::compute_simple_totals(&self.s);
-        let branches = FileCoverage::compute_branch_totals(&self.b);
+    /// Returns a read-only view precomputes derived coverage data once.
+    pub fn frozen(&self) -> FrozenFileCoverage<'_> {
+        FrozenFileCoverage::new(self)
+    }
+
+    pub(crate) fn to_summary_with_line_coverage(
+        &self,
+        line_coverage: &LineHitMap,
+    ) -> CoverageSummary {
+        let mut line = FileCoverage::compute_simple_totals(line_coverage);
+        // Lines only have skipped statements.
+        let mut skipped_lines: Vec<u32> = self
+            .statement_map
+            .values()
+            .filter(|range| range.skip && !line_coverage.contains_key(&range.start.line))
+            .map(|range| range.start.line)
+            .collect();
+        skipped_lines.sort_unstable();
+        skipped_lines.dedup();
+        line.skipped = skipped_lines.len() as u32;
+
+        let function = FileCoverage::compute_totals_with_skip(&self.f, |key| {
+            self.fn_map
+                .get(key)
+                .is_some_and(|function| function.loc.skip)
+        });
+        let statement = FileCoverage::compute_totals_with_skip(&self.s, |key| {
+            self.statement_map.get(key).is_some_and(|range| range.skip)
+        });
+        let branches = FileCoverage::compute_branch_totals(&self.b, &self.branch_map);
 
         let branches_true = if let Some(branches_true) = &self.b_t {
-            Some(FileCoverage::compute_branch_totals(&branches_true))
+            Some(FileCoverage::compute_branch_totals(
+                branches_true,
+                &self.branch_map,
+            ))
         } else {
             None
         };
@@ -384,7 +961,8 @@ mod tests {
         coverage::Coverage,
         coverage_summary::{CoveragePercentage, Totals},
         types::{Branch, Function},
-        BranchType, FileCoverage, Range,
+        BranchDetail, BranchType, EntryKind, FileCoverage, FunctionDetail, MergeConflict, MergeKey,
+        Range, StatementDetail, UncoveredKind, UnusedExport, ValidationError, INTEGRITY_KEY,
     };
 
     #[test]
@@ -405,6 +983,8 @@ mod tests {
                     line: 1,
                     loc: Range::new(1, 1, 1, 50),
                     decl: Default::default(),
+                    export_name: None,
+                    metadata: None,
                 },
             )]),
             branch_map: IndexMap::from([(
@@ -420,6 +1000,9 @@ mod tests {
             b: IndexMap::from([(0, vec![0, 0])]),
             b_t: None,
             input_source_map: None,
+            coverage_schema: None,
+            hash: None,
+            extras: Default::default(),
         };
 
         let mut first = base.clone();
@@ -497,6 +1080,8 @@ mod tests {
                     line: 1,
                     loc: Range::new(1, 1, 1, 50),
                     decl: Default::default(),
+                    export_name: None,
+                    metadata: None,
                 },
             )]),
             branch_map: IndexMap::from([(
@@ -512,6 +1097,9 @@ mod tests {
             b: IndexMap::from([(0, vec![0, 0])]),
             b_t: None,
             input_source_map: None,
+            coverage_schema: None,
+            hash: None,
+            extras: Default::default(),
         };
 
         let base_other = FileCoverage {
@@ -530,6 +1118,8 @@ mod tests {
                     line: 1,
                     loc: Range::new(1, 1, 1, 50),
                     decl: Default::default(),
+                    export_name: None,
+                    metadata: None,
                 },
             )]),
             branch_map: IndexMap::from([(
@@ -545,6 +1135,9 @@ mod tests {
             b: IndexMap::from([(1, vec![0, 0])]),
             b_t: None,
             input_source_map: None,
+            coverage_schema: None,
+            hash: None,
+            extras: Default::default(),
         };
 
         let mut first = base.clone();
@@ -604,6 +1197,62 @@ mod tests {
         assert_eq!(first.b.get(&0).unwrap()[1], 2);
     }
 
+    #[test]
+    fn should_merge_owned_identical_to_merge() {
+        let base = FileCoverage {
+            all: false,
+            path: "/path/to/file".to_string(),
+            statement_map: IndexMap::from([
+                (1, Range::new(1, 1, 1, 100)),
+                (2, Range::new(2, 1, 2, 50)),
+            ]),
+            fn_map: IndexMap::from([(
+                1,
+                Function {
+                    name: "foobar".to_string(),
+                    line: 1,
+                    loc: Range::new(1, 1, 1, 50),
+                    decl: Default::default(),
+                    export_name: None,
+                    metadata: None,
+                },
+            )]),
+            branch_map: IndexMap::from([(
+                1,
+                Branch::from_line(
+                    BranchType::If,
+                    2,
+                    vec![Range::new(2, 1, 2, 20), Range::new(2, 50, 2, 100)],
+                ),
+            )]),
+            s: IndexMap::from([(1, 1), (2, 0)]),
+            f: IndexMap::from([(1, 1)]),
+            b: IndexMap::from([(1, vec![1, 0])]),
+            b_t: Some(IndexMap::from([(1, vec![1, 0])])),
+            input_source_map: None,
+            coverage_schema: None,
+            hash: None,
+            extras: Default::default(),
+        };
+
+        let mut other = base.clone();
+        other.statement_map.insert(3, Range::new(3, 1, 3, 10));
+        other.s = IndexMap::from([(1, 0), (2, 2), (3, 1)]);
+        other.b = IndexMap::from([(1, vec![0, 3])]);
+        other.b_t = Some(IndexMap::from([(1, vec![0, 3])]));
+
+        let mut merged = base.clone();
+        merged.merge(&other);
+
+        let mut merged_owned = base.clone();
+        merged_owned.merge_owned(other);
+
+        assert_eq!(merged, merged_owned);
+        assert_eq!(merged.s, IndexMap::from([(0, 1), (1, 2), (2, 1)]));
+        assert_eq!(merged.b, IndexMap::from([(0, vec![1, 3])]));
+        assert_eq!(merged.b_t, Some(IndexMap::from([(0, vec![1, 3])])));
+    }
+
     #[test]
     fn should_drop_data_while_merge() {
         let base = FileCoverage {
@@ -622,6 +1271,8 @@ mod tests {
                     line: 1,
                     loc: Range::new(1, 1, 1, 50),
                     decl: Default::default(),
+                    export_name: None,
+                    metadata: None,
                 },
             )]),
             branch_map: IndexMap::from([(
@@ -637,6 +1288,9 @@ mod tests {
             b: IndexMap::from([(1, vec![0, 0])]),
             b_t: None,
             input_source_map: None,
+            coverage_schema: None,
+            hash: None,
+            extras: Default::default(),
         };
 
         let create_coverage = |all: bool| {
@@ -681,6 +1335,8 @@ mod tests {
                     line: 1,
                     loc: Range::new(1, 1, 1, 50),
                     decl: Default::default(),
+                    export_name: None,
+                    metadata: None,
                 },
             )]),
             branch_map: IndexMap::from([(
@@ -696,6 +1352,9 @@ mod tests {
             b: IndexMap::from([(0, vec![0, 0])]),
             b_t: None,
             input_source_map: None,
+            coverage_schema: None,
+            hash: None,
+            extras: Default::default(),
         };
 
         let mut first = base.clone();
@@ -780,6 +1439,8 @@ mod tests {
                     line: 1,
                     loc: Range::new(1, 1, 1, 50),
                     decl: Default::default(),
+                    export_name: None,
+                    metadata: None,
                 },
             )]),
             branch_map: IndexMap::from([(
@@ -795,6 +1456,9 @@ mod tests {
             b: IndexMap::from([(1, vec![1, 50])]),
             b_t: Some(IndexMap::from([(1, vec![1, 50])])),
             input_source_map: None,
+            coverage_schema: None,
+            hash: None,
+            extras: Default::default(),
         };
 
         let mut value = base.clone();
@@ -823,6 +1487,9 @@ mod tests {
             b: Default::default(),
             b_t: None,
             input_source_map: None,
+            coverage_schema: None,
+            hash: None,
+            extras: Default::default(),
         };
 
         assert_eq!(base.get_uncovered_lines(), vec![2]);
@@ -844,6 +1511,9 @@ mod tests {
             b: IndexMap::from([(1, vec![1, 0]), (2, vec![0, 0, 0, 1])]),
             b_t: None,
             input_source_map: None,
+            coverage_schema: None,
+            hash: None,
+            extras: Default::default(),
         };
 
         let coverage = base.get_branch_coverage_by_line();
@@ -856,6 +1526,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_return_line_details() {
+        let base = FileCoverage {
+            all: false,
+            path: "/path/to/file".to_string(),
+            statement_map: IndexMap::from([
+                (0, Range::new(1, 0, 3, 1)),
+                (1, Range::new(2, 2, 2, 20)),
+                (2, Range::new(2, 21, 2, 40)),
+            ]),
+            fn_map: IndexMap::from([(
+                0,
+                Function {
+                    name: "foo".to_string(),
+                    decl: Range::new(1, 9, 1, 12),
+                    loc: Range::new(1, 15, 3, 1),
+                    line: 1,
+                    export_name: None,
+                    metadata: None,
+                },
+            )]),
+            branch_map: IndexMap::from([(
+                0,
+                Branch::from_line(BranchType::If, 2, Default::default()),
+            )]),
+            s: IndexMap::from([(0, 1), (1, 0), (2, 2)]),
+            f: IndexMap::from([(0, 1)]),
+            b: IndexMap::from([(0, vec![2, 0])]),
+            b_t: None,
+            input_source_map: None,
+            coverage_schema: None,
+            hash: None,
+            extras: Default::default(),
+        };
+
+        assert_eq!(
+            base.get_function_coverage_by_line(),
+            IndexMap::from([(
+                1,
+                vec![FunctionDetail {
+                    id: 0,
+                    name: "foo".to_string(),
+                    hits: 1
+                }]
+            )])
+        );
+
+        let details = base.get_line_details();
+        assert_eq!(details.keys().collect::<Vec<_>>(), vec![&1, &2]);
+
+        let line = details.get(&2).unwrap();
+        assert_eq!(line.hits, Some(2));
+        assert_eq!(
+            line.statements,
+            vec![
+                StatementDetail { id: 1, hits: 0 },
+                StatementDetail { id: 2, hits: 2 }
+            ]
+        );
+        assert_eq!(
+            line.branches,
+            vec![BranchDetail {
+                id: 0,
+                branch_type: BranchType::If,
+                taken: vec![0],
+                not_taken: vec![1],
+            }]
+        );
+        assert!(line.has_missing_branches());
+        assert!(line.functions.is_empty());
+
+        let line = details.get(&1).unwrap();
+        assert_eq!(line.functions.len(), 1);
+        assert!(!line.has_missing_branches());
+    }
+
     #[test]
     fn should_return_branch_coverage_by_line_with_cobertura_branchmap_structure() {
         let base = FileCoverage {
@@ -882,6 +1628,9 @@ mod tests {
             b: IndexMap::from([(1, vec![1, 0]), (2, vec![0, 0, 0, 1])]),
             b_t: None,
             input_source_map: None,
+            coverage_schema: None,
+            hash: None,
+            extras: Default::default(),
         };
 
         let coverage = base.get_branch_coverage_by_line();
@@ -905,4 +1654,411 @@ mod tests {
             Some(Default::default())
         );
     }
+
+    #[test]
+    fn should_count_skipped_entries_in_summary() {
+        let skipped = Range {
+            skip: true,
+            ..Range::new(3, 0, 3, 10)
+        };
+
+        let base = FileCoverage {
+            all: false,
+            path: "/path/to/file".to_string(),
+            statement_map: IndexMap::from([
+                (0, Range::new(1, 0, 1, 10)),
+                (1, Range::new(2, 0, 2, 10)),
+                (2, skipped),
+            ]),
+            fn_map: IndexMap::from([(
+                0,
+                Function {
+                    name: "a".to_string(),
+                    decl: skipped,
+                    loc: skipped,
+                    line: 3,
+                    export_name: None,
+                    metadata: None,
+                },
+            )]),
+            branch_map: IndexMap::from([(
+                0,
+                Branch {
+                    loc: Some(skipped),
+                    branch_type: BranchType::If,
+                    locations: vec![skipped, skipped],
+                    line: Some(3),
+                },
+            )]),
+            s: IndexMap::from([(0, 1), (1, 0), (2, 0)]),
+            f: IndexMap::from([(0, 0)]),
+            b: IndexMap::from([(0, vec![0, 0])]),
+            b_t: None,
+            input_source_map: None,
+            coverage_schema: None,
+            hash: None,
+            extras: Default::default(),
+        };
+
+        let summary = base.to_summary();
+        assert_eq!(
+            summary.statements,
+            Totals::new(2, 1, 1, CoveragePercentage::Value(50.0))
+        );
+        assert_eq!(
+            summary.lines,
+            Totals::new(2, 1, 1, CoveragePercentage::Value(50.0))
+        );
+        assert_eq!(
+            summary.functions,
+            Totals::new(0, 0, 1, CoveragePercentage::Value(100.0))
+        );
+        assert_eq!(
+            summary.branches,
+            Totals::new(0, 0, 2, CoveragePercentage::Value(100.0))
+        );
+        assert_eq!(base.get_uncovered_lines(), vec![2]);
+
+        // `skip` is omitted unless set, same as istanbul.
+        assert_eq!(
+            serde_json::to_string(&Range::new(1, 0, 1, 10)).unwrap(),
+            r#"{"start":{"line":1,"column":0},"end":{"line":1,"column":10}}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Range>(&serde_json::to_string(&skipped).unwrap()).unwrap(),
+            skipped
+        );
+    }
+
+    #[test]
+    fn should_not_merge_structurally_different_coverage_in_strict_mode() {
+        let create_coverage = |hits: u32| {
+            let mut coverage = FileCoverage::from_file_path("foo.js".to_string(), false);
+            coverage.statement_map.insert(0, Range::new(1, 1, 1, 10));
+            coverage.s.insert(0, hits);
+            coverage.branch_map.insert(
+                0,
+                Branch::from_line(BranchType::If, 1, vec![Range::new(1, 1, 1, 10)]),
+            );
+            coverage.b.insert(0, vec![hits]);
+            coverage
+        };
+
+        let mut base = create_coverage(1);
+        assert_eq!(base.try_merge(&create_coverage(2)), Ok(()));
+        assert_eq!(base.s.get(&0), Some(&3));
+
+        let mut stale = create_coverage(1);
+        stale.statement_map.insert(0, Range::new(2, 1, 2, 10));
+        assert_eq!(
+            base.try_merge(&stale),
+            Err(MergeConflict::Statement {
+                path: "foo.js".to_string(),
+                id: 0
+            })
+        );
+
+        let mut extra = create_coverage(1);
+        extra.branch_map.insert(
+            1,
+            Branch::from_line(BranchType::If, 2, vec![Range::new(2, 1, 2, 10)]),
+        );
+        extra.b.insert(1, vec![1]);
+        assert_eq!(
+            base.try_merge(&extra),
+            Err(MergeConflict::Branch {
+                path: "foo.js".to_string(),
+                id: 1
+            })
+        );
+
+        let mut hashed = base.clone();
+        hashed.hash = Some("a".to_string());
+        let mut other = create_coverage(1);
+        other.hash = Some("b".to_string());
+        assert_eq!(
+            hashed.try_merge(&other),
+            Err(MergeConflict::Hash {
+                path: "foo.js".to_string()
+            })
+        );
+
+        // Conflicting coverage is not merged.
+        assert_eq!(base.s.get(&0), Some(&3));
+        assert_eq!(base.branch_map.len(), 1);
+    }
+
+    #[test]
+    fn should_preserve_unknown_fields_on_merge() {
+        let babel: FileCoverage = serde_json::from_str(
+            r#"{"path":"foo.js","statementMap":{},"fnMap":{},"branchMap":{},"s":{},"f":{},"b":{},"contentHash":"a","l":{}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            babel.extras.get("contentHash"),
+            Some(&serde_json::json!("a"))
+        );
+
+        let mut swc = FileCoverage::from_file_path("foo.js".to_string(), false);
+        swc.merge(&babel);
+        assert_eq!(swc.extras, babel.extras);
+
+        let mut other = babel.clone();
+        other
+            .extras
+            .insert("contentHash".to_string(), serde_json::json!("b"));
+        swc.merge_owned(other);
+        assert_eq!(swc.extras, babel.extras);
+    }
+
+    #[test]
+    fn should_validate() {
+        let mut coverage = FileCoverage::from_file_path("foo.js".to_string(), true);
+        coverage.statement_map.insert(0, Range::new(1, 0, 1, 10));
+        coverage.s.insert(0, 1);
+        coverage.branch_map.insert(
+            0,
+            Branch::from_line(
+                BranchType::BinaryExpr,
+                1,
+                vec![Range::new(1, 0, 1, 4), Range::new(1, 8, 1, 10)],
+            ),
+        );
+        coverage.b.insert(0, vec![1, 0]);
+        coverage.b_t.as_mut().unwrap().insert(0, vec![1, 0]);
+        assert_eq!(coverage.validate(), Ok(()));
+
+        let path = || "foo.js".to_string();
+        coverage.statement_map.insert(1, Range::new(2, 10, 2, 0));
+        coverage.s.insert(1, 0);
+        coverage.f.insert(0, 1);
+        coverage.b.insert(0, vec![1]);
+        assert_eq!(
+            coverage.validate(),
+            Err(vec![
+                ValidationError::MissingEntry {
+                    path: path(),
+                    kind: EntryKind::Function,
+                    id: 0
+                },
+                ValidationError::BranchLength {
+                    path: path(),
+                    kind: EntryKind::Branch,
+                    id: 0,
+                    locations: 2,
+                    hits: 1
+                },
+                ValidationError::InvalidRange {
+                    path: path(),
+                    kind: EntryKind::Statement,
+                    id: 1
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn should_validate_integrity() {
+        let mut coverage = FileCoverage::from_file_path("foo.js".to_string(), false);
+        coverage.statement_map.insert(0, Range::new(1, 0, 1, 10));
+        coverage.statement_map.insert(1, Range::new(2, 0, 2, 10));
+        coverage.s.insert(0, 0);
+        coverage.s.insert(1, 0);
+        coverage.extras.insert(
+            INTEGRITY_KEY.to_string(),
+            serde_json::Value::String(coverage.compute_integrity()),
+        );
+        assert_eq!(coverage.validate(), Ok(()));
+
+        // Hits and the order of the keys do not matter.
+        let mut hit: FileCoverage =
+            serde_json::from_str(&serde_json::to_string(&coverage).unwrap()).unwrap();
+        hit.s.insert(0, 3);
+        hit.statement_map.reverse();
+        assert_eq!(hit.validate(), Ok(()));
+
+        let mut tampered = coverage.clone();
+        tampered.statement_map.insert(1, Range::new(3, 0, 3, 10));
+        assert_eq!(
+            tampered.validate(),
+            Err(vec![ValidationError::Integrity {
+                path: "foo.js".to_string(),
+                expected: coverage.compute_integrity(),
+                actual: tampered.compute_integrity(),
+            }])
+        );
+        assert_ne!(coverage.compute_integrity(), tampered.compute_integrity());
+    }
+
+    #[test]
+    fn should_merge_by_key() {
+        let coverage_with = |ranges: Vec<Range>, hits: Vec<u32>| {
+            let mut coverage = FileCoverage::from_file_path("/path/to/file".to_string(), false);
+            coverage.statement_map = ranges
+                .into_iter()
+                .enumerate()
+                .map(|(idx, range)| (idx as u32, range))
+                .collect();
+            coverage.s = hits
+                .into_iter()
+                .enumerate()
+                .map(|(idx, hits)| (idx as u32, hits))
+                .collect();
+            coverage
+        };
+
+        let base = coverage_with(
+            vec![
+                Range::new(1, 0, 1, 10),
+                Range::new(1, 12, 1, 20),
+                Range::new(2, 0, 2, 5),
+            ],
+            vec![1, 0, 1],
+        );
+        // Same statements with the whitespace changes within the lines
+        let reformatted = coverage_with(
+            vec![
+                Range::new(1, 2, 1, 12),
+                Range::new(1, 14, 1, 22),
+                Range::new(2, 2, 2, 7),
+            ],
+            vec![0, 2, 1],
+        );
+        // Same statements with the shifted lines
+        let shifted = coverage_with(
+            vec![
+                Range::new(2, 0, 2, 10),
+                Range::new(2, 12, 2, 20),
+                Range::new(3, 0, 3, 5),
+            ],
+            vec![0, 2, 1],
+        );
+
+        let mut merged = base.clone();
+        merged.merge(&reformatted);
+        assert_eq!(merged.s.len(), 6);
+
+        let mut merged = base.clone();
+        merged.merge_by(&reformatted, MergeKey::Line);
+        assert_eq!(merged.statement_map, base.statement_map);
+        assert_eq!(merged.s, IndexMap::from([(0, 1), (1, 2), (2, 2)]));
+
+        let mut merged = base.clone();
+        merged.merge_by(&shifted, MergeKey::Line);
+        assert_eq!(merged.s.len(), 5);
+
+        let mut merged = base.clone();
+        merged.merge_owned_by(shifted, MergeKey::Index);
+        assert_eq!(merged.statement_map, base.statement_map);
+        assert_eq!(merged.s, IndexMap::from([(0, 1), (1, 2), (2, 2)]));
+    }
+
+    #[test]
+    fn should_return_uncovered_ranges() {
+        let mut coverage = FileCoverage::from_file_path("/path/to/file".to_string(), false);
+        coverage.statement_map = IndexMap::from([
+            (0, Range::new(1, 0, 1, 10)),
+            (1, Range::new(3, 2, 3, 12)),
+            (
+                2,
+                Range {
+                    skip: true,
+                    ..Range::new(4, 2, 4, 12)
+                },
+            ),
+        ]);
+        coverage.s = IndexMap::from([(0, 1), (1, 0), (2, 0)]);
+        coverage.fn_map = IndexMap::from([(
+            0,
+            Function {
+                name: "foo".to_string(),
+                line: 2,
+                decl: Range::new(2, 9, 2, 12),
+                loc: Range::new(2, 15, 5, 1),
+                export_name: None,
+                metadata: None,
+            },
+        )]);
+        coverage.f = IndexMap::from([(0, 0)]);
+        coverage.branch_map = IndexMap::from([(
+            0,
+            Branch::from_line(
+                BranchType::CondExpr,
+                1,
+                vec![Range::new(1, 4, 1, 5), Range::new(1, 8, 1, 9)],
+            ),
+        )]);
+        coverage.b = IndexMap::from([(0, vec![1, 0])]);
+
+        let uncovered: Vec<_> = coverage
+            .uncovered_ranges()
+            .into_iter()
+            .map(|uncovered| (uncovered.kind, uncovered.range))
+            .collect();
+        assert_eq!(
+            uncovered,
+            vec![
+                (
+                    UncoveredKind::Branch {
+                        id: 0,
+                        branch_type: BranchType::CondExpr,
+                        location: 1,
+                    },
+                    Range::new(1, 8, 1, 9)
+                ),
+                (
+                    UncoveredKind::Function {
+                        id: 0,
+                        name: "foo".to_string(),
+                    },
+                    Range::new(2, 9, 2, 12)
+                ),
+                (UncoveredKind::Statement { id: 1 }, Range::new(3, 2, 3, 12)),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_find_unused_exports() {
+        let function = |name: &str, line: u32, export_name: Option<&str>, skip: bool| Function {
+            name: name.to_string(),
+            line,
+            decl: Range::new(line, 9, line, 12),
+            loc: Range {
+                skip,
+                ..Range::new(line, 15, line, 20)
+            },
+            export_name: export_name.map(|name| name.to_string()),
+            metadata: None,
+        };
+
+        let mut coverage = FileCoverage::from_file_path("/path/to/file".to_string(), false);
+        coverage.fn_map = IndexMap::from([
+            (0, function("foo", 1, Some("foo"), false)),
+            (1, function("bar", 2, Some("bar"), false)),
+            (2, function("baz", 3, None, false)),
+            (3, function("qux", 4, Some("qux"), true)),
+            (4, function("quux", 5, Some("default"), false)),
+        ]);
+        // Functions without the hit count are never hit.
+        coverage.f = IndexMap::from([(0, 0), (1, 2), (2, 0), (3, 0)]);
+
+        assert_eq!(
+            coverage.find_unused_exports(),
+            vec![
+                UnusedExport {
+                    export_name: "foo".to_string(),
+                    id: 0,
+                    name: "foo".to_string(),
+                    decl: Range::new(1, 9, 1, 12),
+                },
+                UnusedExport {
+                    export_name: "default".to_string(),
+                    id: 4,
+                    name: "quux".to_string(),
+                    decl: Range::new(5, 9, 5, 12),
+                },
+            ]
+        );
+    }
 }
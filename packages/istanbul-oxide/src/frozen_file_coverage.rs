@@ -0,0 +1,96 @@
+use crate::{BranchCoverageMap, CoverageSummary, FileCoverage, LineHitMap};
+
+/// A read-only view of the `FileCoverage` holds precomputed line coverage,
+/// branch coverage by line and summary.
+///
+/// Computing these requires iterating over whole coverage maps, and reporters
+/// usually access it multiple times per file. Since the view borrows the coverage,
+/// it cannot be mutated while view exists and precomputed values never go stale.
+#[derive(Clone, Debug)]
+pub struct FrozenFileCoverage<'a> {
+    inner: &'a FileCoverage,
+    line_coverage: LineHitMap,
+    branch_coverage_by_line: BranchCoverageMap,
+    summary: CoverageSummary,
+}
+
+impl<'a> FrozenFileCoverage<'a> {
+    pub fn new(coverage: &'a FileCoverage) -> FrozenFileCoverage<'a> {
+        let line_coverage = coverage.get_line_coverage();
+        let branch_coverage_by_line = coverage.get_branch_coverage_by_line();
+        let summary = coverage.to_summary_with_line_coverage(&line_coverage);
+
+        FrozenFileCoverage {
+            inner: coverage,
+            line_coverage,
+            branch_coverage_by_line,
+            summary,
+        }
+    }
+
+    pub fn get_line_coverage(&self) -> &LineHitMap {
+        &self.line_coverage
+    }
+
+    pub fn get_uncovered_lines(&self) -> Vec<u32> {
+        FileCoverage::uncovered_lines_from(&self.line_coverage)
+    }
+
+    pub fn get_branch_coverage_by_line(&self) -> &BranchCoverageMap {
+        &self.branch_coverage_by_line
+    }
+
+    pub fn to_summary(&self) -> CoverageSummary {
+        self.summary
+    }
+}
+
+impl<'a> AsRef<FileCoverage> for FrozenFileCoverage<'a> {
+    fn as_ref(&self) -> &FileCoverage {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use crate::{Branch, BranchType, FileCoverage, Range};
+
+    #[test]
+    fn should_precompute_same_values_as_file_coverage() {
+        let coverage = FileCoverage {
+            all: false,
+            path: "/path/to/file".to_string(),
+            statement_map: IndexMap::from([
+                (1, Range::new(1, 1, 1, 100)),
+                (2, Range::new(1, 101, 1, 200)),
+                (3, Range::new(2, 1, 2, 100)),
+            ]),
+            fn_map: Default::default(),
+            branch_map: IndexMap::from([(
+                1,
+                Branch::from_line(BranchType::If, 1, Default::default()),
+            )]),
+            s: IndexMap::from([(1, 0), (2, 1), (3, 0)]),
+            f: Default::default(),
+            b: IndexMap::from([(1, vec![1, 0])]),
+            b_t: None,
+            input_source_map: None,
+            coverage_schema: None,
+            hash: None,
+            extras: Default::default(),
+        };
+
+        let frozen = coverage.frozen();
+
+        assert_eq!(frozen.get_line_coverage(), &coverage.get_line_coverage());
+        assert_eq!(frozen.get_uncovered_lines(), coverage.get_uncovered_lines());
+        assert_eq!(
+            frozen.get_branch_coverage_by_line(),
+            &coverage.get_branch_coverage_by_line()
+        );
+
+        assert_eq!(frozen.to_summary(), coverage.to_summary());
+    }
+}
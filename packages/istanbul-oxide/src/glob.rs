@@ -0,0 +1,68 @@
+//! Glob matching of the file paths, shared by the instrumenter's options and the filters of the
+//! coverage map so the same glob selects the same files in both.
+
+fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/").trim_start_matches("./").to_string()
+}
+
+/// Match the path with the glob, `*` / `?` don't match `/` while `**` matches any directories.
+fn glob_match(pattern: &[u8], path: &[u8]) -> bool {
+    match pattern {
+        [] => path.is_empty(),
+        [b'*', b'*', b'/', rest @ ..] => {
+            // `**/` matches nothing or any directories ending with `/`.
+            glob_match(rest, path)
+                || path
+                    .iter()
+                    .enumerate()
+                    .any(|(index, c)| *c == b'/' && glob_match(rest, &path[index + 1..]))
+        }
+        [b'*', b'*', rest @ ..] => (0..=path.len()).any(|index| glob_match(rest, &path[index..])),
+        [b'*', rest @ ..] => (0..=path.len())
+            .take_while(|index| *index == 0 || path[index - 1] != b'/')
+            .any(|index| glob_match(rest, &path[index..])),
+        [b'?', rest @ ..] => matches!(path, [c, path @ ..] if *c != b'/' && glob_match(rest, path)),
+        [c, rest @ ..] => matches!(path, [p, path @ ..] if p == c && glob_match(rest, path)),
+    }
+}
+
+/// Whether the path matches the glob, relative globs match the end of the path at the path separator.
+pub fn is_glob_match(pattern: &str, file_path: &str) -> bool {
+    let pattern = normalize_path(pattern);
+    let file_path = normalize_path(file_path);
+    if pattern.is_empty() {
+        return false;
+    }
+
+    if glob_match(pattern.as_bytes(), file_path.as_bytes()) {
+        return true;
+    }
+
+    !pattern.starts_with('/')
+        && file_path
+            .char_indices()
+            .filter(|(_, c)| *c == '/')
+            .any(|(index, _)| glob_match(pattern.as_bytes(), &file_path.as_bytes()[index + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_glob_match;
+
+    #[test]
+    fn should_match_globs() {
+        assert!(is_glob_match("**/problem.ts", "src/a/problem.ts"));
+        assert!(is_glob_match("**/problem.ts", "problem.ts"));
+        assert!(is_glob_match("problem.ts", "/repo/src/problem.ts"));
+        assert!(is_glob_match("src/*.ts", "/repo/src/problem.ts"));
+        assert!(is_glob_match("src/**", "src/a/b.js"));
+        assert!(is_glob_match("src/?.js", ".\\src\\a.js"));
+        assert!(is_glob_match("/repo/**/*.ts", "/repo/src/problem.ts"));
+
+        assert!(!is_glob_match("src/*.ts", "src/a/problem.ts"));
+        assert!(!is_glob_match("/src/*.ts", "/repo/src/problem.ts"));
+        assert!(!is_glob_match("problem.ts", "src/other-problem.ts"));
+        assert!(!is_glob_match("src/?.js", "src/ab.js"));
+        assert!(!is_glob_match("", "src/a.js"));
+    }
+}
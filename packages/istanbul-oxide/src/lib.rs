@@ -1,16 +1,43 @@
+mod canonicalize;
 mod coverage;
 mod coverage_map;
+mod coverage_session;
 mod coverage_summary;
 mod file_coverage;
+mod frozen_file_coverage;
+mod glob;
+mod line_detail;
+mod merge_conflict;
+mod merge_key;
+mod patch_coverage;
 mod percent;
+#[cfg(feature = "python")]
+pub mod python;
 mod range;
+mod remap;
+pub mod report;
 mod source_map;
+mod source_map_consumer;
 pub mod types;
+mod uncovered_range;
+mod unused_export;
+mod validation_error;
 
+pub use canonicalize::{canonicalize_path, PathCanonicalizer};
 pub use coverage_map::CoverageMap;
-use coverage_summary::*;
-pub use file_coverage::FileCoverage;
+pub use coverage_session::{CoverageSession, InvalidSession};
+pub use coverage_summary::*;
+pub use file_coverage::{FileCoverage, INTEGRITY_KEY};
+pub use frozen_file_coverage::FrozenFileCoverage;
+pub use glob::is_glob_match;
+pub use line_detail::*;
+pub use merge_conflict::MergeConflict;
+pub use merge_key::MergeKey;
+pub use patch_coverage::{parse_changed_lines, FilePatchCoverage, PatchCoverage};
 use percent::*;
 pub use range::*;
 pub use source_map::SourceMap;
 pub use types::*;
+pub use uncovered_range::{UncoveredKind, UncoveredRange};
+pub use unused_export::UnusedExport;
+pub use validation_error::{EntryKind, ValidationError};
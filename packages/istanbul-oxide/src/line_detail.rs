@@ -0,0 +1,56 @@
+use crate::BranchType;
+
+/// Statement starts on the line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatementDetail {
+    pub id: u32,
+    pub hits: u32,
+}
+
+/// Branch starts on the line, with indices of its locations taken or not taken.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BranchDetail {
+    pub id: u32,
+    pub branch_type: BranchType,
+    pub taken: Vec<usize>,
+    pub not_taken: Vec<usize>,
+}
+
+/// Function declared on the line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionDetail {
+    pub id: u32,
+    pub name: String,
+    pub hits: u32,
+}
+
+/// Annotations for the single line of the source, for the reporters render
+/// per-line details.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineDetail {
+    pub line: u32,
+    /// Line hits same as `get_line_coverage`, None if no statement starts on the line.
+    pub hits: Option<u32>,
+    pub statements: Vec<StatementDetail>,
+    pub branches: Vec<BranchDetail>,
+    pub functions: Vec<FunctionDetail>,
+}
+
+impl LineDetail {
+    pub fn new(line: u32) -> LineDetail {
+        LineDetail {
+            line,
+            hits: None,
+            statements: Default::default(),
+            branches: Default::default(),
+            functions: Default::default(),
+        }
+    }
+
+    /// Returns true if any branch on the line has location never taken.
+    pub fn has_missing_branches(&self) -> bool {
+        self.branches
+            .iter()
+            .any(|branch| !branch.not_taken.is_empty())
+    }
+}
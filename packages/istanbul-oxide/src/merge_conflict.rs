@@ -0,0 +1,34 @@
+use std::fmt;
+
+/// Structural mismatch between the coverages of the same path, returned by the strict merges.
+/// Usually means coverages are from different versions of the file (i.e stale shards).
+#[derive(Clone, Debug, PartialEq)]
+pub enum MergeConflict {
+    /// Coverages have different hashes.
+    Hash { path: String },
+    /// Statement of the id has different range, or exists only in one of the coverages.
+    Statement { path: String, id: u32 },
+    /// Function of the id has different range, or exists only in one of the coverages.
+    Function { path: String, id: u32 },
+    /// Branch of the id has different type or paths, or exists only in one of the coverages.
+    Branch { path: String, id: u32 },
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeConflict::Hash { path } => write!(f, "Hash of the coverages differ for {}", path),
+            MergeConflict::Statement { path, id } => {
+                write!(f, "Statement {} of the coverages differ for {}", id, path)
+            }
+            MergeConflict::Function { path, id } => {
+                write!(f, "Function {} of the coverages differ for {}", id, path)
+            }
+            MergeConflict::Branch { path, id } => {
+                write!(f, "Branch {} of the coverages differ for {}", id, path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MergeConflict {}
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use crate::Range;
+
+/// Identity of the entries to line up the coverages of the same path on merge.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MergeKey {
+    /// Exact range of the entry, same as istanbul's merge.
+    Location,
+    /// Start line of the entry, along with its order among the entries starting on the same line.
+    /// Lines up the coverages with changes only within the lines (i.e reformatted whitespaces).
+    Line,
+    /// Order of the entry in the coverage. Lines up the coverages with the same structure
+    /// regardless of the ranges (i.e shifted lines), only for near-identical builds.
+    Index,
+}
+
+#[derive(Hash, PartialEq, Eq)]
+pub(crate) enum EntryKey {
+    Location(Range),
+    Line(u32, u32),
+    Index(u32),
+}
+
+/// Creates keys of the entries of a coverage in its order.
+pub(crate) struct EntryKeys {
+    key: MergeKey,
+    index: u32,
+    lines: HashMap<u32, u32>,
+}
+
+impl EntryKeys {
+    pub(crate) fn new(key: MergeKey) -> EntryKeys {
+        EntryKeys {
+            key,
+            index: 0,
+            lines: Default::default(),
+        }
+    }
+
+    pub(crate) fn next(&mut self, range: &Range) -> EntryKey {
+        let index = self.index;
+        self.index += 1;
+
+        match self.key {
+            MergeKey::Location => EntryKey::Location(Range {
+                skip: false,
+                ..*range
+            }),
+            MergeKey::Line => {
+                let order = self.lines.entry(range.start.line).or_insert(0);
+                *order += 1;
+                EntryKey::Line(range.start.line, *order - 1)
+            }
+            MergeKey::Index => EntryKey::Index(index),
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use indexmap::IndexMap;
+
+use crate::{CoverageMap, FileCoverage, LineHitMap, Totals};
+
+/// Parses added or modified lines of each file from a unified diff (i.e `git diff`),
+/// keyed by the path of the new file without `b/` prefix. Deleted files are not included.
+pub fn parse_changed_lines(diff: &str) -> IndexMap<String, Vec<u32>> {
+    let mut ret: IndexMap<String, Vec<u32>> = Default::default();
+    let mut path: Option<String> = None;
+    // Line number of the new file, and remaining lines of the hunk for (old, new) files.
+    let mut line = 0;
+    let mut remaining: (u32, u32) = (0, 0);
+
+    for diff_line in diff.lines() {
+        if remaining.0 > 0 || remaining.1 > 0 {
+            match diff_line.chars().next() {
+                Some('+') => {
+                    if let Some(path) = &path {
+                        ret.entry(path.clone()).or_default().push(line);
+                    }
+                    line += 1;
+                    remaining.1 = remaining.1.saturating_sub(1);
+                }
+                Some('-') => remaining.0 = remaining.0.saturating_sub(1),
+                Some('\\') => {}
+                // Context line, some tools strip the leading space of empty lines.
+                _ => {
+                    line += 1;
+                    remaining = (remaining.0.saturating_sub(1), remaining.1.saturating_sub(1));
+                }
+            }
+            continue;
+        }
+
+        if let Some(new_path) = diff_line.strip_prefix("+++ ") {
+            let new_path = new_path.split('\t').next().unwrap_or_default().trim();
+            path = if new_path == "/dev/null" {
+                None
+            } else {
+                Some(new_path.strip_prefix("b/").unwrap_or(new_path).to_string())
+            };
+        } else if let Some(hunk) = diff_line.strip_prefix("@@ ") {
+            // @@ -old_start[,old_count] +new_start[,new_count] @@
+            let mut ranges = hunk.split(' ').take(2).map(|range| {
+                let mut values = range[1..].split(',').map(|v| v.parse::<u32>().unwrap_or(0));
+                let start = values.next().unwrap_or(0);
+                (start, values.next().unwrap_or(1))
+            });
+            let (_, old_count) = ranges.next().unwrap_or_default();
+            let (new_start, new_count) = ranges.next().unwrap_or_default();
+
+            line = new_start;
+            remaining = (old_count, new_count);
+        }
+    }
+
+    ret
+}
+
+/// Coverage of the changed lines of a file. Changed lines without any statement
+/// (i.e comments, blank lines) are not counted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FilePatchCoverage {
+    pub covered_lines: Vec<u32>,
+    pub uncovered_lines: Vec<u32>,
+}
+
+impl FilePatchCoverage {
+    pub fn totals(&self) -> Totals {
+        let lines: LineHitMap = self
+            .covered_lines
+            .iter()
+            .map(|line| (*line, 1))
+            .chain(self.uncovered_lines.iter().map(|line| (*line, 0)))
+            .collect();
+        FileCoverage::compute_simple_totals(&lines)
+    }
+}
+
+/// Coverage of the changed lines (patch coverage), same as diff-cover.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PatchCoverage {
+    /// Coverage of the changed files keyed by the path in the diff. Files without
+    /// coverage (i.e not instrumented) are not included.
+    pub files: IndexMap<String, FilePatchCoverage>,
+}
+
+/// Returns true if the coverage path is the path in the diff, which is usually relative
+/// to the root of the repository while coverage path is absolute.
+fn is_same_path(coverage_path: &str, diff_path: &str) -> bool {
+    coverage_path == diff_path
+        || coverage_path
+            .strip_suffix(diff_path)
+            .is_some_and(|prefix| prefix.ends_with('/') || prefix.ends_with('\\'))
+}
+
+impl PatchCoverage {
+    /// Computes the coverage of given changed lines keyed by the path of each file.
+    pub fn from_changed_lines(
+        coverage_map: &CoverageMap,
+        changed_lines: &IndexMap<String, Vec<u32>>,
+    ) -> PatchCoverage {
+        let mut files: IndexMap<String, FilePatchCoverage> = Default::default();
+
+        for (diff_path, lines) in changed_lines {
+            let coverage = coverage_map
+                .get_files()
+                .into_iter()
+                .find(|path| is_same_path(path, diff_path))
+                .and_then(|path| coverage_map.get_coverage_for_file(path));
+
+            if let Some(coverage) = coverage {
+                let line_coverage = coverage.get_line_coverage();
+                let mut file = FilePatchCoverage::default();
+
+                for line in lines {
+                    match line_coverage.get(line) {
+                        Some(0) => file.uncovered_lines.push(*line),
+                        Some(_) => file.covered_lines.push(*line),
+                        None => {}
+                    }
+                }
+
+                files.insert(diff_path.clone(), file);
+            }
+        }
+
+        PatchCoverage { files }
+    }
+
+    /// Computes the coverage of the changed lines of given unified diff.
+    pub fn from_diff(coverage_map: &CoverageMap, diff: &str) -> PatchCoverage {
+        PatchCoverage::from_changed_lines(coverage_map, &parse_changed_lines(diff))
+    }
+
+    /// Totals of the changed lines of all files, percentage is the patch coverage.
+    pub fn totals(&self) -> Totals {
+        let mut ret = Totals::default();
+        for file in self.files.values() {
+            ret.merge(&file.totals());
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use crate::{CoverageMap, CoveragePercentage, FileCoverage, Range};
+
+    use super::{parse_changed_lines, FilePatchCoverage, PatchCoverage};
+
+    const DIFF: &str = "diff --git a/src/foo.js b/src/foo.js
+index 1111111..2222222 100644
+--- a/src/foo.js
++++ b/src/foo.js
+@@ -1,3 +1,6 @@
+ const a = 1;
+-const b = 2;
++const b = 3;
++// comment
++const c = 4;
+ const d = 5;
++++counter;
+@@ -10 +12,0 @@
+-removed();
+diff --git a/src/removed.js b/src/removed.js
+deleted file mode 100644
+--- a/src/removed.js
++++ /dev/null
+@@ -1 +0,0 @@
+-removed();
+diff --git a/src/bar.js b/src/bar.js
+new file mode 100644
+--- /dev/null
++++ b/src/bar.js
+@@ -0,0 +1,2 @@
++const e = 6;
++const f = 7;
+\\ No newline at end of file
+";
+
+    #[test]
+    fn should_parse_changed_lines() {
+        assert_eq!(
+            parse_changed_lines(DIFF),
+            IndexMap::from([
+                ("src/foo.js".to_string(), vec![2, 3, 4, 6]),
+                ("src/bar.js".to_string(), vec![1, 2]),
+            ])
+        );
+    }
+
+    #[test]
+    fn should_compute_patch_coverage() {
+        let mut coverage = FileCoverage::from_file_path("/repo/src/foo.js".to_string(), false);
+        for (idx, (line, hits)) in [(1, 1), (2, 1), (4, 0), (5, 1), (6, 0)].iter().enumerate() {
+            coverage
+                .statement_map
+                .insert(idx as u32, Range::new(*line, 0, *line, 10));
+            coverage.s.insert(idx as u32, *hits);
+        }
+        let coverage_map = CoverageMap::from_iter(vec![
+            &coverage,
+            &FileCoverage::from_file_path("/repo/other/src/bar.js".to_string(), false),
+        ]);
+
+        let patch_coverage = PatchCoverage::from_diff(&coverage_map, DIFF);
+
+        assert_eq!(
+            patch_coverage.files,
+            IndexMap::from([
+                (
+                    "src/foo.js".to_string(),
+                    FilePatchCoverage {
+                        covered_lines: vec![2],
+                        uncovered_lines: vec![4, 6],
+                    }
+                ),
+                ("src/bar.js".to_string(), FilePatchCoverage::default()),
+            ])
+        );
+
+        let totals = patch_coverage.totals();
+        assert_eq!((totals.total, totals.covered), (3, 1));
+        assert_eq!(totals.pct, CoveragePercentage::Value(33.33));
+    }
+}
@@ -0,0 +1,184 @@
+//! Python bindings of the coverage utilities, to load / merge coverage and check the thresholds
+//! in CI driven by Python without Node.js. Built as `istanbul_oxide` module with maturin.
+use std::fs;
+
+use pyo3::{
+    exceptions::{PyIOError, PyValueError},
+    prelude::*,
+    types::PyDict,
+};
+
+use crate::{CoverageMap, CoveragePercentage, CoverageSummary, Totals};
+
+/// Metrics of the summary in the order istanbul reports those.
+const METRICS: [&str; 4] = ["statements", "branches", "functions", "lines"];
+
+fn get_totals<'a>(summary: &'a CoverageSummary, metric: &str) -> &'a Totals {
+    match metric {
+        "statements" => &summary.statements,
+        "branches" => &summary.branches,
+        "functions" => &summary.functions,
+        "lines" => &summary.lines,
+        _ => unreachable!("Unknown metric {}", metric),
+    }
+}
+
+fn totals_to_dict<'py>(py: Python<'py>, totals: &Totals) -> PyResult<&'py PyDict> {
+    let dict = PyDict::new(py);
+    dict.set_item("total", totals.total)?;
+    dict.set_item("covered", totals.covered)?;
+    dict.set_item("skipped", totals.skipped)?;
+    match totals.pct {
+        CoveragePercentage::Value(pct) => dict.set_item("pct", pct)?,
+        CoveragePercentage::Unknown => dict.set_item("pct", py.None())?,
+    }
+    Ok(dict)
+}
+
+/// Messages of the metrics below the threshold, same as nyc's `check-coverage`.
+/// Metrics without any items (`Unknown` percentage) pass.
+fn check_thresholds(summary: &CoverageSummary, thresholds: &[(&str, Option<f32>)]) -> Vec<String> {
+    thresholds
+        .iter()
+        .filter_map(|(metric, threshold)| {
+            let threshold = (*threshold)?;
+            match get_totals(summary, metric).pct {
+                CoveragePercentage::Value(pct) if pct < threshold => Some(format!(
+                    "Coverage for {} ({}%) does not meet global threshold ({}%)",
+                    metric, pct, threshold
+                )),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Coverage of the files keyed by the path, same as `coverage-final.json`.
+#[pyclass(name = "CoverageMap")]
+#[derive(Clone, Default)]
+pub struct PyCoverageMap {
+    inner: CoverageMap,
+}
+
+#[pymethods]
+impl PyCoverageMap {
+    #[new]
+    fn new() -> PyCoverageMap {
+        Default::default()
+    }
+
+    #[staticmethod]
+    fn from_json(value: &str) -> PyResult<PyCoverageMap> {
+        CoverageMap::from_json(value)
+            .map(|inner| PyCoverageMap { inner })
+            .map_err(|err| PyValueError::new_err(format!("Invalid coverage: {}", err)))
+    }
+
+    fn to_json(&self) -> String {
+        self.inner.to_json()
+    }
+
+    /// Merges the other coverage into this one, updating hit counts.
+    fn merge(&mut self, other: &PyCoverageMap) {
+        self.inner.merge(&other.inner);
+    }
+
+    fn files(&self) -> Vec<String> {
+        self.inner.get_files().into_iter().cloned().collect()
+    }
+
+    /// Summary of all files as a dict of `statements`, `branches`, `functions`, `lines`, each with
+    /// `total`, `covered`, `skipped` and `pct` (`None` if there's nothing to cover).
+    fn summary<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let summary = self.inner.get_coverage_summary();
+        let dict = PyDict::new(py);
+        for metric in METRICS {
+            dict.set_item(metric, totals_to_dict(py, get_totals(&summary, metric))?)?;
+        }
+        Ok(dict)
+    }
+
+    /// Returns the messages of the metrics below given threshold percentages, empty if all passed.
+    #[args(
+        statements = "None",
+        branches = "None",
+        functions = "None",
+        lines = "None"
+    )]
+    fn check_thresholds(
+        &self,
+        statements: Option<f32>,
+        branches: Option<f32>,
+        functions: Option<f32>,
+        lines: Option<f32>,
+    ) -> Vec<String> {
+        check_thresholds(
+            &self.inner.get_coverage_summary(),
+            &[
+                ("statements", statements),
+                ("branches", branches),
+                ("functions", functions),
+                ("lines", lines),
+            ],
+        )
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.get_files().len()
+    }
+}
+
+/// Reads the coverage json (i.e `coverage-final.json`).
+#[pyfunction]
+fn load(path: &str) -> PyResult<PyCoverageMap> {
+    let value = fs::read_to_string(path)
+        .map_err(|err| PyIOError::new_err(format!("Failed to read {}: {}", path, err)))?;
+    PyCoverageMap::from_json(&value)
+}
+
+/// Merges the coverages into a new one, in the given order.
+#[pyfunction]
+fn merge(maps: Vec<PyRef<PyCoverageMap>>) -> PyCoverageMap {
+    PyCoverageMap {
+        inner: CoverageMap::merge_all(maps.iter().map(|map| map.inner.clone())),
+    }
+}
+
+#[pymodule]
+fn istanbul_oxide(_py: Python, module: &PyModule) -> PyResult<()> {
+    module.add_class::<PyCoverageMap>()?;
+    module.add_function(wrap_pyfunction!(load, module)?)?;
+    module.add_function(wrap_pyfunction!(merge, module)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CoveragePercentage, CoverageSummary, Totals};
+
+    use super::check_thresholds;
+
+    #[test]
+    fn should_check_thresholds() {
+        let summary = CoverageSummary::new(
+            Totals::new(4, 4, 0, CoveragePercentage::Value(100.0)),
+            Totals::new(4, 3, 0, CoveragePercentage::Value(75.0)),
+            Totals::default(),
+            Totals::default(),
+            None,
+        );
+
+        assert_eq!(
+            check_thresholds(
+                &summary,
+                &[
+                    ("statements", Some(80.0)),
+                    ("branches", Some(80.0)),
+                    ("functions", None),
+                    ("lines", Some(80.0)),
+                ],
+            ),
+            vec!["Coverage for statements (75%) does not meet global threshold (80%)".to_string()]
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Location {
     pub line: u32,
     pub column: u32,
@@ -11,10 +11,18 @@ impl Location {
     }
 }
 
-#[derive(Copy, Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Range {
     pub start: Location,
     pub end: Location,
+    /// Set if the code of the range is excluded from the instrumentation.
+    /// Skipped entries are counted as `skipped` instead of `total` in the summary.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub skip: bool,
 }
 
 impl Range {
@@ -22,6 +30,7 @@ impl Range {
         Range {
             start: Default::default(),
             end: Default::default(),
+            skip: false,
         }
     }
     pub fn new(start_line: u32, start_column: u32, end_line: u32, end_column: u32) -> Range {
@@ -34,6 +43,14 @@ impl Range {
                 line: end_line,
                 column: end_column,
             },
+            skip: false,
         }
     }
+
+    /// Check if given location is within the range, inclusive.
+    pub fn contains(&self, location: &Location) -> bool {
+        let position = (location.line, location.column);
+        (self.start.line, self.start.column) <= position
+            && position <= (self.end.line, self.end.column)
+    }
 }
@@ -0,0 +1,584 @@
+use indexmap::IndexMap;
+
+use crate::{
+    source_map_consumer::{Bias, OriginalPosition, SourceMapConsumer},
+    Branch, FileCoverage, Function, Location, Range, SourceMap,
+};
+
+/// Range mapped to the original source. End column is None if the range extends to the end of the line.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct MappedRange {
+    source: u32,
+    start: Location,
+    end_line: u32,
+    end_column: Option<u32>,
+}
+
+fn original_position_try_both(
+    consumer: &SourceMapConsumer,
+    line: u32,
+    column: u32,
+) -> Option<OriginalPosition> {
+    consumer
+        .original_position_for(line, column, Bias::GreatestLowerBound)
+        .or_else(|| consumer.original_position_for(line, column, Bias::LeastUpperBound))
+}
+
+/// Original end of the range ends at given generated position. The mapping covering the end
+/// gives the start of the range in the original source, so the end is the start of the next mapping.
+fn original_end_position_for(
+    consumer: &SourceMapConsumer,
+    end: &Location,
+) -> Option<(u32, u32, Option<u32>)> {
+    let before_end = original_position_try_both(consumer, end.line, end.column.checked_sub(1)?)?;
+
+    let after_end =
+        consumer.generated_position_for(before_end.source, before_end.line, before_end.column + 1);
+    let after_end = after_end.and_then(|(line, column)| {
+        consumer.original_position_for(line, column, Bias::GreatestLowerBound)
+    });
+
+    match after_end {
+        // Next mapping is on the other line or there's none, range extends to the end of the line.
+        Some(after_end) if after_end.line == before_end.line => {
+            Some((after_end.source, after_end.line, Some(after_end.column)))
+        }
+        _ => Some((before_end.source, before_end.line, None)),
+    }
+}
+
+/// Maps the range of the generated code to the original source, same as istanbul-lib-source-maps'
+/// `getMapping`. Returns None if either end can't be mapped, or ends are in different sources.
+fn get_mapping(consumer: &SourceMapConsumer, range: &Range) -> Option<MappedRange> {
+    let start = original_position_try_both(consumer, range.start.line, range.start.column)?;
+    let (end_source, mut end_line, mut end_column) =
+        original_end_position_for(consumer, &range.end)?;
+
+    if start.source != end_source {
+        return None;
+    }
+
+    if start.line == end_line && Some(start.column) == end_column {
+        let end = consumer.original_position_for(
+            range.end.line,
+            range.end.column,
+            Bias::LeastUpperBound,
+        )?;
+        end_line = end.line;
+        end_column = Some(end.column.checked_sub(1)?);
+    }
+
+    Some(MappedRange {
+        source: start.source,
+        start: Location {
+            line: start.line,
+            column: start.column,
+        },
+        end_line,
+        end_column,
+    })
+}
+
+/// Normalizes `.` / `..` of the path lexically, with `/` separators.
+fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let is_absolute = path.starts_with('/');
+    let mut components: Vec<&str> = vec![];
+
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                if matches!(components.last(), Some(last) if *last != "..") {
+                    components.pop();
+                } else if !is_absolute {
+                    components.push(component);
+                }
+            }
+            _ => components.push(component),
+        }
+    }
+
+    let joined = components.join("/");
+    if is_absolute {
+        format!("/{}", joined)
+    } else {
+        joined
+    }
+}
+
+fn is_absolute_path(path: &str) -> bool {
+    path.starts_with('/')
+        || path.starts_with('\\')
+        || path
+            .as_bytes()
+            .get(1..3)
+            .is_some_and(|prefix| prefix[0] == b':' && (prefix[1] == b'/' || prefix[1] == b'\\'))
+}
+
+/// Path of the original source, relative to the generated file unless it's absolute.
+/// `sourceRoot` is prepended to the source, same as `source-map`.
+fn resolve_source_path(source_map: &SourceMap, source: u32, file_path: &str) -> Option<String> {
+    let source = source_map.sources.get(source as usize)?;
+    let source = match source_map.source_root.as_deref() {
+        Some(root) if !root.is_empty() && !is_absolute_path(source) => {
+            format!("{}/{}", root.trim_end_matches('/'), source)
+        }
+        _ => source.clone(),
+    };
+
+    let path = if is_absolute_path(&source) {
+        source
+    } else {
+        match file_path.replace('\\', "/").rsplit_once('/') {
+            Some((dir, _)) => format!("{}/{}", dir, source),
+            None => source,
+        }
+    };
+
+    Some(normalize_path(&path))
+}
+
+/// Coverage of an original source, collects the entries mapped from the generated files.
+/// Entries mapped to the same range are merged, same as istanbul-lib-source-maps' `MappedCoverage`.
+struct MappedCoverage {
+    coverage: FileCoverage,
+    // Content of each line of the source if the source map has it, to resolve the end of the line.
+    lines: Option<Vec<u32>>,
+    seen: IndexMap<String, u32>,
+}
+
+fn loc_key(range: &Range) -> String {
+    format!(
+        "{}:{}:{}:{}",
+        range.start.line, range.start.column, range.end.line, range.end.column
+    )
+}
+
+impl MappedCoverage {
+    fn new(path: String, report_logic: bool, content: Option<&str>) -> MappedCoverage {
+        MappedCoverage {
+            coverage: FileCoverage::from_file_path(path, report_logic),
+            lines: content.map(|content| {
+                content
+                    .split('\n')
+                    .map(|line| line.trim_end_matches('\r').chars().count() as u32)
+                    .collect()
+            }),
+            seen: Default::default(),
+        }
+    }
+
+    /// Range of the mapped range, range extends to the end of the line ends at the length of the line
+    /// if the content is known. Otherwise the end column is `u32::MAX`, same as `Infinity` of istanbul.
+    fn to_range(&self, mapped: &MappedRange, skip: bool) -> Range {
+        let end_column = mapped.end_column.unwrap_or_else(|| {
+            self.lines
+                .as_ref()
+                .and_then(|lines| lines.get(mapped.end_line as usize - 1).copied())
+                .unwrap_or(u32::MAX)
+        });
+
+        Range {
+            start: mapped.start,
+            end: Location {
+                line: mapped.end_line,
+                column: end_column,
+            },
+            skip,
+        }
+    }
+
+    fn add_statement(&mut self, range: Range, hits: u32) {
+        let key = loc_key(&range);
+        let coverage = &mut self.coverage;
+        let id = *self.seen.entry(key).or_insert_with(|| {
+            let id = coverage.statement_map.len() as u32;
+            coverage.statement_map.insert(id, range);
+            coverage.s.insert(id, 0);
+            id
+        });
+
+        let statement = coverage.statement_map.get_mut(&id).expect("Should exist");
+        statement.skip = statement.skip && range.skip;
+        *coverage.s.entry(id).or_default() += hits;
+    }
+
+    fn add_function(&mut self, function: &Function, decl: Range, loc: Range, hits: u32) {
+        let key = format!("f:{}", loc_key(&decl));
+        let coverage = &mut self.coverage;
+        let id = *self.seen.entry(key).or_insert_with(|| {
+            let id = coverage.fn_map.len() as u32;
+            let name = if function.name.is_empty() {
+                format!("(unknown_{})", id)
+            } else {
+                function.name.clone()
+            };
+            coverage.fn_map.insert(
+                id,
+                Function {
+                    name,
+                    decl,
+                    loc,
+                    line: loc.start.line,
+                    export_name: function.export_name.clone(),
+                    metadata: function.metadata,
+                },
+            );
+            coverage.f.insert(id, 0);
+            id
+        });
+
+        *coverage.f.entry(id).or_default() += hits;
+    }
+
+    fn add_branch(
+        &mut self,
+        branch: &Branch,
+        locations: Vec<Range>,
+        hits: &[u32],
+        b_t: Option<&[u32]>,
+    ) {
+        let key = std::iter::once("br".to_string())
+            .chain(locations.iter().map(loc_key))
+            .collect::<Vec<_>>()
+            .join(":");
+        let coverage = &mut self.coverage;
+        let id = *self.seen.entry(key).or_insert_with(|| {
+            let id = coverage.branch_map.len() as u32;
+            coverage.branch_map.insert(
+                id,
+                Branch::from_loc(branch.branch_type, locations[0], locations.clone()),
+            );
+            coverage.b.insert(id, vec![0; locations.len()]);
+            id
+        });
+
+        let add = |counts: &mut Vec<u32>, hits: &[u32]| {
+            counts
+                .iter_mut()
+                .zip(hits.iter())
+                .for_each(|(count, hits)| *count += hits);
+        };
+        add(coverage.b.entry(id).or_default(), hits);
+        if let (Some(coverage_b_t), Some(b_t)) = (coverage.b_t.as_mut(), b_t) {
+            add(
+                coverage_b_t
+                    .entry(id)
+                    .or_insert_with(|| vec![0; locations.len()]),
+                b_t,
+            );
+        }
+    }
+}
+
+/// Maps the coverage of a generated file through its `inputSourceMap` into the coverages of
+/// each original source, same as istanbul-lib-source-maps' `SourceMapTransformer`.
+/// Entries can't be mapped are dropped.
+pub(crate) fn remap_file_coverage(
+    coverage: &FileCoverage,
+    source_map: &SourceMap,
+) -> IndexMap<String, FileCoverage> {
+    let consumer = SourceMapConsumer::new(source_map);
+    let report_logic = coverage.b_t.is_some();
+    let mut mapped: IndexMap<u32, MappedCoverage> = Default::default();
+
+    let get_mapped_coverage = |mapped: &mut IndexMap<u32, MappedCoverage>, source: u32| {
+        if !mapped.contains_key(&source) {
+            let path = resolve_source_path(source_map, source, &coverage.path)?;
+            let content = source_map
+                .sources_content
+                .as_ref()
+                .and_then(|contents| contents.get(source as usize))
+                .and_then(|content| content.as_deref());
+            mapped.insert(source, MappedCoverage::new(path, report_logic, content));
+        }
+        Some(())
+    };
+
+    for (id, range) in coverage.statement_map.iter() {
+        if let Some(mapping) = get_mapping(&consumer, range) {
+            let hits = coverage.s.get(id).copied().unwrap_or_default();
+            if get_mapped_coverage(&mut mapped, mapping.source).is_some() {
+                let mapped_coverage = mapped
+                    .get_mut(&mapping.source)
+                    .expect("Should have mapped coverage");
+                let range = mapped_coverage.to_range(&mapping, range.skip);
+                mapped_coverage.add_statement(range, hits);
+            }
+        }
+    }
+
+    for (id, function) in coverage.fn_map.iter() {
+        let decl = get_mapping(&consumer, &function.decl);
+        let loc = get_mapping(&consumer, &function.loc);
+        if let (Some(decl), Some(loc)) = (decl, loc) {
+            if decl.source != loc.source {
+                continue;
+            }
+
+            let hits = coverage.f.get(id).copied().unwrap_or_default();
+            if get_mapped_coverage(&mut mapped, decl.source).is_some() {
+                let mapped_coverage = mapped
+                    .get_mut(&decl.source)
+                    .expect("Should have mapped coverage");
+                let decl = mapped_coverage.to_range(&decl, function.decl.skip);
+                let loc = mapped_coverage.to_range(&loc, function.loc.skip);
+                mapped_coverage.add_function(function, decl, loc, hits);
+            }
+        }
+    }
+
+    for (id, branch) in coverage.branch_map.iter() {
+        let hits = coverage.b.get(id).map(Vec::as_slice).unwrap_or_default();
+        let b_t = coverage
+            .b_t
+            .as_ref()
+            .and_then(|b_t| b_t.get(id))
+            .map(Vec::as_slice);
+
+        let mut source = None;
+        let mut mappings = vec![];
+        let mut mapped_hits = vec![];
+        let mut mapped_b_t = vec![];
+        for (index, location) in branch.locations.iter().enumerate() {
+            if let Some(mapping) = get_mapping(&consumer, location) {
+                if *source.get_or_insert(mapping.source) != mapping.source {
+                    source = None;
+                    mappings.clear();
+                    break;
+                }
+
+                mappings.push((mapping, location.skip));
+                mapped_hits.push(hits.get(index).copied().unwrap_or_default());
+                if let Some(b_t) = b_t {
+                    mapped_b_t.push(b_t.get(index).copied().unwrap_or_default());
+                }
+            }
+        }
+
+        let source = match source {
+            Some(source) if !mappings.is_empty() => source,
+            _ => continue,
+        };
+        if get_mapped_coverage(&mut mapped, source).is_some() {
+            let mapped_coverage = mapped
+                .get_mut(&source)
+                .expect("Should have mapped coverage");
+            let locations = mappings
+                .iter()
+                .map(|(mapping, skip)| mapped_coverage.to_range(mapping, *skip))
+                .collect();
+            mapped_coverage.add_branch(
+                branch,
+                locations,
+                &mapped_hits,
+                b_t.map(|_| mapped_b_t.as_slice()),
+            );
+        }
+    }
+
+    mapped
+        .into_values()
+        .map(|mapped| (mapped.coverage.path.clone(), mapped.coverage))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Branch, BranchType, CoverageMap, FileCoverage, Function, Range, SourceMap};
+
+    use super::normalize_path;
+
+    /// Encodes the segments of each generated line, (generated column, source, original line, original column)
+    /// with 0-based lines.
+    fn encode_mappings(lines: &[&[(i64, i64, i64, i64)]]) -> String {
+        fn encode_value(value: i64, ret: &mut String) {
+            const CHARS: &[u8] =
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+            let mut value = if value < 0 {
+                (-value << 1) | 1
+            } else {
+                value << 1
+            };
+            loop {
+                let mut digit = value & 31;
+                value >>= 5;
+                if value > 0 {
+                    digit |= 32;
+                }
+                ret.push(CHARS[digit as usize] as char);
+                if value == 0 {
+                    break;
+                }
+            }
+        }
+
+        let mut previous = (0, 0, 0);
+        lines
+            .iter()
+            .map(|segments| {
+                let mut column = 0;
+                segments
+                    .iter()
+                    .map(|(generated_column, source, line, original_column)| {
+                        let mut ret = String::new();
+                        encode_value(generated_column - column, &mut ret);
+                        encode_value(source - previous.0, &mut ret);
+                        encode_value(line - previous.1, &mut ret);
+                        encode_value(original_column - previous.2, &mut ret);
+                        column = *generated_column;
+                        previous = (*source, *line, *original_column);
+                        ret
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+
+    #[test]
+    fn should_filter_paths_before_or_after_remap() {
+        // dist/a.js, compiled from src/a.ts
+        let mut coverage = FileCoverage::from_file_path("/repo/dist/a.js".to_string(), false);
+        coverage.input_source_map = Some(SourceMap {
+            sources: vec!["../src/a.ts".to_string()],
+            mappings: encode_mappings(&[&[(0, 0, 0, 0), (12, 0, 0, 12)]]),
+            ..Default::default()
+        });
+        coverage.statement_map.insert(0, Range::new(1, 0, 1, 12));
+        coverage.s.insert(0, 1);
+
+        let map = CoverageMap::from_iter(vec![&coverage]);
+
+        assert_eq!(
+            map.remap_and_filter_paths(&["src/**"], true).get_files(),
+            vec!["/repo/src/a.ts"]
+        );
+        assert!(map
+            .remap_and_filter_paths(&["src/**"], false)
+            .get_files()
+            .is_empty());
+        assert_eq!(
+            map.remap_and_filter_paths(&["dist/**"], false).get_files(),
+            vec!["/repo/src/a.ts"]
+        );
+        assert!(map
+            .remap_and_filter_paths(&["!**/*.ts"], true)
+            .get_files()
+            .is_empty());
+    }
+
+    #[test]
+    fn should_normalize_path() {
+        assert_eq!(normalize_path("/repo/dist/../src/./a.ts"), "/repo/src/a.ts");
+        assert_eq!(normalize_path("dist/../../a.ts"), "../a.ts");
+        assert_eq!(normalize_path("C:\\repo\\dist\\..\\a.ts"), "C:/repo/a.ts");
+    }
+
+    #[test]
+    fn should_remap_to_original_sources() {
+        // dist/a.js, compiled from src/a.ts (first 2 lines) and src/b.ts (last line):
+        // const a = 1;
+        // function f(x) { return x; }
+        // g();
+        let source_map = SourceMap {
+            sources: vec!["../src/a.ts".to_string(), "../src/b.ts".to_string()],
+            sources_content: Some(vec![
+                Some("const a: number = 1;\nfunction f(x: number) { return x; }\n".to_string()),
+                None,
+            ]),
+            mappings: encode_mappings(&[
+                &[(0, 0, 0, 0), (6, 0, 0, 6), (8, 0, 0, 16), (11, 0, 0, 19)],
+                &[
+                    (0, 0, 1, 0),
+                    (9, 0, 1, 9),
+                    (11, 0, 1, 11),
+                    (12, 0, 1, 20),
+                    (14, 0, 1, 22),
+                    (16, 0, 1, 24),
+                    (23, 0, 1, 31),
+                    (24, 0, 1, 32),
+                    (26, 0, 1, 34),
+                ],
+                &[(0, 1, 4, 2), (3, 1, 4, 5)],
+            ]),
+            ..Default::default()
+        };
+
+        let mut coverage = FileCoverage::from_file_path("/repo/dist/a.js".to_string(), false);
+        coverage.input_source_map = Some(source_map);
+        for (id, (range, hits)) in [
+            (Range::new(1, 0, 1, 12), 1),
+            (Range::new(2, 16, 2, 25), 2),
+            (Range::new(3, 0, 3, 4), 3),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            coverage.statement_map.insert(id as u32, range);
+            coverage.s.insert(id as u32, hits);
+        }
+        coverage.fn_map.insert(
+            0,
+            Function {
+                name: "f".to_string(),
+                decl: Range::new(2, 9, 2, 10),
+                loc: Range::new(2, 0, 2, 27),
+                line: 2,
+                export_name: None,
+                metadata: None,
+            },
+        );
+        coverage.f.insert(0, 2);
+        // Locations in the different sources can't be mapped.
+        coverage.branch_map.insert(
+            0,
+            Branch::from_loc(
+                BranchType::BinaryExpr,
+                Range::new(2, 16, 3, 4),
+                vec![Range::new(2, 16, 2, 25), Range::new(3, 0, 3, 4)],
+            ),
+        );
+        coverage.b.insert(0, vec![1, 0]);
+
+        let unmapped = FileCoverage::from_file_path("/repo/dist/c.js".to_string(), false);
+        let remapped =
+            CoverageMap::from_iter(vec![&coverage, &unmapped]).remap_with_input_source_maps();
+
+        assert_eq!(
+            remapped.get_files(),
+            vec!["/repo/src/a.ts", "/repo/src/b.ts", "/repo/dist/c.js"]
+        );
+
+        let a = remapped.get_coverage_for_file("/repo/src/a.ts").unwrap();
+        // Statement ends at the end of the line, resolved by `sourcesContent`.
+        assert_eq!(
+            a.statement_map.values().copied().collect::<Vec<_>>(),
+            vec![Range::new(1, 0, 1, 20), Range::new(2, 24, 2, 34)]
+        );
+        assert_eq!(a.s.values().copied().collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(
+            a.fn_map[&0],
+            Function {
+                name: "f".to_string(),
+                decl: Range::new(2, 9, 2, 11),
+                loc: Range::new(2, 0, 2, 35),
+                line: 2,
+                export_name: None,
+                metadata: None,
+            }
+        );
+        assert_eq!(a.f[&0], 2);
+        assert!(a.branch_map.is_empty());
+
+        let b = remapped.get_coverage_for_file("/repo/src/b.ts").unwrap();
+        // Without `sourcesContent`, end of the line is u32::MAX same as istanbul's Infinity.
+        assert_eq!(
+            b.statement_map.values().copied().collect::<Vec<_>>(),
+            vec![Range::new(5, 2, 5, u32::MAX)]
+        );
+        assert_eq!(b.s[&0], 3);
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    fs::{create_dir_all, File},
+    io::{self, BufWriter, Write},
+};
+
+use crate::{CoveragePercentage, CoverageSummary, FileCoverage};
+
+use super::{
+    CoverageLevel, Metric, NumberFormat, NumberFormatter, ReportContext, ReportWriter, Reporter,
+    Watermarks,
+};
+
+/// Name of the file badge reporter writes into.
+pub const BADGE_REPORT_FILE_NAME: &str = "coverage-badge.svg";
+
+/// Writes a shields.io style SVG badge of the coverage of all files, colored by the watermark.
+/// Shows line coverage by default.
+pub struct BadgeReporter<W: Write> {
+    writer: ReportWriter<W>,
+    label: String,
+    metric: Metric,
+    watermarks: Watermarks,
+}
+
+impl BadgeReporter<BufWriter<File>> {
+    /// Creates a reporter writes into `coverage-badge.svg` under the context's directory.
+    pub fn create(context: &ReportContext) -> io::Result<BadgeReporter<BufWriter<File>>> {
+        create_dir_all(&context.dir)?;
+        let file = File::create(context.dir.join(BADGE_REPORT_FILE_NAME))?;
+        Ok(BadgeReporter::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> BadgeReporter<W> {
+    pub fn new(writer: W) -> BadgeReporter<W> {
+        BadgeReporter {
+            writer: ReportWriter::new(writer),
+            label: "coverage".to_string(),
+            metric: Metric::Lines,
+            watermarks: Default::default(),
+        }
+    }
+
+    pub fn with_label(mut self, label: &str) -> BadgeReporter<W> {
+        self.label = label.to_string();
+        self
+    }
+
+    pub fn with_metric(mut self, metric: Metric) -> BadgeReporter<W> {
+        self.metric = metric;
+        self
+    }
+
+    pub fn with_watermarks(mut self, watermarks: Watermarks) -> BadgeReporter<W> {
+        self.watermarks = watermarks;
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+
+    /// Renders the badge of given summary.
+    pub fn render(&self, summary: &CoverageSummary) -> String {
+        let value = match self.metric.get_totals(summary).pct {
+            CoveragePercentage::Value(value) => {
+                format!("{}%", NumberFormat::new().format_pct(value))
+            }
+            CoveragePercentage::Unknown => "unknown".to_string(),
+        };
+        let color = match self.watermarks.classify(self.metric, summary) {
+            CoverageLevel::Low => "#e05d44",
+            CoverageLevel::Medium => "#dfb317",
+            CoverageLevel::High => "#4c1",
+            CoverageLevel::Unknown => "#9f9f9f",
+        };
+
+        render_badge(&self.label, &value, color)
+    }
+}
+
+/// Approximate width of the text in Verdana 11px, shields.io's font.
+fn text_width(value: &str) -> u32 {
+    value.chars().count() as u32 * 7 + 10
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_badge(label: &str, value: &str, color: &str) -> String {
+    let label_width = text_width(label);
+    let value_width = text_width(value);
+    let width = label_width + value_width;
+    let (label, value) = (escape_xml(label), escape_xml(value));
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {value}">
+<title>{label}: {value}</title>
+<linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient>
+<clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath>
+<g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="#555"/><rect x="{label_width}" width="{value_width}" height="20" fill="{color}"/><rect width="{width}" height="20" fill="url(#s)"/></g>
+<g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11"><text x="{label_x}" y="14">{label}</text><text x="{value_x}" y="14">{value}</text></g>
+</svg>
+"##,
+        label_x = label_width / 2,
+        value_x = label_width + value_width / 2,
+    )
+}
+
+impl<W: Write> Reporter for BadgeReporter<W> {
+    fn on_file(&mut self, _coverage: &FileCoverage) {}
+
+    fn on_end(&mut self, summary: &CoverageSummary) {
+        let badge = self.render(summary);
+        self.writer.write(&badge);
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        report::{report, Metric, Watermark, Watermarks},
+        CoverageMap, CoveragePercentage, CoverageSummary, FileCoverage, Range, Totals,
+    };
+
+    use super::BadgeReporter;
+
+    fn summary_with_lines(pct: CoveragePercentage) -> CoverageSummary {
+        CoverageSummary {
+            lines: Totals::new(0, 0, 0, pct),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_color_by_watermarks() {
+        let reporter = BadgeReporter::new(Vec::new());
+
+        let badge = reporter.render(&summary_with_lines(CoveragePercentage::Value(49.5)));
+        assert!(badge.contains(r#"aria-label="coverage: 49.5%""#));
+        assert!(badge.contains("#e05d44"));
+
+        let badge = reporter.render(&summary_with_lines(CoveragePercentage::Value(50.0)));
+        assert!(badge.contains("#dfb317"));
+
+        let badge = reporter.render(&summary_with_lines(CoveragePercentage::Value(80.0)));
+        assert!(badge.contains(r##"fill="#4c1""##));
+
+        let badge = reporter.render(&summary_with_lines(CoveragePercentage::Unknown));
+        assert!(badge.contains("coverage: unknown"));
+        assert!(badge.contains("#9f9f9f"));
+    }
+
+    #[test]
+    fn should_render_with_options() {
+        let watermarks = Watermarks {
+            branches: Watermark::new(90.0, 95.0),
+            ..Default::default()
+        };
+        let reporter = BadgeReporter::new(Vec::new())
+            .with_label("branches <main>")
+            .with_metric(Metric::Branches)
+            .with_watermarks(watermarks);
+
+        let summary = CoverageSummary {
+            branches: Totals::new(10, 9, 0, CoveragePercentage::Value(90.0)),
+            ..Default::default()
+        };
+        let badge = reporter.render(&summary);
+        assert!(badge.contains("<title>branches &lt;main&gt;: 90%</title>"));
+        assert!(badge.contains("#dfb317"));
+    }
+
+    #[test]
+    fn should_write_badge_of_all_files() {
+        let mut coverage = FileCoverage::from_file_path("foo.js".to_string(), false);
+        coverage.statement_map.insert(0, Range::new(1, 0, 1, 10));
+        coverage.s.insert(0, 1);
+        let coverage_map = CoverageMap::from_iter(vec![&coverage]);
+
+        let mut reporter = BadgeReporter::new(Vec::new());
+        report(&coverage_map, &mut reporter).expect("Should write report");
+        let badge = String::from_utf8(reporter.into_inner()).expect("Should be utf8");
+
+        assert!(badge.starts_with("<svg"));
+        assert!(badge.contains("coverage: 100%"));
+    }
+}
@@ -0,0 +1,293 @@
+use std::{
+    fs::{create_dir_all, File},
+    io::{self, BufWriter, Write},
+};
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::{
+    BranchHitMap, BranchMap, CoverageSummary, FileCoverage, FunctionMap, LineHitMap, SourceMap,
+    StatementMap,
+};
+
+use super::{FileRenderer, ReportContext, ReportWriter, Reporter};
+
+/// Name of the file json reporter writes into, same as istanbul-reports.
+pub const JSON_REPORT_FILE_NAME: &str = "coverage-final.json";
+
+/// Serialization shape of each file coverage in `coverage-final.json`.
+/// Field order follows the object istanbul-lib-instrument creates, so the
+/// output is byte-compatible with istanbul's json reporter.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonFileCoverage<'a> {
+    path: &'a str,
+    statement_map: &'a StatementMap,
+    fn_map: &'a FunctionMap,
+    branch_map: &'a BranchMap,
+    s: &'a LineHitMap,
+    f: &'a LineHitMap,
+    b: &'a BranchHitMap,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    b_t: Option<&'a BranchHitMap>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    l: Option<LineHitMap>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    input_source_map: Option<&'a SourceMap>,
+    #[serde(rename = "_coverageSchema", skip_serializing_if = "Option::is_none")]
+    coverage_schema: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash: Option<&'a str>,
+    #[serde(flatten)]
+    extras: IndexMap<&'a str, &'a serde_json::Value>,
+    // nyc only marks files added by `--all`, appending it after the instrumented data.
+    #[serde(skip_serializing_if = "is_false")]
+    all: bool,
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+impl<'a> JsonFileCoverage<'a> {
+    fn new(coverage: &'a FileCoverage, compact: bool, legacy_lines: bool) -> JsonFileCoverage<'a> {
+        JsonFileCoverage {
+            path: &coverage.path,
+            statement_map: &coverage.statement_map,
+            fn_map: &coverage.fn_map,
+            branch_map: &coverage.branch_map,
+            s: &coverage.s,
+            f: &coverage.f,
+            b: &coverage.b,
+            b_t: coverage.b_t.as_ref(),
+            l: if legacy_lines {
+                Some(coverage.get_line_coverage())
+            } else {
+                None
+            },
+            input_source_map: if compact {
+                None
+            } else {
+                coverage.input_source_map.as_ref()
+            },
+            coverage_schema: coverage.coverage_schema.as_deref(),
+            hash: coverage.hash.as_deref(),
+            // Computed `l` replaces the one from the other tools.
+            extras: coverage
+                .extras
+                .iter()
+                .filter(|(key, _)| !(legacy_lines && key.as_str() == "l"))
+                .map(|(key, value)| (key.as_str(), value))
+                .collect(),
+            all: coverage.all,
+        }
+    }
+}
+
+/// Writes the coverage map as `coverage-final.json`.
+/// Compact variant omits inputSourceMap of each file, which is usually
+/// the largest part of the report and not needed once coverage is collected.
+pub struct JsonReporter<W: Write> {
+    writer: ReportWriter<W>,
+    compact: bool,
+    legacy_lines: bool,
+    first: bool,
+}
+
+impl JsonReporter<BufWriter<File>> {
+    /// Creates a reporter writes into `coverage-final.json` under the context's directory.
+    pub fn create(
+        context: &ReportContext,
+        compact: bool,
+    ) -> io::Result<JsonReporter<BufWriter<File>>> {
+        create_dir_all(&context.dir)?;
+        let file = File::create(context.dir.join(JSON_REPORT_FILE_NAME))?;
+        Ok(JsonReporter::new(BufWriter::new(file), compact))
+    }
+}
+
+impl<W: Write> JsonReporter<W> {
+    pub fn new(writer: W, compact: bool) -> JsonReporter<W> {
+        JsonReporter {
+            writer: ReportWriter::new(writer),
+            compact,
+            legacy_lines: false,
+            first: true,
+        }
+    }
+
+    /// Include the deprecated `l` (line hits) of each file computed from the statements,
+    /// for the legacy tools still reading it.
+    pub fn with_legacy_lines(mut self) -> JsonReporter<W> {
+        self.legacy_lines = true;
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+}
+
+/// Renders the entry of a file in `coverage-final.json`.
+struct JsonFileRenderer {
+    compact: bool,
+    legacy_lines: bool,
+}
+
+impl FileRenderer for JsonFileRenderer {
+    fn render(&self, coverage: &FileCoverage) -> String {
+        let path = serde_json::to_string(&coverage.path).expect("Should able to serialize path");
+        let value = serde_json::to_string(&JsonFileCoverage::new(
+            coverage,
+            self.compact,
+            self.legacy_lines,
+        ))
+        .expect("Should able to serialize coverage");
+        format!("{}: {}\n", path, value)
+    }
+}
+
+impl<W: Write> Reporter for JsonReporter<W> {
+    fn on_start(&mut self, _summary: &CoverageSummary) {
+        self.first = true;
+    }
+
+    fn on_file(&mut self, coverage: &FileCoverage) {
+        let rendered = JsonFileRenderer {
+            compact: self.compact,
+            legacy_lines: self.legacy_lines,
+        }
+        .render(coverage);
+        self.on_rendered_file(coverage, rendered);
+    }
+
+    fn file_renderer(&self) -> Option<Box<dyn FileRenderer>> {
+        Some(Box::new(JsonFileRenderer {
+            compact: self.compact,
+            legacy_lines: self.legacy_lines,
+        }))
+    }
+
+    fn on_rendered_file(&mut self, _coverage: &FileCoverage, rendered: String) {
+        if self.first {
+            self.first = false;
+            self.writer.write("{");
+        } else {
+            self.writer.write(",");
+        }
+        self.writer.write(&rendered);
+    }
+
+    fn on_end(&mut self, _summary: &CoverageSummary) {
+        if self.first {
+            self.writer.write("{");
+        }
+        self.writer.write("}\n");
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use crate::{
+        report::{report, report_with_jobs},
+        CoverageMap, FileCoverage,
+    };
+
+    use super::JsonReporter;
+
+    // `nyc --reporter=json` output for a small typescript project: input source map
+    // from tsc, branch truthiness from `reportLogic`, and a file included by `--all`.
+    const NYC_COVERAGE_FINAL: &str = include_str!("../../fixtures/nyc/coverage-final.json");
+
+    fn read_fixture() -> CoverageMap {
+        let files: IndexMap<String, FileCoverage> =
+            serde_json::from_str(NYC_COVERAGE_FINAL).expect("Should parse fixture");
+        CoverageMap::from_iter(files.values())
+    }
+
+    fn write_report(coverage_map: &CoverageMap, compact: bool) -> String {
+        let mut reporter = JsonReporter::new(Vec::new(), compact);
+        report(coverage_map, &mut reporter).expect("Should write report");
+        String::from_utf8(reporter.into_inner()).expect("Should be utf8")
+    }
+
+    #[test]
+    fn should_roundtrip_nyc_coverage_final() {
+        let coverage_map = read_fixture();
+        assert_eq!(write_report(&coverage_map, false), NYC_COVERAGE_FINAL);
+    }
+
+    #[test]
+    fn should_omit_input_source_map_in_compact() {
+        let coverage_map = read_fixture();
+        let compact = write_report(&coverage_map, true);
+
+        assert!(!compact.contains("inputSourceMap"));
+
+        let files: IndexMap<String, FileCoverage> =
+            serde_json::from_str(&compact).expect("Should parse compact report");
+        for (path, coverage) in files {
+            let mut expected = coverage_map
+                .get_coverage_for_file(&path)
+                .expect("Should exist")
+                .clone();
+            expected.input_source_map = None;
+            assert_eq!(coverage, expected);
+        }
+    }
+
+    #[test]
+    fn should_roundtrip_unknown_fields() {
+        let coverage = r#"{"/a.js": {"path":"/a.js","statementMap":{"0":{"start":{"line":1,"column":0},"end":{"line":1,"column":10}}},"fnMap":{},"branchMap":{},"s":{"0":1},"f":{},"b":{},"l":{"1":1},"_coverageSchema":"1a1c01bbd47fc00a2c39e90264f33305004495a9","hash":"a","contentHash":"b"}}"#;
+        let files: IndexMap<String, FileCoverage> =
+            serde_json::from_str(coverage).expect("Should parse coverage");
+        let coverage_map = CoverageMap::from_iter(files.values());
+
+        let written: serde_json::Value =
+            serde_json::from_str(&write_report(&coverage_map, false)).unwrap();
+        let file = &written["/a.js"];
+        assert_eq!(file["contentHash"], "b");
+        assert_eq!(file["l"], serde_json::json!({"1": 1}));
+    }
+
+    #[test]
+    fn should_write_legacy_lines() {
+        let coverage = r#"{"/a.js": {"path":"/a.js","statementMap":{"0":{"start":{"line":1,"column":0},"end":{"line":1,"column":10}},"1":{"start":{"line":3,"column":0},"end":{"line":3,"column":10}}},"fnMap":{},"branchMap":{},"s":{"0":2,"1":0},"f":{},"b":{},"l":{"1":5}}}"#;
+        let files: IndexMap<String, FileCoverage> =
+            serde_json::from_str(coverage).expect("Should parse coverage");
+        let coverage_map = CoverageMap::from_iter(files.values());
+
+        let mut reporter = JsonReporter::new(Vec::new(), false).with_legacy_lines();
+        report(&coverage_map, &mut reporter).expect("Should write report");
+        let written = String::from_utf8(reporter.into_inner()).unwrap();
+        assert_eq!(written.matches(r#""l":"#).count(), 1);
+
+        let written: serde_json::Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(written["/a.js"]["l"], serde_json::json!({"1": 2, "3": 0}));
+    }
+
+    #[test]
+    fn should_write_same_report_with_jobs() {
+        let coverage_map = read_fixture();
+        for jobs in [1, 2, 8] {
+            let mut reporter = JsonReporter::new(Vec::new(), false);
+            report_with_jobs(&coverage_map, &mut reporter, jobs).expect("Should write report");
+            assert_eq!(
+                String::from_utf8(reporter.into_inner()).unwrap(),
+                NYC_COVERAGE_FINAL
+            );
+        }
+    }
+
+    #[test]
+    fn should_write_empty_object() {
+        assert_eq!(write_report(&CoverageMap::default(), false), "{}\n");
+    }
+}
@@ -0,0 +1,106 @@
+use std::{
+    fs::{create_dir_all, File},
+    io::{self, BufWriter, Write},
+};
+
+use crate::{CoverageSummary, FileCoverage};
+
+use super::{ReportContext, ReportWriter, Reporter};
+
+/// Name of the file json summary reporter writes into, same as istanbul-reports.
+pub const JSON_SUMMARY_REPORT_FILE_NAME: &str = "coverage-summary.json";
+
+/// Writes the summary of all files as `total`, followed by the summary of each file
+/// as `coverage-summary.json`. Percentage of the empty summary is written as `"Unknown"`.
+pub struct JsonSummaryReporter<W: Write> {
+    writer: ReportWriter<W>,
+}
+
+impl JsonSummaryReporter<BufWriter<File>> {
+    /// Creates a reporter writes into `coverage-summary.json` under the context's directory.
+    pub fn create(context: &ReportContext) -> io::Result<JsonSummaryReporter<BufWriter<File>>> {
+        create_dir_all(&context.dir)?;
+        let file = File::create(context.dir.join(JSON_SUMMARY_REPORT_FILE_NAME))?;
+        Ok(JsonSummaryReporter::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> JsonSummaryReporter<W> {
+    pub fn new(writer: W) -> JsonSummaryReporter<W> {
+        JsonSummaryReporter {
+            writer: ReportWriter::new(writer),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+
+    fn write_summary(&mut self, key: &str, summary: &CoverageSummary) {
+        let key = serde_json::to_string(key).expect("Should able to serialize path");
+        self.writer.write(&key);
+        self.writer.write(": ");
+        self.writer.write(&summary.to_json());
+        self.writer.write("\n");
+    }
+}
+
+impl<W: Write> Reporter for JsonSummaryReporter<W> {
+    fn on_start(&mut self, summary: &CoverageSummary) {
+        self.writer.write("{");
+        self.write_summary("total", summary);
+    }
+
+    fn on_file(&mut self, coverage: &FileCoverage) {
+        self.writer.write(",");
+        self.write_summary(&coverage.path, &coverage.to_summary());
+    }
+
+    fn on_end(&mut self, _summary: &CoverageSummary) {
+        self.writer.write("}\n");
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{report::report, CoverageMap, FileCoverage, Range};
+
+    use super::JsonSummaryReporter;
+
+    fn write_report(coverage_map: &CoverageMap) -> String {
+        let mut reporter = JsonSummaryReporter::new(Vec::new());
+        report(coverage_map, &mut reporter).expect("Should write report");
+        String::from_utf8(reporter.into_inner()).expect("Should be utf8")
+    }
+
+    #[test]
+    fn should_write_summary() {
+        let mut coverage = FileCoverage::from_file_path("src/a.js".to_string(), false);
+        coverage.statement_map.insert(0, Range::new(1, 0, 1, 10));
+        coverage.statement_map.insert(1, Range::new(2, 0, 2, 10));
+        coverage.statement_map.insert(2, Range::new(3, 0, 3, 10));
+        coverage.s.insert(0, 1);
+        coverage.s.insert(1, 1);
+        coverage.s.insert(2, 0);
+
+        let coverage_map = CoverageMap::from_iter(vec![&coverage]);
+
+        let file = r#"{"lines":{"total":3,"covered":2,"skipped":0,"pct":66.66},"statements":{"total":3,"covered":2,"skipped":0,"pct":66.66},"functions":{"total":0,"covered":0,"skipped":0,"pct":100},"branches":{"total":0,"covered":0,"skipped":0,"pct":100}}"#;
+        assert_eq!(
+            write_report(&coverage_map),
+            format!("{{\"total\": {}\n,\"src/a.js\": {}\n}}\n", file, file)
+        );
+    }
+
+    #[test]
+    fn should_write_unknown_for_empty_coverage() {
+        assert_eq!(
+            write_report(&CoverageMap::default()),
+            "{\"total\": {\"lines\":{\"total\":0,\"covered\":0,\"skipped\":0,\"pct\":\"Unknown\"},\"statements\":{\"total\":0,\"covered\":0,\"skipped\":0,\"pct\":\"Unknown\"},\"functions\":{\"total\":0,\"covered\":0,\"skipped\":0,\"pct\":\"Unknown\"},\"branches\":{\"total\":0,\"covered\":0,\"skipped\":0,\"pct\":\"Unknown\"}}\n}\n"
+        );
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    fs::{create_dir_all, File},
+    io::{self, BufWriter, Write},
+};
+
+use indexmap::IndexMap;
+
+use crate::{CoverageMap, CoveragePercentage, CoverageSummary, FileCoverage};
+
+use super::{NumberFormat, NumberFormatter, ReportContext, ReportWriter, Reporter};
+
+/// Name of the file markdown reporter writes into.
+pub const MARKDOWN_REPORT_FILE_NAME: &str = "coverage-summary.md";
+
+/// Writes a GitHub-flavored Markdown table of the coverage per file, with a totals row.
+/// If a baseline coverage map is given, an additional column shows the difference of
+/// line coverage against the baseline.
+pub struct MarkdownReporter<W: Write> {
+    writer: ReportWriter<W>,
+    baseline: Option<Baseline>,
+    formatter: Box<dyn NumberFormatter>,
+}
+
+struct Baseline {
+    files: IndexMap<String, CoverageSummary>,
+    total: CoverageSummary,
+}
+
+impl MarkdownReporter<BufWriter<File>> {
+    /// Creates a reporter writes into `coverage-summary.md` under the context's directory.
+    pub fn create(context: &ReportContext) -> io::Result<MarkdownReporter<BufWriter<File>>> {
+        create_dir_all(&context.dir)?;
+        let file = File::create(context.dir.join(MARKDOWN_REPORT_FILE_NAME))?;
+        Ok(MarkdownReporter::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write> MarkdownReporter<W> {
+    pub fn new(writer: W) -> MarkdownReporter<W> {
+        MarkdownReporter {
+            writer: ReportWriter::new(writer),
+            baseline: None,
+            formatter: Box::new(NumberFormat::new()),
+        }
+    }
+
+    /// Format the percentages with given formatter, i.e `NumberFormat` with the separators
+    /// of the readers' locale. Numbers are written as-is by default.
+    pub fn with_number_formatter<F: NumberFormatter + 'static>(
+        mut self,
+        formatter: F,
+    ) -> MarkdownReporter<W> {
+        self.formatter = Box::new(formatter);
+        self
+    }
+
+    /// Compare coverage against given baseline, i.e coverage of the target branch of the PR.
+    pub fn with_baseline(mut self, baseline: &CoverageMap) -> MarkdownReporter<W> {
+        let files = baseline
+            .get_files()
+            .into_iter()
+            .map(|path| {
+                let summary = baseline
+                    .get_coverage_for_file(path)
+                    .expect("Coverage for the file should exist")
+                    .to_summary();
+                (path.clone(), summary)
+            })
+            .collect();
+
+        self.baseline = Some(Baseline {
+            files,
+            total: baseline.get_coverage_summary(),
+        });
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+
+    fn write_row(&mut self, name: &str, summary: &CoverageSummary, delta: Option<String>) {
+        let mut row = format!(
+            "| {} | {} | {} | {} | {} |",
+            name,
+            self.format_pct(&summary.statements.pct),
+            self.format_pct(&summary.branches.pct),
+            self.format_pct(&summary.functions.pct),
+            self.format_pct(&summary.lines.pct)
+        );
+
+        if let Some(delta) = delta {
+            row.push_str(&format!(" {} |", delta));
+        }
+
+        row.push('\n');
+        self.writer.write(&row);
+    }
+
+    fn format_pct(&self, pct: &CoveragePercentage) -> String {
+        match pct {
+            CoveragePercentage::Value(value) => self.formatter.format_pct(*value),
+            CoveragePercentage::Unknown => "-".to_string(),
+        }
+    }
+
+    fn format_delta(&self, current: &CoveragePercentage, baseline: &CoveragePercentage) -> String {
+        match (current, baseline) {
+            (CoveragePercentage::Value(current), CoveragePercentage::Value(baseline)) => {
+                let delta = ((current - baseline) * 100.0).round() / 100.0;
+                self.formatter.format_delta(delta)
+            }
+            _ => "-".to_string(),
+        }
+    }
+}
+
+/// Escape characters breaking table cells.
+fn escape_cell(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+impl<W: Write> Reporter for MarkdownReporter<W> {
+    fn on_start(&mut self, _summary: &CoverageSummary) {
+        if self.baseline.is_some() {
+            self.writer.write(
+                "| File | % Stmts | % Branch | % Funcs | % Lines | Δ % Lines |\n\
+                 | :--- | ---: | ---: | ---: | ---: | ---: |\n",
+            );
+        } else {
+            self.writer.write(
+                "| File | % Stmts | % Branch | % Funcs | % Lines |\n\
+                 | :--- | ---: | ---: | ---: | ---: |\n",
+            );
+        }
+    }
+
+    fn on_file(&mut self, coverage: &FileCoverage) {
+        let summary = coverage.to_summary();
+        let name = format!("`{}`", escape_cell(&coverage.path));
+
+        let delta =
+            self.baseline
+                .as_ref()
+                .map(|baseline| match baseline.files.get(&coverage.path) {
+                    Some(baseline) => self.format_delta(&summary.lines.pct, &baseline.lines.pct),
+                    None => "new".to_string(),
+                });
+        self.write_row(&name, &summary, delta);
+    }
+
+    fn on_end(&mut self, summary: &CoverageSummary) {
+        let delta = self
+            .baseline
+            .as_ref()
+            .map(|baseline| self.format_delta(&summary.lines.pct, &baseline.total.lines.pct));
+        self.write_row("**All files**", summary, delta);
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use crate::{report::report, CoverageMap, FileCoverage, Range};
+
+    use super::MarkdownReporter;
+    use crate::report::NumberFormat;
+
+    fn file_coverage(path: &str, hits: &[u32]) -> FileCoverage {
+        let mut coverage = FileCoverage::from_file_path(path.to_string(), false);
+        coverage.statement_map = hits
+            .iter()
+            .enumerate()
+            .map(|(idx, _)| {
+                (
+                    idx as u32,
+                    Range::new(idx as u32 + 1, 0, idx as u32 + 1, 10),
+                )
+            })
+            .collect();
+        coverage.s =
+            IndexMap::from_iter(hits.iter().enumerate().map(|(idx, hit)| (idx as u32, *hit)));
+        coverage
+    }
+
+    fn write_report(reporter: MarkdownReporter<Vec<u8>>, coverage_map: &CoverageMap) -> String {
+        let mut reporter = reporter;
+        report(coverage_map, &mut reporter).expect("Should write report");
+        String::from_utf8(reporter.into_inner()).expect("Should be utf8")
+    }
+
+    #[test]
+    fn should_write_table() {
+        let coverage_map = CoverageMap::from_iter(vec![
+            &file_coverage("src/a.js", &[1, 1, 0, 0]),
+            &file_coverage("src/b|c.js", &[1]),
+        ]);
+
+        assert_eq!(
+            write_report(MarkdownReporter::new(Vec::new()), &coverage_map),
+            "| File | % Stmts | % Branch | % Funcs | % Lines |\n\
+             | :--- | ---: | ---: | ---: | ---: |\n\
+             | `src/a.js` | 50 | 100 | 100 | 50 |\n\
+             | `src/b\\|c.js` | 100 | 100 | 100 | 100 |\n\
+             | **All files** | 60 | 100 | 100 | 60 |\n"
+        );
+    }
+
+    #[test]
+    fn should_write_unknown_for_empty_coverage() {
+        assert_eq!(
+            write_report(MarkdownReporter::new(Vec::new()), &CoverageMap::default()),
+            "| File | % Stmts | % Branch | % Funcs | % Lines |\n\
+             | :--- | ---: | ---: | ---: | ---: |\n\
+             | **All files** | - | - | - | - |\n"
+        );
+    }
+
+    #[test]
+    fn should_write_delta_against_baseline() {
+        let baseline = CoverageMap::from_iter(vec![&file_coverage("src/a.js", &[1, 0, 0, 0])]);
+        let coverage_map = CoverageMap::from_iter(vec![
+            &file_coverage("src/a.js", &[1, 1, 0, 0]),
+            &file_coverage("src/b.js", &[0]),
+        ]);
+
+        assert_eq!(
+            write_report(
+                MarkdownReporter::new(Vec::new()).with_baseline(&baseline),
+                &coverage_map
+            ),
+            "| File | % Stmts | % Branch | % Funcs | % Lines | Δ % Lines |\n\
+             | :--- | ---: | ---: | ---: | ---: | ---: |\n\
+             | `src/a.js` | 50 | 100 | 100 | 50 | +25 |\n\
+             | `src/b.js` | 0 | 100 | 100 | 0 | new |\n\
+             | **All files** | 40 | 100 | 100 | 40 | +15 |\n"
+        );
+    }
+
+    #[test]
+    fn should_write_with_number_formatter() {
+        let baseline = CoverageMap::from_iter(vec![&file_coverage("src/a.js", &[1, 0, 0])]);
+        let coverage_map = CoverageMap::from_iter(vec![&file_coverage("src/a.js", &[1, 1, 0])]);
+        let format = NumberFormat::new()
+            .with_decimal_separator(",")
+            .with_fraction_digits(1);
+
+        assert_eq!(
+            write_report(
+                MarkdownReporter::new(Vec::new())
+                    .with_baseline(&baseline)
+                    .with_number_formatter(format),
+                &coverage_map
+            ),
+            "| File | % Stmts | % Branch | % Funcs | % Lines | Δ % Lines |\n\
+             | :--- | ---: | ---: | ---: | ---: | ---: |\n\
+             | `src/a.js` | 66,7 | 100,0 | 100,0 | 66,7 | +33,3 |\n\
+             | **All files** | 66,7 | 100,0 | 100,0 | 66,7 | +33,3 |\n"
+        );
+    }
+}
@@ -0,0 +1,388 @@
+//! Extension points to generate reports from the coverage data.
+//! This mimics istanbul-lib-report's ReportBase interface, with a registry to
+//! allow to plug in custom reporters by its name.
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use indexmap::IndexMap;
+
+use crate::{CoverageMap, CoverageSummary, FileCoverage};
+
+mod badge;
+mod json;
+mod json_summary;
+mod markdown;
+mod number_format;
+mod watermarks;
+pub use badge::{BadgeReporter, BADGE_REPORT_FILE_NAME};
+pub use json::{JsonReporter, JSON_REPORT_FILE_NAME};
+pub use json_summary::{JsonSummaryReporter, JSON_SUMMARY_REPORT_FILE_NAME};
+pub use markdown::{MarkdownReporter, MARKDOWN_REPORT_FILE_NAME};
+pub use number_format::{NumberFormat, NumberFormatter};
+pub use watermarks::{CoverageLevel, Metric, Watermark, Watermarks};
+
+/// A reporter receives coverage of each files, then the summary for the all files.
+pub trait Reporter {
+    /// Called once before visiting any files, with the summary of all files.
+    fn on_start(&mut self, _summary: &CoverageSummary) {}
+
+    /// Called for each file in the order of the coverage map.
+    fn on_file(&mut self, coverage: &FileCoverage);
+
+    /// Renderer of the part of the report for each file, if it doesn't depend on the other files.
+    /// [report_with_jobs] renders files with it on multiple threads, then passes the output to
+    /// [Reporter::on_rendered_file] instead of calling [Reporter::on_file].
+    fn file_renderer(&self) -> Option<Box<dyn FileRenderer>> {
+        None
+    }
+
+    /// Called for each file in the order of the coverage map, with the output of
+    /// [Reporter::file_renderer].
+    fn on_rendered_file(&mut self, coverage: &FileCoverage, _rendered: String) {
+        self.on_file(coverage);
+    }
+
+    /// Called once after all files are visited, with the summary of all files.
+    fn on_end(&mut self, summary: &CoverageSummary);
+
+    /// Flush the output and returns the first error occurred while writing the report, if any.
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Renders the part of the report for a file, shared between the threads.
+pub trait FileRenderer: Send + Sync {
+    fn render(&self, coverage: &FileCoverage) -> String;
+}
+
+/// Options shared by the reporters created from the registry.
+#[derive(Clone, Debug, Default)]
+pub struct ReportContext {
+    /// Directory to write report files into.
+    pub dir: PathBuf,
+}
+
+impl ReportContext {
+    pub fn new(dir: impl Into<PathBuf>) -> ReportContext {
+        ReportContext { dir: dir.into() }
+    }
+}
+
+/// Writer for the reporters, holds the first io error occurred since [Reporter]'s
+/// callbacks can't return it. The error is returned from [Reporter::finish].
+pub(crate) struct ReportWriter<W: Write> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> ReportWriter<W> {
+    pub(crate) fn new(writer: W) -> ReportWriter<W> {
+        ReportWriter {
+            writer,
+            error: None,
+        }
+    }
+
+    pub(crate) fn write(&mut self, value: &str) {
+        if self.error.is_none() {
+            if let Err(e) = self.writer.write_all(value.as_bytes()) {
+                self.error = Some(e);
+            }
+        }
+    }
+
+    pub(crate) fn finish(&mut self) -> io::Result<()> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        self.writer.flush()
+    }
+
+    pub(crate) fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Creates a new instance of the reporter.
+pub type ReporterFactory = Box<dyn Fn(&ReportContext) -> io::Result<Box<dyn Reporter>>>;
+
+/// A registry of reporter factories keyed by its name.
+#[derive(Default)]
+pub struct ReporterRegistry {
+    inner: IndexMap<String, ReporterFactory>,
+}
+
+impl ReporterRegistry {
+    pub fn new() -> ReporterRegistry {
+        ReporterRegistry {
+            inner: Default::default(),
+        }
+    }
+
+    /// Register a reporter factory. Registering a factory with the existing name
+    /// replaces previous one.
+    /// Creates a registry with the reporters this crate provides:
+    /// `json` (`coverage-final.json`), `json-compact` (same, without inputSourceMap),
+    /// `json-summary` (`coverage-summary.json`), `markdown` and `badge` (`coverage-badge.svg`).
+    pub fn with_builtins() -> ReporterRegistry {
+        let mut registry = ReporterRegistry::new();
+        registry.register(
+            "json",
+            Box::new(|context| Ok(Box::new(JsonReporter::create(context, false)?))),
+        );
+        registry.register(
+            "json-compact",
+            Box::new(|context| Ok(Box::new(JsonReporter::create(context, true)?))),
+        );
+        registry.register(
+            "json-summary",
+            Box::new(|context| Ok(Box::new(JsonSummaryReporter::create(context)?))),
+        );
+        registry.register(
+            "markdown",
+            Box::new(|context| Ok(Box::new(MarkdownReporter::create(context)?))),
+        );
+        registry.register(
+            "badge",
+            Box::new(|context| Ok(Box::new(BadgeReporter::create(context)?))),
+        );
+        registry
+    }
+
+    pub fn register(&mut self, name: &str, factory: ReporterFactory) {
+        self.inner.insert(name.to_string(), factory);
+    }
+
+    pub fn get_names(&self) -> Vec<&String> {
+        self.inner.keys().collect()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.inner.contains_key(name)
+    }
+
+    /// Creates a new reporter instance for the given name if it's registered.
+    pub fn create(
+        &self,
+        name: &str,
+        context: &ReportContext,
+    ) -> Option<io::Result<Box<dyn Reporter>>> {
+        self.inner.get(name).map(|factory| factory(context))
+    }
+}
+
+/// Visit coverage map with given reporter.
+pub fn report(coverage_map: &CoverageMap, reporter: &mut dyn Reporter) -> io::Result<()> {
+    let summary = coverage_map.get_coverage_summary();
+
+    reporter.on_start(&summary);
+    for file in coverage_map.get_files() {
+        let coverage = coverage_map
+            .get_coverage_for_file(file)
+            .expect("Coverage for the file should exist");
+        reporter.on_file(coverage);
+    }
+    reporter.on_end(&summary);
+    reporter.finish()
+}
+
+/// Number of files rendered per job at once by [report_all], to bound the memory holding
+/// the rendered output not written yet.
+const FILES_PER_JOB: usize = 64;
+
+/// Visit coverage map with given reporter same as [report], rendering files on `jobs` threads
+/// if the reporter provides [Reporter::file_renderer] and `parallel` feature is enabled.
+/// Rendered output is passed to the reporter in the order of the coverage map, so the report
+/// is identical to the one of [report] regardless of `jobs`.
+pub fn report_with_jobs(
+    coverage_map: &CoverageMap,
+    reporter: &mut dyn Reporter,
+    jobs: usize,
+) -> io::Result<()> {
+    report_all(coverage_map, &mut [reporter], jobs)
+}
+
+/// Visit coverage map with multiple reporters in a single pass, sharing the summary computed once.
+/// Each reporter receives the files in the order of the coverage map, rendered on `jobs` threads
+/// same as [report_with_jobs]. Every reporter is finished even if the others failed, returns
+/// the first error occurred.
+pub fn report_all(
+    coverage_map: &CoverageMap,
+    reporters: &mut [&mut dyn Reporter],
+    jobs: usize,
+) -> io::Result<()> {
+    let renderers: Vec<Option<Box<dyn FileRenderer>>> = reporters
+        .iter()
+        .map(|reporter| {
+            if jobs > 1 {
+                reporter.file_renderer()
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    #[cfg(feature = "parallel")]
+    let pool = if renderers.iter().any(Option::is_some) {
+        Some(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(jobs)
+                .build()
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?,
+        )
+    } else {
+        None
+    };
+
+    let summary = coverage_map.get_coverage_summary();
+    let files: Vec<&FileCoverage> = coverage_map
+        .get_files()
+        .into_iter()
+        .map(|file| {
+            coverage_map
+                .get_coverage_for_file(file)
+                .expect("Coverage for the file should exist")
+        })
+        .collect();
+
+    for reporter in reporters.iter_mut() {
+        reporter.on_start(&summary);
+    }
+    for chunk in files.chunks(jobs.max(1) * FILES_PER_JOB) {
+        for (reporter, renderer) in reporters.iter_mut().zip(&renderers) {
+            let renderer = match renderer {
+                Some(renderer) => renderer,
+                None => {
+                    for coverage in chunk {
+                        reporter.on_file(coverage);
+                    }
+                    continue;
+                }
+            };
+
+            #[cfg(feature = "parallel")]
+            let rendered: Vec<String> = {
+                use rayon::prelude::*;
+                pool.as_ref().expect("Pool should exist").install(|| {
+                    chunk
+                        .par_iter()
+                        .map(|coverage| renderer.render(coverage))
+                        .collect()
+                })
+            };
+
+            #[cfg(not(feature = "parallel"))]
+            let rendered: Vec<String> = chunk
+                .iter()
+                .map(|coverage| renderer.render(coverage))
+                .collect();
+
+            for (coverage, rendered) in chunk.iter().zip(rendered) {
+                reporter.on_rendered_file(coverage, rendered);
+            }
+        }
+    }
+
+    let mut result = Ok(());
+    for reporter in reporters.iter_mut() {
+        reporter.on_end(&summary);
+        let finished = reporter.finish();
+        if result.is_ok() {
+            result = finished;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{CoverageMap, CoverageSummary, FileCoverage};
+
+    use super::{report, report_all, ReportContext, Reporter, ReporterRegistry};
+
+    struct TestReporter {
+        events: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Reporter for TestReporter {
+        fn on_start(&mut self, _summary: &CoverageSummary) {
+            self.events.borrow_mut().push("start".to_string());
+        }
+
+        fn on_file(&mut self, coverage: &FileCoverage) {
+            self.events.borrow_mut().push(coverage.path.clone());
+        }
+
+        fn on_end(&mut self, _summary: &CoverageSummary) {
+            self.events.borrow_mut().push("end".to_string());
+        }
+    }
+
+    #[test]
+    fn should_create_registered_reporter() {
+        let events: Rc<RefCell<Vec<String>>> = Default::default();
+        let mut registry = ReporterRegistry::new();
+
+        let reporter_events = events.clone();
+        registry.register(
+            "test",
+            Box::new(move |_| {
+                Ok(Box::new(TestReporter {
+                    events: reporter_events.clone(),
+                }))
+            }),
+        );
+
+        assert!(registry.contains("test"));
+        let context = ReportContext::default();
+        assert!(registry.create("unknown", &context).is_none());
+
+        let coverage_map = CoverageMap::from_iter(vec![
+            &FileCoverage::from_file_path("foo.js".to_string(), false),
+            &FileCoverage::from_file_path("bar.js".to_string(), false),
+        ]);
+
+        let mut reporter = registry
+            .create("test", &context)
+            .expect("Should find reporter")
+            .expect("Should create reporter");
+        report(&coverage_map, reporter.as_mut()).expect("Should report");
+
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                "start".to_string(),
+                "foo.js".to_string(),
+                "bar.js".to_string(),
+                "end".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_all_in_single_pass() {
+        let events: Rc<RefCell<Vec<String>>> = Default::default();
+        let coverage_map = CoverageMap::from_iter(vec![
+            &FileCoverage::from_file_path("foo.js".to_string(), false),
+            &FileCoverage::from_file_path("bar.js".to_string(), false),
+        ]);
+
+        let mut first = TestReporter {
+            events: events.clone(),
+        };
+        let mut second = TestReporter {
+            events: events.clone(),
+        };
+        report_all(&coverage_map, &mut [&mut first, &mut second], 4).expect("Should report");
+
+        assert_eq!(
+            *events.borrow(),
+            vec!["start", "start", "foo.js", "bar.js", "foo.js", "bar.js", "end", "end"]
+        );
+    }
+}
@@ -0,0 +1,140 @@
+/// Formats the numbers reporters write for humans, i.e to follow the locale of the readers.
+/// Closures `Fn(f32) -> String` can be used as a formatter of the percentages.
+pub trait NumberFormatter {
+    /// Formats a percentage in the range of 0 to 100, without the `%` sign.
+    fn format_pct(&self, pct: f32) -> String;
+
+    /// Formats a difference of the percentages with its sign, `0` if there's no difference.
+    fn format_delta(&self, delta: f32) -> String {
+        if delta > 0.0 {
+            format!("+{}", self.format_pct(delta))
+        } else if delta < 0.0 {
+            format!("-{}", self.format_pct(-delta))
+        } else {
+            self.format_pct(0.0)
+        }
+    }
+}
+
+impl<F: Fn(f32) -> String> NumberFormatter for F {
+    fn format_pct(&self, pct: f32) -> String {
+        self(pct)
+    }
+}
+
+/// Formats numbers with the given separators, i.e `NumberFormat::new().with_decimal_separator(",")`
+/// writes `33,33`. Default writes numbers as-is (`33.33`, `100`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct NumberFormat {
+    /// Separator between integer and fraction part.
+    pub decimal_separator: String,
+    /// Separator between each group of thousands of the integer part.
+    pub thousands_separator: String,
+    /// Number of the fraction digits to write, fraction is written as-is if not set.
+    pub fraction_digits: Option<usize>,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat {
+            decimal_separator: ".".to_string(),
+            thousands_separator: "".to_string(),
+            fraction_digits: None,
+        }
+    }
+}
+
+impl NumberFormat {
+    pub fn new() -> NumberFormat {
+        Default::default()
+    }
+
+    pub fn with_decimal_separator(mut self, separator: &str) -> NumberFormat {
+        self.decimal_separator = separator.to_string();
+        self
+    }
+
+    pub fn with_thousands_separator(mut self, separator: &str) -> NumberFormat {
+        self.thousands_separator = separator.to_string();
+        self
+    }
+
+    pub fn with_fraction_digits(mut self, digits: usize) -> NumberFormat {
+        self.fraction_digits = Some(digits);
+        self
+    }
+
+    /// Formats given number with the separators.
+    pub fn format(&self, value: f64) -> String {
+        let value = match self.fraction_digits {
+            Some(digits) => format!("{:.*}", digits, value),
+            None => value.to_string(),
+        };
+
+        let (sign, value) = match value.strip_prefix('-') {
+            Some(value) => ("-", value),
+            None => ("", value.as_str()),
+        };
+        let (integer, fraction) = match value.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (value, None),
+        };
+
+        let mut ret = sign.to_string();
+        for (idx, digit) in integer.chars().enumerate() {
+            if idx > 0 && (integer.len() - idx) % 3 == 0 {
+                ret.push_str(&self.thousands_separator);
+            }
+            ret.push(digit);
+        }
+
+        if let Some(fraction) = fraction {
+            ret.push_str(&self.decimal_separator);
+            ret.push_str(fraction);
+        }
+
+        ret
+    }
+}
+
+impl NumberFormatter for NumberFormat {
+    fn format_pct(&self, pct: f32) -> String {
+        // Widen without picking up the noise of f32 (`33.33f32 as f64` is `33.33000183105469`).
+        let value = pct.to_string().parse().unwrap_or(pct as f64);
+        self.format(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NumberFormat, NumberFormatter};
+
+    #[test]
+    fn should_format_as_is_by_default() {
+        let format = NumberFormat::new();
+        assert_eq!(format.format_pct(33.33), "33.33");
+        assert_eq!(format.format_pct(100.0), "100");
+        assert_eq!(format.format_delta(-2.5), "-2.5");
+        assert_eq!(format.format_delta(0.0), "0");
+    }
+
+    #[test]
+    fn should_format_with_separators() {
+        let format = NumberFormat::new()
+            .with_decimal_separator(",")
+            .with_thousands_separator(".")
+            .with_fraction_digits(2);
+        assert_eq!(format.format_pct(33.33), "33,33");
+        assert_eq!(format.format_pct(100.0), "100,00");
+        assert_eq!(format.format(1234567.5), "1.234.567,50");
+        assert_eq!(format.format(-1234.0), "-1.234,00");
+        assert_eq!(format.format_delta(12.5), "+12,50");
+    }
+
+    #[test]
+    fn should_use_closure_as_formatter() {
+        let format = |pct: f32| format!("{}%", pct);
+        assert_eq!(format.format_pct(50.0), "50%");
+        assert_eq!(format.format_delta(-50.0), "-50%");
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{CoveragePercentage, CoverageSummary, Totals};
+
+/// Metrics of the coverage summary.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Statements,
+    Branches,
+    Functions,
+    Lines,
+}
+
+impl Metric {
+    pub fn get_totals<'a>(&self, summary: &'a CoverageSummary) -> &'a Totals {
+        match self {
+            Metric::Statements => &summary.statements,
+            Metric::Branches => &summary.branches,
+            Metric::Functions => &summary.functions,
+            Metric::Lines => &summary.lines,
+        }
+    }
+}
+
+/// Level of the coverage classified by the watermark.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CoverageLevel {
+    Low,
+    Medium,
+    High,
+    /// Nothing to cover.
+    Unknown,
+}
+
+/// Thresholds of the percentage, coverage below `low` is low and coverage
+/// at or above `high` is high.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Watermark {
+    pub low: f32,
+    pub high: f32,
+}
+
+impl Watermark {
+    pub fn new(low: f32, high: f32) -> Watermark {
+        Watermark { low, high }
+    }
+
+    pub fn classify(&self, pct: &CoveragePercentage) -> CoverageLevel {
+        match pct {
+            CoveragePercentage::Value(value) if *value < self.low => CoverageLevel::Low,
+            CoveragePercentage::Value(value) if *value >= self.high => CoverageLevel::High,
+            CoveragePercentage::Value(_) => CoverageLevel::Medium,
+            CoveragePercentage::Unknown => CoverageLevel::Unknown,
+        }
+    }
+}
+
+/// Watermarks for each metric, defaults to istanbul's `[50, 80]`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Watermarks {
+    pub statements: Watermark,
+    pub branches: Watermark,
+    pub functions: Watermark,
+    pub lines: Watermark,
+}
+
+impl Default for Watermarks {
+    fn default() -> Self {
+        let watermark = Watermark::new(50.0, 80.0);
+        Watermarks {
+            statements: watermark,
+            branches: watermark,
+            functions: watermark,
+            lines: watermark,
+        }
+    }
+}
+
+impl Watermarks {
+    pub fn get(&self, metric: Metric) -> &Watermark {
+        match metric {
+            Metric::Statements => &self.statements,
+            Metric::Branches => &self.branches,
+            Metric::Functions => &self.functions,
+            Metric::Lines => &self.lines,
+        }
+    }
+
+    /// Classify the coverage of given metric of the summary.
+    pub fn classify(&self, metric: Metric, summary: &CoverageSummary) -> CoverageLevel {
+        self.get(metric).classify(&metric.get_totals(summary).pct)
+    }
+}
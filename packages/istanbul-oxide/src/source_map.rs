@@ -1,5 +1,7 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
+/// Source map (revision 3) of the input, i.e `inputSourceMap` of the coverage.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", default)]
 pub struct SourceMap {
@@ -13,6 +15,17 @@ pub struct SourceMap {
     pub sources_content: Option<Vec<Option<String>>>,
     pub names: Vec<String>,
     pub mappings: String,
+    /// Indices of the sources to be ignored by the debuggers / reports (i.e bundled dependencies).
+    /// Reads the earlier `x_google_ignoreList` as well.
+    #[serde(
+        default,
+        alias = "x_google_ignoreList",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ignore_list: Option<Vec<u32>>,
+    /// Fields not modeled here (i.e `debugId`, `x_*` extensions), preserved as-is.
+    #[serde(flatten)]
+    pub extras: IndexMap<String, serde_json::Value>,
 }
 
 impl Default for SourceMap {
@@ -25,6 +38,48 @@ impl Default for SourceMap {
             sources_content: Default::default(),
             names: Default::default(),
             mappings: "".to_string(),
+            ignore_list: Default::default(),
+            extras: Default::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::SourceMap;
+
+    #[test]
+    fn should_round_trip_all_fields() {
+        let value = serde_json::json!({
+            "version": 3,
+            "file": "out.js",
+            "sourceRoot": "/src",
+            "sources": ["a.ts", "b.ts"],
+            "sourcesContent": ["const a = 1;", null],
+            "names": ["a"],
+            "mappings": "AAAA",
+            "ignoreList": [1],
+            "debugId": "85314830-023f-4cf1-a267-535f4e37bb17",
+            "x_custom": { "a": 1 }
+        });
+
+        let source_map: SourceMap = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(source_map.ignore_list, Some(vec![1]));
+        assert_eq!(
+            source_map.sources_content,
+            Some(vec![Some("const a = 1;".to_string()), None])
+        );
+        assert_eq!(source_map.extras.len(), 2);
+        assert_eq!(serde_json::to_value(&source_map).unwrap(), value);
+    }
+
+    #[test]
+    fn should_read_legacy_ignore_list() {
+        let source_map: SourceMap = serde_json::from_str(
+            r#"{ "version": 3, "sources": [], "names": [], "mappings": "", "x_google_ignoreList": [0] }"#,
+        )
+        .unwrap();
+        assert_eq!(source_map.ignore_list, Some(vec![0]));
+        assert!(source_map.extras.is_empty());
+    }
+}
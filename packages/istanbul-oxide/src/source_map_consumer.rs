@@ -0,0 +1,250 @@
+use crate::SourceMap;
+
+/// Bias of the lookup when there's no mapping at the exact position, same as `source-map`'s.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Bias {
+    GreatestLowerBound,
+    LeastUpperBound,
+}
+
+/// Position of the original source, 1-based line and 0-based column.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct OriginalPosition {
+    pub source: u32,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Mapping {
+    generated_line: u32,
+    generated_column: u32,
+    original: Option<OriginalPosition>,
+}
+
+fn decode_base64(c: u8) -> Option<i64> {
+    match c {
+        b'A'..=b'Z' => Some((c - b'A') as i64),
+        b'a'..=b'z' => Some((c - b'a') as i64 + 26),
+        b'0'..=b'9' => Some((c - b'0') as i64 + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+/// Decode the base64 VLQ values of a segment.
+fn decode_segment(segment: &str) -> Vec<i64> {
+    let mut values = vec![];
+    let (mut value, mut shift) = (0i64, 0u32);
+
+    for digit in segment.bytes().filter_map(decode_base64) {
+        value += (digit & 31) << shift;
+        if digit & 32 != 0 {
+            shift += 5;
+            continue;
+        }
+
+        values.push(if value & 1 == 1 {
+            -(value >> 1)
+        } else {
+            value >> 1
+        });
+        value = 0;
+        shift = 0;
+    }
+
+    values
+}
+
+/// Looks up the positions of a source map in both directions, same as `SourceMapConsumer`
+/// of the `source-map` package istanbul-lib-source-maps uses.
+#[derive(Clone, Debug)]
+pub(crate) struct SourceMapConsumer {
+    // Sorted by the generated position.
+    generated: Vec<Mapping>,
+    // Mappings with the original position, sorted by the original position.
+    original: Vec<Mapping>,
+}
+
+impl SourceMapConsumer {
+    pub fn new(source_map: &SourceMap) -> SourceMapConsumer {
+        let mut generated = vec![];
+        // Fields other than the generated column are relative to the previous segment of the map.
+        let (mut source, mut original_line, mut original_column) = (0i64, 0i64, 0i64);
+
+        for (line, segments) in source_map.mappings.split(';').enumerate() {
+            let mut generated_column = 0i64;
+            for values in segments.split(',').map(decode_segment) {
+                if values.is_empty() {
+                    continue;
+                }
+
+                generated_column += values[0];
+                let original = if values.len() >= 4 {
+                    source += values[1];
+                    original_line += values[2];
+                    original_column += values[3];
+                    Some(OriginalPosition {
+                        source: source.max(0) as u32,
+                        line: original_line.max(0) as u32 + 1,
+                        column: original_column.max(0) as u32,
+                    })
+                } else {
+                    None
+                };
+
+                generated.push(Mapping {
+                    generated_line: line as u32 + 1,
+                    generated_column: generated_column.max(0) as u32,
+                    original,
+                });
+            }
+        }
+
+        generated.sort_by_key(|mapping| {
+            (
+                mapping.generated_line,
+                mapping.generated_column,
+                mapping
+                    .original
+                    .map(|original| (original.source, original.line, original.column)),
+            )
+        });
+
+        let mut original: Vec<Mapping> = generated
+            .iter()
+            .filter(|mapping| mapping.original.is_some())
+            .copied()
+            .collect();
+        original.sort_by_key(|mapping| {
+            let position = mapping.original.expect("Should have original position");
+            (
+                position.source,
+                position.line,
+                position.column,
+                mapping.generated_line,
+                mapping.generated_column,
+            )
+        });
+
+        SourceMapConsumer {
+            generated,
+            original,
+        }
+    }
+
+    /// Original position of the generated position (1-based line, 0-based column). Returns None
+    /// if there's no mapping on the line in the direction of the bias, or the mapping has no source.
+    pub fn original_position_for(
+        &self,
+        line: u32,
+        column: u32,
+        bias: Bias,
+    ) -> Option<OriginalPosition> {
+        let key = |mapping: &Mapping| (mapping.generated_line, mapping.generated_column);
+        let needle = (line, column);
+        let index = find_index(&self.generated, key, needle, bias)?;
+
+        let mapping = &self.generated[index];
+        if mapping.generated_line != line {
+            return None;
+        }
+        mapping.original
+    }
+
+    /// Generated position (1-based line, 0-based column) of the first mapping at or after
+    /// the original position of the source.
+    pub fn generated_position_for(
+        &self,
+        source: u32,
+        line: u32,
+        column: u32,
+    ) -> Option<(u32, u32)> {
+        let key = |mapping: &Mapping| {
+            let position = mapping.original.expect("Should have original position");
+            (position.source, position.line, position.column)
+        };
+        let index = find_index(
+            &self.original,
+            key,
+            (source, line, column),
+            Bias::LeastUpperBound,
+        )?;
+
+        let mapping = &self.original[index];
+        (key(mapping).0 == source).then_some((mapping.generated_line, mapping.generated_column))
+    }
+}
+
+/// Binary search of `source-map`: the first of the exact matches, otherwise the first of
+/// the mappings before / after the needle by the bias.
+fn find_index<K: Ord + Copy>(
+    mappings: &[Mapping],
+    key: impl Fn(&Mapping) -> K,
+    needle: K,
+    bias: Bias,
+) -> Option<usize> {
+    let index = mappings.partition_point(|mapping| key(mapping) < needle);
+    if index < mappings.len() && key(&mappings[index]) == needle {
+        return Some(index);
+    }
+
+    match bias {
+        Bias::LeastUpperBound => (index < mappings.len()).then_some(index),
+        Bias::GreatestLowerBound => {
+            let lower = key(&mappings[index.checked_sub(1)?]);
+            Some(mappings.partition_point(|mapping| key(mapping) < lower))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SourceMap;
+
+    use super::{Bias, OriginalPosition, SourceMapConsumer};
+
+    fn position(source: u32, line: u32, column: u32) -> Option<OriginalPosition> {
+        Some(OriginalPosition {
+            source,
+            line,
+            column,
+        })
+    }
+
+    #[test]
+    fn should_look_up_positions() {
+        // 1:0 -> a.js 1:0, 1:4 -> a.js 1:6, 1:8 (no source), 2:2 -> b.js 3:1
+        let consumer = SourceMapConsumer::new(&SourceMap {
+            sources: vec!["a.js".to_string(), "b.js".to_string()],
+            mappings: "AAAA,IAAM,I;ECEL".to_string(),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            consumer.original_position_for(1, 5, Bias::GreatestLowerBound),
+            position(0, 1, 6)
+        );
+        assert_eq!(
+            consumer.original_position_for(1, 5, Bias::LeastUpperBound),
+            None
+        );
+        assert_eq!(
+            consumer.original_position_for(1, 3, Bias::LeastUpperBound),
+            position(0, 1, 6)
+        );
+        assert_eq!(
+            consumer.original_position_for(2, 0, Bias::GreatestLowerBound),
+            None
+        );
+        assert_eq!(
+            consumer.original_position_for(2, 0, Bias::LeastUpperBound),
+            position(1, 3, 1)
+        );
+
+        assert_eq!(consumer.generated_position_for(0, 1, 1), Some((1, 4)));
+        assert_eq!(consumer.generated_position_for(0, 1, 7), None);
+        assert_eq!(consumer.generated_position_for(1, 3, 2), None);
+    }
+}
@@ -9,6 +9,39 @@ pub struct Function {
     pub decl: Range,
     pub loc: Range,
     pub line: u32,
+    /// Name the function is exported from the module as (`default` for the default export),
+    /// recorded by the instrumenter's `fnExportNames`. Not a part of istanbul's schema.
+    #[serde(
+        rename = "exportName",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub export_name: Option<String>,
+    /// Kind and flags of the function, recorded by the instrumenter's `fnMetadata`.
+    /// Written as the properties of the function. Not a part of istanbul's schema.
+    #[serde(flatten)]
+    pub metadata: Option<FunctionMetadata>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FunctionKind {
+    Declaration,
+    Expression,
+    Arrow,
+    Method,
+    Getter,
+    Setter,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionMetadata {
+    pub is_async: bool,
+    pub is_generator: bool,
+    pub kind: FunctionKind,
+    /// Whether the function is exported from the module.
+    pub exported: bool,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -35,10 +68,12 @@ impl ToString for BranchType {
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Branch {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub loc: Option<Range>,
     #[serde(rename = "type")]
     pub branch_type: BranchType,
     pub locations: Vec<Range>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub line: Option<u32>,
 }
 
@@ -59,6 +94,18 @@ impl Branch {
             line: None,
         }
     }
+
+    /// Line number the branch starts, either from line or loc.
+    pub fn get_line(&self) -> u32 {
+        if let Some(line) = self.line {
+            line
+        } else {
+            self.loc
+                .expect("Either line or loc should exist")
+                .start
+                .line
+        }
+    }
 }
 
 /// Map to line number to hit count.
@@ -71,7 +118,7 @@ pub type BranchCoverageMap = IndexMap<u32, Coverage>;
 
 #[cfg(test)]
 mod tests {
-    use crate::BranchType;
+    use crate::{BranchType, Function, FunctionKind, FunctionMetadata, Range};
 
     #[test]
     fn branch_type_should_return_kebab_string() {
@@ -81,4 +128,33 @@ mod tests {
         assert_eq!(&BranchType::Switch.to_string(), "switch");
         assert_eq!(&BranchType::CondExpr.to_string(), "cond-expr");
     }
+
+    #[test]
+    fn function_metadata_should_be_written_as_properties() {
+        let mut function = Function {
+            name: "foo".to_string(),
+            decl: Range::new(1, 15, 1, 18),
+            loc: Range::new(1, 21, 1, 23),
+            line: 1,
+            export_name: None,
+            metadata: None,
+        };
+
+        let value = serde_json::to_value(&function).unwrap();
+        assert_eq!(value.get("kind"), None);
+        assert_eq!(serde_json::from_value::<Function>(value).unwrap(), function);
+
+        function.metadata = Some(FunctionMetadata {
+            is_async: true,
+            is_generator: false,
+            kind: FunctionKind::Declaration,
+            exported: true,
+        });
+        let value = serde_json::to_value(&function).unwrap();
+        assert_eq!(value["isAsync"], true);
+        assert_eq!(value["isGenerator"], false);
+        assert_eq!(value["kind"], "declaration");
+        assert_eq!(value["exported"], true);
+        assert_eq!(serde_json::from_value::<Function>(value).unwrap(), function);
+    }
 }
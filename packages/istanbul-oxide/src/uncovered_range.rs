@@ -0,0 +1,27 @@
+use crate::{BranchType, Range};
+
+/// Kind of the entry never hit, with its id.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UncoveredKind {
+    Statement {
+        id: u32,
+    },
+    Function {
+        id: u32,
+        name: String,
+    },
+    /// Location of the branch never taken, with its index in the locations of the branch.
+    Branch {
+        id: u32,
+        branch_type: BranchType,
+        location: usize,
+    },
+}
+
+/// Range of the code never executed, returned by `FileCoverage::uncovered_ranges`
+/// for the reporters / editors to highlight. Range of the functions is its declaration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UncoveredRange {
+    pub kind: UncoveredKind,
+    pub range: Range,
+}
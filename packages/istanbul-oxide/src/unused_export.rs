@@ -0,0 +1,15 @@
+use serde::Serialize;
+
+use crate::Range;
+
+/// Exported function never executed, returned by `CoverageMap::find_unused_exports` to guide
+/// the dead code cleanup. Requires `exportName` of the functions recorded by the instrumenter.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnusedExport {
+    pub export_name: String,
+    /// Id of the function in `fnMap`.
+    pub id: u32,
+    pub name: String,
+    pub decl: Range,
+}
@@ -0,0 +1,103 @@
+use std::fmt;
+
+/// Kind of the entries of the coverage, to describe which one is invalid.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EntryKind {
+    Statement,
+    Function,
+    Branch,
+    /// Truthiness of the branch (`bT`), only with `reportLogic`.
+    BranchTrue,
+}
+
+impl fmt::Display for EntryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntryKind::Statement => write!(f, "Statement"),
+            EntryKind::Function => write!(f, "Function"),
+            EntryKind::Branch => write!(f, "Branch"),
+            EntryKind::BranchTrue => write!(f, "Branch truthiness"),
+        }
+    }
+}
+
+/// Internal inconsistency of a file coverage, returned by `FileCoverage::validate`.
+/// Merging or reporting such coverage can panic, or produce wrong results.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationError {
+    /// Hit counts of the id exist without the entry in the map.
+    MissingEntry {
+        path: String,
+        kind: EntryKind,
+        id: u32,
+    },
+    /// Entry of the id exists in the map without its hit counts.
+    MissingHits {
+        path: String,
+        kind: EntryKind,
+        id: u32,
+    },
+    /// Number of the hit counts of the branch differs from its paths.
+    BranchLength {
+        path: String,
+        kind: EntryKind,
+        id: u32,
+        locations: usize,
+        hits: usize,
+    },
+    /// Range of the entry ends before it starts.
+    InvalidRange {
+        path: String,
+        kind: EntryKind,
+        id: u32,
+    },
+    /// Maps of the coverage were altered after the instrumentation, the integrity stamped by
+    /// the instrumenter differs from the one computed from the maps.
+    Integrity {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::MissingEntry { path, kind, id } => {
+                write!(f, "{} {} has hits without the entry for {}", kind, id, path)
+            }
+            ValidationError::MissingHits { path, kind, id } => {
+                write!(f, "{} {} has no hits for {}", kind, id, path)
+            }
+            ValidationError::BranchLength {
+                path,
+                kind,
+                id,
+                locations,
+                hits,
+            } => write!(
+                f,
+                "{} {} has {} hits for {} paths for {}",
+                kind, id, hits, locations, path
+            ),
+            ValidationError::InvalidRange { path, kind, id } => {
+                write!(
+                    f,
+                    "{} {} has a range ends before it starts for {}",
+                    kind, id, path
+                )
+            }
+            ValidationError::Integrity {
+                path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Integrity {} of the maps differs from the stamped {} for {}",
+                actual, expected, path
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
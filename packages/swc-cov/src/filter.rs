@@ -0,0 +1,100 @@
+use clap::{App, Arg, ArgMatches};
+use istanbul_oxide::{canonicalize_path, CoverageMap, PathCanonicalizer};
+
+/// Filtering of the files in the coverage by `--include` / `--exclude` globs, optionally
+/// remapping the coverage to the original sources with `--remap`. Paths are canonicalized
+/// with `--canonicalize` first, so the globs match the canonical paths.
+pub struct CoverageFilter {
+    canonicalizers: Vec<PathCanonicalizer>,
+    globs: Vec<String>,
+    remap: bool,
+    exclude_after_remap: bool,
+}
+
+impl CoverageFilter {
+    /// Adds the args of the filter to the subcommand.
+    pub fn args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        app.arg(
+            Arg::with_name("include")
+                .long("include")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .help("Glob of the files to keep, can be repeated. Keeps all files if not set"),
+        )
+        .arg(
+            Arg::with_name("exclude")
+                .long("exclude")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .help("Glob of the files to remove, can be repeated"),
+        )
+        .arg(
+            Arg::with_name("remap").long("remap").help(
+                "Remap the coverage of the files with inputSourceMap to the original sources",
+            ),
+        )
+        .arg(
+            Arg::with_name("exclude-before-remap")
+                .long("exclude-before-remap")
+                .requires("remap")
+                .help(
+                    "Match the globs against the generated files instead of the original sources",
+                ),
+        )
+        .arg(
+            Arg::with_name("canonicalize")
+                .long("canonicalize")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .possible_values(&["realpath", "lowercaseDrive", "pnpmVirtualStore"])
+                .help("Canonicalize the paths of the files in the given order, can be repeated"),
+        )
+    }
+
+    pub fn from_matches(matches: &ArgMatches) -> CoverageFilter {
+        let include = matches
+            .values_of("include")
+            .into_iter()
+            .flatten()
+            .map(|glob| glob.to_string());
+        let exclude = matches
+            .values_of("exclude")
+            .into_iter()
+            .flatten()
+            .map(|glob| format!("!{}", glob));
+
+        let canonicalizers = matches
+            .values_of("canonicalize")
+            .into_iter()
+            .flatten()
+            .map(|name| match name {
+                "realpath" => PathCanonicalizer::Realpath,
+                "lowercaseDrive" => PathCanonicalizer::LowercaseDrive,
+                _ => PathCanonicalizer::PnpmVirtualStore,
+            })
+            .collect();
+
+        CoverageFilter {
+            canonicalizers,
+            globs: include.chain(exclude).collect(),
+            remap: matches.is_present("remap"),
+            exclude_after_remap: !matches.is_present("exclude-before-remap"),
+        }
+    }
+
+    pub fn apply(&self, mut coverage: CoverageMap) -> CoverageMap {
+        if !self.canonicalizers.is_empty() {
+            coverage.canonicalize_paths(|path| canonicalize_path(path, &self.canonicalizers));
+        }
+
+        if self.remap {
+            return coverage.remap_and_filter_paths(&self.globs, self.exclude_after_remap);
+        }
+
+        coverage.filter_paths(&self.globs);
+        coverage
+    }
+}
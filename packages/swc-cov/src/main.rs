@@ -0,0 +1,142 @@
+use std::{path::PathBuf, thread, time::Duration};
+
+use clap::{App, AppSettings, Arg, SubCommand};
+use filter::CoverageFilter;
+
+mod filter;
+mod merge;
+mod report;
+mod rpc;
+mod serve;
+
+fn main() {
+    let matches = App::new("swc-cov")
+        .about("Command line interface of the coverage data collected from swc-coverage-instrument")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("serve")
+                .about("Serve uncovered ranges of the coverage file over JSON-RPC on stdio")
+                .arg(
+                    Arg::with_name("coverage")
+                        .help("Path to the coverage json (i.e coverage-final.json)")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("poll-interval")
+                        .long("poll-interval")
+                        .takes_value(true)
+                        .default_value("1000")
+                        .help("Interval in ms to check changes of the coverage file"),
+                ),
+        )
+        .subcommand(CoverageFilter::args(
+            SubCommand::with_name("report")
+                .about("Write the reports of the coverage file")
+                .arg(
+                    Arg::with_name("coverage")
+                        .help("Path to the coverage json (i.e coverage-final.json)")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("reporter")
+                        .long("reporter")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .default_value("json")
+                        .help("Reporter to write with, can be repeated (i.e json, json-summary)"),
+                )
+                .arg(
+                    Arg::with_name("dir")
+                        .long("dir")
+                        .takes_value(true)
+                        .default_value("coverage")
+                        .help("Directory to write the report into"),
+                )
+                .arg(
+                    Arg::with_name("jobs")
+                        .long("jobs")
+                        .short("j")
+                        .takes_value(true)
+                        .help("Threads to render the files with, defaults to the number of CPUs"),
+                ),
+        ))
+        .subcommand(CoverageFilter::args(
+            SubCommand::with_name("merge")
+                .about("Merge the coverage files into one")
+                .arg(
+                    Arg::with_name("coverage")
+                        .help("Paths to the coverage json (i.e coverage-final.json) to merge in order")
+                        .required(true)
+                        .multiple(true),
+                )
+                .arg(
+                    Arg::with_name("out")
+                        .long("out")
+                        .short("o")
+                        .takes_value(true)
+                        .default_value("coverage/coverage-final.json")
+                        .help("Path to write the merged coverage json"),
+                ),
+        ))
+        .get_matches();
+
+    if let Some(matches) = matches.subcommand_matches("serve") {
+        let coverage_path = PathBuf::from(matches.value_of("coverage").expect("Required arg"));
+        let poll_interval = matches
+            .value_of("poll-interval")
+            .and_then(|value| value.parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or_else(|| {
+                eprintln!("--poll-interval should be a number of ms");
+                std::process::exit(1);
+            });
+
+        if let Err(err) = serve::serve(coverage_path, poll_interval) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(matches) = matches.subcommand_matches("report") {
+        let coverage_path = PathBuf::from(matches.value_of("coverage").expect("Required arg"));
+        let reporters: Vec<&str> = matches
+            .values_of("reporter")
+            .expect("Has default value")
+            .collect();
+        let dir = PathBuf::from(matches.value_of("dir").expect("Has default value"));
+        let jobs = match matches.value_of("jobs") {
+            Some(value) => value
+                .parse()
+                .ok()
+                .filter(|jobs| *jobs > 0)
+                .unwrap_or_else(|| {
+                    eprintln!("--jobs should be a positive number");
+                    std::process::exit(1);
+                }),
+            None => thread::available_parallelism().map_or(1, |jobs| jobs.get()),
+        };
+
+        let filter = CoverageFilter::from_matches(matches);
+
+        if let Err(err) = report::report(coverage_path, &reporters, &filter, dir, jobs) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(matches) = matches.subcommand_matches("merge") {
+        let coverage_paths: Vec<PathBuf> = matches
+            .values_of("coverage")
+            .expect("Required arg")
+            .map(PathBuf::from)
+            .collect();
+        let out = PathBuf::from(matches.value_of("out").expect("Has default value"));
+        let filter = CoverageFilter::from_matches(matches);
+
+        if let Err(err) = merge::merge(&coverage_paths, &filter, out) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use std::{fs, io, path::PathBuf};
+
+use istanbul_oxide::CoverageMap;
+
+use crate::{filter::CoverageFilter, serve::load_coverage};
+
+/// Merges the coverage files in given order and writes the filtered result into `out`.
+pub fn merge(coverage_paths: &[PathBuf], filter: &CoverageFilter, out: PathBuf) -> io::Result<()> {
+    let coverages = coverage_paths
+        .iter()
+        .map(load_coverage)
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let coverage = filter.apply(CoverageMap::merge_all(coverages));
+
+    if let Some(dir) = out.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(out, coverage.to_json())
+}
@@ -0,0 +1,48 @@
+use std::{io, path::PathBuf};
+
+use istanbul_oxide::report::{report_all, ReportContext, Reporter, ReporterRegistry};
+
+use crate::{filter::CoverageFilter, serve::load_coverage};
+
+/// Writes the reports of the coverage file with the reporters of given names into `dir` in a
+/// single pass, reading the coverage file once. Files are rendered on `jobs` threads if the
+/// reporter supports it. Coverage is filtered (and remapped) with `filter` before reporting.
+pub fn report(
+    coverage_path: PathBuf,
+    reporters: &[&str],
+    filter: &CoverageFilter,
+    dir: PathBuf,
+    jobs: usize,
+) -> io::Result<()> {
+    let registry = ReporterRegistry::with_builtins();
+    let context = ReportContext::new(dir);
+
+    // Fail on the unknown reporters before reading the coverage.
+    let mut reporters = reporters
+        .iter()
+        .map(|name| {
+            registry.create(name, &context).unwrap_or_else(|| {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "Unknown reporter {}, available reporters are {}",
+                        name,
+                        registry
+                            .get_names()
+                            .iter()
+                            .map(|name| name.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                ))
+            })
+        })
+        .collect::<io::Result<Vec<Box<dyn Reporter>>>>()?;
+
+    let coverage = filter.apply(load_coverage(&coverage_path)?);
+    let mut reporters: Vec<&mut dyn Reporter> = reporters
+        .iter_mut()
+        .map(|reporter| reporter.as_mut() as &mut dyn Reporter)
+        .collect();
+    report_all(&coverage, &mut reporters, jobs)
+}
@@ -0,0 +1,124 @@
+use std::io::{self, BufRead, Write};
+
+use serde_json::{json, Value};
+
+/// Reads a message framed with `Content-Length` header, same as the language server protocol.
+/// Returns None at the end of the input. A message which can't be parsed is returned as an
+/// error message to be replied with [PARSE_ERROR], the reader can keep reading the next one.
+pub fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<Result<Value, String>>> {
+    let mut content_length = None;
+    let mut has_headers = false;
+
+    loop {
+        let mut header = vec![];
+        if reader.read_until(b'\n', &mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = String::from_utf8_lossy(&header);
+        let header = header.trim_end();
+        if header.is_empty() {
+            if has_headers {
+                break;
+            }
+            continue;
+        }
+
+        has_headers = true;
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = Some(value.trim().parse::<usize>());
+            }
+        }
+    }
+
+    let content_length = match content_length {
+        Some(Ok(content_length)) => content_length,
+        Some(Err(err)) => return Ok(Some(Err(format!("Invalid Content-Length: {}", err)))),
+        None => return Ok(Some(Err("Missing Content-Length".to_string()))),
+    };
+
+    let mut content = vec![0; content_length];
+    match reader.read_exact(&mut content) {
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        ret => ret?,
+    }
+
+    Ok(Some(
+        serde_json::from_slice(&content).map_err(|err| err.to_string()),
+    ))
+}
+
+/// Writes a message framed with `Content-Length` header.
+pub fn write_message<W: Write>(writer: &mut W, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    writer.flush()
+}
+
+pub fn response(id: &Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+pub fn error_response(id: &Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+pub fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{read_message, write_message};
+
+    #[test]
+    fn should_read_written_messages() {
+        let mut buf = vec![];
+        write_message(&mut buf, &json!({ "id": 1, "method": "files" })).unwrap();
+        write_message(&mut buf, &json!({ "id": 2, "method": "ü" })).unwrap();
+
+        let mut reader = &buf[..];
+        assert_eq!(
+            read_message(&mut reader).unwrap(),
+            Some(Ok(json!({ "id": 1, "method": "files" })))
+        );
+        assert_eq!(
+            read_message(&mut reader).unwrap(),
+            Some(Ok(json!({ "id": 2, "method": "ü" })))
+        );
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn should_keep_reading_after_malformed_messages() {
+        let mut buf = b"Content-Length: 3\r\n\r\n{]}".to_vec();
+        buf.extend(b"Content-Type: application/json\r\n\r\n");
+        buf.extend(b"Content-Length: abc\r\n\r\n");
+        write_message(&mut buf, &json!({ "id": 1, "method": "files" })).unwrap();
+
+        let mut reader = &buf[..];
+        assert!(matches!(read_message(&mut reader).unwrap(), Some(Err(_))));
+        assert_eq!(
+            read_message(&mut reader).unwrap(),
+            Some(Err("Missing Content-Length".to_string()))
+        );
+        assert!(matches!(read_message(&mut reader).unwrap(), Some(Err(_))));
+        assert_eq!(
+            read_message(&mut reader).unwrap(),
+            Some(Ok(json!({ "id": 1, "method": "files" })))
+        );
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+    }
+}
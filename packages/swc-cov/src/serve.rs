@@ -0,0 +1,241 @@
+use std::{
+    fs, io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use istanbul_oxide::{CoverageMap, UncoveredKind, UncoveredRange};
+use serde_json::{json, Value};
+
+use crate::rpc::{
+    error_response, notification, read_message, response, write_message, INVALID_PARAMS,
+    METHOD_NOT_FOUND, PARSE_ERROR,
+};
+
+/// Coverage file served, reloaded when its modified time changes.
+pub struct CoverageServer {
+    coverage_path: PathBuf,
+    coverage: CoverageMap,
+    modified: Option<SystemTime>,
+}
+
+impl CoverageServer {
+    pub fn new(coverage_path: PathBuf) -> io::Result<CoverageServer> {
+        let modified = fs::metadata(&coverage_path)?.modified().ok();
+        let coverage = load_coverage(&coverage_path)?;
+
+        Ok(CoverageServer {
+            coverage_path,
+            coverage,
+            modified,
+        })
+    }
+
+    /// Reloads the coverage if the file changed. Returns true if it's reloaded.
+    /// File being written by the test runner may not be a valid json yet, keeps the previous coverage
+    /// and retries on the next call in that case.
+    pub fn reload_if_changed(&mut self) -> io::Result<bool> {
+        let modified = fs::metadata(&self.coverage_path)?.modified().ok();
+        if modified == self.modified {
+            return Ok(false);
+        }
+
+        self.coverage = load_coverage(&self.coverage_path)?;
+        self.modified = modified;
+        Ok(true)
+    }
+
+    /// Handles a request, returns the response to be sent if the message has an id.
+    pub fn handle(&self, message: &Value) -> Option<Value> {
+        let id = message.get("id")?;
+        let method = message.get("method").and_then(Value::as_str);
+
+        Some(match method {
+            Some("files") => response(id, json!(self.coverage.get_files())),
+            Some("uncoveredRanges") => {
+                match message
+                    .get("params")
+                    .and_then(|params| params.get("path"))
+                    .and_then(Value::as_str)
+                {
+                    Some(path) => {
+                        let ranges = self
+                            .coverage
+                            .get_coverage_for_file(path)
+                            .map(|file_coverage| file_coverage.uncovered_ranges())
+                            .unwrap_or_default();
+
+                        response(
+                            id,
+                            Value::Array(ranges.iter().map(uncovered_range_to_json).collect()),
+                        )
+                    }
+                    None => error_response(id, INVALID_PARAMS, "Missing `path` param"),
+                }
+            }
+            Some("shutdown") => response(id, Value::Null),
+            _ => error_response(id, METHOD_NOT_FOUND, "Unknown method"),
+        })
+    }
+}
+
+pub(crate) fn load_coverage(coverage_path: &PathBuf) -> io::Result<CoverageMap> {
+    let value = fs::read_to_string(coverage_path)?;
+    CoverageMap::from_json(&value).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+fn uncovered_range_to_json(uncovered: &UncoveredRange) -> Value {
+    match &uncovered.kind {
+        UncoveredKind::Statement { id } => json!({
+            "kind": "statement",
+            "id": id,
+            "range": uncovered.range,
+        }),
+        UncoveredKind::Function { id, name } => json!({
+            "kind": "function",
+            "id": id,
+            "name": name,
+            "range": uncovered.range,
+        }),
+        UncoveredKind::Branch {
+            id,
+            branch_type,
+            location,
+        } => json!({
+            "kind": "branch",
+            "id": id,
+            "branchType": branch_type,
+            "location": location,
+            "range": uncovered.range,
+        }),
+    }
+}
+
+/// Serves the coverage over JSON-RPC on stdio until `shutdown` request or the end of the input.
+pub fn serve(coverage_path: PathBuf, poll_interval: Duration) -> io::Result<()> {
+    let server = Arc::new(Mutex::new(CoverageServer::new(coverage_path)?));
+    let stdout = Arc::new(Mutex::new(io::stdout()));
+
+    {
+        let server = server.clone();
+        let stdout = stdout.clone();
+        thread::spawn(move || loop {
+            thread::sleep(poll_interval);
+            let reloaded = server
+                .lock()
+                .expect("Should able to lock server")
+                .reload_if_changed();
+
+            if let Ok(true) = reloaded {
+                let mut stdout = stdout.lock().expect("Should able to lock stdout");
+                if write_message(&mut *stdout, &notification("coverageChanged", Value::Null))
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        });
+    }
+
+    let stdin = io::stdin();
+    let mut stdin = stdin.lock();
+    while let Some(message) = read_message(&mut stdin)? {
+        let message = match message {
+            Ok(message) => message,
+            Err(err) => {
+                let mut stdout = stdout.lock().expect("Should able to lock stdout");
+                write_message(
+                    &mut *stdout,
+                    &error_response(&Value::Null, PARSE_ERROR, &err),
+                )?;
+                continue;
+            }
+        };
+
+        let result = server
+            .lock()
+            .expect("Should able to lock server")
+            .handle(&message);
+
+        if let Some(result) = result {
+            let mut stdout = stdout.lock().expect("Should able to lock stdout");
+            write_message(&mut *stdout, &result)?;
+        }
+
+        if message.get("method").and_then(Value::as_str) == Some("shutdown") {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use istanbul_oxide::{CoverageMap, FileCoverage, Range};
+    use serde_json::{json, Value};
+
+    use super::CoverageServer;
+
+    fn server() -> CoverageServer {
+        let mut file_coverage = FileCoverage::from_file_path("/a.js".to_string(), false);
+        file_coverage
+            .statement_map
+            .insert(0, Range::new(1, 0, 1, 10));
+        file_coverage.s.insert(0, 0);
+
+        let mut coverage = CoverageMap::default();
+        coverage.add_coverage_for_file(&file_coverage);
+
+        CoverageServer {
+            coverage_path: PathBuf::from("coverage-final.json"),
+            coverage,
+            modified: None,
+        }
+    }
+
+    #[test]
+    fn should_respond_uncovered_ranges() {
+        let server = server();
+
+        assert_eq!(
+            server.handle(&json!({ "id": 1, "method": "files" })),
+            Some(json!({ "jsonrpc": "2.0", "id": 1, "result": ["/a.js"] }))
+        );
+
+        let response = server
+            .handle(&json!({ "id": 2, "method": "uncoveredRanges", "params": { "path": "/a.js" } }))
+            .unwrap();
+        assert_eq!(
+            response["result"],
+            json!([{
+                "kind": "statement",
+                "id": 0,
+                "range": { "start": { "line": 1, "column": 0 }, "end": { "line": 1, "column": 10 } },
+            }])
+        );
+
+        let response = server
+            .handle(&json!({ "id": 3, "method": "uncoveredRanges", "params": { "path": "/b.js" } }))
+            .unwrap();
+        assert_eq!(response["result"], json!([]));
+
+        let response = server
+            .handle(&json!({ "id": 4, "method": "uncoveredRanges" }))
+            .unwrap();
+        assert_eq!(response["error"]["code"], json!(-32602));
+
+        let response = server.handle(&json!({ "id": 5, "method": "foo" })).unwrap();
+        assert_eq!(response["error"]["code"], json!(-32601));
+
+        assert_eq!(server.handle(&json!({ "method": "files" })), None);
+        assert_eq!(
+            server.handle(&json!({ "id": 6, "method": "shutdown" })),
+            Some(json!({ "jsonrpc": "2.0", "id": 6, "result": Value::Null }))
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use istanbul_oxide::{CoverageMap, FileCoverage, Range};
+
+/// Creates an empty directory under the temp dir for the test of given name.
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("swc-cov-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Writes the coverage of `/src/a.js` and `/src/a.test.js`, each with a covered and
+/// an uncovered statement.
+fn write_coverage(dir: &Path) -> PathBuf {
+    let coverage_path = dir.join("coverage.json");
+    let files: Vec<_> = ["/src/a.js", "/src/a.test.js"]
+        .iter()
+        .map(|path| {
+            let mut coverage = FileCoverage::from_file_path(path.to_string(), false);
+            coverage.statement_map.insert(0, Range::new(1, 0, 1, 10));
+            coverage.statement_map.insert(1, Range::new(2, 0, 2, 10));
+            coverage.s.insert(0, 1);
+            coverage.s.insert(1, 0);
+            coverage
+        })
+        .collect();
+    let coverage = CoverageMap::from_iter(&files);
+    fs::write(&coverage_path, coverage.to_json()).unwrap();
+    coverage_path
+}
+
+#[test]
+fn should_write_reports() {
+    let dir = test_dir("report");
+    let coverage_path = write_coverage(&dir);
+    let report_dir = dir.join("report");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_swc-cov"))
+        .arg("report")
+        .arg(&coverage_path)
+        .args(["--reporter", "json", "--reporter", "json-summary"])
+        .args(["--exclude", "**/*.test.js"])
+        .arg("--dir")
+        .arg(&report_dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let reported = CoverageMap::from_json(
+        &fs::read_to_string(report_dir.join("coverage-final.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(reported.get_files(), vec!["/src/a.js"]);
+
+    let summary: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(report_dir.join("coverage-summary.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(summary["total"]["statements"]["total"], 2);
+    assert_eq!(summary["total"]["statements"]["covered"], 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn should_fail_on_unknown_reporter() {
+    let dir = test_dir("unknown-reporter");
+    let coverage_path = write_coverage(&dir);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_swc-cov"))
+        .arg("report")
+        .arg(&coverage_path)
+        .args(["--reporter", "foo"])
+        .arg("--dir")
+        .arg(dir.join("report"))
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown reporter foo"));
+    assert!(!dir.join("report").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
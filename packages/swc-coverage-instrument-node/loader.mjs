@@ -0,0 +1,48 @@
+// Node.js module customization hooks instrumenting the loaded files on the fly,
+// registered by `register.mjs` (`node --import swc-coverage-instrument-node/register app.js`)
+// or directly as `node --loader swc-coverage-instrument-node/loader app.js`.
+import { readFile } from "node:fs/promises";
+import { createRequire } from "node:module";
+import { extname } from "node:path";
+import { fileURLToPath } from "node:url";
+
+const require = createRequire(import.meta.url);
+const { instrumentSync } = require("./index.js");
+
+// Instrumentation options, same as the plugin's `InstrumentationOptions`.
+const options = JSON.parse(process.env.SWC_COVERAGE_INSTRUMENT_OPTIONS || "{}");
+// Extensions of the files to instrument, same as nyc's `extension`.
+const extensions = (process.env.SWC_COVERAGE_INSTRUMENT_EXTENSION || ".js,.cjs,.mjs,.jsx")
+  .split(",")
+  .map((extension) => extension.trim())
+  .filter(Boolean);
+// Directory to cache the instrumented output in, to skip unchanged files on the next run.
+const cacheDir = process.env.SWC_COVERAGE_CACHE_DIR || undefined;
+
+const shouldInstrument = (url, format) =>
+  url.startsWith("file:") &&
+  !url.includes("/node_modules/") &&
+  (format === "module" || format === "commonjs") &&
+  extensions.includes(extname(new URL(url).pathname));
+
+export async function load(url, context, nextLoad) {
+  const result = await nextLoad(url, context);
+  if (!shouldInstrument(url, result.format)) {
+    return result;
+  }
+
+  const filename = fileURLToPath(url);
+  // Older versions of Node.js do not return the source of commonjs modules.
+  const source = result.source == null ? await readFile(filename) : result.source;
+  const { code, map } = instrumentSync(
+    Buffer.from(source).toString("utf8"),
+    filename,
+    options,
+    cacheDir
+  );
+
+  return {
+    ...result,
+    source: `${code}\n//# sourceMappingURL=data:application/json;base64,${Buffer.from(map).toString("base64")}\n`,
+  };
+}
@@ -0,0 +1,34 @@
+// Registers `loader.mjs` and writes the collected coverage on exit, to `.nyc_output` same as nyc
+// (or `SWC_COVERAGE_TEMP_DIR`) for `nyc report` to read it. Requires Node.js 20.6 or later.
+import { randomUUID } from "node:crypto";
+import { mkdirSync, writeFileSync } from "node:fs";
+import { createRequire, register } from "node:module";
+import { join, resolve } from "node:path";
+
+register("./loader.mjs", import.meta.url);
+
+const { coverageVariable = "__coverage__" } = JSON.parse(
+  process.env.SWC_COVERAGE_INSTRUMENT_OPTIONS || "{}"
+);
+const tempDir = resolve(process.env.SWC_COVERAGE_TEMP_DIR || ".nyc_output");
+const cacheDir = process.env.SWC_COVERAGE_CACHE_DIR;
+const cacheMaxSize = Number(process.env.SWC_COVERAGE_CACHE_MAX_SIZE);
+
+process.on("exit", () => {
+  if (cacheDir && cacheMaxSize >= 0) {
+    const { evictInstrumentCacheSync } = createRequire(import.meta.url)("./index.js");
+    try {
+      evictInstrumentCacheSync(cacheDir, cacheMaxSize);
+    } catch {
+      // Cache directory is not created if nothing is instrumented.
+    }
+  }
+
+  const coverage = globalThis[coverageVariable];
+  if (!coverage || Object.keys(coverage).length === 0) {
+    return;
+  }
+
+  mkdirSync(tempDir, { recursive: true });
+  writeFileSync(join(tempDir, `${randomUUID()}.json`), JSON.stringify(coverage));
+});
@@ -0,0 +1,368 @@
+#[macro_use]
+extern crate napi_derive;
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use napi::{Error, Status};
+use serde::Deserialize;
+use swc_coverage::{
+    pipeline::{self, get_syntax, get_syntax_by_name},
+    swc_ecmascript::parser::Syntax,
+};
+use swc_coverage_instrument::{
+    create_coverage_registry_module, extract_coverage_from_comments, CachedInstrumentation,
+    InstrumentCache, InstrumentOptions,
+};
+
+#[napi(object)]
+pub struct InstrumentOutput {
+    pub code: String,
+    /// Source map of the instrumented code, as a JSON string.
+    pub map: String,
+}
+
+fn to_napi_error(message: String) -> Error {
+    Error::new(Status::GenericFailure, message)
+}
+
+/// Extensions of the files instrumented by `instrumentDirSync` by default, same as nyc's `extension`.
+const DEFAULT_EXTENSIONS: [&str; 6] = [".js", ".cjs", ".mjs", ".ts", ".tsx", ".jsx"];
+
+/// Options of `instrumentDirSync`, other than the instrumentation options.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct InstrumentDirOptions {
+    /// Extensions of the files to instrument, with the leading dot (`.vue`, `.svelte.ts`).
+    extension: Vec<String>,
+    /// Syntax to parse the files of the extension with, one of `js`, `jsx`, `ts`, `tsx`.
+    /// Files of the extensions not listed here are parsed by its extension.
+    parser_syntax: HashMap<String, String>,
+    /// Directory to cache the instrumented output in, to skip unchanged files on the next run.
+    cache_dir: Option<String>,
+    /// Max total size of the cache in bytes, least recently used entries are evicted after instrumenting.
+    cache_max_size: Option<u64>,
+    /// Path of the coverage registry module to write, relative to the output dir. Instrumented files
+    /// import it to register coverage instead of looking up the global each. Written as commonjs if
+    /// the extension is `.cjs`.
+    registry_module: Option<String>,
+}
+
+impl Default for InstrumentDirOptions {
+    fn default() -> Self {
+        InstrumentDirOptions {
+            extension: DEFAULT_EXTENSIONS
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+            parser_syntax: Default::default(),
+            cache_dir: None,
+            cache_max_size: None,
+            registry_module: None,
+        }
+    }
+}
+
+fn parse_instrument_options(options: Option<serde_json::Value>) -> napi::Result<InstrumentOptions> {
+    let (options, warnings) = pipeline::parse_instrument_options(options).map_err(to_napi_error)?;
+    for warning in warnings {
+        eprintln!("swc-coverage-instrument: {}", warning);
+    }
+    Ok(options)
+}
+
+/// Instruments given code, without going through swc's wasm plugin. Code is emitted
+/// as-is other than the instrumentation, i.e types are not stripped from typescript.
+/// `options` is the same as the plugin's `InstrumentationOptions`. If `cacheDir` is given,
+/// output is read from / written to the cache in the directory.
+#[napi]
+pub fn instrument_sync(
+    code: String,
+    filename: String,
+    options: Option<serde_json::Value>,
+    cache_dir: Option<String>,
+) -> napi::Result<InstrumentOutput> {
+    let syntax = get_syntax(&filename);
+    let cache = cache_dir.map(InstrumentCache::new);
+    instrument_with_cache(
+        code,
+        filename,
+        parse_instrument_options(options)?,
+        syntax,
+        cache.as_ref(),
+    )
+}
+
+/// Evicts least recently used entries of the cache in `cacheDir` until its total size is at most
+/// `maxSize` bytes, returns the number of the evicted entries.
+#[napi]
+pub fn evict_instrument_cache_sync(cache_dir: String, max_size: i64) -> napi::Result<u32> {
+    InstrumentCache::new(&cache_dir)
+        .evict(max_size.max(0) as u64)
+        .map(|removed| removed as u32)
+        .map_err(|err| to_napi_error(format!("Failed to evict cache in {}: {}", cache_dir, err)))
+}
+
+fn instrument_with_cache(
+    code: String,
+    filename: String,
+    instrument_options: InstrumentOptions,
+    syntax: Syntax,
+    cache: Option<&InstrumentCache>,
+) -> napi::Result<InstrumentOutput> {
+    let cache = match cache {
+        Some(cache) => cache.clone().with_salt(format!("{:?}", syntax)),
+        None => return instrument(code, filename, instrument_options, syntax),
+    };
+
+    if let Some(cached) = cache.get(&filename, &code, &instrument_options) {
+        return Ok(InstrumentOutput {
+            code: cached.code,
+            map: cached.map.unwrap_or_default(),
+        });
+    }
+
+    let output = instrument(
+        code.clone(),
+        filename.clone(),
+        instrument_options.clone(),
+        syntax,
+    )?;
+    // Failing to write the cache doesn't fail the instrumentation, file is instrumented again next time.
+    let _ = cache.put(
+        &filename,
+        &code,
+        &instrument_options,
+        &CachedInstrumentation {
+            code: output.code.clone(),
+            map: Some(output.map.clone()),
+            coverage: extract_coverage_from_comments(&output.code),
+        },
+    );
+
+    Ok(output)
+}
+
+fn instrument(
+    code: String,
+    filename: String,
+    instrument_options: InstrumentOptions,
+    syntax: Syntax,
+) -> napi::Result<InstrumentOutput> {
+    let output = pipeline::instrument(code, &filename, instrument_options, syntax)
+        .map_err(to_napi_error)?;
+
+    Ok(InstrumentOutput {
+        code: output.code,
+        map: output.map,
+    })
+}
+
+/// Collects the files under `dir` with one of the `extensions`, returns the path with the matched extension.
+/// `node_modules` is excluded, same as nyc's default `exclude`.
+fn collect_files(
+    dir: &Path,
+    extensions: &[String],
+    files: &mut Vec<(PathBuf, String)>,
+) -> std::io::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            if path.file_name().and_then(|name| name.to_str()) != Some("node_modules") {
+                collect_files(&path, extensions, files)?;
+            }
+            continue;
+        }
+
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        // Longest extension wins, i.e `.d.ts` over `.ts`.
+        if let Some(extension) = extensions
+            .iter()
+            .filter(|extension| name.ends_with(extension.as_str()))
+            .max_by_key(|extension| extension.len())
+        {
+            let extension = extension.clone();
+            files.push((path, extension));
+        }
+    }
+
+    Ok(())
+}
+
+/// Creates the specifier to import the registry module at `registry_path` from the file at
+/// `file_path`, both relative to the output dir.
+fn get_registry_specifier(file_path: &Path, registry_path: &Path) -> String {
+    let depth = file_path
+        .parent()
+        .map(|parent| parent.components().count())
+        .unwrap_or_default();
+    let registry_path = registry_path.to_string_lossy().replace('\\', "/");
+
+    if depth == 0 {
+        format!("./{}", registry_path)
+    } else {
+        format!("{}{}", "../".repeat(depth), registry_path)
+    }
+}
+
+/// Writes the coverage registry module at `registry_module` under `output_dir`, returns the path
+/// of the module relative to the output dir.
+fn write_registry_module(
+    output_dir: &Path,
+    registry_module: &str,
+    coverage_variable: &str,
+) -> napi::Result<PathBuf> {
+    let registry_path = PathBuf::from(registry_module);
+    if registry_path.is_absolute()
+        || registry_path
+            .components()
+            .any(|component| !matches!(component, Component::Normal(..)))
+    {
+        return Err(to_napi_error(format!(
+            "registryModule should be a path relative to the output dir, got {}",
+            registry_module
+        )));
+    }
+
+    let output_path = output_dir.join(&registry_path);
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent).map_err(|err| {
+            to_napi_error(format!("Failed to create {}: {}", parent.display(), err))
+        })?;
+    }
+
+    let commonjs = registry_path.extension().and_then(|ext| ext.to_str()) == Some("cjs");
+    fs::write(
+        &output_path,
+        create_coverage_registry_module(coverage_variable, commonjs),
+    )
+    .map_err(|err| {
+        to_napi_error(format!(
+            "Failed to write {}: {}",
+            output_path.display(),
+            err
+        ))
+    })?;
+
+    Ok(registry_path)
+}
+
+/// Instruments the files under `inputDir` with one of the extensions of `dirOptions.extension`,
+/// writes instrumented code to the same relative path under `outputDir`. Returns the paths of the
+/// written files. Files of the extensions the parser can't determine (i.e scripts extracted from
+/// `.vue` files) can be parsed with the syntax in `dirOptions.parserSyntax`, like `{ ".vue": "ts" }`.
+/// Unchanged files are read from the cache if `dirOptions.cacheDir` is set. If `dirOptions.registryModule`
+/// is set, the registry module is written to the path under `outputDir` and the files register coverage through it.
+#[napi]
+pub fn instrument_dir_sync(
+    input_dir: String,
+    output_dir: String,
+    options: Option<serde_json::Value>,
+    dir_options: Option<serde_json::Value>,
+) -> napi::Result<Vec<String>> {
+    let instrument_options = parse_instrument_options(options)?;
+    let dir_options: InstrumentDirOptions = match dir_options {
+        Some(options) => serde_json::from_value(options).map_err(|err| {
+            to_napi_error(format!("Could not deserialize directory option: {}", err))
+        })?,
+        None => Default::default(),
+    };
+
+    let mut syntax_by_extension = HashMap::new();
+    for (extension, name) in &dir_options.parser_syntax {
+        let syntax = get_syntax_by_name(name).ok_or_else(|| {
+            to_napi_error(format!(
+                "Unknown parser syntax {} for the extension {}, expected one of js, jsx, ts, tsx",
+                name, extension
+            ))
+        })?;
+        syntax_by_extension.insert(extension.as_str(), syntax);
+    }
+
+    let cache = dir_options.cache_dir.as_ref().map(InstrumentCache::new);
+
+    let input_dir = Path::new(&input_dir);
+    let output_dir = Path::new(&output_dir);
+    let mut files = vec![];
+    collect_files(input_dir, &dir_options.extension, &mut files)
+        .map_err(|err| to_napi_error(format!("Failed to read {}: {}", input_dir.display(), err)))?;
+
+    let mut written = vec![];
+    let registry_path = match &dir_options.registry_module {
+        Some(registry_module) => {
+            let registry_path = write_registry_module(
+                output_dir,
+                registry_module,
+                &instrument_options.coverage_variable,
+            )?;
+            written.push(output_dir.join(&registry_path).to_string_lossy().to_string());
+            Some(registry_path)
+        }
+        None => None,
+    };
+
+    for (path, extension) in files {
+        let filename = path.to_string_lossy().to_string();
+        let code = fs::read_to_string(&path)
+            .map_err(|err| to_napi_error(format!("Failed to read {}: {}", filename, err)))?;
+        let syntax = syntax_by_extension
+            .get(extension.as_str())
+            .copied()
+            .unwrap_or_else(|| get_syntax(&filename));
+
+        let relative_path = path
+            .strip_prefix(input_dir)
+            .expect("Collected file should be under the input dir");
+        let mut instrument_options = instrument_options.clone();
+        if let Some(registry_path) = &registry_path {
+            instrument_options.registry_module =
+                Some(get_registry_specifier(relative_path, registry_path));
+        }
+
+        let output = instrument_with_cache(
+            code,
+            filename.clone(),
+            instrument_options,
+            syntax,
+            cache.as_ref(),
+        )?;
+
+        let output_path = output_dir.join(relative_path);
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent).map_err(|err| {
+                to_napi_error(format!("Failed to create {}: {}", parent.display(), err))
+            })?;
+        }
+        fs::write(&output_path, output.code).map_err(|err| {
+            to_napi_error(format!(
+                "Failed to write {}: {}",
+                output_path.display(),
+                err
+            ))
+        })?;
+
+        written.push(output_path.to_string_lossy().to_string());
+    }
+
+    if let (Some(cache), Some(max_size)) = (&cache, dir_options.cache_max_size) {
+        cache.evict(max_size).map_err(|err| {
+            to_napi_error(format!(
+                "Failed to evict cache in {}: {}",
+                cache.dir().display(),
+                err
+            ))
+        })?;
+    }
+
+    Ok(written)
+}
@@ -53,6 +53,11 @@ pub static IDENT_COLUMN: Lazy<Ident> = Lazy::new(|| Ident {
     ..Ident::dummy()
 });
 
+pub static IDENT_SKIP: Lazy<Ident> = Lazy::new(|| Ident {
+    sym: "skip".into(),
+    ..Ident::dummy()
+});
+
 pub static IDENT_STATEMENT_MAP: Lazy<Ident> = Lazy::new(|| Ident {
     sym: "statementMap".into(),
     ..Ident::dummy()
@@ -157,3 +162,18 @@ pub static IDENT_MAPPINGS: Lazy<Ident> = Lazy::new(|| Ident {
     sym: "mappings".into(),
     ..Ident::dummy()
 });
+
+pub static IDENT_IGNORE_LIST: Lazy<Ident> = Lazy::new(|| Ident {
+    sym: "ignoreList".into(),
+    ..Ident::dummy()
+});
+
+pub static IDENT_EXPORT_NAME: Lazy<Ident> = Lazy::new(|| Ident {
+    sym: "exportName".into(),
+    ..Ident::dummy()
+});
+
+pub static IDENT_HIT_FN: Lazy<Ident> = Lazy::new(|| Ident {
+    sym: "hitFn".into(),
+    ..Ident::dummy()
+});
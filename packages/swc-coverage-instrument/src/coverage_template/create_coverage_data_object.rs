@@ -45,8 +45,43 @@ pub fn create_str_key_value_prop(key: &str, value: Expr) -> PropOrSpread {
     })))
 }
 
+fn create_json_value_expr(value: &serde_json::Value) -> Expr {
+    match value {
+        serde_json::Value::Null => Expr::Lit(Lit::Null(Null::dummy())),
+        serde_json::Value::Bool(value) => Expr::Lit(Lit::Bool((*value).into())),
+        serde_json::Value::Number(value) => Expr::Lit(Lit::Num(Number {
+            value: value.as_f64().unwrap_or_default(),
+            raw: Some(value.to_string().into()),
+            span: DUMMY_SP,
+        })),
+        serde_json::Value::String(value) => Expr::Lit(Lit::Str(Str {
+            value: value.clone().into(),
+            ..Str::dummy()
+        })),
+        serde_json::Value::Array(values) => Expr::Array(ArrayLit {
+            span: DUMMY_SP,
+            elems: values
+                .iter()
+                .map(|value| {
+                    Some(ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(create_json_value_expr(value)),
+                    })
+                })
+                .collect(),
+        }),
+        serde_json::Value::Object(values) => Expr::Object(ObjectLit {
+            span: DUMMY_SP,
+            props: values
+                .iter()
+                .map(|(key, value)| create_str_key_value_prop(key, create_json_value_expr(value)))
+                .collect(),
+        }),
+    }
+}
+
 fn create_range_object_prop(value: &Range) -> Vec<PropOrSpread> {
-    vec![
+    let mut props = vec![
         create_ident_key_value_prop(
             &IDENT_START,
             Expr::Object(ObjectLit {
@@ -73,7 +108,19 @@ fn create_range_object_prop(value: &Range) -> Vec<PropOrSpread> {
                 ],
             }),
         ),
-    ]
+    ];
+
+    if value.skip {
+        props.push(create_ident_key_value_prop(
+            &IDENT_SKIP,
+            Expr::Lit(Lit::Bool(Bool {
+                span: DUMMY_SP,
+                value: true,
+            })),
+        ));
+    }
+
+    props
 }
 
 fn create_range_object_lit(value: &Range) -> Expr {
@@ -84,16 +131,39 @@ fn create_range_object_lit(value: &Range) -> Expr {
 }
 
 fn create_fn_prop(key: &str, value: &istanbul_oxide::types::Function) -> PropOrSpread {
+    let mut props = vec![
+        create_ident_key_value_prop(&IDENT_NAME, create_str_lit_expr(&value.name)),
+        create_ident_key_value_prop(&IDENT_DECL, create_range_object_lit(&value.decl)),
+        create_ident_key_value_prop(&IDENT_LOC, create_range_object_lit(&value.loc)),
+        create_ident_key_value_prop(&IDENT_LINE, create_num_lit_expr(value.line)),
+    ];
+
+    if let Some(export_name) = &value.export_name {
+        props.push(create_ident_key_value_prop(
+            &IDENT_EXPORT_NAME,
+            create_str_lit_expr(export_name),
+        ));
+    }
+
+    // assign `isAsync`, `isGenerator`, `kind`, `exported` recorded by `fnMetadata`
+    if let Some(metadata) = &value.metadata {
+        if let serde_json::Value::Object(metadata) =
+            serde_json::to_value(metadata).expect("Should able to serialize metadata")
+        {
+            for (key, value) in &metadata {
+                props.push(create_str_key_value_prop(
+                    key,
+                    create_json_value_expr(value),
+                ));
+            }
+        }
+    }
+
     create_str_key_value_prop(
         key,
         Expr::Object(ObjectLit {
             span: DUMMY_SP,
-            props: vec![
-                create_ident_key_value_prop(&IDENT_NAME, create_str_lit_expr(&value.name)),
-                create_ident_key_value_prop(&IDENT_DECL, create_range_object_lit(&value.decl)),
-                create_ident_key_value_prop(&IDENT_LOC, create_range_object_lit(&value.loc)),
-                create_ident_key_value_prop(&IDENT_LINE, create_num_lit_expr(value.line)),
-            ],
+            props,
         }),
     )
 }
@@ -403,6 +473,31 @@ pub fn create_coverage_data_object(coverage_data: &FileCoverage) -> (String, Exp
             })),
         ));
 
+        if let Some(ignore_list) = &input_source_map.ignore_list {
+            source_map_props.push(create_ident_key_value_prop(
+                &IDENT_IGNORE_LIST,
+                Expr::Array(ArrayLit {
+                    span: DUMMY_SP,
+                    elems: ignore_list
+                        .iter()
+                        .map(|v| {
+                            Some(ExprOrSpread {
+                                spread: None,
+                                expr: Box::new(create_num_lit_expr(*v)),
+                            })
+                        })
+                        .collect(),
+                }),
+            ));
+        }
+
+        for (key, value) in &input_source_map.extras {
+            source_map_props.push(create_str_key_value_prop(
+                key,
+                create_json_value_expr(value),
+            ));
+        }
+
         let input_source_map_prop = create_ident_key_value_prop(
             &IDENT_INPUT_SOURCE_MAP,
             Expr::Object(ObjectLit {
@@ -414,6 +509,14 @@ pub fn create_coverage_data_object(coverage_data: &FileCoverage) -> (String, Exp
         props.push(input_source_map_prop);
     }
 
+    // assign metadata attached by `SourceCoverage::set_metadata`
+    for (key, value) in &coverage_data.extras {
+        props.push(create_str_key_value_prop(
+            key,
+            create_json_value_expr(value),
+        ));
+    }
+
     // assign coverage['_coverageSchema']
     let coverage_schema_prop = create_ident_key_value_prop(
         &IDENT_COVERAGE_MAGIC_KEY,
@@ -496,6 +599,127 @@ mod tests {
         assert_eq!(expected, coverage_data_expr);
     }
 
+    #[test]
+    fn should_create_metadata() {
+        let mut coverage_data = FileCoverage::empty("anon".to_string(), false);
+        coverage_data.extras.insert(
+            "generator".to_string(),
+            serde_json::json!({ "name": "foo", "tags": [1, null] }),
+        );
+        let (_hash, coverage_data_expr) = create_coverage_data_object(&coverage_data);
+
+        let props = match coverage_data_expr {
+            Expr::Object(ObjectLit { props, .. }) => props,
+            _ => panic!("Coverage data should be an object"),
+        };
+        let value = props
+            .iter()
+            .find_map(|prop| match prop {
+                PropOrSpread::Prop(prop) => match &**prop {
+                    Prop::KeyValue(KeyValueProp {
+                        key: PropName::Str(key),
+                        value,
+                    }) if &*key.value == "generator" => Some(value),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .expect("Metadata should be written");
+
+        match &**value {
+            Expr::Object(ObjectLit { props, .. }) => {
+                assert_eq!(props.len(), 2);
+                assert_eq!(
+                    props[1],
+                    create_str_key_value_prop(
+                        "tags",
+                        Expr::Array(ArrayLit {
+                            span: DUMMY_SP,
+                            elems: vec![
+                                Some(ExprOrSpread {
+                                    spread: None,
+                                    expr: Box::new(Expr::Lit(Lit::Num(Number {
+                                        value: 1.0,
+                                        raw: Some("1".into()),
+                                        span: DUMMY_SP,
+                                    }))),
+                                }),
+                                Some(ExprOrSpread {
+                                    spread: None,
+                                    expr: Box::new(Expr::Lit(Lit::Null(Null::dummy()))),
+                                }),
+                            ],
+                        })
+                    )
+                );
+            }
+            _ => panic!("Metadata should be an object"),
+        }
+    }
+
+    #[test]
+    fn should_create_input_source_map() {
+        let mut coverage_data = FileCoverage::empty("anon".to_string(), false);
+        coverage_data.input_source_map = Some(
+            serde_json::from_str(
+                r#"{ "version": 3, "sources": ["a.ts"], "sourcesContent": ["a;"], "names": [], "mappings": "AAAA", "ignoreList": [0], "debugId": "abc" }"#,
+            )
+            .unwrap(),
+        );
+        let (_hash, coverage_data_expr) = create_coverage_data_object(&coverage_data);
+
+        let props = match coverage_data_expr {
+            Expr::Object(ObjectLit { props, .. }) => props,
+            _ => panic!("Coverage data should be an object"),
+        };
+        let source_map_props = props
+            .iter()
+            .find_map(|prop| match prop {
+                PropOrSpread::Prop(prop) => match &**prop {
+                    Prop::KeyValue(KeyValueProp {
+                        key: PropName::Ident(key),
+                        value,
+                    }) if &*key.sym == "inputSourceMap" => match &**value {
+                        Expr::Object(ObjectLit { props, .. }) => Some(props),
+                        _ => None,
+                    },
+                    _ => None,
+                },
+                _ => None,
+            })
+            .expect("Input source map should be written");
+
+        let keys: Vec<String> = source_map_props
+            .iter()
+            .filter_map(|prop| match prop {
+                PropOrSpread::Prop(prop) => match &**prop {
+                    Prop::KeyValue(KeyValueProp {
+                        key: PropName::Ident(key),
+                        ..
+                    }) => Some(key.sym.to_string()),
+                    Prop::KeyValue(KeyValueProp {
+                        key: PropName::Str(key),
+                        ..
+                    }) => Some(key.value.to_string()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                "version",
+                "sources",
+                "sourcesContent",
+                "names",
+                "mappings",
+                "ignoreList",
+                "debugId"
+            ]
+        );
+    }
+
     #[test]
     fn should_create_empty_all() {
         let file_path = "anon";
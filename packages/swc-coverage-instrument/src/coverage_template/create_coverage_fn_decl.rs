@@ -1,5 +1,5 @@
 use std::{
-    collections::hash_map::DefaultHasher,
+    collections::{hash_map::DefaultHasher, HashSet},
     hash::{Hash, Hasher},
 };
 
@@ -12,29 +12,314 @@ use swc_common::{
 use swc_ecma_quote::quote;
 use swc_ecmascript::ast::*;
 
-use once_cell::sync::OnceCell;
-
 use crate::constants::idents::*;
 
-use crate::{create_assignment_stmt, create_coverage_data_object};
-
-pub static COVERAGE_FN_IDENT: OnceCell<Ident> = OnceCell::new();
-/// temporal ident being used for b_t true counter
-pub static COVERAGE_FN_TRUE_TEMP_IDENT: OnceCell<Ident> = OnceCell::new();
+use crate::{
+    create_assignment_stmt, create_coverage_data_object, CoverageManifestEntry,
+    COVERAGE_DATA_COMMENT_PREFIX, COVERAGE_MANIFEST_COMMENT_PREFIX,
+};
 
-/// Create a unique ident for the injected coverage counter fn,
-/// Stores it into a global scope.
-///
-/// Do not use static value directly - create_instrumentation_visitor macro
-/// should inject this into a struct accordingly.
-pub fn create_coverage_fn_ident(value: &str) {
+/// Create the name of the coverage fn of the given file path.
+pub fn create_coverage_fn_name(value: &str) -> String {
     let mut s = DefaultHasher::new();
     value.hash(&mut s);
-    let var_name_hash = format!("cov_{}", s.finish());
+    format!("cov_{}", s.finish())
+}
+
+/// Create an ident for the b_t true counter's temporal variable of the given coverage fn.
+pub fn create_coverage_fn_temp_ident(cov_fn_ident: &Ident) -> Ident {
+    Ident::new(format!("{}_temp", &*cov_fn_ident.sym).into(), DUMMY_SP)
+}
+
+/// Create an ident for the imported `onCover` fn of the given coverage fn.
+pub fn create_on_cover_ident(cov_fn_ident: &Ident) -> Ident {
+    Ident::new(format!("{}_onCover", &*cov_fn_ident.sym).into(), DUMMY_SP)
+}
+
+/// Create an ident for the imported `register` fn of the coverage registry module.
+pub fn create_register_ident(cov_fn_ident: &Ident) -> Ident {
+    Ident::new(format!("{}_register", &*cov_fn_ident.sym).into(), DUMMY_SP)
+}
+
+/// Create an ident for the coverage fn of the additional coverage variable at given index,
+/// which the counters of the given coverage fn are multiplexed into.
+pub fn create_multiplexed_coverage_fn_ident(cov_fn_ident: &Ident, index: usize) -> Ident {
+    Ident::new(
+        format!("{}_{}", &*cov_fn_ident.sym, index + 1).into(),
+        DUMMY_SP,
+    )
+}
+
+/// Check if given name for the coverage fn, or the names derived from it
+/// (b_t true counter's temp, hoisted counters, onCover / register fns, multiplexed coverage fns)
+/// collide with any of the identifiers.
+pub fn is_coverage_fn_name_taken(name: &str, idents: &HashSet<String>) -> bool {
+    idents.contains(name)
+        || idents.iter().any(|ident| {
+            ident
+                .strip_prefix(name)
+                .and_then(|suffix| suffix.strip_prefix('_'))
+                .is_some_and(|suffix| suffix.starts_with(|c: char| c.is_ascii_digit()))
+        })
+        || [
+            "temp",
+            "onCover",
+            "register",
+            &*IDENT_S.sym,
+            &*IDENT_F.sym,
+            &*IDENT_B.sym,
+            &*IDENT_BT.sym,
+        ]
+        .iter()
+        .any(|suffix| idents.contains(&format!("{}_{}", name, suffix)))
+}
+
+/// Create an ident for the coverage fn does not collide with any of the identifiers
+/// in the file. If it does, the hash is salted until it doesn't.
+pub fn create_unique_coverage_fn_ident(value: &str, idents: &HashSet<String>) -> Ident {
+    let mut name = create_coverage_fn_name(value);
+    let mut salt = 0;
+    while is_coverage_fn_name_taken(&name, idents) {
+        salt += 1;
+        name = create_coverage_fn_name(&format!("{}#{}", value, salt));
+    }
+
+    Ident::new(name.into(), DUMMY_SP)
+}
+
+/// Create an ident for the injected coverage counter fn of the given file path.
+///
+/// Derived per file, a host instrumenting multiple files in a single process
+/// (i.e `instrumentSync`) gets a distinct coverage fn for each file.
+pub fn create_coverage_fn_ident(value: &str) -> Ident {
+    Ident::new(create_coverage_fn_name(value).into(), DUMMY_SP)
+}
+
+/// Creates statements to look up coverage of the current realm from the namespaced store,
+/// `global[gcv][realmId]`. Realm id can be injected by setting `global[gcv + "RealmId"]`
+/// before any instrumented code runs, otherwise a random id is assigned.
+/// Also emits `global[gcv + "Extract"](realmId?)`, returns coverage of the given (or current) realm.
+fn create_realm_coverage_stmts(coverage_ident: &Ident) -> Vec<Stmt> {
+    let realm_id_ident = Ident::new("realmId".into(), DUMMY_SP);
+    let realms_ident = Ident::new("realms".into(), DUMMY_SP);
+
+    vec![
+        quote!(
+            r#"var $realm_id = $global[$gcv + "RealmId"] || ($global[$gcv + "RealmId"] = Math.random().toString(36).slice(2));"#
+                as Stmt,
+            realm_id = realm_id_ident.clone(),
+            gcv = IDENT_GCV.clone(),
+            global = IDENT_GLOBAL.clone()
+        ),
+        quote!(
+            "var $realms = $global[$gcv] || ($global[$gcv] = {});" as Stmt,
+            realms = realms_ident.clone(),
+            gcv = IDENT_GCV.clone(),
+            global = IDENT_GLOBAL.clone()
+        ),
+        quote!(
+            "var $coverage = $realms[$realm_id] || ($realms[$realm_id] = {});" as Stmt,
+            coverage = coverage_ident.clone(),
+            realms = realms_ident,
+            realm_id = realm_id_ident
+        ),
+        quote!(
+            r#"
+$global[$gcv + "Extract"] = function (id) {
+  var store = $global[$gcv] || {};
+  return store[id === undefined ? $global[$gcv + "RealmId"] : id];
+};
+"# as Stmt,
+            gcv = IDENT_GCV.clone(),
+            global = IDENT_GLOBAL.clone()
+        ),
+    ]
+}
+
+/// Creates statements to make the properties of the global defined by the template non-enumerable
+/// and non-configurable, so they don't show up enumerating the keys of the global. Properties
+/// are still writable to reset coverage, redefining with the same attributes is a no-op.
+fn create_non_enumerable_stmts(realm_isolation: bool) -> Vec<Stmt> {
+    let mut stmts = vec![quote!(
+        "Object.defineProperty($global, $gcv, { enumerable: false, configurable: false });" as Stmt,
+        gcv = IDENT_GCV.clone(),
+        global = IDENT_GLOBAL.clone()
+    )];
+
+    if realm_isolation {
+        stmts.push(quote!(
+            r#"Object.defineProperty($global, $gcv + "RealmId", { enumerable: false, configurable: false });"#
+                as Stmt,
+            gcv = IDENT_GCV.clone(),
+            global = IDENT_GLOBAL.clone()
+        ));
+        stmts.push(quote!(
+            r#"Object.defineProperty($global, $gcv + "Extract", { enumerable: false, configurable: false });"#
+                as Stmt,
+            gcv = IDENT_GCV.clone(),
+            global = IDENT_GLOBAL.clone()
+        ));
+    }
+
+    stmts
+}
+
+/// Creates statements to look up the coverage store from the global, register coverage data
+/// of the file into it and read the coverage of the file as `actual_coverage_ident`.
+fn create_global_coverage_stmts(
+    actual_coverage_ident: &Ident,
+    realm_isolation: bool,
+    non_enumerable: bool,
+) -> Vec<Stmt> {
+    let mut stmts = vec![];
+
+    let coverage_ident = Ident::new("coverage".into(), DUMMY_SP);
+    if realm_isolation {
+        stmts.extend(create_realm_coverage_stmts(&coverage_ident));
+    } else {
+        stmts.push(quote!(
+            "var $coverage = $global[$gcv] || ($global[$gcv] = {})" as Stmt,
+            coverage = coverage_ident.clone(),
+            gcv = IDENT_GCV.clone(),
+            global = IDENT_GLOBAL.clone()
+        ));
+    }
 
-    COVERAGE_FN_IDENT.get_or_init(|| Ident::new(var_name_hash.clone().into(), DUMMY_SP));
-    COVERAGE_FN_TRUE_TEMP_IDENT
-        .get_or_init(|| Ident::new(format!("{}_temp", var_name_hash).into(), DUMMY_SP));
+    if non_enumerable {
+        stmts.extend(create_non_enumerable_stmts(realm_isolation));
+    }
+
+    stmts.push(quote!(
+        r#"
+if (!$coverage[$path] || $coverage[$path].$hash !== $hash) {
+  $coverage[$path] = $coverage_data;
+}
+"# as Stmt,
+        coverage = coverage_ident.clone(),
+        path = IDENT_PATH.clone(),
+        hash = IDENT_HASH.clone(),
+        coverage_data = IDENT_COVERAGE_DATA.clone()
+    ));
+
+    // var actualCoverage = coverage[path];
+    stmts.push(quote!(
+        "var $actual_coverage = $coverage[$path];" as Stmt,
+        actual_coverage = actual_coverage_ident.clone(),
+        coverage = coverage_ident.clone(),
+        path = IDENT_PATH.clone()
+    ));
+
+    stmts
+}
+
+/// Creates statements to freeze the maps of the coverage data, including the nested objects.
+/// Hit counts (`s`, `f`, `b`, `bT`) are left writable.
+fn create_freeze_stmts() -> Vec<Stmt> {
+    let freeze_ident = Ident::new("freeze".into(), DUMMY_SP);
+
+    vec![
+        quote!(
+            r#"
+var $freeze = function (value) {
+  if (value && typeof value === "object" && !Object.isFrozen(value)) {
+    Object.freeze(value);
+    for (var key in value) {
+      $freeze(value[key]);
+    }
+  }
+};
+"# as Stmt,
+            freeze = freeze_ident.clone()
+        ),
+        quote!(
+            r#"
+[
+  $coverage_data.statementMap,
+  $coverage_data.fnMap,
+  $coverage_data.branchMap,
+  $coverage_data.inputSourceMap
+].forEach($freeze);
+"# as Stmt,
+            coverage_data = IDENT_COVERAGE_DATA.clone(),
+            freeze = freeze_ident
+        ),
+    ]
+}
+
+/// Creates statements to define the global fn of `globalHitFn` if it's not defined yet, and
+/// register the coverage into it under the name of the coverage fn. Counters call it as
+/// `hitFn("cov_xyz", "s", 0)`, or `hitFn("cov_xyz", "b", 0, 1)` for the branches.
+fn create_global_hit_fn_stmts(
+    actual_coverage_ident: &Ident,
+    cov_fn_ident: &Ident,
+    global_hit_fn: &str,
+) -> Vec<Stmt> {
+    vec![
+        // var hitFn = $global_hit_fn;
+        create_assignment_stmt(
+            &IDENT_HIT_FN,
+            Expr::Lit(Lit::Str(Str {
+                value: global_hit_fn.into(),
+                ..Str::dummy()
+            })),
+        ),
+        quote!(
+            r#"
+if (typeof $global[$hit_fn] !== "function") {
+  $global[$hit_fn] = function hit(id, type, n, i) {
+    var counters = hit[id][type];
+    i === undefined ? counters[n]++ : counters[n][i]++;
+  };
+}
+"# as Stmt,
+            global = IDENT_GLOBAL.clone(),
+            hit_fn = IDENT_HIT_FN.clone()
+        ),
+        // $global[$hit_fn]["cov_xyz"] = $actual_coverage;
+        Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Assign(AssignExpr {
+                op: AssignOp::Assign,
+                left: PatOrExpr::Expr(Box::new(Expr::Member(MemberExpr {
+                    obj: quote!(
+                        "$global[$hit_fn]" as Box<Expr>,
+                        global = IDENT_GLOBAL.clone(),
+                        hit_fn = IDENT_HIT_FN.clone()
+                    ),
+                    prop: MemberProp::Computed(ComputedPropName {
+                        span: DUMMY_SP,
+                        expr: Box::new(Expr::Lit(Lit::Str(Str {
+                            value: cov_fn_ident.sym.clone(),
+                            ..Str::dummy()
+                        }))),
+                    }),
+                    ..MemberExpr::dummy()
+                }))),
+                right: Box::new(Expr::Ident(actual_coverage_ident.clone())),
+                ..AssignExpr::dummy()
+            })),
+        }),
+    ]
+}
+
+/// Options of the coverage fn declaration, resolved from `InstrumentOptions`
+/// for each coverage variable.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CoverageFnDeclOptions<'a> {
+    /// Register coverage through the fn imported from the registry module
+    /// instead of looking up the global with the coverage template.
+    pub register_ident: Option<&'a Ident>,
+    /// Attach the coverage data as a trailing comment.
+    pub attach_debug_comment: bool,
+    /// Attach the manifest entry of the coverage as a trailing comment.
+    pub attach_manifest_comment: bool,
+    pub realm_isolation: bool,
+    pub non_enumerable: bool,
+    /// Attach `@ts-ignore` to the reassignment of the coverage fn.
+    pub ts_ignore: bool,
+    /// Register coverage into the global fn of the name as well.
+    pub global_hit_fn: Option<&'a str>,
+    pub freeze: bool,
 }
 
 /// Creates a function declaration for actual coverage collection.
@@ -45,8 +330,19 @@ pub fn create_coverage_fn_decl<C: Clone + Comments>(
     file_path: &str,
     coverage_data: &FileCoverage,
     comments: &C,
-    attach_debug_comment: bool,
+    options: &CoverageFnDeclOptions,
 ) -> Stmt {
+    let CoverageFnDeclOptions {
+        register_ident,
+        attach_debug_comment,
+        attach_manifest_comment,
+        realm_isolation,
+        non_enumerable,
+        ts_ignore,
+        global_hit_fn,
+        freeze,
+    } = *options;
+
     // Actual fn body statements will be injected
     let mut stmts = vec![];
 
@@ -68,7 +364,9 @@ pub fn create_coverage_fn_decl<C: Clone + Comments>(
     stmts.push(hash_stmt);
 
     // var global = new Function("return $global_coverage_scope")();
-    stmts.push(coverage_template);
+    if register_ident.is_none() || global_hit_fn.is_some() {
+        stmts.push(coverage_template);
+    }
 
     // var gcv = ${coverage_variable};
     let gcv_stmt = create_assignment_stmt(
@@ -84,40 +382,43 @@ pub fn create_coverage_fn_decl<C: Clone + Comments>(
     let coverage_data_stmt = create_assignment_stmt(&IDENT_COVERAGE_DATA, coverage_data_object);
     stmts.push(coverage_data_stmt);
 
-    let coverage_ident = Ident::new("coverage".into(), DUMMY_SP);
-    stmts.push(quote!(
-        "var $coverage = $global[$gcv] || ($global[$gcv] = {})" as Stmt,
-        coverage = coverage_ident.clone(),
-        gcv = IDENT_GCV.clone(),
-        global = IDENT_GLOBAL.clone()
-    ));
-
-    stmts.push(quote!(
-        r#"
-if (!$coverage[$path] || $coverage[$path].$hash !== $hash) {
-  $coverage[$path] = $coverage_data;
-}
-"# as Stmt,
-        coverage = coverage_ident.clone(),
-        path = IDENT_PATH.clone(),
-        hash = IDENT_HASH.clone(),
-        coverage_data = IDENT_COVERAGE_DATA.clone()
-    ));
+    if freeze {
+        stmts.extend(create_freeze_stmts());
+    }
 
-    // var actualCoverage = coverage[path];
     let actual_coverage_ident = Ident::new("actualCoverage".into(), DUMMY_SP);
-    stmts.push(quote!(
-        "var $actual_coverage = $coverage[$path];" as Stmt,
-        actual_coverage = actual_coverage_ident.clone(),
-        coverage = coverage_ident.clone(),
-        path = IDENT_PATH.clone()
-    ));
+    if let Some(register_ident) = register_ident {
+        // var actualCoverage = $register(gcv, path, hash, coverageData);
+        stmts.push(quote!(
+            "var $actual_coverage = $register($gcv, $path, $hash, $coverage_data);" as Stmt,
+            actual_coverage = actual_coverage_ident.clone(),
+            register = register_ident.clone(),
+            gcv = IDENT_GCV.clone(),
+            path = IDENT_PATH.clone(),
+            hash = IDENT_HASH.clone(),
+            coverage_data = IDENT_COVERAGE_DATA.clone()
+        ));
+    } else {
+        stmts.extend(create_global_coverage_stmts(
+            &actual_coverage_ident,
+            realm_isolation,
+            non_enumerable,
+        ));
+    }
+
+    if let Some(global_hit_fn) = global_hit_fn {
+        stmts.extend(create_global_hit_fn_stmts(
+            &actual_coverage_ident,
+            cov_fn_ident,
+            global_hit_fn,
+        ));
+    }
 
     //
+    // @ts-ignore
     //COVERAGE_FUNCTION = function () {
     //   return actualCoverage;
     //}
-    // TODO: need to add @ts-ignore leading comment
     let coverage_fn_assign_expr = Expr::Assign(AssignExpr {
         left: PatOrExpr::Pat(Box::new(Pat::Ident(BindingIdent::from(
             cov_fn_ident.clone(),
@@ -138,9 +439,25 @@ if (!$coverage[$path] || $coverage[$path].$hash !== $hash) {
         ..AssignExpr::dummy()
     });
 
+    // Reassigning the fn declaration is an error for the type checkers.
+    let coverage_fn_assign_span = if ts_ignore {
+        let span = Span::dummy_with_cmt();
+        comments.add_leading(
+            span.lo,
+            Comment {
+                kind: CommentKind::Line,
+                span: DUMMY_SP,
+                text: " @ts-ignore".into(),
+            },
+        );
+        span
+    } else {
+        DUMMY_SP
+    };
+
     stmts.push(Stmt::Block(BlockStmt {
         stmts: vec![Stmt::Expr(ExprStmt {
-            span: DUMMY_SP,
+            span: coverage_fn_assign_span,
             expr: Box::new(coverage_fn_assign_expr),
         })],
         ..BlockStmt::dummy()
@@ -163,7 +480,23 @@ if (!$coverage[$path] || $coverage[$path].$hash !== $hash) {
             Comment {
                 kind: CommentKind::Block,
                 span: Span::dummy_with_cmt(),
-                text: format!("__coverage_data_json_comment__::{}", coverage_data_json_str).into(),
+                text: format!("{}{}", COVERAGE_DATA_COMMENT_PREFIX, coverage_data_json_str),
+            },
+        );
+    }
+
+    if attach_manifest_comment {
+        let manifest_entry =
+            CoverageManifestEntry::new(coverage_data, coverage_variable, &cov_fn_ident.sym, &hash);
+        let manifest_json_str =
+            serde_json::to_string(&manifest_entry).expect("Should able to serialize manifest");
+
+        comments.add_trailing(
+            Span::dummy_with_cmt().hi,
+            Comment {
+                kind: CommentKind::Block,
+                span: Span::dummy_with_cmt(),
+                text: format!("{}{}", COVERAGE_MANIFEST_COMMENT_PREFIX, manifest_json_str),
             },
         );
     }
@@ -183,3 +516,153 @@ if (!$coverage[$path] || $coverage[$path].$hash !== $hash) {
         },
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use istanbul_oxide::FileCoverage;
+    use swc_common::{comments::SingleThreadedComments, DUMMY_SP};
+    use swc_ecmascript::{
+        ast::*,
+        visit::{Visit, VisitWith},
+    };
+
+    use super::{
+        create_coverage_fn_decl, create_coverage_fn_name, create_unique_coverage_fn_ident,
+        CoverageFnDeclOptions,
+    };
+
+    #[test]
+    fn should_keep_name_without_collision() {
+        let idents: HashSet<String> = ["cov", "cov_", "coverage"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        assert_eq!(
+            &*create_unique_coverage_fn_ident("a.js", &idents).sym,
+            create_coverage_fn_name("a.js")
+        );
+    }
+
+    #[test]
+    fn should_salt_name_collides_with_idents() {
+        let name = create_coverage_fn_name("a.js");
+
+        for taken in [
+            name.clone(),
+            format!("{}_temp", name),
+            format!("{}_s", name),
+            format!("{}_f", name),
+            format!("{}_b", name),
+            format!("{}_bT", name),
+            format!("{}_onCover", name),
+            format!("{}_register", name),
+            format!("{}_1", name),
+            format!("{}_2_s", name),
+        ] {
+            let idents = HashSet::from([taken.clone()]);
+            let ident = create_unique_coverage_fn_ident("a.js", &idents);
+
+            assert_ne!(&*ident.sym, name, "should not collide with {}", taken);
+            assert!(ident.sym.starts_with("cov_"));
+            // Deterministic for the same input
+            assert_eq!(ident, create_unique_coverage_fn_ident("a.js", &idents));
+        }
+    }
+
+    #[test]
+    fn should_salt_until_name_does_not_collide() {
+        let mut idents = HashSet::new();
+        let mut names = vec![];
+        // Each iteration occupies the name previously generated.
+        for _ in 0..5 {
+            let name = create_unique_coverage_fn_ident("a.js", &idents)
+                .sym
+                .to_string();
+            assert!(!names.contains(&name));
+            idents.insert(name.clone());
+            names.push(name);
+        }
+    }
+
+    /// Collects the callees of the member calls in the coverage fn, `Object.defineProperty(..)`
+    /// as `Object.defineProperty` and `[..].forEach(..)` as `.forEach`.
+    struct MemberCallFinder(Vec<String>);
+
+    impl Visit for MemberCallFinder {
+        fn visit_call_expr(&mut self, call: &CallExpr) {
+            if let Callee::Expr(callee) = &call.callee {
+                if let Expr::Member(MemberExpr {
+                    obj,
+                    prop: MemberProp::Ident(prop),
+                    ..
+                }) = &**callee
+                {
+                    let obj = match &**obj {
+                        Expr::Ident(ident) => ident.sym.to_string(),
+                        _ => String::new(),
+                    };
+                    self.0.push(format!("{}.{}", obj, prop.sym));
+                }
+            }
+            call.visit_children_with(self);
+        }
+    }
+
+    fn find_member_calls(options: &CoverageFnDeclOptions) -> Vec<String> {
+        let decl = create_coverage_fn_decl(
+            "__coverage__",
+            crate::create_global_stmt_template("this"),
+            &Ident::new("cov_1".into(), DUMMY_SP),
+            "a.js",
+            &FileCoverage::from_file_path("a.js".to_string(), false),
+            &SingleThreadedComments::default(),
+            options,
+        );
+
+        let mut finder = MemberCallFinder(vec![]);
+        decl.visit_with(&mut finder);
+        finder.0
+    }
+
+    #[test]
+    fn should_define_non_enumerable_coverage_variable() {
+        let count_define_property = |realm_isolation: bool, non_enumerable: bool| {
+            find_member_calls(&CoverageFnDeclOptions {
+                realm_isolation,
+                non_enumerable,
+                ..Default::default()
+            })
+            .iter()
+            .filter(|callee| *callee == "Object.defineProperty")
+            .count()
+        };
+
+        assert_eq!(count_define_property(false, false), 0);
+        assert_eq!(count_define_property(false, true), 1);
+        assert_eq!(count_define_property(true, false), 0);
+        assert_eq!(count_define_property(true, true), 3);
+    }
+
+    #[test]
+    fn should_freeze_coverage_maps() {
+        let find_freeze_calls = |freeze: bool| {
+            find_member_calls(&CoverageFnDeclOptions {
+                freeze,
+                ..Default::default()
+            })
+            .into_iter()
+            .filter(|callee| callee == "Object.freeze" || callee == ".forEach")
+            .collect::<Vec<_>>()
+        };
+
+        assert!(find_freeze_calls(false).is_empty());
+        // `freeze` fn calling `Object.freeze`, applied to each of the maps.
+        assert_eq!(
+            find_freeze_calls(true),
+            vec!["Object.freeze".to_string(), ".forEach".to_string()]
+        );
+    }
+}
@@ -61,3 +61,84 @@ pub fn create_increase_counter_expr(
         arg: Box::new(Expr::Member(expr)),
     })
 }
+
+fn get_num_prop(prop: &MemberProp) -> Option<u32> {
+    match prop {
+        MemberProp::Computed(ComputedPropName { expr, .. }) => match &**expr {
+            Expr::Lit(Lit::Num(Number { value, .. })) => Some(*value as u32),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Reverse of `create_increase_counter_expr`. Returns the ident of the coverage fn,
+/// the type of the counter, the id and the idx if given expr is a counter.
+pub fn parse_increase_counter_expr(expr: &Expr) -> Option<(&Ident, &str, u32, Option<u32>)> {
+    let arg = match expr {
+        Expr::Update(UpdateExpr {
+            op: UpdateOp::PlusPlus,
+            arg,
+            ..
+        }) => &**arg,
+        _ => return None,
+    };
+
+    let (obj, last) = match arg {
+        Expr::Member(MemberExpr { obj, prop, .. }) => (&**obj, get_num_prop(prop)?),
+        _ => return None,
+    };
+
+    // `cov_xyz().b[0][1]` has an idx, `cov_xyz().s[0]` doesn't.
+    let (obj, id, idx) = match obj {
+        Expr::Member(MemberExpr { obj, prop, .. }) if get_num_prop(prop).is_some() => {
+            (&**obj, get_num_prop(prop)?, Some(last))
+        }
+        _ => (obj, last, None),
+    };
+
+    match obj {
+        Expr::Member(MemberExpr {
+            obj,
+            prop: MemberProp::Ident(type_ident),
+            ..
+        }) => match &**obj {
+            Expr::Call(CallExpr {
+                callee: Callee::Expr(callee),
+                args,
+                ..
+            }) if args.is_empty() => match &**callee {
+                Expr::Ident(var_name) => Some((var_name, &*type_ident.sym, id, idx)),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_ecmascript::ast::Ident;
+
+    use super::{create_increase_counter_expr, parse_increase_counter_expr};
+
+    #[test]
+    fn should_parse_increase_counter_expr() {
+        let var_name = Ident::new("cov_1".into(), Default::default());
+        let s = Ident::new("s".into(), Default::default());
+        let b = Ident::new("b".into(), Default::default());
+
+        let expr = create_increase_counter_expr(&s, 3, &var_name, None);
+        assert_eq!(
+            parse_increase_counter_expr(&expr),
+            Some((&var_name, "s", 3, None))
+        );
+
+        let expr = create_increase_counter_expr(&b, 2, &var_name, Some(1));
+        assert_eq!(
+            parse_increase_counter_expr(&expr),
+            Some((&var_name, "b", 2, Some(1)))
+        );
+    }
+}
@@ -52,6 +52,26 @@ pub fn create_increase_true_expr(
     ret
 }
 
+/// Parse the expr created by `create_increase_true_expr`, returns the bT counter expr and
+/// the original expr it reads.
+pub fn parse_increase_true_expr(expr: &Expr) -> Option<(&Expr, &Expr)> {
+    if let Expr::Seq(SeqExpr { exprs, .. }) = expr {
+        if let [assign, paren, _] = &exprs[..] {
+            if let (
+                Expr::Assign(AssignExpr { right, .. }),
+                Expr::Paren(ParenExpr { expr: cond, .. }),
+            ) = (&**assign, &**paren)
+            {
+                if let Expr::Cond(CondExpr { cons, .. }) = &**cond {
+                    return Some((&**cons, &**right));
+                }
+            }
+        }
+    }
+
+    None
+}
+
 fn validate_true_non_trivial(var_name: &Ident, temp_var_name: &Ident) -> Expr {
     // TODO: duplicate code with create_increase_true_expr
     let member = Expr::Member(MemberExpr {
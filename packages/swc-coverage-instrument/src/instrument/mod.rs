@@ -1,2 +1,3 @@
 pub mod create_increase_counter_expr;
 pub mod create_increase_true_expr;
+pub mod wrap_expr_with_counter;
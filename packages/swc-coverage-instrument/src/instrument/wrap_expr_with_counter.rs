@@ -0,0 +1,136 @@
+use swc_common::{util::take::Take, DUMMY_SP};
+use swc_ecmascript::ast::*;
+
+fn create_seq_expr(increment_expr: Expr, expr: Expr) -> Expr {
+    Expr::Paren(ParenExpr {
+        span: DUMMY_SP,
+        expr: Box::new(Expr::Seq(SeqExpr {
+            span: DUMMY_SP,
+            exprs: vec![Box::new(increment_expr), Box::new(expr)],
+        })),
+    })
+}
+
+/// Wraps given expr to evaluate the counter right before it, like `(cov_17709493053001988098().s[0]++, foo)`.
+/// Result is always parenthesized, so it can replace the expr in any position
+/// without changing the precedence (i.e `new (a || b)()`, or an operand of the logical expr).
+/// Expr is moved into the sequence and never cloned, so it is evaluated once even if it has
+/// side effects (i.e a spread argument `f(...(a || g()))`).
+///
+/// Member expr `foo.bar` is called with `foo` as its receiver, which is lost if whole member
+/// expr is wrapped into a sequence (i.e as a callee, or a decorator `@foo.bar`). Counter is
+/// placed in the object of the member instead, like `((cov_17709493053001988098().s[0]++, foo).bar)`.
+pub fn wrap_expr_with_counter(increment_expr: Expr, mut expr: Expr) -> Expr {
+    match find_leftmost_object(&mut expr) {
+        Some(obj) => {
+            *obj = create_seq_expr(increment_expr, obj.take());
+            Expr::Paren(ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(expr),
+            })
+        }
+        None => create_seq_expr(increment_expr, expr),
+    }
+}
+
+fn is_member(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Member(..)
+            | Expr::OptChain(OptChainExpr {
+                base: OptChainBase::Member(..),
+                ..
+            })
+    )
+}
+
+/// Find the leftmost object of the chained member (`foo.bar.baz`, `foo?.bar`).
+fn find_leftmost_object(expr: &mut Expr) -> Option<&mut Expr> {
+    let obj = match expr {
+        Expr::Member(MemberExpr { obj, .. })
+        | Expr::OptChain(OptChainExpr {
+            base: OptChainBase::Member(MemberExpr { obj, .. }),
+            ..
+        }) => obj,
+        _ => return None,
+    };
+
+    if is_member(obj) {
+        find_leftmost_object(obj)
+    } else {
+        Some(&mut **obj)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_common::DUMMY_SP;
+    use swc_ecmascript::ast::*;
+
+    use super::wrap_expr_with_counter;
+
+    fn ident(sym: &str) -> Expr {
+        Expr::Ident(Ident::new(sym.into(), DUMMY_SP))
+    }
+
+    fn member(obj: Expr, prop: &str) -> MemberExpr {
+        MemberExpr {
+            span: DUMMY_SP,
+            obj: Box::new(obj),
+            prop: MemberProp::Ident(Ident::new(prop.into(), DUMMY_SP)),
+        }
+    }
+
+    fn seq(increment_expr: Expr, expr: Expr) -> Expr {
+        Expr::Paren(ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Seq(SeqExpr {
+                span: DUMMY_SP,
+                exprs: vec![Box::new(increment_expr), Box::new(expr)],
+            })),
+        })
+    }
+
+    fn paren(expr: Expr) -> Expr {
+        Expr::Paren(ParenExpr {
+            span: DUMMY_SP,
+            expr: Box::new(expr),
+        })
+    }
+
+    #[test]
+    fn should_wrap_expr_with_paren() {
+        // `(a, b)` stays as a single operand: `(counter, (a, b))`
+        let expr = seq(ident("a"), ident("b"));
+        assert_eq!(
+            wrap_expr_with_counter(ident("counter"), expr.clone()),
+            seq(ident("counter"), expr)
+        );
+    }
+
+    #[test]
+    fn should_preserve_receiver_of_member() {
+        // `foo.bar.baz` -> `((counter, foo).bar.baz)`
+        let expr = Expr::Member(member(Expr::Member(member(ident("foo"), "bar")), "baz"));
+        assert_eq!(
+            wrap_expr_with_counter(ident("counter"), expr),
+            paren(Expr::Member(member(
+                Expr::Member(member(seq(ident("counter"), ident("foo")), "bar")),
+                "baz"
+            )))
+        );
+
+        // `foo?.bar` -> `((counter, foo)?.bar)`
+        let opt_chain = |obj: Expr| {
+            Expr::OptChain(OptChainExpr {
+                span: DUMMY_SP,
+                question_dot_token: DUMMY_SP,
+                base: OptChainBase::Member(member(obj, "bar")),
+            })
+        };
+        assert_eq!(
+            wrap_expr_with_counter(ident("counter"), opt_chain(ident("foo"))),
+            paren(opt_chain(seq(ident("counter"), ident("foo"))))
+        );
+    }
+}
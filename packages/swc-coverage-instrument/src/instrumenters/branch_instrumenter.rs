@@ -0,0 +1,178 @@
+use swc_common::{util::take::Take, DUMMY_SP};
+use swc_ecmascript::{
+    ast::*,
+    visit::{VisitMut, VisitMutWith},
+};
+
+use super::InstrumentationContext;
+
+/// Creates branches and wraps its paths with the branch counters.
+pub trait BranchInstrumenter: InstrumentationContext + VisitMut + Sized {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn replace_expr_with_branch_counter(&mut self, expr: &mut Expr, branch: u32) {
+        self.replace_expr_with_counter(expr, |cov, cov_fn_ident, range| {
+            let idx = cov.add_branch_path(branch, range);
+
+            crate::create_increase_counter_expr(
+                &crate::constants::idents::IDENT_B,
+                branch,
+                cov_fn_ident,
+                Some(idx),
+            )
+        });
+    }
+
+    /// Create a branch for the condition of the loop if `cover_loop_conditions` is set.
+    /// Paths of a logical condition are its operands same as logical exprs, others have
+    /// two paths whether the loop is entered or exited by the condition. Paths are created by
+    /// `wrap_loop_condition_with_branch_counter`.
+    fn create_loop_condition_branch(&mut self, test: &Expr) -> Option<u32> {
+        if !self.instrument_options().cover_loop_conditions
+            || !self.instrument_options().cover_branches
+        {
+            return None;
+        }
+
+        let span =
+            crate::lookup_range::get_expr_span(test).filter(|span| !self.is_unmapped_span(span))?;
+        let range = crate::lookup_range::get_range_from_span(self.source_map(), span);
+        let (branch_type, report_logic) = if crate::visitors::finders::is_logical_expr(test) {
+            (
+                crate::BranchType::BinaryExpr,
+                self.instrument_options().report_logic,
+            )
+        } else {
+            (crate::BranchType::If, false)
+        };
+        Some(
+            self.cov()
+                .borrow_mut()
+                .new_branch(branch_type, &range, report_logic),
+        )
+    }
+
+    /// Wrap the condition of the loop with the counters of the branch created by
+    /// `create_loop_condition_branch`. Non logical condition becomes
+    /// `test ? (++b[0], true) : (++b[1], false)`, the loop only reads truthiness of it.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn wrap_loop_condition_with_branch_counter(&mut self, branch: u32, test: &mut Expr) {
+        if crate::visitors::finders::is_logical_expr(test) {
+            self.wrap_bin_expr_with_branch_counter(branch, test);
+            return;
+        }
+
+        test.visit_mut_with(self);
+
+        let span = match crate::lookup_range::get_expr_span(test) {
+            Some(span) => *span,
+            None => return,
+        };
+        let range = crate::lookup_range::get_range_from_span(self.source_map(), &span);
+
+        let create_path = |value: bool| {
+            let idx = self.cov().borrow_mut().add_branch_path(branch, &range);
+            let increase_expr = crate::create_increase_counter_expr(
+                &crate::constants::idents::IDENT_B,
+                branch,
+                self.cov_fn_ident(),
+                Some(idx),
+            );
+
+            Box::new(Expr::Paren(ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Seq(SeqExpr {
+                    span: DUMMY_SP,
+                    exprs: vec![
+                        Box::new(increase_expr),
+                        Box::new(Expr::Lit(Lit::Bool(Bool {
+                            span: DUMMY_SP,
+                            value,
+                        }))),
+                    ],
+                })),
+            }))
+        };
+
+        let cons = create_path(true);
+        let alt = create_path(false);
+
+        *test = Expr::Cond(CondExpr {
+            span: DUMMY_SP,
+            test: Box::new(Expr::Paren(ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(test.take()),
+            })),
+            cons,
+            alt,
+        });
+    }
+
+    /// Attempt to wrap expression with branch increase counter.
+    /// Given Expr may be left, or right of the logical expression.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn wrap_bin_expr_with_branch_counter(&mut self, branch: u32, expr: &mut Expr) {
+        let span = crate::lookup_range::get_expr_span(expr).copied();
+        let should_ignore = crate::hint_comments::should_ignore(self.comments(), span.as_ref());
+
+        if let Some(crate::hint_comments::IgnoreScope::Next) = should_ignore {
+            return;
+        }
+
+        // Logical expression can have inner logical expression as non-direct child
+        // (i.e `args[0] > 0 && (args[0] < 5 || args[0] > 10)`, logical || expr is child of ParenExpr.
+        // Same as istanbul's findLeaves, inner logical exprs share the branch of the outermost one:
+        // traverse down until reaches to the leaves.
+        match expr {
+            Expr::Paren(ParenExpr { expr: inner, .. })
+                if crate::visitors::finders::is_logical_expr(inner) =>
+            {
+                self.wrap_bin_expr_with_branch_counter(branch, inner);
+                return;
+            }
+            Expr::Bin(BinExpr {
+                op: BinaryOp::LogicalOr | BinaryOp::LogicalAnd | BinaryOp::NullishCoalescing,
+                left,
+                right,
+                ..
+            }) => {
+                self.wrap_bin_expr_with_branch_counter(branch, left);
+                self.wrap_bin_expr_with_branch_counter(branch, right);
+                return;
+            }
+            _ => {}
+        }
+
+        // Now we believe this expr is the leaf of the logical expr tree.
+        // Leaf itself can have nodes to instrument (i.e functions, or logical exprs in the call arguments
+        // as a new branch), visit it before wrapping it with branch counter.
+        expr.visit_mut_with(self);
+
+        if self.instrument_options().report_logic {
+            if let Some(span) = span.filter(|span| !self.is_unmapped_span(span)) {
+                let range = crate::lookup_range::get_range_from_span(self.source_map(), &span);
+                let branch_path_index = self.cov().borrow_mut().add_branch_path(branch, &range);
+
+                let increase_expr = crate::create_increase_counter_expr(
+                    &crate::constants::idents::IDENT_B,
+                    branch,
+                    self.cov_fn_ident(),
+                    Some(branch_path_index),
+                );
+                let increase_true_expr = crate::create_increase_true_expr(
+                    branch,
+                    branch_path_index,
+                    self.cov_fn_ident(),
+                    self.cov_fn_temp_ident(),
+                    expr.take(),
+                );
+
+                // replace init with increase expr + init seq
+                *expr = crate::wrap_expr_with_counter(increase_expr, increase_true_expr);
+            }
+        } else {
+            self.replace_expr_with_branch_counter(expr, branch);
+        }
+    }
+}
+
+impl<T: InstrumentationContext + VisitMut> BranchInstrumenter for T {}
@@ -0,0 +1,178 @@
+use swc_common::{util::take::Take, Span};
+use swc_ecmascript::ast::*;
+
+use super::InstrumentationContext;
+use crate::{FunctionKind, FunctionMetadata};
+
+/// Creates functions and inserts the function counters into its body.
+pub trait FunctionInstrumenter: InstrumentationContext {
+    /// Common logics for the fn-like visitors to insert fn instrumentation counters.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn create_fn_instrumentation(
+        &mut self,
+        ident: &Option<&Ident>,
+        kind: FunctionKind,
+        function: &mut Function,
+    ) {
+        if !self.instrument_options().cover_functions || self.is_unmapped_span(&function.span) {
+            return;
+        }
+
+        let (range, name) = if let Some(ident) = &ident {
+            (
+                crate::lookup_range::get_range_from_span(self.source_map(), &ident.span),
+                Some(ident.sym.to_string()),
+            )
+        } else {
+            (
+                crate::lookup_range::get_anonymous_fn_decl_range(self.source_map(), &function.span),
+                None,
+            )
+        };
+
+        self.insert_fn_counter(&name, &range, kind, function);
+    }
+
+    /// Records `fnMetadata` of the function created, if enabled.
+    fn set_fn_metadata(&mut self, id: u32, kind: FunctionKind, is_async: bool, is_generator: bool) {
+        if !self.instrument_options().fn_metadata {
+            return;
+        }
+
+        self.cov().borrow_mut().set_function_metadata(
+            id,
+            FunctionMetadata {
+                is_async,
+                is_generator,
+                kind,
+                // Resolved once the whole module is visited.
+                exported: false,
+            },
+        );
+    }
+
+    /// Whether the method is listed in `ignore_class_methods`. Applies to the methods of
+    /// the object literals as well, same as istanbul.
+    fn should_ignore_method(&self, name: &Option<String>) -> bool {
+        name.as_ref().is_some_and(|name| {
+            self.instrument_options()
+                .ignore_class_methods
+                .iter()
+                .any(|v| v == name)
+        })
+    }
+
+    /// Insert fn instrumentation counters for the methods, named by its key.
+    /// Declaration is the start of the method (given span) same as istanbul, not the key.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn create_method_instrumentation(
+        &mut self,
+        name: &Option<String>,
+        span: &Span,
+        kind: FunctionKind,
+        function: &mut Function,
+    ) {
+        if !self.instrument_options().cover_functions || self.is_unmapped_span(&function.span) {
+            return;
+        }
+
+        let range = crate::lookup_range::get_anonymous_fn_decl_range(self.source_map(), span);
+        self.insert_fn_counter(name, &range, kind, function);
+    }
+
+    /// Insert fn instrumentation counters for the getters / setters of the object literals,
+    /// which have the body only. Declaration is the start of the prop.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn create_accessor_instrumentation(
+        &mut self,
+        name: &Option<String>,
+        span: &Span,
+        kind: FunctionKind,
+        body: &mut Option<BlockStmt>,
+    ) {
+        if !self.instrument_options().cover_functions || self.is_unmapped_span(span) {
+            return;
+        }
+
+        if let Some(body) = body {
+            let range = crate::lookup_range::get_anonymous_fn_decl_range(self.source_map(), span);
+            let body_range =
+                crate::lookup_range::get_range_from_span(self.source_map(), &body.span);
+            let index = self
+                .cov()
+                .borrow_mut()
+                .new_function(name, &range, &body_range);
+            self.set_fn_metadata(index, kind, false, false);
+
+            let b = crate::create_increase_counter_expr(
+                &crate::constants::idents::IDENT_F,
+                index,
+                self.cov_fn_ident(),
+                None,
+            );
+            let mut prepended_vec = vec![Stmt::Expr(ExprStmt {
+                span: swc_common::DUMMY_SP,
+                expr: Box::new(b),
+            })];
+            prepended_vec.extend(body.stmts.take());
+            body.stmts = prepended_vec;
+        }
+    }
+
+    fn insert_fn_counter(
+        &mut self,
+        name: &Option<String>,
+        range: &crate::Range,
+        kind: FunctionKind,
+        function: &mut Function,
+    ) {
+        // Overload signatures, `declare` and abstract methods (ts) have no body to run.
+        let body = match &mut function.body {
+            Some(body) => body,
+            None => return,
+        };
+
+        let body_range = crate::lookup_range::get_range_from_span(self.source_map(), &body.span);
+        let index = self
+            .cov()
+            .borrow_mut()
+            .new_function(name, range, &body_range);
+        self.set_fn_metadata(index, kind, function.is_async, function.is_generator);
+
+        let b = crate::create_increase_counter_expr(
+            &crate::constants::idents::IDENT_F,
+            index,
+            self.cov_fn_ident(),
+            None,
+        );
+        let mut prepended_vec = vec![Stmt::Expr(ExprStmt {
+            span: swc_common::DUMMY_SP,
+            expr: Box::new(b),
+        })];
+        prepended_vec.extend(body.stmts.take());
+        body.stmts = prepended_vec;
+    }
+}
+
+impl<T: InstrumentationContext> FunctionInstrumenter for T {}
+
+/// Name of the method / accessor by its key. Computed keys are evaluated at runtime,
+/// those are anonymous.
+pub(crate) fn get_prop_name(key: &PropName) -> Option<String> {
+    match key {
+        PropName::Ident(ident) => Some(ident.sym.to_string()),
+        PropName::Str(str) => Some(str.value.to_string()),
+        PropName::Num(num) => Some(num.value.to_string()),
+        PropName::BigInt(big_int) => Some(big_int.value.to_string()),
+        PropName::Computed(_) => None,
+    }
+}
+
+/// Kind of the class method recorded by `fnMetadata`.
+pub(crate) fn get_method_kind(kind: MethodKind) -> FunctionKind {
+    match kind {
+        MethodKind::Method => FunctionKind::Method,
+        MethodKind::Getter => FunctionKind::Getter,
+        MethodKind::Setter => FunctionKind::Setter,
+    }
+}
@@ -0,0 +1,102 @@
+//! Counter helpers of the instrumentation visitor, composed by the visitor
+//! through `InstrumentationContext`.
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use swc_common::{comments::Comments, util::take::Take, SourceMapper, Span};
+use swc_ecmascript::ast::*;
+
+use crate::{InstrumentOptions, Node, SourceCoverage};
+
+pub mod branch_instrumenter;
+pub mod function_instrumenter;
+pub mod statement_instrumenter;
+
+pub use branch_instrumenter::BranchInstrumenter;
+pub use function_instrumenter::FunctionInstrumenter;
+pub use statement_instrumenter::StatementInstrumenter;
+
+/// State of the visitor the instrumenters rely on. `InstrumentationVisitor`
+/// implements this, instrumenters are implemented for any context.
+pub trait InstrumentationContext {
+    type Comments: Clone + Comments;
+    type SourceMap: SourceMapper;
+
+    fn source_map(&self) -> &Arc<Self::SourceMap>;
+    fn comments(&self) -> &Self::Comments;
+    fn cov(&self) -> &Rc<RefCell<SourceCoverage>>;
+    fn cov_fn_ident(&self) -> &Ident;
+    fn cov_fn_temp_ident(&self) -> &Ident;
+    fn instrument_options(&self) -> &InstrumentOptions;
+    /// Nodes from the root to the current node.
+    fn nodes(&self) -> &[Node];
+    /// Stmts to be prepended by the parent node.
+    fn before_mut(&mut self) -> &mut Vec<Stmt>;
+
+    /// Determine if given span is not from the input, so the node should not have counters
+    /// (i.e statements synthesized by the earlier transforms). Children are still instrumented.
+    fn is_unmapped_span(&self, span: &Span) -> bool {
+        // Synthesized nodes have dummy or empty spans.
+        if span.lo == span.hi {
+            return true;
+        }
+
+        self.instrument_options().skip_unmapped
+            && !self
+                .cov()
+                .borrow()
+                .is_mapped(&crate::lookup_range::get_range_from_span(
+                    self.source_map(),
+                    span,
+                ))
+    }
+
+    // Base wrapper fn to replace given expr to wrapped paren expr with counter
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn replace_expr_with_counter<F>(&mut self, expr: &mut Expr, get_counter: F)
+    where
+        F: core::ops::Fn(&mut SourceCoverage, &Ident, &crate::Range) -> Expr,
+    {
+        let span = crate::lookup_range::get_expr_span(expr);
+        if let Some(span) = span.filter(|span| !self.is_unmapped_span(span)) {
+            let init_range = crate::lookup_range::get_range_from_span(self.source_map(), span);
+            let prepend_expr = get_counter(
+                &mut self.cov().borrow_mut(),
+                self.cov_fn_ident(),
+                &init_range,
+            );
+
+            // replace init with increase expr + init seq
+            *expr = crate::wrap_expr_with_counter(prepend_expr, expr.take());
+        }
+    }
+
+    fn is_injected_counter_expr(&self, expr: &Expr) -> bool {
+        if let Expr::Update(UpdateExpr { arg, .. }) = expr {
+            if let Expr::Member(MemberExpr { obj, .. }) = &**arg {
+                if let Expr::Member(MemberExpr { obj, .. }) = &**obj {
+                    if let Expr::Call(CallExpr {
+                        callee: Callee::Expr(expr),
+                        ..
+                    }) = &**obj
+                    {
+                        if let Expr::Ident(ident) = &**expr {
+                            if ident == self.cov_fn_ident() {
+                                return true;
+                            }
+                        }
+                    }
+                }
+            }
+        };
+        false
+    }
+
+    /// Determine if given stmt is an injected counter by transform.
+    fn is_injected_counter_stmt(&self, stmt: &Stmt) -> bool {
+        if let Stmt::Expr(ExprStmt { expr, .. }) = stmt {
+            self.is_injected_counter_expr(expr)
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use swc_common::Span;
+use swc_ecmascript::{ast::*, visit::VisitWith};
+
+use super::InstrumentationContext;
+
+/// Interfaces to mark counters. Parent node visitor should pick up and insert marked counter accordingly.
+/// Unlike istanbul we can't have single insert logic to be called in any arbitary child node.
+pub trait StatementInstrumenter: InstrumentationContext {
+    // if (path.isExpression()) {
+    //    path.replaceWith(T.sequenceExpression([increment, path.node]));
+    //}
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn replace_expr_with_stmt_counter(&mut self, expr: &mut Expr) {
+        if !self.instrument_options().cover_statements {
+            return;
+        }
+
+        self.replace_expr_with_counter(expr, |cov, cov_fn_ident, range| {
+            let idx = cov.new_statement(range);
+            crate::create_increase_counter_expr(
+                &crate::constants::idents::IDENT_S,
+                idx,
+                cov_fn_ident,
+                None,
+            )
+        });
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, span, idx), fields(stmt_id)))]
+    fn create_stmt_increase_counter_expr(&mut self, span: &Span, idx: Option<u32>) -> Expr {
+        let stmt_range = crate::lookup_range::get_range_from_span(self.source_map(), span);
+
+        let stmt_id = self.cov().borrow_mut().new_statement(&stmt_range);
+
+        #[cfg(feature = "tracing")]
+        tracing::Span::current().record("stmt_id", stmt_id);
+
+        crate::create_increase_counter_expr(
+            &crate::constants::idents::IDENT_S,
+            stmt_id,
+            self.cov_fn_ident(),
+            idx,
+        )
+    }
+
+    // Mark to prepend statement increase counter to current stmt.
+    // Counters are only ever inserted before the statement, never after: a counter following
+    // `return` / `throw` / `break` / `continue` would never run (and can be dropped by the dead
+    // code elimination of later passes), and the completion of the statement is kept as-is.
+    // if (path.isStatement()) {
+    //    path.insertBefore(T.expressionStatement(increment));
+    // }
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn mark_prepend_stmt_counter(&mut self, span: &Span) {
+        if !self.instrument_options().cover_statements || self.is_unmapped_span(span) {
+            return;
+        }
+
+        let increment_expr = self.create_stmt_increase_counter_expr(span, None);
+        self.before_mut().push(Stmt::Expr(ExprStmt {
+            span: swc_common::DUMMY_SP,
+            expr: Box::new(increment_expr),
+        }));
+    }
+
+    fn cover_statement(&mut self, expr: &mut Expr) {
+        let span = crate::lookup_range::get_expr_span(expr).copied();
+        // This is ugly, poor man's substitute to istanbul's `insertCounter` to determine
+        // when to replace givn expr to wrapped Paren or prepend stmt counter.
+        // We can't do insert parent node's sibling in downstream's child node.
+        // TODO: there should be a better way.
+        if let Some(span) = span {
+            let span = &span;
+            let mut block = crate::visitors::finders::BlockStmtFinder::new();
+            expr.visit_with(&mut block);
+            // TODO: this may not required as visit_mut_block_stmt recursively visits inner instead.
+            if block.0 {
+                //path.node.body.unshift(T.expressionStatement(increment));
+                self.mark_prepend_stmt_counter(span);
+                return;
+            }
+
+            let mut stmt = crate::visitors::finders::StmtFinder::new();
+            expr.visit_with(&mut stmt);
+            if stmt.0 {
+                //path.insertBefore(T.expressionStatement(increment));
+                self.mark_prepend_stmt_counter(span);
+            }
+
+            let mut hoist = crate::visitors::finders::HoistingFinder::new();
+            expr.visit_with(&mut hoist);
+            let parent = *self.nodes().last().unwrap();
+            if hoist.0 && parent == crate::Node::VarDeclarator {
+                // TODO: need to polish logic to determine prepending instead of traversing parent node types
+                let nodes_len = self.nodes().len();
+                let parent_index = nodes_len.saturating_sub(3);
+                let parent = self.nodes().get(parent_index);
+
+                if parent.is_some() {
+                    let parent_index = nodes_len.saturating_sub(4);
+                    let parent = self.nodes().get(parent_index).copied();
+
+                    if let Some(crate::Node::BlockStmt | crate::Node::Program) = parent {
+                        self.mark_prepend_stmt_counter(span);
+                    }
+                } else {
+                    self.replace_expr_with_stmt_counter(expr);
+                }
+
+                return;
+            }
+
+            let mut expr_finder = crate::visitors::finders::ExprFinder::new();
+            expr.visit_with(&mut expr_finder);
+            if expr_finder.0 {
+                self.replace_expr_with_stmt_counter(expr);
+            }
+        }
+    }
+}
+
+impl<T: InstrumentationContext> StatementInstrumenter for T {}
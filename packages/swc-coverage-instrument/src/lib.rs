@@ -4,31 +4,47 @@ mod constants;
 mod source_coverage;
 
 mod instrument;
-use instrument::create_increase_counter_expr::create_increase_counter_expr;
-use instrument::create_increase_true_expr::create_increase_true_expr;
+use instrument::create_increase_counter_expr::{
+    create_increase_counter_expr, parse_increase_counter_expr,
+};
+use instrument::create_increase_true_expr::{create_increase_true_expr, parse_increase_true_expr};
+use instrument::wrap_expr_with_counter::wrap_expr_with_counter;
 
 mod coverage_template;
 use coverage_template::create_assignment_stmt::create_assignment_stmt;
 use coverage_template::create_coverage_data_object::create_coverage_data_object;
 use coverage_template::create_coverage_fn_decl::*;
 use coverage_template::create_global_stmt_template::create_global_stmt_template;
-use source_coverage::SourceCoverage;
-
-#[macro_use]
-mod macros;
+use source_coverage::module_coverage::{find_module_coverage, ModuleCoverage};
+pub use source_coverage::{SkippedEntries, SourceCoverage};
 
+mod instrumenters;
 mod visitors;
 pub use visitors::coverage_visitor::{create_coverage_instrumentation_visitor, CoverageVisitor};
 mod options;
 pub use options::instrument_options::*;
+pub use options::validation::InstrumentOptionsError;
 
 mod utils;
+pub use utils::coverage_data_comment::{
+    extract_coverage_from_comments, strip_coverage_comments, COVERAGE_DATA_COMMENT_PREFIX,
+};
+pub use utils::coverage_manifest::{
+    extract_manifest_from_comments, verify_coverage_manifest, CoverageManifestEntry,
+    CoverageManifestMismatch, COVERAGE_MANIFEST_COMMENT_PREFIX,
+};
+pub use utils::coverage_registry::{create_coverage_registry_module, COVERAGE_REGISTRY_EXPORT};
+pub use utils::debug_snapshot::{write_debug_snapshot, DEBUG_SNAPSHOT_PREFIX};
 use utils::hint_comments;
+pub use utils::instrument_cache::{CachedInstrumentation, InstrumentCache};
+pub use utils::instrument_stats::{InstrumentStats, INSTRUMENT_STATS_PREFIX};
 use utils::lookup_range;
 pub use utils::node::Node;
+use utils::static_conditions;
 
 // Reexports
 pub use istanbul_oxide::types::*;
 pub use istanbul_oxide::FileCoverage;
+pub use istanbul_oxide::PathCanonicalizer;
 pub use istanbul_oxide::Range;
 pub use istanbul_oxide::SourceMap;
@@ -1,5 +0,0 @@
-pub(crate) mod create_instrumentation_visitor;
-pub(crate) mod instrumentation_counter_helper;
-pub(crate) mod instrumentation_stmt_counter_helper;
-pub(crate) mod instrumentation_visitor;
-pub(crate) mod visit_mut_for_like;
@@ -1,4 +1,6 @@
-use istanbul_oxide::SourceMap;
+use std::collections::HashMap;
+
+use istanbul_oxide::{BranchType, Location, PathCanonicalizer, SourceMap};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -6,6 +8,9 @@ use serde::{Deserialize, Serialize};
 pub struct InstrumentLogOptions {
     pub level: Option<String>,
     pub enable_trace: bool,
+    /// Collect `InstrumentStats` of each file (time taken, counters added), to find the files
+    /// dominating the overhead of the instrumentation.
+    pub stats: bool,
 }
 
 impl Default for InstrumentLogOptions {
@@ -13,32 +18,367 @@ impl Default for InstrumentLogOptions {
         InstrumentLogOptions {
             level: None,
             enable_trace: false,
+            stats: false,
         }
     }
 }
 
+/// Start of an original module in the concatenated input (i.e webpack's `ConcatenatedModule`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleBoundary {
+    /// Byte offset of the module in the input.
+    pub offset: u32,
+    /// Path of the module, used as a path of its coverage.
+    pub name: String,
+}
+
+/// Fn to be called on each counter hit, to stream coverage while the code runs.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnCoverOptions {
+    /// Module to import the fn from.
+    pub module: String,
+    /// Name of the fn exported from the module, called as
+    /// `fn(path, counterType, id, branchPathIndex?)`.
+    pub export_name: String,
+}
+
+/// Kinds of the branches to create, all default to true. Branches of the kinds turned off
+/// are not counted at all, as if `cover_branches` is off only for those.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InstrumentBranchOptions {
+    /// `if` statements.
+    #[serde(rename = "if")]
+    pub if_stmt: bool,
+    /// `switch` statements.
+    pub switch: bool,
+    /// Conditional exprs (`a ? b : c`).
+    pub ternary: bool,
+    /// Logical exprs (`a || b`, `a && b`, `a ?? b`).
+    pub logical: bool,
+    /// Default values of the params / destructuring (`function (a = 1) {}`).
+    pub default_arg: bool,
+}
+
+impl Default for InstrumentBranchOptions {
+    fn default() -> Self {
+        InstrumentBranchOptions {
+            if_stmt: true,
+            switch: true,
+            ternary: true,
+            logical: true,
+            default_arg: true,
+        }
+    }
+}
+
+/// Form of the snapshots dumped by `DebugOptions`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DebugEmit {
+    /// AST of the program, in rust's debug format.
+    #[default]
+    Ast,
+    /// Code of the program. Requires the host to emit the code, the plugin dumps the AST instead.
+    Code,
+}
+
+/// Dump snapshots of the files before / after the instrumentation, to investigate the
+/// instrumentation bugs without hand-rolling the printing.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DebugOptions {
+    /// Globs of the files to dump (`**/problem.ts`). `*` doesn't match `/`, `**` matches any
+    /// directories. Relative globs match the end of the file path at the path separator.
+    pub files: Vec<String>,
+    pub emit: DebugEmit,
+    /// Directory to write the snapshots to, as `<path>.before.<emit>` / `<path>.after.<emit>`
+    /// with the separators of the path replaced. Printed to stderr if not set or not writable
+    /// (i.e the plugin).
+    pub out_dir: Option<String>,
+}
+
+impl DebugOptions {
+    /// Whether the file matches any of `files`.
+    pub fn should_dump(&self, file_path: &str) -> bool {
+        self.files
+            .iter()
+            .any(|pattern| istanbul_oxide::is_glob_match(pattern, file_path))
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", default)]
 pub struct InstrumentOptions {
     pub coverage_variable: String,
+    /// Additional global variables to record coverage into, along with `coverage_variable`
+    /// (i.e `__coverage_e2e__`). Each variable has its own coverage object initialized by the
+    /// template, so it can be reset independently; every counter hit increases all of them.
+    pub additional_coverage_variables: Vec<String>,
     pub compact: bool,
     pub report_logic: bool,
+    /// Set false to skip statement counters. Line coverage is derived from
+    /// statements, so this disables it as well.
+    pub cover_statements: bool,
+    /// Set false to skip function counters.
+    pub cover_functions: bool,
+    /// Set false to skip branch counters.
+    pub cover_branches: bool,
+    /// Kinds of the branches to create when `cover_branches` is set, to align the branch metrics
+    /// with the other tools (i.e only `if` / `switch`).
+    pub instrument: InstrumentBranchOptions,
+    /// Create branches for the conditions of `for` / `while` / `do-while` loops, counting each
+    /// operand of a logical condition same as logical exprs, or whether the loop is entered or
+    /// exited by the others. istanbul doesn't count them.
+    pub cover_loop_conditions: bool,
+    /// Read counters through references declared once at the top of the file
+    /// (`cov_xyz_s[0]++`) instead of calling the coverage fn per each hit.
+    /// Counters are not available until the top of the file is evaluated, so
+    /// a fn called earlier than that (i.e circular imports) will throw.
+    pub hoist_counters: bool,
+    /// Do not call the coverage fn on load of the file; coverage is initialized
+    /// on the first counter hit instead. Takes precedence over `hoist_counters`.
+    pub lazy_init: bool,
+    /// Store coverage under the realm id, `global[coverage_variable][realmId][path]`,
+    /// to avoid coverage from multiple realms (iframes) sharing a store overwrite each other.
+    pub realm_isolation: bool,
+    /// Define the properties of the global created for the coverage (`coverage_variable`, and the
+    /// helpers of `realm_isolation`) as non-enumerable / non-configurable, so those don't show up
+    /// enumerating the keys of the global. Still writable to reset coverage.
+    pub non_enumerable_coverage_variable: bool,
+    /// Keep the output valid typescript if the input is, for the runtimes stripping types
+    /// without type checking the code (i.e node's `--experimental-strip-types`) and the type checkers
+    /// running on the instrumented output. Attaches `// @ts-ignore` to the template, same as istanbul.
+    pub preserve_types: bool,
+    /// Freeze the maps (`statementMap`, `fnMap`, `branchMap`, `inputSourceMap`) of the coverage
+    /// object with `Object.freeze`, so the code under test can't mutate those by accident. Hit counts
+    /// stay writable. Also stamps `integrity` of the maps to the coverage, `FileCoverage::validate`
+    /// reports the coverage extracted with the maps altered anyway.
+    pub freeze_coverage: bool,
+    /// Canonicalize the path of the file (and the names of `module_boundaries`) with the built-in
+    /// canonicalizers in the given order (`realpath`, `lowercaseDrive`, `pnpmVirtualStore`), so
+    /// the same file gets the same path in the coverage of each run to be merged.
+    pub canonicalize_paths: Vec<PathCanonicalizer>,
+    /// Emit separate coverage per each original module if the input is a concatenation
+    /// of multiple modules. Ranges are relative to the start of each module, code before
+    /// the first module is covered as the input file.
+    pub module_boundaries: Vec<ModuleBoundary>,
+    /// Call the fn of the module on each counter hit, in addition to increasing the counter.
+    /// Imported once per file, only if there are any counters.
+    pub on_cover: Option<OnCoverOptions>,
+    /// Name of the global fn to emit each counter as a call to (`__swc_cov_hit`), like
+    /// `__swc_cov_hit("cov_xyz", "s", 3)` instead of `cov_xyz().s[3]++`, with the index of the
+    /// path as the last argument for the branches. Minifiers keep the calls to an unresolved global
+    /// as-is, so the counters stay detectable in the minified output. The template defines the fn
+    /// on the global if it's not defined yet. Takes precedence over `hoist_counters` / `lazy_init`.
+    pub global_hit_fn: Option<String>,
+    /// Module to import `register` of the coverage registry from (i.e the one written by
+    /// `create_coverage_registry_module`), to register coverage of the file through it instead of
+    /// looking up the global in each file. `realm_isolation` / `non_enumerable_coverage_variable`
+    /// are up to the registry module, the template doesn't touch the global.
+    pub registry_module: Option<String>,
+    /// Count re-exports (`export * from "mod"`, `export { a } from "mod"`) as statements.
+    /// istanbul doesn't, while nyc counts them if the code is transpiled to commonjs first.
+    pub cover_re_exports: bool,
+    /// Location where the input starts in the original file, if the input is a script block
+    /// extracted from the file (i.e `<script>` of `.vue` / `.svelte`). Ranges are shifted to be
+    /// relative to the original file, column is shifted only on the first line of the input.
+    pub script_offset: Option<Location>,
+    /// Lines / columns to shift the ranges by, if the input is embedded in the other file at a known
+    /// offset. Same as node's `vm.Script`, column offset applies only to the first line of the input.
+    /// Added on top of `script_offset` if both are set.
+    pub line_offset: u32,
+    pub column_offset: u32,
+    /// Skip nodes which do not map back to the original source through `input_source_map`
+    /// (i.e helpers injected by the earlier transforms). Nodes with dummy spans are always skipped.
+    pub skip_unmapped: bool,
+    /// Create a single statement for the statements of the same range (i.e code duplicated by
+    /// the earlier transforms), hits of those are summed. Otherwise each gets its own statement,
+    /// inflating the totals.
+    pub dedupe_statements: bool,
     pub ignore_class_methods: Vec<String>,
+    /// Mark the code behind statically false conditions of `if` statements and conditional exprs
+    /// as skipped instead of uncovered, i.e `if (false)` or `if (process.env.NODE_ENV !== "production")`
+    /// when `NODE_ENV` is in `defines`. The branch of the condition itself is still counted.
+    pub skip_dead_code: bool,
+    /// Values of the identifiers / member exprs keyed by its path, to evaluate conditions
+    /// for `skip_dead_code` same as the define-replacement of the bundler
+    /// (i.e `{ "process.env.NODE_ENV": "production", "typeof window": "undefined" }`).
+    pub defines: HashMap<String, serde_json::Value>,
+    /// Instrument only the files of the list (i.e `git diff --name-only`), other files are left as-is.
+    /// Relative paths match the end of the file path at the path separator.
+    pub changed_files: Option<Vec<String>>,
+    /// Emit coverage of the files not in `changed_files` with `all: true` and no counters in the code,
+    /// so those are still listed in the report as not executed, same as nyc's `--all`.
+    pub stub_unchanged_files: bool,
+    /// Attach `/* istanbul ignore next */` to the counter statements inserted, so istanbul-aware
+    /// tools processing the instrumented output again skip those. Hint comments of the input are
+    /// kept in the output regardless.
+    pub annotate_counters: bool,
+    /// Record the name each exported function is exported as (`exportName` of the function,
+    /// `default` for the default export), to find the exports never executed from the merged
+    /// coverage with `CoverageMap::find_unused_exports`.
+    pub fn_export_names: bool,
+    /// Record the kind (`declaration`, `arrow`, `method`, ...), `isAsync`, `isGenerator` and
+    /// `exported` of each function as the properties of the function in `fnMap`, for the reports
+    /// grouping functions by those (i.e coverage of async functions). Not a part of istanbul's schema.
+    pub fn_metadata: bool,
     pub input_source_map: Option<SourceMap>,
     pub instrument_log: InstrumentLogOptions,
-    pub debug_initial_coverage_comment: bool,
+    /// Attach initial coverage data to the transformed output as a JSON comment,
+    /// for the host to read it. Runtime counters are not affected.
+    #[serde(alias = "debugInitialCoverageComment")]
+    pub emit_coverage_comment: bool,
+    /// Attach a manifest entry of the file (path, coverage fn, hash, counts of the counters)
+    /// to the transformed output as a JSON comment, to verify coverage collected from
+    /// separate runs are from the same instrumentation.
+    pub emit_manifest: bool,
+    /// Debug option to verify the instrumentation: strip counters from the instrumented code and compare
+    /// it with the input, reports an error if instrumentation altered anything other than inserting counters.
+    pub self_check: bool,
+    #[serde(alias = "debugAst")]
+    pub debug: Option<DebugOptions>,
 }
 
 impl Default for InstrumentOptions {
     fn default() -> Self {
         InstrumentOptions {
             coverage_variable: "__coverage__".to_string(),
+            additional_coverage_variables: Default::default(),
             compact: false,
             report_logic: false,
+            cover_statements: true,
+            cover_functions: true,
+            cover_branches: true,
+            instrument: Default::default(),
+            cover_loop_conditions: false,
+            hoist_counters: false,
+            lazy_init: false,
+            realm_isolation: false,
+            non_enumerable_coverage_variable: false,
+            preserve_types: false,
+            freeze_coverage: false,
+            canonicalize_paths: Default::default(),
+            module_boundaries: Default::default(),
+            on_cover: None,
+            global_hit_fn: None,
+            registry_module: None,
+            cover_re_exports: false,
+            script_offset: None,
+            line_offset: 0,
+            column_offset: 0,
+            skip_unmapped: false,
+            dedupe_statements: false,
             ignore_class_methods: Default::default(),
+            skip_dead_code: false,
+            defines: Default::default(),
+            changed_files: None,
+            stub_unchanged_files: false,
+            annotate_counters: false,
+            fn_export_names: false,
+            fn_metadata: false,
             input_source_map: Default::default(),
             instrument_log: Default::default(),
-            debug_initial_coverage_comment: false,
+            emit_coverage_comment: false,
+            emit_manifest: false,
+            self_check: false,
+            debug: None,
+        }
+    }
+}
+
+impl InstrumentOptions {
+    /// Location where the input starts in the original file, if any of
+    /// `script_offset`, `line_offset`, `column_offset` is set.
+    pub(crate) fn get_offset(&self) -> Option<Location> {
+        if self.script_offset.is_none() && self.line_offset == 0 && self.column_offset == 0 {
+            return None;
         }
+
+        let start = self
+            .script_offset
+            .unwrap_or(Location { line: 1, column: 0 });
+        Some(Location {
+            line: start.line + self.line_offset,
+            column: start.column + self.column_offset,
+        })
+    }
+
+    /// Whether to create the branches of the kind, by `cover_branches` and `instrument`.
+    /// Branches of the loop conditions are controlled by `cover_loop_conditions` instead.
+    pub fn should_cover_branch(&self, branch_type: &BranchType) -> bool {
+        self.cover_branches
+            && match branch_type {
+                BranchType::If => self.instrument.if_stmt,
+                BranchType::Switch => self.instrument.switch,
+                BranchType::CondExpr => self.instrument.ternary,
+                BranchType::BinaryExpr => self.instrument.logical,
+                BranchType::DefaultArg => self.instrument.default_arg,
+            }
+    }
+
+    /// Whether the file is in `changed_files`, or `changed_files` is not set.
+    pub fn is_changed_file(&self, file_path: &str) -> bool {
+        let changed_files = match &self.changed_files {
+            Some(changed_files) => changed_files,
+            None => return true,
+        };
+
+        let normalize = |path: &str| path.replace('\\', "/").trim_start_matches("./").to_string();
+        let file_path = normalize(file_path);
+
+        changed_files
+            .iter()
+            .map(|path| normalize(path))
+            .any(|changed| {
+                !changed.is_empty()
+                    && file_path.ends_with(&changed)
+                    && (file_path.len() == changed.len()
+                        || file_path[..file_path.len() - changed.len()].ends_with('/'))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use istanbul_oxide::BranchType;
+
+    use super::InstrumentOptions;
+
+    #[test]
+    fn should_cover_branches_by_kind() {
+        let options: InstrumentOptions =
+            serde_json::from_str(r#"{ "instrument": { "ternary": false, "defaultArg": false } }"#)
+                .unwrap();
+
+        assert!(options.should_cover_branch(&BranchType::If));
+        assert!(options.should_cover_branch(&BranchType::BinaryExpr));
+        assert!(!options.should_cover_branch(&BranchType::CondExpr));
+        assert!(!options.should_cover_branch(&BranchType::DefaultArg));
+
+        let options = InstrumentOptions {
+            cover_branches: false,
+            ..Default::default()
+        };
+        assert!(!options.should_cover_branch(&BranchType::If));
+    }
+
+    #[test]
+    fn should_match_changed_files_by_path_suffix() {
+        let options = InstrumentOptions {
+            changed_files: Some(vec!["src/a.js".to_string(), "./lib\\b.ts".to_string()]),
+            ..Default::default()
+        };
+
+        assert!(options.is_changed_file("/repo/src/a.js"));
+        assert!(options.is_changed_file("src/a.js"));
+        assert!(options.is_changed_file("C:\\repo\\lib\\b.ts"));
+        assert!(!options.is_changed_file("/repo/mysrc/a.js"));
+        assert!(!options.is_changed_file("/repo/src/a.jsx"));
+        assert!(!options.is_changed_file("a.js"));
+
+        assert!(InstrumentOptions::default().is_changed_file("a.js"));
     }
 }
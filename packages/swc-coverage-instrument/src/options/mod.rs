@@ -1 +1,2 @@
 pub mod instrument_options;
+pub mod validation;
@@ -0,0 +1,419 @@
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::InstrumentOptions;
+
+/// Keys of the options taking arbitrary values, not checked for unknown keys.
+const FREEFORM_KEYS: &[&str] = &["defines", "inputSourceMap"];
+
+/// Previous names of the options, still accepted.
+const ALIASES: &[(&str, &str)] = &[
+    ("debugInitialCoverageComment", "emitCoverageComment"),
+    ("debugAst", "debug"),
+];
+
+const LOG_LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+
+/// Invalid option, named by the path of its key (i.e `instrument.if`, `moduleBoundaries[1].name`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct InstrumentOptionsError {
+    /// Empty if the options are not a JSON object at all.
+    pub key: String,
+    pub message: String,
+}
+
+impl InstrumentOptionsError {
+    fn new(key: impl Into<String>, message: impl Into<String>) -> InstrumentOptionsError {
+        InstrumentOptionsError {
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for InstrumentOptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.key.is_empty() {
+            write!(f, "Invalid instrumentation options: {}", self.message)
+        } else {
+            write!(f, "Invalid option `{}`: {}", self.key, self.message)
+        }
+    }
+}
+
+impl std::error::Error for InstrumentOptionsError {}
+
+#[derive(Clone, Debug)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+fn format_path(path: &[Segment]) -> String {
+    let mut key = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(name) if key.is_empty() => key.push_str(name),
+            Segment::Key(name) => {
+                key.push('.');
+                key.push_str(name);
+            }
+            Segment::Index(index) => key.push_str(&format!("[{}]", index)),
+        }
+    }
+    key
+}
+
+fn get_value<'a>(value: &'a Value, path: &[Segment]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, segment| match segment {
+        Segment::Key(key) => value.get(key),
+        Segment::Index(index) => value.get(index),
+    })
+}
+
+fn without_value(value: &Value, path: &[Segment]) -> Value {
+    let mut value = value.clone();
+    if let Some((last, parent)) = path.split_last() {
+        let parent = parent
+            .iter()
+            .try_fold(&mut value, |value, segment| match segment {
+                Segment::Key(key) => value.get_mut(key),
+                Segment::Index(index) => value.get_mut(index),
+            });
+        match (parent, last) {
+            (Some(Value::Object(map)), Segment::Key(key)) => {
+                map.remove(key);
+            }
+            (Some(Value::Array(values)), Segment::Index(index)) => {
+                values.remove(*index);
+            }
+            _ => {}
+        }
+    }
+    value
+}
+
+/// Narrow down the path of the value failing to deserialize, by finding the child without which
+/// the options deserialize. Stops at the value if none does (i.e a required field of the value).
+fn find_invalid_path(root: &Value, path: Vec<Segment>) -> Vec<Segment> {
+    let children: Vec<Segment> = match get_value(root, &path) {
+        Some(Value::Object(map)) => map.keys().cloned().map(Segment::Key).collect(),
+        Some(Value::Array(values)) => (0..values.len()).map(Segment::Index).collect(),
+        _ => return path,
+    };
+
+    for child in children {
+        let mut child_path = path.clone();
+        child_path.push(child);
+        if serde_json::from_value::<InstrumentOptions>(without_value(root, &child_path)).is_ok() {
+            return find_invalid_path(root, child_path);
+        }
+    }
+    path
+}
+
+/// Collect the keys of the input not in the deserialized options, which serde ignores.
+fn collect_unknown_keys(
+    input: &Value,
+    known: &Value,
+    path: &mut Vec<Segment>,
+    keys: &mut Vec<String>,
+) {
+    match (input, known) {
+        (Value::Object(input), Value::Object(known)) => {
+            for (key, value) in input {
+                let key = if path.is_empty() {
+                    ALIASES
+                        .iter()
+                        .find(|(alias, _)| alias == key)
+                        .map(|(_, name)| name.to_string())
+                        .unwrap_or_else(|| key.clone())
+                } else {
+                    key.clone()
+                };
+                if path.is_empty() && FREEFORM_KEYS.contains(&key.as_str()) {
+                    continue;
+                }
+
+                path.push(Segment::Key(key.clone()));
+                match known.get(&key) {
+                    Some(known) => collect_unknown_keys(value, known, path, keys),
+                    None => keys.push(format_path(path)),
+                }
+                path.pop();
+            }
+        }
+        (Value::Array(input), Value::Array(known)) => {
+            for (index, (value, known)) in input.iter().zip(known).enumerate() {
+                path.push(Segment::Index(index));
+                collect_unknown_keys(value, known, path, keys);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+impl InstrumentOptions {
+    /// Parse the options from JSON, i.e config of the plugin. Unlike deserializing directly,
+    /// errors name the offending key and values are validated.
+    /// Unknown keys are ignored to accept options of the other versions, returned along with the options.
+    pub fn from_json(
+        json: &str,
+    ) -> Result<(InstrumentOptions, Vec<String>), InstrumentOptionsError> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|err| InstrumentOptionsError::new("", err.to_string()))?;
+        InstrumentOptions::from_json_value(value)
+    }
+
+    /// Same as `from_json`, for the options already parsed as JSON.
+    pub fn from_json_value(
+        value: Value,
+    ) -> Result<(InstrumentOptions, Vec<String>), InstrumentOptionsError> {
+        if !value.is_object() {
+            return Err(InstrumentOptionsError::new(
+                "",
+                format!("expected an object, got `{}`", value),
+            ));
+        }
+
+        let options: InstrumentOptions = serde_json::from_value(value.clone()).map_err(|err| {
+            InstrumentOptionsError::new(
+                format_path(&find_invalid_path(&value, vec![])),
+                err.to_string(),
+            )
+        })?;
+        options.validate()?;
+
+        let known = serde_json::to_value(&options).expect("Should able to serialize options");
+        let mut unknown_keys = vec![];
+        collect_unknown_keys(&value, &known, &mut vec![], &mut unknown_keys);
+
+        Ok((options, unknown_keys))
+    }
+
+    /// Check the values deserialized but not meaningful, i.e empty names.
+    pub fn validate(&self) -> Result<(), InstrumentOptionsError> {
+        if self.coverage_variable.is_empty() {
+            return Err(InstrumentOptionsError::new(
+                "coverageVariable",
+                "should not be empty",
+            ));
+        }
+
+        for (index, coverage_variable) in self.additional_coverage_variables.iter().enumerate() {
+            let key = format!("additionalCoverageVariables[{}]", index);
+            if coverage_variable.is_empty() {
+                return Err(InstrumentOptionsError::new(key, "should not be empty"));
+            }
+            if coverage_variable == &self.coverage_variable
+                || self.additional_coverage_variables[..index].contains(coverage_variable)
+            {
+                return Err(InstrumentOptionsError::new(
+                    key,
+                    format!("`{}` is already used", coverage_variable),
+                ));
+            }
+        }
+
+        if let Some(level) = &self.instrument_log.level {
+            if !LOG_LEVELS.contains(&level.as_str()) {
+                return Err(InstrumentOptionsError::new(
+                    "instrumentLog.level",
+                    format!("expected one of {}, got `{}`", LOG_LEVELS.join(", "), level),
+                ));
+            }
+        }
+
+        if let Some(on_cover) = &self.on_cover {
+            if on_cover.module.is_empty() {
+                return Err(InstrumentOptionsError::new(
+                    "onCover.module",
+                    "should not be empty",
+                ));
+            }
+            if !is_identifier(&on_cover.export_name) {
+                return Err(InstrumentOptionsError::new(
+                    "onCover.exportName",
+                    format!("`{}` is not an identifier", on_cover.export_name),
+                ));
+            }
+        }
+
+        if let Some(global_hit_fn) = &self.global_hit_fn {
+            if !is_identifier(global_hit_fn) {
+                return Err(InstrumentOptionsError::new(
+                    "globalHitFn",
+                    format!("`{}` is not an identifier", global_hit_fn),
+                ));
+            }
+        }
+
+        if self
+            .registry_module
+            .as_ref()
+            .is_some_and(|registry_module| registry_module.is_empty())
+        {
+            return Err(InstrumentOptionsError::new(
+                "registryModule",
+                "should not be empty",
+            ));
+        }
+
+        for (index, boundary) in self.module_boundaries.iter().enumerate() {
+            if boundary.name.is_empty() {
+                return Err(InstrumentOptionsError::new(
+                    format!("moduleBoundaries[{}].name", index),
+                    "should not be empty",
+                ));
+            }
+            if self.module_boundaries[..index]
+                .iter()
+                .any(|other| other.offset == boundary.offset)
+            {
+                return Err(InstrumentOptionsError::new(
+                    format!("moduleBoundaries[{}].offset", index),
+                    format!("another module starts at {}", boundary.offset),
+                ));
+            }
+        }
+
+        if let Some(script_offset) = &self.script_offset {
+            if script_offset.line == 0 {
+                return Err(InstrumentOptionsError::new(
+                    "scriptOffset.line",
+                    "lines start from 1",
+                ));
+            }
+        }
+
+        if let Some(changed_files) = &self.changed_files {
+            if let Some(index) = changed_files.iter().position(|path| path.is_empty()) {
+                return Err(InstrumentOptionsError::new(
+                    format!("changedFiles[{}]", index),
+                    "should not be empty",
+                ));
+            }
+        }
+
+        if let Some(debug) = &self.debug {
+            if debug.files.is_empty() {
+                return Err(InstrumentOptionsError::new(
+                    "debug.files",
+                    "should have at least one glob",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::InstrumentOptions;
+
+    fn get_error(json: &str) -> String {
+        InstrumentOptions::from_json(json).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn should_parse_options_with_unknown_keys() {
+        let (options, unknown_keys) = InstrumentOptions::from_json(
+            r#"{
+                "coverageVariable": "__cov__",
+                "debugInitialCoverageComment": true,
+                "instrument": { "if": false, "loops": false },
+                "instrumentLog": { "logLevel": "trace" },
+                "moduleBoundaries": [{ "offset": 0, "name": "a.js", "size": 1 }],
+                "defines": { "process.env.NODE_ENV": "production" },
+                "newerOption": 1
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(options.coverage_variable, "__cov__");
+        assert!(options.emit_coverage_comment);
+        assert!(!options.instrument.if_stmt);
+        assert_eq!(
+            unknown_keys,
+            vec![
+                "instrument.loops",
+                "instrumentLog.logLevel",
+                "moduleBoundaries[0].size",
+                "newerOption"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_name_invalid_key() {
+        assert_eq!(
+            get_error(r#"{ "instrument": { "if": "no" } }"#),
+            "Invalid option `instrument.if`: invalid type: string \"no\", expected a boolean"
+        );
+        assert_eq!(
+            get_error(r#"{ "debug": { "files": ["a.ts"], "emit": "json" } }"#),
+            "Invalid option `debug.emit`: unknown variant `json`, expected `ast` or `code`"
+        );
+        assert_eq!(
+            get_error(r#"{ "changedFiles": ["a.js", 1] }"#),
+            "Invalid option `changedFiles[1]`: invalid type: integer `1`, expected a string"
+        );
+        // Required fields can't be removed to tell which one is invalid.
+        assert_eq!(
+            get_error(r#"{ "onCover": { "module": "m", "exportName": 1 } }"#),
+            "Invalid option `onCover`: invalid type: integer `1`, expected a string"
+        );
+        assert_eq!(
+            get_error("[]"),
+            "Invalid instrumentation options: expected an object, got `[]`"
+        );
+    }
+
+    #[test]
+    fn should_validate_values() {
+        assert_eq!(
+            get_error(r#"{ "coverageVariable": "" }"#),
+            "Invalid option `coverageVariable`: should not be empty"
+        );
+        assert_eq!(
+            get_error(r#"{ "additionalCoverageVariables": ["__e2e__", "__coverage__"] }"#),
+            "Invalid option `additionalCoverageVariables[1]`: `__coverage__` is already used"
+        );
+        assert_eq!(
+            get_error(r#"{ "instrumentLog": { "level": "verbose" } }"#),
+            "Invalid option `instrumentLog.level`: expected one of error, warn, info, debug, trace, got `verbose`"
+        );
+        assert_eq!(
+            get_error(r#"{ "onCover": { "module": "m", "exportName": "report-hit" } }"#),
+            "Invalid option `onCover.exportName`: `report-hit` is not an identifier"
+        );
+        assert_eq!(
+            get_error(
+                r#"{ "moduleBoundaries": [{ "offset": 0, "name": "a" }, { "offset": 0, "name": "b" }] }"#
+            ),
+            "Invalid option `moduleBoundaries[1].offset`: another module starts at 0"
+        );
+        assert_eq!(
+            get_error(r#"{ "scriptOffset": { "line": 0, "column": 0 } }"#),
+            "Invalid option `scriptOffset.line`: lines start from 1"
+        );
+        assert_eq!(
+            get_error(r#"{ "globalHitFn": "cov.hit" }"#),
+            "Invalid option `globalHitFn`: `cov.hit` is not an identifier"
+        );
+        assert_eq!(
+            get_error(r#"{ "registryModule": "" }"#),
+            "Invalid option `registryModule`: should not be empty"
+        );
+        assert!(InstrumentOptions::from_json("{}").is_ok());
+    }
+}
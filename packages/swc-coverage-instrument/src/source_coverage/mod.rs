@@ -1,4 +1,28 @@
-use istanbul_oxide::{Branch, BranchType, FileCoverage, Function, Range, SourceMap};
+use std::collections::{HashMap, HashSet};
+
+use istanbul_oxide::{
+    Branch, BranchType, FileCoverage, Function, FunctionMetadata, Location, Range, SourceMap,
+};
+
+pub mod module_coverage;
+
+use crate::utils::source_map_mappings::SourceMapMappings;
+
+/// Keys of the coverage object, can't be used as a key of the metadata.
+const RESERVED_KEYS: &[&str] = &[
+    "all",
+    "path",
+    "statementMap",
+    "fnMap",
+    "branchMap",
+    "s",
+    "f",
+    "b",
+    "bT",
+    "inputSourceMap",
+    "_coverageSchema",
+    "hash",
+];
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct SourceCoverageMetaHitCount {
@@ -16,16 +40,38 @@ impl Default for SourceCoverageMetaHitCount {
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct SourceCoverageMeta {
     last: SourceCoverageMetaHitCount,
+    // Location of the source starts in the given input, if the input is
+    // a concatenation of multiple sources.
+    origin: Option<Location>,
+    // Location of the input starts in the original file, if the input is
+    // a part of the file (i.e script block of the SFC).
+    offset: Option<Location>,
 }
 
 impl Default for SourceCoverageMeta {
     fn default() -> Self {
         SourceCoverageMeta {
             last: Default::default(),
+            origin: None,
+            offset: None,
         }
     }
 }
 
+/// Ids of the entries skipped by `SourceCoverage::skip_ranges`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SkippedEntries {
+    pub s: HashSet<u32>,
+    pub f: HashSet<u32>,
+    pub b: HashSet<u32>,
+}
+
+impl SkippedEntries {
+    pub fn is_empty(&self) -> bool {
+        self.s.is_empty() && self.f.is_empty() && self.b.is_empty()
+    }
+}
+
 /// SourceCoverage provides mutation methods to manipulate the structure of
 /// a file coverage object. Used by the instrumenter to create a full coverage
 /// object for a file incrementally.
@@ -33,6 +79,10 @@ impl Default for SourceCoverageMeta {
 pub struct SourceCoverage {
     inner: FileCoverage,
     meta: SourceCoverageMeta,
+    // Decoded mappings of the input source map, to look up positions of the input.
+    mappings: Option<SourceMapMappings>,
+    // Ids of the statements keyed by its range, if statements of the same range share the id.
+    statement_ids: Option<HashMap<(Location, Location), u32>>,
 }
 
 impl SourceCoverage {
@@ -40,6 +90,8 @@ impl SourceCoverage {
         SourceCoverage {
             inner: FileCoverage::from_file_path(file_path, report_logic),
             meta: Default::default(),
+            mappings: None,
+            statement_ids: None,
         }
     }
 
@@ -49,8 +101,19 @@ impl SourceCoverage {
 }
 
 impl SourceCoverage {
+    /// Ids of the entries are allocated sequentially in the order of the calls,
+    /// which is the order the visitor enters the nodes.
     pub fn new_statement(&mut self, loc: &Range) -> u32 {
+        if let Some(statement_ids) = &self.statement_ids {
+            if let Some(id) = statement_ids.get(&(loc.start, loc.end)) {
+                return *id;
+            }
+        }
+
         let s = self.meta.last.s;
+        if let Some(statement_ids) = &mut self.statement_ids {
+            statement_ids.insert((loc.start, loc.end), s);
+        }
         self.inner.statement_map.insert(s, loc.clone());
         self.inner.s.insert(s, 0);
         self.meta.last.s += 1;
@@ -73,6 +136,8 @@ impl SourceCoverage {
                 loc: loc.clone(),
                 // DEPRECATED: some legacy reports require this info.
                 line: loc.start.line,
+                export_name: None,
+                metadata: None,
             },
         );
 
@@ -163,11 +228,203 @@ impl SourceCoverage {
         }
     }
 
+    /// Marks the statement as skipped, counted as `skipped` instead of `total` in the summary.
+    /// Counter of the statement is left as-is.
+    pub fn set_statement_skip(&mut self, id: u32) {
+        self.inner
+            .statement_map
+            .get_mut(&id)
+            .unwrap_or_else(|| panic!("Invalid statement {}", id))
+            .skip = true;
+        assert!(self.inner.s.contains_key(&id), "Counts should be available");
+    }
+
+    /// Marks the function as skipped, same as `set_statement_skip`.
+    pub fn set_function_skip(&mut self, id: u32) {
+        let function = self
+            .inner
+            .fn_map
+            .get_mut(&id)
+            .unwrap_or_else(|| panic!("Invalid function {}", id));
+        function.decl.skip = true;
+        function.loc.skip = true;
+        assert!(self.inner.f.contains_key(&id), "Counts should be available");
+    }
+
+    /// Records the names the functions are exported as, by the `loc` (body range) of the function.
+    /// Marks the metadata of the functions as exported as well, if recorded.
+    pub fn set_export_names(&mut self, export_names: &[(Range, String)], record_names: bool) {
+        for function in self.inner.fn_map.values_mut() {
+            if let Some((_, name)) = export_names
+                .iter()
+                .find(|(loc, _)| loc.start == function.loc.start && loc.end == function.loc.end)
+            {
+                if record_names {
+                    function.export_name = Some(name.clone());
+                }
+                if let Some(metadata) = &mut function.metadata {
+                    metadata.exported = true;
+                }
+            }
+        }
+    }
+
+    /// Records the kind and flags of the function, written as the properties of the function.
+    pub fn set_function_metadata(&mut self, id: u32, metadata: FunctionMetadata) {
+        self.inner
+            .fn_map
+            .get_mut(&id)
+            .unwrap_or_else(|| panic!("Invalid function {}", id))
+            .metadata = Some(metadata);
+    }
+
+    /// Marks the branch and all of its paths as skipped, same as `set_statement_skip`.
+    pub fn set_branch_skip(&mut self, id: u32) {
+        let branch = self
+            .inner
+            .branch_map
+            .get_mut(&id)
+            .unwrap_or_else(|| panic!("Invalid branch {}", id));
+        if let Some(loc) = &mut branch.loc {
+            loc.skip = true;
+        }
+        branch
+            .locations
+            .iter_mut()
+            .for_each(|location| location.skip = true);
+        assert!(self.inner.b.contains_key(&id), "Counts should be available");
+    }
+
+    /// Attach arbitrary metadata to the file coverage, written as a property of the coverage object
+    /// along with the known ones (`path`, `statementMap`, ...) which can't be used as a key.
+    pub fn set_metadata(&mut self, key: &str, value: serde_json::Value) {
+        assert!(
+            !RESERVED_KEYS.contains(&key),
+            "{} is reserved for the coverage",
+            key
+        );
+        self.inner.extras.insert(key.to_string(), value);
+    }
+
+    /// Marks the file as included without being executed, same as nyc's `--all`.
+    pub fn set_all(&mut self) {
+        self.inner.all = true;
+    }
+
+    /// Marks statements, functions and branches start within any of given ranges as skipped.
+    /// Returns the ids of the skipped entries, to remove their counters.
+    pub fn skip_ranges(&mut self, ranges: &[Range]) -> SkippedEntries {
+        let should_skip = |range: &Range| ranges.iter().any(|r| r.contains(&range.start));
+
+        let skipped = SkippedEntries {
+            s: self
+                .inner
+                .statement_map
+                .iter()
+                .filter(|(_, range)| should_skip(range))
+                .map(|(id, _)| *id)
+                .collect(),
+            f: self
+                .inner
+                .fn_map
+                .iter()
+                .filter(|(_, function)| should_skip(&function.loc))
+                .map(|(id, _)| *id)
+                .collect(),
+            b: self
+                .inner
+                .branch_map
+                .iter()
+                .filter(|(_, branch)| branch.loc.as_ref().is_some_and(should_skip))
+                .map(|(id, _)| *id)
+                .collect(),
+        };
+
+        skipped.s.iter().for_each(|id| self.set_statement_skip(*id));
+        skipped.f.iter().for_each(|id| self.set_function_skip(*id));
+        skipped.b.iter().for_each(|id| self.set_branch_skip(*id));
+
+        skipped
+    }
+
     pub fn set_input_source_map(&mut self, source_map: &Option<SourceMap>) {
         self.inner.input_source_map = source_map.clone();
+        self.mappings = source_map
+            .as_ref()
+            .map(|source_map| SourceMapMappings::new(&source_map.mappings));
+    }
+
+    /// Returns true if the start of given range maps back to the original source
+    /// through the input source map, or there's no input source map.
+    pub fn is_mapped(&self, range: &Range) -> bool {
+        self.mappings
+            .as_ref()
+            .is_none_or(|mappings| mappings.is_mapped(range.start.line, range.start.column))
+    }
+
+    /// Statements of the same range share the id of the first one, so hits of the statements
+    /// duplicated by the earlier transforms are summed into a single entry.
+    pub fn set_dedupe_statements(&mut self) {
+        self.statement_ids.get_or_insert_with(Default::default);
+    }
+
+    /// Set the location where the source starts in the input. Ranges are
+    /// relocated to be relative to the origin when coverage freezes.
+    pub fn set_origin(&mut self, origin: Location) {
+        self.meta.origin = Some(origin);
+    }
+
+    /// Set the location where the input starts in the original file. Ranges are
+    /// shifted to be relative to the original file when coverage freezes.
+    pub fn set_offset(&mut self, offset: Location) {
+        self.meta.offset = Some(offset);
+    }
+
+    fn relocate<F: Fn(&mut Location)>(&mut self, relocate_location: F) {
+        let relocate_range = |range: &mut Range| {
+            relocate_location(&mut range.start);
+            relocate_location(&mut range.end);
+        };
+
+        self.inner
+            .statement_map
+            .values_mut()
+            .for_each(relocate_range);
+
+        for function in self.inner.fn_map.values_mut() {
+            relocate_range(&mut function.decl);
+            relocate_range(&mut function.loc);
+            function.line = function.loc.start.line;
+        }
+
+        for branch in self.inner.branch_map.values_mut() {
+            if let Some(loc) = &mut branch.loc {
+                relocate_range(loc);
+                branch.line = Some(loc.start.line);
+            }
+            branch.locations.iter_mut().for_each(relocate_range);
+        }
     }
 
     pub fn freeze(&mut self) {
+        if let Some(origin) = self.meta.origin.take() {
+            self.relocate(|location| {
+                if location.line == origin.line {
+                    location.column = location.column.saturating_sub(origin.column);
+                }
+                location.line = location.line.saturating_sub(origin.line) + 1;
+            });
+        }
+
+        if let Some(offset) = self.meta.offset.take() {
+            self.relocate(|location| {
+                if location.line == 1 {
+                    location.column += offset.column;
+                }
+                location.line += offset.line.saturating_sub(1);
+            });
+        }
+
         // prune empty branches
         let map = &mut self.inner.branch_map;
         let branches = &mut self.inner.b;
@@ -189,10 +446,29 @@ impl SourceCoverage {
 
 #[cfg(test)]
 mod tests {
-    use istanbul_oxide::{Branch, BranchType, Function, Range};
+    use std::collections::HashSet;
+
+    use istanbul_oxide::{Branch, BranchType, Function, Location, Range};
 
     use crate::source_coverage::SourceCoverage;
 
+    #[test]
+    fn should_dedupe_statements_of_same_range() {
+        let mut coverage = SourceCoverage::new("anon".to_string(), false);
+        assert_eq!(coverage.new_statement(&Range::new(1, 0, 1, 4)), 0);
+        assert_eq!(coverage.new_statement(&Range::new(1, 0, 1, 4)), 1);
+
+        let mut coverage = SourceCoverage::new("anon".to_string(), false);
+        coverage.set_dedupe_statements();
+        assert_eq!(coverage.new_statement(&Range::new(1, 0, 1, 4)), 0);
+        assert_eq!(coverage.new_statement(&Range::new(2, 0, 2, 4)), 1);
+        assert_eq!(coverage.new_statement(&Range::new(1, 0, 1, 4)), 0);
+        assert_eq!(coverage.new_statement(&Range::new(1, 0, 1, 5)), 2);
+
+        assert_eq!(coverage.as_ref().statement_map.len(), 3);
+        assert_eq!(coverage.meta.last.s, 3);
+    }
+
     #[test]
     fn should_insert_new_statement() {
         let mut coverage = SourceCoverage::new("anon".to_string(), false);
@@ -243,7 +519,9 @@ mod tests {
                 name: "(anonymous_0)".to_string(),
                 decl: dummy_decl_range.clone(),
                 loc: dummy_range.clone(),
-                line: dummy_range.start.line
+                line: dummy_range.start.line,
+                export_name: None,
+                metadata: None,
             })
             .as_ref()
         );
@@ -267,7 +545,9 @@ mod tests {
                 name: "dummy".to_string(),
                 decl: dummy_decl_range.clone(),
                 loc: dummy_range.clone(),
-                line: dummy_range.start.line
+                line: dummy_range.start.line,
+                export_name: None,
+                metadata: None,
             })
             .as_ref()
         );
@@ -378,4 +658,129 @@ mod tests {
         assert_eq!(coverage.as_ref().b.len(), 1);
         assert_eq!(coverage.as_ref().branch_map.len(), 1);
     }
+
+    #[test]
+    fn should_relocate_to_origin_on_freeze() {
+        let mut coverage = SourceCoverage::new("anon".to_string(), false);
+        coverage.set_origin(Location { line: 3, column: 4 });
+
+        coverage.new_statement(&Range::new(3, 10, 5, 2));
+        coverage.new_function(&None, &Range::new(3, 4, 3, 8), &Range::new(3, 4, 6, 1));
+        let branch = coverage.new_branch(BranchType::If, &Range::new(4, 2, 4, 20), false);
+        coverage.add_branch_path(branch, &Range::new(4, 2, 4, 20));
+
+        coverage.freeze();
+
+        let coverage_ref = coverage.as_ref();
+        assert_eq!(
+            coverage_ref.statement_map.get(&0),
+            Some(Range::new(1, 6, 3, 2)).as_ref()
+        );
+
+        let function = coverage_ref.fn_map.get(&0).unwrap();
+        assert_eq!(function.decl, Range::new(1, 0, 1, 4));
+        assert_eq!(function.loc, Range::new(1, 0, 4, 1));
+        assert_eq!(function.line, 1);
+
+        let branch = coverage_ref.branch_map.get(&0).unwrap();
+        assert_eq!(branch.loc, Some(Range::new(2, 2, 2, 20)));
+        assert_eq!(branch.locations, vec![Range::new(2, 2, 2, 20)]);
+        assert_eq!(branch.line, Some(2));
+    }
+
+    #[test]
+    fn should_shift_to_offset_on_freeze() {
+        let mut coverage = SourceCoverage::new("anon".to_string(), false);
+        coverage.set_offset(Location { line: 3, column: 8 });
+
+        coverage.new_statement(&Range::new(1, 2, 2, 4));
+        let branch = coverage.new_branch(BranchType::If, &Range::new(2, 2, 2, 20), false);
+        coverage.add_branch_path(branch, &Range::new(2, 2, 2, 20));
+
+        coverage.freeze();
+
+        // Column is shifted only on the first line of the input.
+        let coverage_ref = coverage.as_ref();
+        assert_eq!(
+            coverage_ref.statement_map.get(&0),
+            Some(Range::new(3, 10, 4, 4)).as_ref()
+        );
+
+        let branch = coverage_ref.branch_map.get(&0).unwrap();
+        assert_eq!(branch.loc, Some(Range::new(4, 2, 4, 20)));
+        assert_eq!(branch.locations, vec![Range::new(4, 2, 4, 20)]);
+        assert_eq!(branch.line, Some(4));
+    }
+
+    #[test]
+    fn should_skip_ranges() {
+        let mut coverage = SourceCoverage::new("anon".to_string(), false);
+
+        coverage.new_statement(&Range::new(1, 0, 1, 10));
+        coverage.new_statement(&Range::new(3, 2, 3, 10));
+        coverage.new_function(&None, &Range::new(4, 0, 4, 5), &Range::new(4, 0, 6, 1));
+        let branch = coverage.new_branch(BranchType::If, &Range::new(5, 2, 5, 20), false);
+        coverage.add_branch_path(branch, &Range::new(5, 2, 5, 20));
+
+        let skipped = coverage.skip_ranges(&[Range::new(2, 0, 4, 0)]);
+        assert_eq!(skipped.s, HashSet::from([1]));
+        assert_eq!(skipped.f, HashSet::from([0]));
+        assert!(skipped.b.is_empty());
+
+        let coverage_ref = coverage.as_ref();
+        assert!(!coverage_ref.statement_map.get(&0).unwrap().skip);
+        assert!(coverage_ref.statement_map.get(&1).unwrap().skip);
+        assert!(coverage_ref.fn_map.get(&0).unwrap().loc.skip);
+        assert!(!coverage_ref.branch_map.get(&0).unwrap().loc.unwrap().skip);
+    }
+
+    #[test]
+    fn should_set_skip() {
+        let mut coverage = SourceCoverage::new("anon".to_string(), false);
+
+        coverage.new_statement(&Range::new(1, 0, 1, 10));
+        coverage.new_function(&None, &Range::new(2, 0, 2, 5), &Range::new(2, 0, 3, 1));
+        let branch = coverage.new_branch(BranchType::If, &Range::new(4, 2, 4, 20), false);
+        coverage.add_branch_path(branch, &Range::new(4, 2, 4, 20));
+        coverage.add_branch_path(branch, &Range::new(4, 2, 4, 20));
+
+        coverage.set_statement_skip(0);
+        coverage.set_function_skip(0);
+        coverage.set_branch_skip(branch);
+
+        let coverage_ref = coverage.as_ref();
+        assert!(coverage_ref.statement_map.get(&0).unwrap().skip);
+        assert_eq!(coverage_ref.s.get(&0), Some(&0));
+        let function = coverage_ref.fn_map.get(&0).unwrap();
+        assert!(function.decl.skip && function.loc.skip);
+        let branch = coverage_ref.branch_map.get(&branch).unwrap();
+        assert!(branch.loc.as_ref().unwrap().skip);
+        assert!(branch.locations.iter().all(|location| location.skip));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid statement 1")]
+    fn should_not_skip_invalid_statement() {
+        let mut coverage = SourceCoverage::new("anon".to_string(), false);
+        coverage.new_statement(&Range::new(1, 0, 1, 10));
+        coverage.set_statement_skip(1);
+    }
+
+    #[test]
+    fn should_set_metadata() {
+        let mut coverage = SourceCoverage::new("anon".to_string(), false);
+        coverage.set_metadata("generator", serde_json::json!({ "name": "foo" }));
+
+        assert_eq!(
+            coverage.as_ref().extras.get("generator"),
+            Some(&serde_json::json!({ "name": "foo" }))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "statementMap is reserved for the coverage")]
+    fn should_not_set_reserved_metadata() {
+        let mut coverage = SourceCoverage::new("anon".to_string(), false);
+        coverage.set_metadata("statementMap", serde_json::Value::Null);
+    }
 }
@@ -0,0 +1,21 @@
+use std::{cell::RefCell, rc::Rc};
+
+use swc_common::BytePos;
+use swc_ecmascript::ast::Ident;
+
+use crate::SourceCoverage;
+
+/// Coverage of an original module in the concatenated input, spans from `start`
+/// until the start of the next module. Each module has its own coverage fn.
+#[derive(Clone, Debug)]
+pub struct ModuleCoverage {
+    pub start: BytePos,
+    pub file_path: String,
+    pub cov: Rc<RefCell<SourceCoverage>>,
+    pub cov_fn_ident: Ident,
+}
+
+/// Returns the module given position belongs to. Modules are sorted by its start.
+pub fn find_module_coverage(modules: &[ModuleCoverage], pos: BytePos) -> Option<&ModuleCoverage> {
+    modules.iter().rev().find(|module| module.start <= pos)
+}
@@ -0,0 +1,133 @@
+use std::ops::Range;
+
+use istanbul_oxide::FileCoverage;
+use serde::de::DeserializeOwned;
+
+use super::coverage_manifest::{CoverageManifestEntry, COVERAGE_MANIFEST_COMMENT_PREFIX};
+
+/// Prefix of the block comment carries coverage data as JSON,
+/// attached to the transformed output if `emitCoverageComment` is set.
+/// Plugin does not have a way to pass data to the host other than the transformed program.
+pub static COVERAGE_DATA_COMMENT_PREFIX: &str = "__coverage_data_json_comment__::";
+
+/// Finds block comments start with given prefix followed by JSON in the code, returns byte range
+/// of each comment (including `/*`, `*/`) with parsed value. Malformed comments are ignored.
+pub(crate) fn find_json_comments<T: DeserializeOwned>(
+    code: &str,
+    prefix: &str,
+) -> Vec<(Range<usize>, T)> {
+    let comment_start = format!("/*{}", prefix);
+    let mut ret = vec![];
+    let mut pos = 0;
+
+    while let Some(found) = code[pos..].find(&comment_start) {
+        let start = pos + found;
+        let json_start = start + comment_start.len();
+        pos = json_start;
+
+        // JSON may contain `*/` in its strings, parse the value first instead of looking up the end of comment.
+        let mut stream = serde_json::Deserializer::from_str(&code[json_start..]).into_iter::<T>();
+        if let Some(Ok(value)) = stream.next() {
+            let json_end = json_start + stream.byte_offset();
+            if code[json_end..].starts_with("*/") {
+                pos = json_end + 2;
+                ret.push((start..pos, value));
+            }
+        }
+    }
+
+    ret
+}
+
+fn find_coverage_comments(code: &str) -> Vec<(Range<usize>, FileCoverage)> {
+    find_json_comments(code, COVERAGE_DATA_COMMENT_PREFIX)
+}
+
+/// Extracts coverage data from the comments in the transformed output, in order of appearance.
+pub fn extract_coverage_from_comments(code: &str) -> Vec<FileCoverage> {
+    find_coverage_comments(code)
+        .into_iter()
+        .map(|(_, coverage)| coverage)
+        .collect()
+}
+
+/// Removes coverage data comments, and coverage manifest comments from the transformed output.
+pub fn strip_coverage_comments(code: &str) -> String {
+    let mut ranges: Vec<Range<usize>> = find_coverage_comments(code)
+        .into_iter()
+        .map(|(range, _)| range)
+        .chain(
+            find_json_comments::<CoverageManifestEntry>(code, COVERAGE_MANIFEST_COMMENT_PREFIX)
+                .into_iter()
+                .map(|(range, _)| range),
+        )
+        .collect();
+    ranges.sort_by_key(|range| range.start);
+
+    let mut ret = String::with_capacity(code.len());
+    let mut pos = 0;
+
+    for range in ranges {
+        // Prefix can appear inside of the other comment's JSON strings.
+        if range.start < pos {
+            continue;
+        }
+        ret.push_str(&code[pos..range.start]);
+        pos = range.end;
+    }
+    ret.push_str(&code[pos..]);
+
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use istanbul_oxide::FileCoverage;
+
+    use super::{extract_coverage_from_comments, strip_coverage_comments};
+
+    fn create_comment(coverage: &FileCoverage) -> String {
+        format!(
+            "/*__coverage_data_json_comment__::{}*/",
+            serde_json::to_string(coverage).unwrap()
+        )
+    }
+
+    #[test]
+    fn should_extract_coverage() {
+        let first = FileCoverage::from_file_path("a.js".to_string(), false);
+        let second = FileCoverage::from_file_path("b/*/c.js".to_string(), true);
+        let code = format!(
+            "var a = 1;\n{}\nvar b = 2;{}",
+            create_comment(&first),
+            create_comment(&second)
+        );
+
+        assert_eq!(extract_coverage_from_comments(&code), vec![first, second]);
+    }
+
+    #[test]
+    fn should_ignore_malformed_comments() {
+        let coverage = FileCoverage::from_file_path("a.js".to_string(), false);
+        let code = format!(
+            "/*__coverage_data_json_comment__::{{\"path\": */\n{}",
+            create_comment(&coverage)
+        );
+
+        assert_eq!(extract_coverage_from_comments(&code), vec![coverage]);
+        assert_eq!(extract_coverage_from_comments("var a = 1;"), vec![]);
+    }
+
+    #[test]
+    fn should_strip_comments() {
+        let coverage = FileCoverage::from_file_path("a.js".to_string(), false);
+        let code = format!(
+            "var a = 1;\n{}\nvar b = 2;{}",
+            create_comment(&coverage),
+            create_comment(&coverage)
+        );
+
+        assert_eq!(strip_coverage_comments(&code), "var a = 1;\n\nvar b = 2;");
+        assert_eq!(strip_coverage_comments("var a = 1;"), "var a = 1;");
+    }
+}
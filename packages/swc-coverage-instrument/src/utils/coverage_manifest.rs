@@ -0,0 +1,172 @@
+use istanbul_oxide::{CoverageMap, FileCoverage};
+use serde::{Deserialize, Serialize};
+
+use super::coverage_data_comment::find_json_comments;
+
+/// Prefix of the block comment carries the manifest entry of the instrumented file as JSON,
+/// attached to the transformed output if `emitManifest` is set.
+pub static COVERAGE_MANIFEST_COMMENT_PREFIX: &str = "__coverage_manifest_json_comment__::";
+
+/// Describes a file instrumented, to verify coverage collected from separate runs
+/// (i.e sharded tests) are from the same instrumentation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageManifestEntry {
+    pub path: String,
+    /// Name of the global variable coverage is stored.
+    pub coverage_variable: String,
+    /// Name of the coverage fn injected into the file.
+    pub coverage_fn: String,
+    /// Hash of the initial coverage data, same as `hash` of the coverage.
+    pub hash: String,
+    pub statements: usize,
+    pub functions: usize,
+    pub branches: usize,
+}
+
+impl CoverageManifestEntry {
+    pub fn new(
+        coverage: &FileCoverage,
+        coverage_variable: &str,
+        coverage_fn: &str,
+        hash: &str,
+    ) -> CoverageManifestEntry {
+        CoverageManifestEntry {
+            path: coverage.path.clone(),
+            coverage_variable: coverage_variable.to_string(),
+            coverage_fn: coverage_fn.to_string(),
+            hash: hash.to_string(),
+            statements: coverage.statement_map.len(),
+            functions: coverage.fn_map.len(),
+            branches: coverage.branch_map.len(),
+        }
+    }
+
+    /// Check if given coverage is collected from the instrumentation the entry describes.
+    pub fn matches(&self, coverage: &FileCoverage) -> bool {
+        coverage.path == self.path
+            && coverage.hash.as_deref() == Some(self.hash.as_str())
+            && coverage.statement_map.len() == self.statements
+            && coverage.fn_map.len() == self.functions
+            && coverage.branch_map.len() == self.branches
+    }
+}
+
+/// Difference between the manifest and the coverage, returned by `verify_coverage_manifest`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CoverageManifestMismatch {
+    /// File in the manifest does not have coverage.
+    Missing(String),
+    /// Coverage of the file is from a different instrumentation.
+    Stale(String),
+    /// Coverage of the file is not in the manifest.
+    Unknown(String),
+}
+
+/// Verifies the coverage covers the same files instrumented as the manifest, returns mismatches
+/// in the order of the manifest followed by the coverage map.
+pub fn verify_coverage_manifest(
+    manifest: &[CoverageManifestEntry],
+    coverage_map: &CoverageMap,
+) -> Vec<CoverageManifestMismatch> {
+    let mut ret = vec![];
+
+    for entry in manifest {
+        match coverage_map.get_coverage_for_file(&entry.path) {
+            Some(coverage) if entry.matches(coverage) => {}
+            Some(_) => ret.push(CoverageManifestMismatch::Stale(entry.path.clone())),
+            None => ret.push(CoverageManifestMismatch::Missing(entry.path.clone())),
+        }
+    }
+
+    for path in coverage_map.get_files() {
+        if !manifest.iter().any(|entry| &entry.path == path) {
+            ret.push(CoverageManifestMismatch::Unknown(path.clone()));
+        }
+    }
+
+    ret
+}
+
+/// Extracts manifest entries from the comments in the transformed output, in order of appearance.
+pub fn extract_manifest_from_comments(code: &str) -> Vec<CoverageManifestEntry> {
+    find_json_comments(code, COVERAGE_MANIFEST_COMMENT_PREFIX)
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use istanbul_oxide::{CoverageMap, FileCoverage, Range};
+
+    use crate::strip_coverage_comments;
+
+    use super::{
+        extract_manifest_from_comments, verify_coverage_manifest, CoverageManifestEntry,
+        CoverageManifestMismatch,
+    };
+
+    fn create_coverage(path: &str, hash: &str) -> FileCoverage {
+        let mut coverage = FileCoverage::from_file_path(path.to_string(), false);
+        coverage.statement_map.insert(0, Range::new(1, 0, 1, 10));
+        coverage.s.insert(0, 0);
+        coverage.hash = Some(hash.to_string());
+        coverage
+    }
+
+    #[test]
+    fn should_extract_and_strip_manifest() {
+        let entry = CoverageManifestEntry::new(
+            &create_coverage("a.js", "1234"),
+            "__coverage__",
+            "cov_1",
+            "1234",
+        );
+        let code = format!(
+            "var a = 1;\n/*__coverage_manifest_json_comment__::{}*/",
+            serde_json::to_string(&entry).unwrap()
+        );
+
+        assert_eq!(extract_manifest_from_comments(&code), vec![entry]);
+        assert_eq!(strip_coverage_comments(&code), "var a = 1;\n");
+    }
+
+    #[test]
+    fn should_verify_coverage_against_manifest() {
+        let manifest: Vec<CoverageManifestEntry> = [("a.js", "1"), ("b.js", "2"), ("c.js", "3")]
+            .iter()
+            .map(|(path, hash)| {
+                CoverageManifestEntry::new(
+                    &create_coverage(path, hash),
+                    "__coverage__",
+                    "cov",
+                    hash,
+                )
+            })
+            .collect();
+
+        let coverage_map = CoverageMap::from_iter(vec![
+            &create_coverage("a.js", "1"),
+            // Instrumented again after the manifest is created
+            &create_coverage("b.js", "4"),
+            &create_coverage("d.js", "5"),
+        ]);
+
+        assert_eq!(
+            verify_coverage_manifest(&manifest, &coverage_map),
+            vec![
+                CoverageManifestMismatch::Stale("b.js".to_string()),
+                CoverageManifestMismatch::Missing("c.js".to_string()),
+                CoverageManifestMismatch::Unknown("d.js".to_string()),
+            ]
+        );
+
+        let coverage_map = CoverageMap::from_iter(vec![
+            &create_coverage("a.js", "1"),
+            &create_coverage("b.js", "2"),
+            &create_coverage("c.js", "3"),
+        ]);
+        assert!(verify_coverage_manifest(&manifest, &coverage_map).is_empty());
+    }
+}
@@ -0,0 +1,59 @@
+/// Name of the fn the instrumented files import from `registryModule`, called as
+/// `register(coverageVariable, path, hash, coverageData)` and returns the coverage of the file.
+pub static COVERAGE_REGISTRY_EXPORT: &str = "register";
+
+/// Creates the code of the coverage registry module for `registryModule`, written once per build
+/// instead of each file looking up the global. Besides `register`, exports
+/// `getCoverage(coverageVariable?)` returning the coverage object of the variable (defaults to
+/// `coverage_variable`), for the tests to extract coverage with a single import.
+/// `commonjs` emits `exports.*` instead of ES module exports, for the scripts `require`-ing it.
+pub fn create_coverage_registry_module(coverage_variable: &str, commonjs: bool) -> String {
+    let coverage_variable =
+        serde_json::to_string(coverage_variable).expect("Should able to serialize string");
+    let export = if commonjs { "" } else { "export " };
+
+    let mut code = format!(
+        r#"var global = new ((function () {{}}).constructor)("return this")();
+{export}function {register}(gcv, path, hash, coverageData) {{
+  var coverage = global[gcv] || (global[gcv] = {{}});
+  if (!coverage[path] || coverage[path].hash !== hash) {{
+    coverage[path] = coverageData;
+  }}
+  return coverage[path];
+}}
+{export}function getCoverage(gcv) {{
+  return global[gcv === undefined ? {coverage_variable} : gcv];
+}}
+"#,
+        export = export,
+        register = COVERAGE_REGISTRY_EXPORT,
+        coverage_variable = coverage_variable,
+    );
+
+    if commonjs {
+        code.push_str(&format!(
+            "exports.{register} = {register};\nexports.getCoverage = getCoverage;\n",
+            register = COVERAGE_REGISTRY_EXPORT
+        ));
+    }
+
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::create_coverage_registry_module;
+
+    #[test]
+    fn should_create_registry_module() {
+        let esm = create_coverage_registry_module("__cov\"__", false);
+        assert!(esm.contains("export function register(gcv, path, hash, coverageData)"));
+        assert!(esm.contains(r#"global[gcv === undefined ? "__cov\"__" : gcv]"#));
+        assert!(!esm.contains("exports."));
+
+        let cjs = create_coverage_registry_module("__coverage__", true);
+        assert!(!cjs.contains("export function"));
+        assert!(cjs.contains("exports.register = register;"));
+        assert!(cjs.contains("exports.getCoverage = getCoverage;"));
+    }
+}
@@ -0,0 +1,70 @@
+use std::{fs, io, path::Path};
+
+use crate::DebugOptions;
+
+/// Prefix of the header line of the snapshots printed to stderr, followed by the path and the stage.
+pub static DEBUG_SNAPSHOT_PREFIX: &str = "swc-coverage-debug: ";
+
+fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/").trim_start_matches("./").to_string()
+}
+
+fn get_snapshot_name(file_path: &str, stage: &str, extension: &str) -> String {
+    let name = normalize_path(file_path)
+        .trim_start_matches('/')
+        .replace(['/', ':'], "__");
+    format!("{}.{}.{}", name, stage, extension)
+}
+
+fn write_snapshot_file(
+    out_dir: &str,
+    file_path: &str,
+    stage: &str,
+    extension: &str,
+    content: &str,
+) -> io::Result<()> {
+    let out_dir = Path::new(out_dir);
+    fs::create_dir_all(out_dir)?;
+    fs::write(
+        out_dir.join(get_snapshot_name(file_path, stage, extension)),
+        content,
+    )
+}
+
+/// Write the snapshot of the file at the stage (`before` / `after`) to `out_dir` of the options,
+/// or print to stderr if it's not set or not writable.
+pub fn write_debug_snapshot(
+    options: &DebugOptions,
+    file_path: &str,
+    stage: &str,
+    extension: &str,
+    content: &str,
+) {
+    if let Some(out_dir) = &options.out_dir {
+        if write_snapshot_file(out_dir, file_path, stage, extension, content).is_ok() {
+            return;
+        }
+    }
+
+    eprintln!(
+        "{}{} {} {}\n{}",
+        DEBUG_SNAPSHOT_PREFIX, file_path, stage, extension, content
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_snapshot_name;
+
+    #[test]
+    fn should_name_snapshots_by_path() {
+        assert_eq!(
+            get_snapshot_name("/repo/src/a.ts", "before", "ast"),
+            "repo__src__a.ts.before.ast"
+        );
+        assert_eq!(
+            get_snapshot_name("C:\\src\\a.ts", "after", "code"),
+            "C____src__a.ts.after.code"
+        );
+    }
+}
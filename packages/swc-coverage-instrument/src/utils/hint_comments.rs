@@ -1,21 +1,174 @@
-use once_cell::sync::Lazy;
-use regex::Regex as Regexp;
+use std::sync::Arc;
+
+use istanbul_oxide::{Location, Range};
 use swc_common::{
     comments::{Comment, Comments},
-    Span,
+    BytePos, SourceMapper, Span,
 };
 use swc_ecmascript::ast::*;
 
-/// pattern for istanbul to ignore the whole file
-/// This is not fully identical to original file comments
-/// https://github.com/istanbuljs/istanbuljs/blob/6f45283feo31faaa066375528f6b68e3a9927b2d5/packages/istanbul-lib-instrument/src/visitor.js#L10=
-/// as regex package doesn't support lookaround
-static COMMENT_FILE_REGEX: Lazy<Regexp> =
-    Lazy::new(|| Regexp::new(r"^\s*istanbul\s+ignore\s+(file)(\W|$)").unwrap());
+/// Matchers of the hint comment patterns, with `regex` if the feature is enabled. Otherwise
+/// the same patterns are matched by hand, to keep the binary small (i.e wasm plugin).
+#[cfg(feature = "regex")]
+mod patterns {
+    use once_cell::sync::Lazy;
+    use regex::Regex as Regexp;
+
+    /// pattern for istanbul to ignore the whole file
+    /// This is not fully identical to original file comments
+    /// https://github.com/istanbuljs/istanbuljs/blob/6f45283feo31faaa066375528f6b68e3a9927b2d5/packages/istanbul-lib-instrument/src/visitor.js#L10=
+    /// as regex package doesn't support lookaround
+    static COMMENT_FILE_REGEX: Lazy<Regexp> =
+        Lazy::new(|| Regexp::new(r"^\s*istanbul\s+ignore\s+(file)(\W|$)").unwrap());
+
+    /// pattern for istanbul to ignore a section
+    static COMMENT_RE: Lazy<Regexp> =
+        Lazy::new(|| Regexp::new(r"^\s*istanbul\s+ignore\s+(if|else|next)(\W|$)").unwrap());
+
+    /// pattern for the comments start / end a region excluded from the instrumentation,
+    /// `#region coverage-off` and `#region coverage-on` (or `#endregion`) in any of line or block comments.
+    static COMMENT_REGION_RE: Lazy<Regexp> =
+        Lazy::new(|| Regexp::new(r"(//|/\*)\s*#(end)?region\s+coverage-(off|on)\b").unwrap());
+
+    pub(super) fn match_ignore_file(text: &str) -> bool {
+        COMMENT_FILE_REGEX.is_match(text)
+    }
+
+    /// Scope of the `istanbul ignore` hint, one of `if`, `else`, `next`.
+    pub(super) fn match_ignore_hint(text: &str) -> Option<&str> {
+        COMMENT_RE
+            .captures(text)
+            .and_then(|captures| captures.get(1))
+            .map(|hint| hint.as_str())
+    }
+
+    /// Start, end, and whether it's `coverage-off` of the region pragmas in the code.
+    pub(super) fn find_region_pragmas(code: &str) -> Vec<(usize, usize, bool)> {
+        COMMENT_REGION_RE
+            .captures_iter(code)
+            .map(|captures| {
+                let pragma = captures.get(0).expect("Match should exist");
+                (pragma.start(), pragma.end(), &captures[3] == "off")
+            })
+            .collect()
+    }
+}
+
+#[cfg(not(feature = "regex"))]
+mod patterns {
+    fn is_word_char(c: char) -> bool {
+        c.is_alphanumeric() || c == '_'
+    }
+
+    /// Strips the leading whitespaces, `at_least_one` requires at least one of those (`\s+`).
+    fn strip_whitespace(text: &str, at_least_one: bool) -> Option<&str> {
+        let stripped = text.trim_start();
+        (!at_least_one || stripped.len() < text.len()).then_some(stripped)
+    }
+
+    /// Matches `^\s*istanbul\s+ignore\s+(<scopes>)(\W|$)`.
+    fn match_ignore<'a>(text: &str, scopes: &[&'a str]) -> Option<&'a str> {
+        let text = strip_whitespace(text, false)?.strip_prefix("istanbul")?;
+        let text = strip_whitespace(text, true)?.strip_prefix("ignore")?;
+        let text = strip_whitespace(text, true)?;
+
+        scopes.iter().copied().find(|scope| {
+            text.strip_prefix(scope)
+                .is_some_and(|rest| !rest.starts_with(is_word_char))
+        })
+    }
+
+    pub(super) fn match_ignore_file(text: &str) -> bool {
+        match_ignore(text, &["file"]).is_some()
+    }
+
+    /// Scope of the `istanbul ignore` hint, one of `if`, `else`, `next`.
+    pub(super) fn match_ignore_hint(text: &str) -> Option<&str> {
+        match_ignore(text, &["if", "else", "next"])
+    }
+
+    /// Length of the pragma `\s*#(end)?region\s+coverage-(off|on)\b` after the comment
+    /// start, and whether it's `coverage-off`.
+    fn match_region_pragma(text: &str) -> Option<(usize, bool)> {
+        let rest = strip_whitespace(text, false)?.strip_prefix('#')?;
+        let rest = rest.strip_prefix("end").unwrap_or(rest);
+        let rest = rest.strip_prefix("region")?;
+        let rest = strip_whitespace(rest, true)?.strip_prefix("coverage-")?;
+
+        let (rest, is_off) = match rest.strip_prefix("off") {
+            Some(rest) => (rest, true),
+            None => (rest.strip_prefix("on")?, false),
+        };
+        (!rest.starts_with(is_word_char)).then_some((text.len() - rest.len(), is_off))
+    }
 
-/// pattern for istanbul to ignore a section
-pub static COMMENT_RE: Lazy<Regexp> =
-    Lazy::new(|| Regexp::new(r"^\s*istanbul\s+ignore\s+(if|else|next)(\W|$)").unwrap());
+    /// Start, end, and whether it's `coverage-off` of the region pragmas in the code.
+    pub(super) fn find_region_pragmas(code: &str) -> Vec<(usize, usize, bool)> {
+        let mut pragmas = vec![];
+        let mut offset = 0;
+
+        while let Some(index) = code[offset..].find('/') {
+            let start = offset + index;
+            let rest = &code[start..];
+            let pragma = (rest.starts_with("//") || rest.starts_with("/*"))
+                .then(|| match_region_pragma(&rest[2..]))
+                .flatten();
+
+            offset = match pragma {
+                Some((len, is_off)) => {
+                    pragmas.push((start, start + 2 + len, is_off));
+                    start + 2 + len
+                }
+                None => start + 1,
+            };
+        }
+
+        pragmas
+    }
+}
+
+/// Returns the ranges between `coverage-off` and `coverage-on` region comments in the file
+/// given span belongs to. Region without `coverage-on` extends to the end of the file.
+pub fn find_ignore_regions<S: SourceMapper>(source_map: &Arc<S>, span: &Span) -> Vec<Range> {
+    let (file_start, file_end) = crate::lookup_range::get_file_bounds(source_map, span.lo);
+    let code = match source_map.span_to_snippet(Span::new(file_start, file_end, Default::default()))
+    {
+        Ok(code) => code,
+        Err(_) => return vec![],
+    };
+
+    let get_location = |offset: usize| {
+        let (line, column) =
+            crate::lookup_range::get_line_col(source_map, file_start + BytePos(offset as u32));
+        Location { line, column }
+    };
+
+    let mut regions = vec![];
+    let mut region_start = None;
+    for (start, end, is_off) in patterns::find_region_pragmas(&code) {
+        match (is_off, region_start) {
+            (true, None) => region_start = Some(start),
+            (false, Some(start)) => {
+                regions.push((start, end));
+                region_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(start) = region_start {
+        regions.push((start, code.len()));
+    }
+
+    regions
+        .into_iter()
+        .map(|(start, end)| Range {
+            start: get_location(start),
+            end: get_location(end),
+            skip: true,
+        })
+        .collect()
+}
 
 pub fn should_ignore_file<C: Clone + Comments>(comments: &C, program: &Program) -> bool {
     let pos = match program {
@@ -27,7 +180,7 @@ pub fn should_ignore_file<C: Clone + Comments>(comments: &C, program: &Program)
         if let Some(comments) = comments {
             comments
                 .iter()
-                .any(|comment| COMMENT_FILE_REGEX.is_match(&comment.text))
+                .any(|comment| patterns::match_ignore_file(&comment.text))
         } else {
             false
         }
@@ -52,12 +205,9 @@ pub fn lookup_hint_comments<C: Clone + Comments>(
         let l = comments.get_leading(span.lo);
 
         if let Some(h) = h {
-            let h_value = h.iter().find_map(|c| {
-                COMMENT_RE
-                    .captures(&c.text)
-                    .map(|captures| captures.get(1).map(|c| c.as_str().trim().to_string()))
-                    .flatten()
-            });
+            let h_value = h
+                .iter()
+                .find_map(|c| patterns::match_ignore_hint(&c.text).map(|hint| hint.to_string()));
 
             if let Some(h_value) = h_value {
                 return Some(h_value);
@@ -65,12 +215,9 @@ pub fn lookup_hint_comments<C: Clone + Comments>(
         }
 
         if let Some(l) = l {
-            let l_value = l.iter().find_map(|c| {
-                COMMENT_RE
-                    .captures(&c.text)
-                    .map(|captures| captures.get(1).map(|c| c.as_str().trim().to_string()))
-                    .flatten()
-            });
+            let l_value = l
+                .iter()
+                .find_map(|c| patterns::match_ignore_hint(&c.text).map(|hint| hint.to_string()));
 
             return l_value;
         }
@@ -79,6 +226,19 @@ pub fn lookup_hint_comments<C: Clone + Comments>(
     return None;
 }
 
+/// Lookup hint comments trailing the token ends at given pos, i.e `${ /* istanbul ignore next */ expr }`
+/// where parser attaches the comment to the `${` instead of the expr.
+pub fn lookup_trailing_hint_comments<C: Clone + Comments>(
+    comments: &C,
+    pos: BytePos,
+) -> Option<String> {
+    comments.get_trailing(pos).and_then(|trailing| {
+        trailing
+            .iter()
+            .find_map(|c| patterns::match_ignore_hint(&c.text).map(|hint| hint.to_string()))
+    })
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum IgnoreScope {
     Next,
@@ -103,3 +263,37 @@ pub fn should_ignore<C: Clone + Comments>(
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::patterns;
+
+    #[test]
+    fn should_match_hint_patterns() {
+        assert!(patterns::match_ignore_file(" istanbul ignore file "));
+        assert!(!patterns::match_ignore_file(" istanbul ignore files"));
+        assert!(!patterns::match_ignore_file("istanbulignore file"));
+
+        assert_eq!(
+            patterns::match_ignore_hint(" istanbul ignore next"),
+            Some("next")
+        );
+        assert_eq!(
+            patterns::match_ignore_hint("*\n * istanbul  ignore\telse: reason"),
+            None
+        );
+        assert_eq!(
+            patterns::match_ignore_hint("\n  istanbul  ignore\telse: reason"),
+            Some("else")
+        );
+        assert_eq!(patterns::match_ignore_hint(" istanbul ignore iffy"), None);
+        assert_eq!(patterns::match_ignore_hint(" istanbul ignore"), None);
+
+        assert_eq!(
+            patterns::find_region_pragmas(
+                "a;\n// #region coverage-off\nb;\n///#endregion coverage-on\n/* #region coverage-offs */"
+            ),
+            vec![(3, 26, true), (31, 55, false)]
+        );
+    }
+}
@@ -0,0 +1,373 @@
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use istanbul_oxide::FileCoverage;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::InstrumentOptions;
+
+/// Extension of the entry files, other files in the cache directory are left as-is.
+static CACHE_ENTRY_EXTENSION: &str = "swccov";
+
+/// Instrumented output of a file stored in `InstrumentCache`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedInstrumentation {
+    pub code: String,
+    /// Source map of the instrumented code, as a JSON string.
+    pub map: Option<String>,
+    /// Coverage data of the file (i.e read by `extract_coverage_from_comments`).
+    pub coverage: Vec<FileCoverage>,
+}
+
+/// First line of the entry file, describes what the entry was created from.
+/// Payload (`CachedInstrumentation` as JSON) follows the line.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheEntryHeader {
+    version: String,
+    filename: String,
+    source_hash: String,
+    options_hash: String,
+    payload_len: u64,
+    payload_hash: String,
+}
+
+/// SHA-256 of the parts as a hex string. Entries outlive the process, the hash has to be stable across
+/// the releases and the platforms (unlike `DefaultHasher`), and wide enough for a collision not to return
+/// the output of the other file. Parts are length-prefixed, `["ab", "c"]` and `["a", "bc"]` differ.
+fn hash_to_string(parts: &[&[u8]]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn get_source_hash(filename: &str, code: &str) -> String {
+    hash_to_string(&[filename.as_bytes(), code.as_bytes()])
+}
+
+/// Options are hashed with the version of the instrumentation, so upgrading the package invalidates the cache.
+fn get_options_hash(options: &InstrumentOptions, salt: &str) -> String {
+    // Keys of the maps are sorted in `serde_json::Value`, the hash does not depend on the order of `defines`.
+    let options = serde_json::to_value(options).expect("Should able to serialize options");
+    hash_to_string(&[
+        env!("CARGO_PKG_VERSION").as_bytes(),
+        options.to_string().as_bytes(),
+        salt.as_bytes(),
+    ])
+}
+
+/// On-disk cache of the instrumented output keyed by the content of the file and the options,
+/// to skip instrumenting unchanged files across runs. Entries are validated on read, corrupted
+/// or mismatching entries are removed and treated as a miss. Safe to share between processes:
+/// entries are written to a temporary file then renamed.
+#[derive(Clone, Debug)]
+pub struct InstrumentCache {
+    dir: PathBuf,
+    salt: String,
+}
+
+impl InstrumentCache {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> InstrumentCache {
+        InstrumentCache {
+            dir: dir.into(),
+            salt: Default::default(),
+        }
+    }
+
+    /// Adds the settings affecting the output other than the options (i.e syntax of the parser) to the key.
+    pub fn with_salt(mut self, salt: String) -> InstrumentCache {
+        self.salt = salt;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn get_entry_path(&self, source_hash: &str, options_hash: &str) -> PathBuf {
+        self.dir.join(format!(
+            "{}-{}.{}",
+            source_hash, options_hash, CACHE_ENTRY_EXTENSION
+        ))
+    }
+
+    /// Returns the cached output of the file instrumented with the options, if any.
+    pub fn get(
+        &self,
+        filename: &str,
+        code: &str,
+        options: &InstrumentOptions,
+    ) -> Option<CachedInstrumentation> {
+        let source_hash = get_source_hash(filename, code);
+        let options_hash = get_options_hash(options, &self.salt);
+        let path = self.get_entry_path(&source_hash, &options_hash);
+
+        let file = fs::File::open(&path).ok()?;
+        match read_entry(file, filename, &source_hash, &options_hash) {
+            Some(entry) => {
+                // Bump the modified time for `evict` to remove least recently used entries first.
+                if let Ok(file) = fs::File::options().append(true).open(&path) {
+                    let _ = file.set_modified(SystemTime::now());
+                }
+                Some(entry)
+            }
+            None => {
+                let _ = fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    /// Stores the output of the file instrumented with the options.
+    pub fn put(
+        &self,
+        filename: &str,
+        code: &str,
+        options: &InstrumentOptions,
+        entry: &CachedInstrumentation,
+    ) -> io::Result<()> {
+        let source_hash = get_source_hash(filename, code);
+        let options_hash = get_options_hash(options, &self.salt);
+        let path = self.get_entry_path(&source_hash, &options_hash);
+
+        let payload = serde_json::to_vec(entry)?;
+        let header = CacheEntryHeader {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            filename: filename.to_string(),
+            source_hash,
+            options_hash,
+            payload_len: payload.len() as u64,
+            payload_hash: hash_to_string(&[&payload]),
+        };
+
+        fs::create_dir_all(&self.dir)?;
+        let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+        {
+            let mut file = fs::File::create(&temp_path)?;
+            serde_json::to_writer(&mut file, &header)?;
+            file.write_all(b"\n")?;
+            file.write_all(&payload)?;
+        }
+        fs::rename(&temp_path, &path).inspect_err(|_| {
+            let _ = fs::remove_file(&temp_path);
+        })
+    }
+
+    /// Removes least recently used entries until the total size of the entries is at most `max_size`
+    /// bytes. Returns the number of the removed entries.
+    pub fn evict(&self, max_size: u64) -> io::Result<usize> {
+        let mut entries = vec![];
+        let mut total_size = 0;
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(CACHE_ENTRY_EXTENSION) {
+                continue;
+            }
+            // Entry can be removed by the other process in the meantime.
+            if let Ok(metadata) = fs::metadata(&path) {
+                total_size += metadata.len();
+                entries.push((
+                    metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                    metadata.len(),
+                    path,
+                ));
+            }
+        }
+
+        entries.sort();
+        let mut removed = 0;
+        for (_, len, path) in entries {
+            if total_size <= max_size {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                removed += 1;
+            }
+            total_size -= len;
+        }
+
+        Ok(removed)
+    }
+
+    /// Removes all entries of the cache.
+    pub fn clear(&self) -> io::Result<usize> {
+        self.evict(0)
+    }
+}
+
+/// Reads the entry, returns None if it's not written for the given input or corrupted.
+fn read_entry(
+    file: fs::File,
+    filename: &str,
+    source_hash: &str,
+    options_hash: &str,
+) -> Option<CachedInstrumentation> {
+    let mut reader = BufReader::new(file);
+    let mut header = String::new();
+    reader.read_line(&mut header).ok()?;
+    let header: CacheEntryHeader = serde_json::from_str(&header).ok()?;
+
+    if header.version != env!("CARGO_PKG_VERSION")
+        || header.filename != filename
+        || header.source_hash != source_hash
+        || header.options_hash != options_hash
+    {
+        return None;
+    }
+
+    let mut payload = vec![];
+    reader.read_to_end(&mut payload).ok()?;
+    if payload.len() as u64 != header.payload_len
+        || hash_to_string(&[&payload]) != header.payload_hash
+    {
+        return None;
+    }
+
+    serde_json::from_slice(&payload).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use istanbul_oxide::FileCoverage;
+
+    use crate::InstrumentOptions;
+
+    use super::{CachedInstrumentation, InstrumentCache};
+
+    fn create_cache(name: &str) -> InstrumentCache {
+        let dir: PathBuf = std::env::temp_dir().join(format!(
+            "swc-coverage-instrument-cache-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        InstrumentCache::new(dir)
+    }
+
+    fn create_entry(code: &str) -> CachedInstrumentation {
+        CachedInstrumentation {
+            code: code.to_string(),
+            map: Some("{}".to_string()),
+            coverage: vec![FileCoverage::from_file_path("a.js".to_string(), false)],
+        }
+    }
+
+    fn entry_paths(cache: &InstrumentCache) -> Vec<PathBuf> {
+        fs::read_dir(cache.dir())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect()
+    }
+
+    #[test]
+    fn should_get_entry_of_same_input() {
+        let cache = create_cache("get");
+        let options = InstrumentOptions::default();
+        let entry = create_entry("cov();");
+
+        assert_eq!(cache.get("a.js", "a;", &options), None);
+        cache.put("a.js", "a;", &options, &entry).unwrap();
+        assert_eq!(cache.get("a.js", "a;", &options), Some(entry));
+
+        assert_eq!(cache.get("a.js", "b;", &options), None);
+        assert_eq!(cache.get("b.js", "a;", &options), None);
+        let options = InstrumentOptions {
+            report_logic: true,
+            ..Default::default()
+        };
+        assert_eq!(cache.get("a.js", "a;", &options), None);
+        assert_eq!(
+            cache.clone().with_salt("ts".to_string()).get(
+                "a.js",
+                "a;",
+                &InstrumentOptions::default()
+            ),
+            None
+        );
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn should_use_stable_source_hash() {
+        assert_eq!(
+            super::get_source_hash("a.js", "a;"),
+            "df3e64ccf5b7c748c88510950317485e3f56ec8423b70d38fc66b145f7185272"
+        );
+        assert_ne!(
+            super::get_source_hash("a.js", "a;"),
+            super::get_source_hash("a.j", "sa;")
+        );
+    }
+
+    #[test]
+    fn should_remove_corrupted_entry() {
+        let cache = create_cache("corrupted");
+        let options = InstrumentOptions::default();
+        cache
+            .put("a.js", "a;", &options, &create_entry("cov();"))
+            .unwrap();
+
+        let path = entry_paths(&cache).pop().unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        fs::write(&path, content.replace("cov();", "cov(); ")).unwrap();
+
+        assert_eq!(cache.get("a.js", "a;", &options), None);
+        assert!(entry_paths(&cache).is_empty());
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn should_evict_least_recently_used_entries() {
+        let cache = create_cache("evict");
+        let options = InstrumentOptions::default();
+        for code in ["a;", "b;", "c;"] {
+            cache
+                .put("a.js", code, &options, &create_entry(code))
+                .unwrap();
+        }
+        let entry_size = fs::metadata(&entry_paths(&cache)[0]).unwrap().len();
+
+        let now = std::time::SystemTime::now();
+        for (index, code) in ["b;", "a;", "c;"].iter().enumerate() {
+            let path = cache.get_entry_path(
+                &super::get_source_hash("a.js", code),
+                &super::get_options_hash(&options, ""),
+            );
+            fs::File::options()
+                .append(true)
+                .open(path)
+                .unwrap()
+                .set_modified(now - std::time::Duration::from_secs(10 - index as u64))
+                .unwrap();
+        }
+
+        assert_eq!(cache.evict(entry_size * 2).unwrap(), 1);
+        assert_eq!(cache.get("a.js", "b;", &options), None);
+        assert!(cache.get("a.js", "a;", &options).is_some());
+        assert!(cache.get("a.js", "c;", &options).is_some());
+
+        assert_eq!(cache.clear().unwrap(), 2);
+        assert!(entry_paths(&cache).is_empty());
+
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the line reporting `InstrumentStats` of a file, followed by the stats as JSON.
+pub static INSTRUMENT_STATS_PREFIX: &str = "swc-coverage-stats: ";
+
+/// Cost of instrumenting a file, collected if `instrumentLog.stats` is set.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstrumentStats {
+    pub path: String,
+    /// Time taken by the instrumentation visitor, parsing / codegen of the host are not included.
+    pub elapsed_ms: f64,
+    pub statements: usize,
+    pub functions: usize,
+    pub branches: usize,
+    /// Size of the input file.
+    pub input_bytes: u64,
+    /// Size of the instrumented output, only known to the callers emitting the code.
+    pub output_bytes: Option<u64>,
+    /// Bytes added by the instrumentation, if `output_bytes` is known.
+    pub bytes_grown: Option<i64>,
+}
+
+impl InstrumentStats {
+    pub fn set_output_bytes(&mut self, output_bytes: u64) {
+        self.output_bytes = Some(output_bytes);
+        self.bytes_grown = Some(output_bytes as i64 - self.input_bytes as i64);
+    }
+
+    /// Line to report the stats with, one per file so the lines of a build can be aggregated.
+    pub fn to_log_line(&self) -> String {
+        format!(
+            "{}{}",
+            INSTRUMENT_STATS_PREFIX,
+            serde_json::to_string(self).expect("Should able to serialize stats")
+        )
+    }
+
+    /// Parse the stats from the line created by `to_log_line`, other lines return None.
+    pub fn from_log_line(line: &str) -> Option<InstrumentStats> {
+        line.trim()
+            .strip_prefix(INSTRUMENT_STATS_PREFIX)
+            .and_then(|json| serde_json::from_str(json).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InstrumentStats;
+
+    #[test]
+    fn should_roundtrip_log_line() {
+        let mut stats = InstrumentStats {
+            path: "a.js".to_string(),
+            elapsed_ms: 1.5,
+            statements: 3,
+            functions: 1,
+            branches: 2,
+            input_bytes: 100,
+            ..Default::default()
+        };
+        stats.set_output_bytes(340);
+
+        let line = stats.to_log_line();
+        assert!(line.starts_with("swc-coverage-stats: {\"path\":\"a.js\",\"elapsedMs\":1.5,"));
+        assert_eq!(stats.bytes_grown, Some(240));
+        assert_eq!(InstrumentStats::from_log_line(&line), Some(stats));
+        assert_eq!(InstrumentStats::from_log_line("warning: a.js"), None);
+    }
+}
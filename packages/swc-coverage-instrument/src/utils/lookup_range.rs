@@ -2,20 +2,40 @@ use std::sync::Arc;
 
 use istanbul_oxide::Range;
 
-use swc_common::{SourceMapper, Span};
+use swc_common::{BytePos, SourceMapper, Span};
 use swc_ecmascript::ast::*;
 
+// Position lookups into the source map are done only via fns below, as the plugin's
+// source map is a proxy calling into the host which differs across plugin ABIs.
+
+/// Returns the line & column of given position.
+pub fn get_line_col<S: SourceMapper>(source_map: &Arc<S>, pos: BytePos) -> (u32, u32) {
+    let loc = source_map.lookup_char_pos(pos);
+
+    // TODO: swc_plugin::source_map::Pos to use to_u32() instead
+    (loc.line as u32, loc.col.0 as u32)
+}
+
+/// Returns the start & end position of the file given position belongs to.
+pub fn get_file_bounds<S: SourceMapper>(source_map: &Arc<S>, pos: BytePos) -> (BytePos, BytePos) {
+    let file = source_map.lookup_char_pos(pos).file;
+    (file.start_pos, file.end_pos)
+}
+
 pub fn get_range_from_span<S: SourceMapper>(source_map: &Arc<S>, span: &Span) -> Range {
-    let span_hi_loc = source_map.lookup_char_pos(span.hi);
-    let span_lo_loc = source_map.lookup_char_pos(span.lo);
+    let (start_line, start_column) = get_line_col(source_map, span.lo);
+    let (end_line, end_column) = get_line_col(source_map, span.hi);
+
+    Range::new(start_line, start_column, end_line, end_column)
+}
 
-    Range::new(
-        span_lo_loc.line as u32,
-        // TODO: swc_plugin::source_map::Pos to use to_u32() instead
-        span_lo_loc.col.0 as u32,
-        span_hi_loc.line as u32,
-        span_hi_loc.col.0 as u32,
-    )
+/// Returns the range of the declaration of a fn without its own name (anonymous fn exprs,
+/// arrow fns, methods), given the span of the node. Same as istanbul, it is the first column
+/// of the node rather than the whole node, since istanbul only takes the id of
+/// `FunctionDeclaration` / `FunctionExpression` as the declaration.
+pub fn get_anonymous_fn_decl_range<S: SourceMapper>(source_map: &Arc<S>, span: &Span) -> Range {
+    let (line, column) = get_line_col(source_map, span.lo);
+    Range::new(line, column, line, column + 1)
 }
 
 pub fn get_expr_span(expr: &Expr) -> Option<&Span> {
@@ -57,7 +77,12 @@ pub fn get_expr_span(expr: &Expr) -> Option<&Span> {
         | Expr::Await(AwaitExpr { span, .. })
         | Expr::Paren(ParenExpr { span, .. })
         | Expr::PrivateName(PrivateName { span, .. })
-        | Expr::OptChain(OptChainExpr { span, .. }) => Some(span),
+        | Expr::OptChain(OptChainExpr { span, .. })
+        | Expr::TsTypeAssertion(TsTypeAssertion { span, .. })
+        | Expr::TsConstAssertion(TsConstAssertion { span, .. })
+        | Expr::TsNonNull(TsNonNullExpr { span, .. })
+        | Expr::TsAs(TsAsExpr { span, .. })
+        | Expr::TsInstantiation(TsInstantiation { span, .. }) => Some(span),
         _ => None,
     }
 }
@@ -106,7 +131,9 @@ pub fn get_module_decl_span(decl: &ModuleDecl) -> Option<&Span> {
         | ModuleDecl::ExportNamed(NamedExport { span, .. })
         | ModuleDecl::ExportDefaultDecl(ExportDefaultDecl { span, .. })
         | ModuleDecl::ExportDefaultExpr(ExportDefaultExpr { span, .. })
-        | ModuleDecl::ExportAll(ExportAll { span, .. }) => Some(span),
-        _ => None,
+        | ModuleDecl::ExportAll(ExportAll { span, .. })
+        | ModuleDecl::TsImportEquals(TsImportEqualsDecl { span, .. })
+        | ModuleDecl::TsExportAssignment(TsExportAssignment { span, .. })
+        | ModuleDecl::TsNamespaceExport(TsNamespaceExportDecl { span, .. }) => Some(span),
     }
 }
@@ -1,3 +1,11 @@
+pub mod coverage_data_comment;
+pub mod coverage_manifest;
+pub mod coverage_registry;
+pub mod debug_snapshot;
 pub mod hint_comments;
+pub mod instrument_cache;
+pub mod instrument_stats;
 pub mod lookup_range;
 pub mod node;
+pub mod source_map_mappings;
+pub mod static_conditions;
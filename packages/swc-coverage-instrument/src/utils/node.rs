@@ -17,6 +17,7 @@ pub enum Node {
     BinExpr,
     LogicalExpr,
     CondExpr,
+    ParenExpr,
     LabeledStmt,
     FnExpr,
     FnDecl,
@@ -40,10 +41,17 @@ pub enum Node {
     ClassProp,
     ClassDecl,
     ClassMethod,
+    PrivateMethod,
     ExportDecl,
     ExportDefaultDecl,
+    ExportAll,
+    NamedExport,
+    TsExportAssignment,
+    TsImportEqualsDecl,
     BlockStmt,
     AssignPat,
+    AssignPatProp,
+    Decorator,
 }
 
 impl Display for Node {
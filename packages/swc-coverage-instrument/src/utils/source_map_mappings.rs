@@ -0,0 +1,105 @@
+/// Generated positions of the input source map's `mappings`, to look up if a position
+/// of the input maps back to the original source.
+/// Only the generated column and whether the segment has a source are decoded.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SourceMapMappings {
+    // Segments of each generated line, (generated column, has source) sorted by the column.
+    lines: Vec<Vec<(u32, bool)>>,
+}
+
+fn decode_base64(c: u8) -> Option<u32> {
+    match c {
+        b'A'..=b'Z' => Some((c - b'A') as u32),
+        b'a'..=b'z' => Some((c - b'a') as u32 + 26),
+        b'0'..=b'9' => Some((c - b'0') as u32 + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+/// Decode the base64 VLQ values of a segment.
+fn decode_segment(segment: &str) -> Vec<i64> {
+    let mut values = vec![];
+    let (mut value, mut shift) = (0i64, 0u32);
+
+    for digit in segment.bytes().filter_map(decode_base64) {
+        value += ((digit & 31) as i64) << shift;
+        if digit & 32 != 0 {
+            shift += 5;
+            continue;
+        }
+
+        values.push(if value & 1 == 1 {
+            -(value >> 1)
+        } else {
+            value >> 1
+        });
+        value = 0;
+        shift = 0;
+    }
+
+    values
+}
+
+impl SourceMapMappings {
+    pub fn new(mappings: &str) -> SourceMapMappings {
+        let lines = mappings
+            .split(';')
+            .map(|line| {
+                let mut column = 0i64;
+                let mut segments: Vec<(u32, bool)> = line
+                    .split(',')
+                    .map(decode_segment)
+                    .filter(|values| !values.is_empty())
+                    .map(|values| {
+                        // Generated column is relative to the previous segment of the line.
+                        column += values[0];
+                        (column.max(0) as u32, values.len() >= 4)
+                    })
+                    .collect();
+                segments.sort_by_key(|(column, _)| *column);
+                segments
+            })
+            .collect();
+
+        SourceMapMappings { lines }
+    }
+
+    /// Returns true if given position (1-based line, 0-based column) of the input
+    /// is in the segment maps to the original source.
+    pub fn is_mapped(&self, line: u32, column: u32) -> bool {
+        let segments = match line
+            .checked_sub(1)
+            .and_then(|idx| self.lines.get(idx as usize))
+        {
+            Some(segments) => segments,
+            None => return false,
+        };
+
+        segments
+            .iter()
+            .take_while(|(segment_column, _)| *segment_column <= column)
+            .last()
+            .is_some_and(|(_, has_source)| *has_source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SourceMapMappings;
+
+    #[test]
+    fn should_lookup_mapped_positions() {
+        // line 1: [0, source], [4, source]; line 2: [2, no source]; line 3: empty; line 4: [0, source]
+        let mappings = SourceMapMappings::new("AAAA,IAAI;EAC;;AAAA");
+
+        assert!(mappings.is_mapped(1, 0));
+        assert!(mappings.is_mapped(1, 10));
+        assert!(!mappings.is_mapped(2, 0));
+        assert!(!mappings.is_mapped(2, 5));
+        assert!(!mappings.is_mapped(3, 0));
+        assert!(mappings.is_mapped(4, 3));
+        assert!(!mappings.is_mapped(5, 0));
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+
+use swc_ecmascript::ast::*;
+
+/// Value of an expression known without running the code.
+#[derive(Clone, Debug, PartialEq)]
+enum StaticValue {
+    Undefined,
+    Null,
+    Bool(bool),
+    Num(f64),
+    Str(String),
+}
+
+impl StaticValue {
+    fn from_define(value: &serde_json::Value) -> Option<StaticValue> {
+        match value {
+            serde_json::Value::Null => Some(StaticValue::Null),
+            serde_json::Value::Bool(value) => Some(StaticValue::Bool(*value)),
+            serde_json::Value::Number(value) => value.as_f64().map(StaticValue::Num),
+            serde_json::Value::String(value) => Some(StaticValue::Str(value.clone())),
+            _ => None,
+        }
+    }
+
+    fn is_truthy(&self) -> bool {
+        match self {
+            StaticValue::Undefined | StaticValue::Null => false,
+            StaticValue::Bool(value) => *value,
+            StaticValue::Num(value) => *value != 0.0 && !value.is_nan(),
+            StaticValue::Str(value) => !value.is_empty(),
+        }
+    }
+
+    fn is_nullish(&self) -> bool {
+        matches!(self, StaticValue::Undefined | StaticValue::Null)
+    }
+
+    /// Loose equality, None if it depends on the type coercion.
+    fn loose_eq(&self, other: &StaticValue) -> Option<bool> {
+        if self.is_nullish() || other.is_nullish() {
+            return Some(self.is_nullish() && other.is_nullish());
+        }
+
+        (std::mem::discriminant(self) == std::mem::discriminant(other)).then(|| self == other)
+    }
+}
+
+/// Returns the key of the define given expr is replaced with, i.e `process.env.NODE_ENV`.
+fn get_define_key(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Ident(ident) => Some(ident.sym.to_string()),
+        Expr::Member(MemberExpr { obj, prop, .. }) => {
+            let prop = match prop {
+                MemberProp::Ident(ident) => ident.sym.to_string(),
+                MemberProp::Computed(ComputedPropName { expr, .. }) => match &**expr {
+                    Expr::Lit(Lit::Str(str)) => str.value.to_string(),
+                    _ => return None,
+                },
+                MemberProp::PrivateName(..) => return None,
+            };
+            Some(format!("{}.{}", get_define_key(obj)?, prop))
+        }
+        Expr::Paren(ParenExpr { expr, .. }) => get_define_key(expr),
+        _ => None,
+    }
+}
+
+fn eval(expr: &Expr, defines: &HashMap<String, serde_json::Value>) -> Option<StaticValue> {
+    match expr {
+        Expr::Lit(Lit::Str(str)) => Some(StaticValue::Str(str.value.to_string())),
+        Expr::Lit(Lit::Bool(value)) => Some(StaticValue::Bool(value.value)),
+        Expr::Lit(Lit::Null(..)) => Some(StaticValue::Null),
+        Expr::Lit(Lit::Num(num)) => Some(StaticValue::Num(num.value)),
+        Expr::Paren(ParenExpr { expr, .. }) => eval(expr, defines),
+        Expr::Unary(UnaryExpr {
+            op: UnaryOp::Bang,
+            arg,
+            ..
+        }) => Some(StaticValue::Bool(!eval(arg, defines)?.is_truthy())),
+        Expr::Unary(UnaryExpr {
+            op: UnaryOp::Void,
+            arg,
+            ..
+        }) if matches!(&**arg, Expr::Lit(..)) => Some(StaticValue::Undefined),
+        // `typeof window` can be defined as a key same as webpack's DefinePlugin.
+        Expr::Unary(UnaryExpr {
+            op: UnaryOp::TypeOf,
+            arg,
+            ..
+        }) => defines
+            .get(&format!("typeof {}", get_define_key(arg)?))
+            .and_then(StaticValue::from_define),
+        Expr::Bin(BinExpr {
+            op, left, right, ..
+        }) => match op {
+            BinaryOp::LogicalAnd => {
+                let left = eval(left, defines)?;
+                if left.is_truthy() {
+                    eval(right, defines)
+                } else {
+                    Some(left)
+                }
+            }
+            BinaryOp::LogicalOr => {
+                let left = eval(left, defines)?;
+                if left.is_truthy() {
+                    Some(left)
+                } else {
+                    eval(right, defines)
+                }
+            }
+            BinaryOp::NullishCoalescing => {
+                let left = eval(left, defines)?;
+                if left.is_nullish() {
+                    eval(right, defines)
+                } else {
+                    Some(left)
+                }
+            }
+            BinaryOp::EqEqEq | BinaryOp::NotEqEq | BinaryOp::EqEq | BinaryOp::NotEq => {
+                let (left, right) = (eval(left, defines)?, eval(right, defines)?);
+                let eq = match op {
+                    BinaryOp::EqEqEq | BinaryOp::NotEqEq => left == right,
+                    _ => left.loose_eq(&right)?,
+                };
+                Some(StaticValue::Bool(
+                    eq == matches!(op, BinaryOp::EqEqEq | BinaryOp::EqEq),
+                ))
+            }
+            _ => None,
+        },
+        _ => defines
+            .get(&get_define_key(expr)?)
+            .and_then(StaticValue::from_define),
+    }
+}
+
+/// Evaluates the truthiness of given condition if it's known statically, replacing
+/// the identifiers / member exprs with the values of `defines` keyed by its path
+/// (i.e `process.env.NODE_ENV`). Returns None if the condition depends on the runtime.
+pub fn eval_static_condition(
+    expr: &Expr,
+    defines: &HashMap<String, serde_json::Value>,
+) -> Option<bool> {
+    eval(expr, defines).map(|value| value.is_truthy())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use swc_common::{sync::Lrc, FileName, SourceMap};
+    use swc_ecma_parser::{parse_file_as_expr, Syntax};
+    use swc_ecmascript::ast::EsVersion;
+
+    use super::eval_static_condition;
+
+    fn eval(code: &str) -> Option<bool> {
+        let source_map: Lrc<SourceMap> = Default::default();
+        let file = source_map.new_source_file(FileName::Anon, code.to_string());
+        let expr = parse_file_as_expr(
+            &file,
+            Syntax::Es(Default::default()),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap();
+
+        let defines: HashMap<String, serde_json::Value> = serde_json::from_str(
+            r#"{ "process.env.NODE_ENV": "production", "DEBUG": false, "typeof window": "undefined" }"#,
+        )
+        .unwrap();
+        eval_static_condition(&expr, &defines)
+    }
+
+    #[test]
+    fn should_eval_static_conditions() {
+        assert_eq!(eval("false"), Some(false));
+        assert_eq!(eval("!0"), Some(true));
+        assert_eq!(eval("void 0"), Some(false));
+        assert_eq!(eval("process.env.NODE_ENV === 'production'"), Some(true));
+        assert_eq!(
+            eval("process.env['NODE_ENV'] !== \"production\""),
+            Some(false)
+        );
+        assert_eq!(eval("(DEBUG)"), Some(false));
+        assert_eq!(eval("typeof window !== 'undefined'"), Some(false));
+        assert_eq!(eval("DEBUG && someCall()"), Some(false));
+        assert_eq!(eval("null == void 0"), Some(true));
+    }
+
+    #[test]
+    fn should_not_eval_runtime_conditions() {
+        assert_eq!(eval("process.env.PORT === '80'"), None);
+        assert_eq!(eval("!DEBUG && someCall()"), None);
+        assert_eq!(eval("DEBUG == 0"), None);
+        assert_eq!(eval("typeof document === 'undefined'"), None);
+    }
+}
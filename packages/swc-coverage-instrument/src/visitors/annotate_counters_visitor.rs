@@ -0,0 +1,57 @@
+use swc_common::{
+    comments::{Comment, CommentKind, Comments},
+    Span, DUMMY_SP,
+};
+use swc_ecmascript::{
+    ast::*,
+    visit::{noop_visit_mut_type, VisitMut, VisitMutWith},
+};
+
+/// A visitor to attach `/* istanbul ignore next */` to each counter statement
+/// (`cov_xyz().s[0]++;`), so istanbul-aware tools processing the instrumented output
+/// again skip those. Counters inlined into the exprs are not annotated.
+pub struct AnnotateCountersVisitor<C: Comments> {
+    comments: C,
+    cov_fn_idents: Vec<Ident>,
+}
+
+impl<C: Comments> AnnotateCountersVisitor<C> {
+    pub fn new(comments: C, cov_fn_idents: Vec<Ident>) -> AnnotateCountersVisitor<C> {
+        AnnotateCountersVisitor {
+            comments,
+            cov_fn_idents,
+        }
+    }
+
+    fn is_counter_stmt(&self, expr_stmt: &ExprStmt) -> bool {
+        crate::parse_increase_counter_expr(&expr_stmt.expr).is_some_and(|(var_name, ..)| {
+            self.cov_fn_idents
+                .iter()
+                .any(|cov_fn_ident| cov_fn_ident.sym == var_name.sym)
+        })
+    }
+}
+
+impl<C: Comments> VisitMut for AnnotateCountersVisitor<C> {
+    noop_visit_mut_type!();
+
+    fn visit_mut_stmt(&mut self, stmt: &mut Stmt) {
+        if let Stmt::Expr(expr_stmt) = stmt {
+            if self.is_counter_stmt(expr_stmt) {
+                // Counters have no position of the input, give those a position only for the comments.
+                expr_stmt.span = Span::dummy_with_cmt();
+                self.comments.add_leading(
+                    expr_stmt.span.lo,
+                    Comment {
+                        kind: CommentKind::Block,
+                        span: DUMMY_SP,
+                        text: " istanbul ignore next ".into(),
+                    },
+                );
+                return;
+            }
+        }
+
+        stmt.visit_mut_children_with(self);
+    }
+}
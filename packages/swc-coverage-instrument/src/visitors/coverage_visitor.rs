@@ -1,16 +1,38 @@
-use swc_common::{comments::Comments, util::take::Take, SourceMapper, DUMMY_SP};
+use std::collections::HashSet;
+
+use swc_common::{
+    comments::Comments, errors::HANDLER, util::take::Take, BytePos, EqIgnoreSpan, SourceMapper,
+    Span, Spanned, DUMMY_SP,
+};
 use swc_ecmascript::{
     ast::*,
     visit::{noop_visit_mut_type, VisitMut, VisitMutWith, VisitWith},
 };
-use tracing::instrument;
 
 use crate::{
-    create_instrumentation_visitor, instrumentation_counter_helper,
-    instrumentation_stmt_counter_helper, instrumentation_visitor, InstrumentOptions,
+    visitors::{
+        annotate_counters_visitor::AnnotateCountersVisitor,
+        finders::{find_exported_fns, DeadCodeFinder, IdentFinder},
+        global_hit_counters_visitor::GlobalHitCountersVisitor,
+        hoist_counters_visitor::HoistCountersVisitor,
+        instrumentation_visitor::InstrumentationVisitor,
+        multiplex_counters_visitor::MultiplexCountersVisitor,
+        on_cover_visitor::OnCoverVisitor,
+        skip_counters_visitor::SkipCountersVisitor,
+        strip_counters_visitor::StripCountersVisitor,
+    },
+    InstrumentOptions,
 };
 
-create_instrumentation_visitor!(CoverageVisitor { file_path: String });
+/// A visitor instruments the file: resolves the file level states, lets `InstrumentationVisitor`
+/// inject the counters then post-processes the counters by the options, prepends the coverage templates.
+pub struct CoverageVisitor<C: Clone + Comments, S: SourceMapper> {
+    pub(crate) instrumentation: InstrumentationVisitor<C, S>,
+    file_path: String,
+    // Regions of the file excluded by `coverage-off` comments.
+    ignore_regions: Vec<crate::Range>,
+    stats: Option<crate::InstrumentStats>,
+}
 
 /// Public interface to create a visitor performs transform to inject
 /// coverage instrumentation counter.
@@ -20,36 +42,395 @@ pub fn create_coverage_instrumentation_visitor<C: Clone + Comments, S: SourceMap
     instrument_options: InstrumentOptions,
     filename: String,
 ) -> CoverageVisitor<C, S> {
+    let filename =
+        istanbul_oxide::canonicalize_path(&filename, &instrument_options.canonicalize_paths);
+
     // create a function name ident for the injected coverage instrumentation counters.
-    crate::create_coverage_fn_ident(&filename);
+    let cov_fn_ident = crate::create_coverage_fn_ident(&filename);
+    let cov_fn_temp_ident = crate::create_coverage_fn_temp_ident(&cov_fn_ident);
 
     let mut cov = crate::SourceCoverage::new(filename.to_string(), instrument_options.report_logic);
     cov.set_input_source_map(&instrument_options.input_source_map);
+    if instrument_options.dedupe_statements {
+        cov.set_dedupe_statements();
+    }
+    if let Some(offset) = instrument_options.get_offset() {
+        cov.set_offset(offset);
+    }
 
-    CoverageVisitor::new(
-        source_map,
-        comments.clone(),
-        std::rc::Rc::new(std::cell::RefCell::new(cov)),
-        instrument_options,
-        vec![],
-        None,
-        filename,
-    )
+    CoverageVisitor {
+        instrumentation: InstrumentationVisitor::new(
+            source_map,
+            comments,
+            std::rc::Rc::new(std::cell::RefCell::new(cov)),
+            cov_fn_ident,
+            cov_fn_temp_ident,
+            instrument_options,
+        ),
+        file_path: filename,
+        ignore_regions: vec![],
+        stats: None,
+    }
 }
 
 impl<C: Clone + Comments, S: SourceMapper> CoverageVisitor<C, S> {
-    instrumentation_counter_helper!();
-    instrumentation_stmt_counter_helper!();
-
     /// Not implemented.
     /// TODO: is this required?
     fn is_instrumented_already(&self) -> bool {
-        return false;
+        false
+    }
+
+    /// Salt the coverage fn ident if it collides with any of the identifiers in the file,
+    /// otherwise references to the coverage fn can be shadowed by the user code.
+    fn resolve_cov_fn_ident_collision(&mut self, idents: &HashSet<String>) {
+        if crate::is_coverage_fn_name_taken(&self.instrumentation.cov_fn_ident.sym, idents) {
+            self.instrumentation.cov_fn_ident =
+                crate::create_unique_coverage_fn_ident(&self.file_path, idents);
+            self.instrumentation.cov_fn_temp_ident =
+                crate::create_coverage_fn_temp_ident(&self.instrumentation.cov_fn_ident);
+        }
+    }
+
+    /// Coverage fns the counters of the instrumented code reference, the fn of each module
+    /// if the input is a concatenation of modules, otherwise the fn of the input file.
+    fn cov_fn_idents(&self) -> Vec<Ident> {
+        if self.instrumentation.modules.is_empty() {
+            vec![self.instrumentation.cov_fn_ident.clone()]
+        } else {
+            self.instrumentation
+                .modules
+                .iter()
+                .map(|module| module.cov_fn_ident.clone())
+                .collect()
+        }
+    }
+
+    /// Whether the file is not in `changedFiles`, to be left as-is or stubbed.
+    fn is_unchanged_file(&self) -> bool {
+        !self
+            .instrumentation
+            .instrument_options
+            .is_changed_file(&self.file_path)
+    }
+
+    /// Remove the counters of the instrumented code and mark the coverage as `all`
+    /// if the file is stubbed by `stubUnchangedFiles`.
+    fn stub_unchanged_file<N: VisitMutWith<StripCountersVisitor>>(&self, node: &mut N) {
+        if !self.is_unchanged_file() {
+            return;
+        }
+
+        node.visit_mut_with(&mut StripCountersVisitor::new(self.cov_fn_idents()));
+        self.instrumentation.cov.borrow_mut().set_all();
+        for module in self.instrumentation.modules.iter() {
+            module.cov.borrow_mut().set_all();
+        }
+    }
+
+    /// Stats of the file if `instrumentLog.stats` is set, available once the file is visited.
+    pub fn stats(&self) -> Option<&crate::InstrumentStats> {
+        self.stats.as_ref()
+    }
+
+    /// Start measuring the stats of the file if `instrumentLog.stats` is set.
+    fn start_stats(&self) -> Option<std::time::Instant> {
+        self.instrumentation
+            .instrument_options
+            .instrument_log
+            .stats
+            .then(std::time::Instant::now)
+    }
+
+    /// Record the stats of the file instrumented since `started`, using given span
+    /// to look up the size of the input file.
+    fn record_stats(&mut self, started: Option<std::time::Instant>, span: Option<Span>) {
+        let started = match started {
+            Some(started) => started,
+            None => return,
+        };
+
+        let mut stats = crate::InstrumentStats {
+            path: self.file_path.clone(),
+            elapsed_ms: started.elapsed().as_secs_f64() * 1000.0,
+            ..Default::default()
+        };
+        let coverages = if self.instrumentation.modules.is_empty() {
+            vec![self.instrumentation.cov.clone()]
+        } else {
+            self.instrumentation
+                .modules
+                .iter()
+                .map(|module| module.cov.clone())
+                .collect()
+        };
+        for cov in coverages {
+            let cov = cov.borrow();
+            let cov = cov.as_ref();
+            stats.statements += cov.statement_map.len();
+            stats.functions += cov.fn_map.len();
+            stats.branches += cov.branch_map.len();
+        }
+        if let Some(span) = span.filter(|span| !span.is_dummy()) {
+            let (file_start, file_end) =
+                crate::lookup_range::get_file_bounds(&self.instrumentation.source_map, span.lo);
+            stats.input_bytes = (file_end - file_start).0 as u64;
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            path = %stats.path,
+            elapsed_ms = stats.elapsed_ms,
+            statements = stats.statements,
+            functions = stats.functions,
+            branches = stats.branches,
+            input_bytes = stats.input_bytes,
+            "instrumented"
+        );
+
+        self.stats = Some(stats);
+    }
+
+    /// Dump the AST of the file at the stage if the file matches `debug.files`.
+    /// Code snapshots are left to the host emitting the code.
+    fn dump_debug_snapshot<N: std::fmt::Debug>(&self, stage: &str, node: &N) {
+        if let Some(debug) = self
+            .instrumentation
+            .instrument_options
+            .debug
+            .as_ref()
+            .filter(|debug| {
+                debug.emit == crate::DebugEmit::Ast && debug.should_dump(&self.file_path)
+            })
+        {
+            crate::write_debug_snapshot(
+                debug,
+                &self.file_path,
+                stage,
+                "ast",
+                &format!("{:#?}", node),
+            );
+        }
+    }
+
+    /// Find the regions excluded by `coverage-off` comments in the input file, using given span
+    /// to look up the input file.
+    fn resolve_ignore_regions(&mut self, span: Option<Span>) {
+        if let Some(span) = span.filter(|span| !span.is_dummy()) {
+            self.ignore_regions =
+                crate::hint_comments::find_ignore_regions(&self.instrumentation.source_map, &span);
+        }
+    }
+
+    /// Find the code behind statically false conditions if `skipDeadCode` is set, to be
+    /// skipped same as the regions of `coverage-off` comments.
+    fn resolve_dead_code<N: for<'a> VisitWith<DeadCodeFinder<'a>>>(&mut self, node: &N) {
+        if !self.instrumentation.instrument_options.skip_dead_code {
+            return;
+        }
+
+        let mut finder = DeadCodeFinder::new(&self.instrumentation.instrument_options.defines);
+        node.visit_with(&mut finder);
+
+        let dead_code_regions: Vec<_> = finder
+            .spans
+            .iter()
+            .filter(|span| !span.is_dummy())
+            .map(|span| crate::Range {
+                skip: true,
+                ..crate::lookup_range::get_range_from_span(&self.instrumentation.source_map, span)
+            })
+            .collect();
+        self.ignore_regions.extend(dead_code_regions);
+    }
+
+    /// Resolve the body ranges of the fns exported from the module for `fnExportNames`
+    /// and `exported` of `fnMetadata`.
+    fn resolve_export_names(&self, items: &[ModuleItem]) -> Vec<(crate::Range, String)> {
+        if !self.instrumentation.instrument_options.fn_export_names
+            && !self.instrumentation.instrument_options.fn_metadata
+        {
+            return vec![];
+        }
+
+        find_exported_fns(items)
+            .into_iter()
+            .filter(|(span, _)| !span.is_dummy())
+            .map(|(span, name)| {
+                (
+                    crate::lookup_range::get_range_from_span(
+                        &self.instrumentation.source_map,
+                        &span,
+                    ),
+                    name,
+                )
+            })
+            .collect()
+    }
+
+    /// Resolve `moduleBoundaries` into the coverage of each module, using given span
+    /// to look up the position of the input file.
+    fn resolve_module_boundaries(&mut self, span: Option<Span>, idents: &HashSet<String>) {
+        if self
+            .instrumentation
+            .instrument_options
+            .module_boundaries
+            .is_empty()
+        {
+            return;
+        }
+
+        let span = match span {
+            Some(span) if !span.is_dummy() => span,
+            _ => return,
+        };
+        let (file_start, file_end) =
+            crate::lookup_range::get_file_bounds(&self.instrumentation.source_map, span.lo);
+        let file_len = (file_end - file_start).0;
+
+        // Code before the first module is covered as the input file itself.
+        let mut modules = vec![crate::ModuleCoverage {
+            start: file_start,
+            file_path: self.file_path.clone(),
+            cov: self.instrumentation.cov.clone(),
+            cov_fn_ident: self.instrumentation.cov_fn_ident.clone(),
+        }];
+
+        let mut boundaries = self
+            .instrumentation
+            .instrument_options
+            .module_boundaries
+            .clone();
+        boundaries.sort_by_key(|boundary| boundary.offset);
+
+        for boundary in boundaries {
+            let name = istanbul_oxide::canonicalize_path(
+                &boundary.name,
+                &self.instrumentation.instrument_options.canonicalize_paths,
+            );
+            let start = file_start + BytePos(boundary.offset.min(file_len));
+            let (line, column) =
+                crate::lookup_range::get_line_col(&self.instrumentation.source_map, start);
+
+            let mut cov = crate::SourceCoverage::new(
+                name.clone(),
+                self.instrumentation.instrument_options.report_logic,
+            );
+            cov.set_origin(istanbul_oxide::Location { line, column });
+            if self.instrumentation.instrument_options.dedupe_statements {
+                cov.set_dedupe_statements();
+            }
+
+            modules.push(crate::ModuleCoverage {
+                start,
+                cov_fn_ident: crate::create_unique_coverage_fn_ident(&name, idents),
+                file_path: name,
+                cov: std::rc::Rc::new(std::cell::RefCell::new(cov)),
+            });
+        }
+
+        self.instrumentation.modules = std::rc::Rc::new(modules);
+    }
+
+    /// Create coverage templates for the input file, or each module if the input is
+    /// a concatenation of modules, followed by the statements to initialize coverage.
+    fn create_coverage_templates<N: VisitMutWith<HoistCountersVisitor>>(
+        &mut self,
+        node: &mut N,
+    ) -> Vec<Stmt> {
+        let coverages = if self.instrumentation.modules.is_empty() {
+            vec![(
+                self.instrumentation.cov.clone(),
+                self.instrumentation.cov_fn_ident.clone(),
+                self.file_path.clone(),
+            )]
+        } else {
+            self.instrumentation
+                .modules
+                .iter()
+                .filter(|module| {
+                    // Skip the input file if there's no code before the first module.
+                    let cov = module.cov.borrow();
+                    let cov = cov.as_ref();
+                    !std::rc::Rc::ptr_eq(&module.cov, &self.instrumentation.cov)
+                        || !cov.statement_map.is_empty()
+                        || !cov.fn_map.is_empty()
+                        || !cov.branch_map.is_empty()
+                })
+                .map(|module| {
+                    (
+                        module.cov.clone(),
+                        module.cov_fn_ident.clone(),
+                        module.file_path.clone(),
+                    )
+                })
+                .collect()
+        };
+
+        let mut templates = vec![];
+        let mut init_stmts = vec![];
+        for (cov, cov_fn_ident, file_path) in coverages {
+            let coverage_variables = std::iter::once((
+                self.instrumentation
+                    .instrument_options
+                    .coverage_variable
+                    .as_str(),
+                cov_fn_ident.clone(),
+                true,
+            ))
+            .chain(
+                self.instrumentation
+                    .instrument_options
+                    .additional_coverage_variables
+                    .iter()
+                    .enumerate()
+                    .map(|(index, coverage_variable)| {
+                        (
+                            coverage_variable.as_str(),
+                            crate::create_multiplexed_coverage_fn_ident(&cov_fn_ident, index),
+                            false,
+                        )
+                    }),
+            );
+
+            for (coverage_variable, cov_fn_ident, emit_comments) in coverage_variables {
+                let (coverage_template, call_coverage_template_stmt) = self.get_coverage_template(
+                    &cov,
+                    &cov_fn_ident,
+                    &file_path,
+                    coverage_variable,
+                    emit_comments,
+                );
+
+                templates.push(coverage_template);
+                init_stmts.extend(self.hoist_counters(
+                    node,
+                    &cov_fn_ident,
+                    call_coverage_template_stmt,
+                ));
+            }
+        }
+
+        templates.extend(init_stmts);
+        templates
     }
 
     /// Create coverage instrumentation template exprs to be injected into the top of the transformed output.
-    fn get_coverage_templates(&mut self) -> (Stmt, Stmt) {
-        self.cov.borrow_mut().freeze();
+    /// Call statement to initialize coverage is omitted if `lazyInit` is set.
+    /// Comments of the coverage data are attached only once, for the `coverageVariable`.
+    fn get_coverage_template(
+        &self,
+        cov: &std::cell::RefCell<crate::SourceCoverage>,
+        cov_fn_ident: &Ident,
+        file_path: &str,
+        coverage_variable: &str,
+        emit_comments: bool,
+    ) -> (Stmt, Option<Stmt>) {
+        cov.borrow_mut().freeze();
+        if self.instrumentation.instrument_options.freeze_coverage {
+            let integrity = cov.borrow().as_ref().compute_integrity();
+            cov.borrow_mut()
+                .set_metadata(istanbul_oxide::INTEGRITY_KEY, integrity.into());
+        }
 
         //TODO: option: global coverage variable scope. (optional, default `this`)
         let coverage_global_scope = "this";
@@ -81,36 +462,374 @@ impl<C: Clone + Comments, S: SourceMapper> CoverageVisitor<C, S> {
             */
         };
 
+        let register_ident = self
+            .instrumentation
+            .instrument_options
+            .registry_module
+            .as_ref()
+            .map(|_| crate::create_register_ident(&self.instrumentation.cov_fn_ident));
+
         let coverage_template = crate::create_coverage_fn_decl(
-            &self.instrument_options.coverage_variable,
+            coverage_variable,
             gv_template,
-            &self.cov_fn_ident,
-            &self.file_path,
-            self.cov.borrow().as_ref(),
-            &self.comments,
-            self.instrument_options.debug_initial_coverage_comment,
+            cov_fn_ident,
+            file_path,
+            cov.borrow().as_ref(),
+            &self.instrumentation.comments,
+            &crate::CoverageFnDeclOptions {
+                register_ident: register_ident.as_ref(),
+                attach_debug_comment: emit_comments
+                    && self
+                        .instrumentation
+                        .instrument_options
+                        .emit_coverage_comment,
+                attach_manifest_comment: emit_comments
+                    && self.instrumentation.instrument_options.emit_manifest,
+                realm_isolation: self.instrumentation.instrument_options.realm_isolation,
+                non_enumerable: self
+                    .instrumentation
+                    .instrument_options
+                    .non_enumerable_coverage_variable,
+                ts_ignore: self.instrumentation.instrument_options.preserve_types,
+                global_hit_fn: self
+                    .instrumentation
+                    .instrument_options
+                    .global_hit_fn
+                    .as_deref(),
+                freeze: self.instrumentation.instrument_options.freeze_coverage,
+            },
         );
 
+        // Stubbed file has no counters to initialize coverage on the first hit. Calls to the
+        // global fn don't initialize coverage, it has to be registered upfront.
+        if self.instrumentation.instrument_options.lazy_init
+            && self
+                .instrumentation
+                .instrument_options
+                .global_hit_fn
+                .is_none()
+            && !self.is_unchanged_file()
+        {
+            return (coverage_template, None);
+        }
+
         // explicitly call this.varName to ensure coverage is always initialized
         let call_coverage_template_stmt = Stmt::Expr(ExprStmt {
             span: DUMMY_SP,
             expr: Box::new(Expr::Call(CallExpr {
-                callee: Callee::Expr(Box::new(Expr::Ident(self.cov_fn_ident.clone()))),
+                callee: Callee::Expr(Box::new(Expr::Ident(cov_fn_ident.clone()))),
                 ..CallExpr::dummy()
             })),
         });
 
-        (coverage_template, call_coverage_template_stmt)
+        (coverage_template, Some(call_coverage_template_stmt))
+    }
+
+    /// Mark entries in the regions of `coverage-off` comments as skipped, and remove their counters.
+    fn skip_ignore_regions<N: VisitMutWith<SkipCountersVisitor>>(&self, node: &mut N) {
+        if self.ignore_regions.is_empty() {
+            return;
+        }
+
+        let coverages = if self.instrumentation.modules.is_empty() {
+            vec![(
+                self.instrumentation.cov.clone(),
+                self.instrumentation.cov_fn_ident.clone(),
+            )]
+        } else {
+            self.instrumentation
+                .modules
+                .iter()
+                .map(|module| (module.cov.clone(), module.cov_fn_ident.clone()))
+                .collect()
+        };
+
+        let skipped: Vec<_> = coverages
+            .into_iter()
+            .map(|(cov, cov_fn_ident)| {
+                (
+                    cov_fn_ident,
+                    cov.borrow_mut().skip_ranges(&self.ignore_regions),
+                )
+            })
+            .filter(|(_, skipped)| !skipped.is_empty())
+            .collect();
+
+        if !skipped.is_empty() {
+            node.visit_mut_with(&mut SkipCountersVisitor::new(skipped));
+        }
+    }
+
+    /// Strip counters from the instrumented items and compare them with the original items
+    /// if `selfCheck` is set. Reports an error with the location of the first altered item
+    /// to the host through [HANDLER].
+    fn self_check<T>(&self, original: Option<Vec<T>>, instrumented: &[T])
+    where
+        T: Clone + EqIgnoreSpan + Spanned,
+        Vec<T>: VisitMutWith<StripCountersVisitor>,
+    {
+        let original = match original {
+            Some(original) => original,
+            None => return,
+        };
+
+        let mut stripped = instrumented.to_vec();
+        stripped.visit_mut_with(&mut StripCountersVisitor::new(self.cov_fn_idents()));
+
+        let altered = original
+            .iter()
+            .zip(stripped.iter())
+            .position(|(original, stripped)| !original.eq_ignore_span(stripped))
+            .or_else(|| {
+                (original.len() != stripped.len()).then(|| original.len().min(stripped.len()))
+            });
+
+        if let Some(index) = altered {
+            let span = original
+                .get(index)
+                .or_else(|| original.last())
+                .map(|item| item.span())
+                .filter(|span| !span.is_dummy());
+            let location = span
+                .map(|span| {
+                    let (line, column) = crate::lookup_range::get_line_col(
+                        &self.instrumentation.source_map,
+                        span.lo,
+                    );
+                    format!("{}:{}", line, column)
+                })
+                .unwrap_or_else(|| "unknown location".to_string());
+
+            HANDLER.with(|handler| {
+                handler
+                    .struct_span_err(
+                        span.unwrap_or(DUMMY_SP),
+                        &format!(
+                            "selfCheck failed: instrumentation altered the code of {} other than inserting counters, at {}",
+                            self.file_path, location
+                        ),
+                    )
+                    .emit()
+            });
+        }
+    }
+
+    /// Attach `/* istanbul ignore next */` to the counter statements if `annotateCounters` is set.
+    fn annotate_counters<N: VisitMutWith<AnnotateCountersVisitor<C>>>(&self, node: &mut N) {
+        if !self.instrumentation.instrument_options.annotate_counters {
+            return;
+        }
+
+        node.visit_mut_with(&mut AnnotateCountersVisitor::new(
+            self.instrumentation.comments.clone(),
+            self.cov_fn_idents(),
+        ));
+    }
+
+    /// Make counters in the instrumented code increase the counters of the coverage fns for
+    /// `additionalCoverageVariables` as well, in the same expression.
+    fn multiplex_counters<N: VisitMutWith<MultiplexCountersVisitor>>(&self, node: &mut N) {
+        let count = self
+            .instrumentation
+            .instrument_options
+            .additional_coverage_variables
+            .len();
+        if count == 0 {
+            return;
+        }
+
+        let coverages = self
+            .cov_fn_idents()
+            .into_iter()
+            .map(|cov_fn_ident| {
+                let multiplexed = (0..count)
+                    .map(|index| crate::create_multiplexed_coverage_fn_ident(&cov_fn_ident, index))
+                    .collect();
+                (cov_fn_ident, multiplexed)
+            })
+            .collect();
+
+        node.visit_mut_with(&mut MultiplexCountersVisitor::new(coverages));
+    }
+
+    /// Replace counters in the instrumented code with the calls to the global fn of
+    /// `globalHitFn` if it's set, including the counters of `additionalCoverageVariables`.
+    fn global_hit_counters<N: VisitMutWith<GlobalHitCountersVisitor>>(&self, node: &mut N) {
+        let hit_fn_name = match &self.instrumentation.instrument_options.global_hit_fn {
+            Some(hit_fn_name) => hit_fn_name,
+            None => return,
+        };
+
+        let count = self
+            .instrumentation
+            .instrument_options
+            .additional_coverage_variables
+            .len();
+        let cov_fn_idents = self
+            .cov_fn_idents()
+            .into_iter()
+            .flat_map(|cov_fn_ident| {
+                let multiplexed: Vec<_> = (0..count)
+                    .map(|index| crate::create_multiplexed_coverage_fn_ident(&cov_fn_ident, index))
+                    .collect();
+                std::iter::once(cov_fn_ident).chain(multiplexed)
+            })
+            .collect();
+
+        node.visit_mut_with(&mut GlobalHitCountersVisitor::new(
+            hit_fn_name,
+            cov_fn_idents,
+        ));
+    }
+
+    /// Make counters in the instrumented code call the fn of `onCover` as well if it's set.
+    /// Returns the local ident of the fn to be imported, if any of the counters reference it.
+    fn on_cover<N: VisitMutWith<OnCoverVisitor>>(&self, node: &mut N) -> Option<Ident> {
+        self.instrumentation.instrument_options.on_cover.as_ref()?;
+
+        let coverages = if self.instrumentation.modules.is_empty() {
+            vec![(
+                self.instrumentation.cov_fn_ident.clone(),
+                self.file_path.clone(),
+            )]
+        } else {
+            self.instrumentation
+                .modules
+                .iter()
+                .map(|module| (module.cov_fn_ident.clone(), module.file_path.clone()))
+                .collect()
+        };
+
+        let on_cover_ident = crate::create_on_cover_ident(&self.instrumentation.cov_fn_ident);
+        let mut visitor = OnCoverVisitor::new(&on_cover_ident, coverages);
+        node.visit_mut_with(&mut visitor);
+
+        if visitor.referenced {
+            Some(on_cover_ident)
+        } else {
+            None
+        }
+    }
+
+    /// Local ident of the `register` fn and the module to import it from, if `registryModule` is set.
+    /// Imported once per file, shared by the coverage fns of the modules and the coverage variables.
+    fn get_registry_import(&self) -> Option<(Ident, &str)> {
+        self.instrumentation
+            .instrument_options
+            .registry_module
+            .as_deref()
+            .map(|registry_module| {
+                (
+                    crate::create_register_ident(&self.instrumentation.cov_fn_ident),
+                    registry_module,
+                )
+            })
     }
+
+    /// Replace counter lookups in the instrumented code into the hoisted references
+    /// if `hoistCounters` is set. Returns the declaration of the references to be used
+    /// instead of the call statement to initialize coverage.
+    /// Counters are not hoisted with `lazyInit`, as hoisted references initialize coverage eagerly.
+    fn hoist_counters<N: VisitMutWith<HoistCountersVisitor>>(
+        &self,
+        node: &mut N,
+        cov_fn_ident: &Ident,
+        call_coverage_template_stmt: Option<Stmt>,
+    ) -> Option<Stmt> {
+        let call_coverage_template_stmt = match call_coverage_template_stmt {
+            Some(stmt)
+                if self.instrumentation.instrument_options.hoist_counters
+                    && self
+                        .instrumentation
+                        .instrument_options
+                        .global_hit_fn
+                        .is_none() =>
+            {
+                stmt
+            }
+            _ => return call_coverage_template_stmt,
+        };
+
+        let mut visitor = HoistCountersVisitor::new(cov_fn_ident);
+        node.visit_mut_with(&mut visitor);
+        Some(
+            visitor
+                .create_hoisted_decl()
+                .unwrap_or(call_coverage_template_stmt),
+        )
+    }
+}
+
+/// Whether the stmt is a directive (`"use strict"`, `"use client"`), a string literal statement.
+/// Leading directives of the file are its prologue, which has to stay at the top of the file.
+fn is_directive(stmt: &Stmt) -> bool {
+    matches!(stmt, Stmt::Expr(ExprStmt { expr, .. }) if matches!(&**expr, Expr::Lit(Lit::Str(..))))
+}
+
+/// Anchors the first coverage fn declaration to the span of a file without any code.
+/// Hosts preserving all comments move comments of generated nodes after the last node with a known span,
+/// which drops the coverage data comments if the file has no such node.
+fn anchor_coverage_fn_decl<'a>(mut stmts: impl Iterator<Item = &'a mut Stmt>, span: Span) {
+    if let Some(function) = stmts.find_map(|stmt| match stmt {
+        Stmt::Decl(Decl::Fn(FnDecl { function, .. })) => Some(function),
+        _ => None,
+    }) {
+        function.span = span;
+    }
+}
+
+/// Creates `import { $imported as $local } from "$src";`.
+fn create_import(local: Ident, imported: &str, src: &str) -> ModuleItem {
+    ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+        span: DUMMY_SP,
+        specifiers: vec![ImportSpecifier::Named(ImportNamedSpecifier {
+            span: DUMMY_SP,
+            local,
+            imported: Some(ModuleExportName::Ident(Ident::new(
+                imported.into(),
+                DUMMY_SP,
+            ))),
+            is_type_only: false,
+        })],
+        src: Str {
+            value: src.into(),
+            ..Str::dummy()
+        },
+        type_only: false,
+        asserts: None,
+    }))
+}
+
+/// Creates `var $local = require("$src").$imported;`.
+fn create_require(local: &Ident, imported: &str, src: &str) -> Stmt {
+    let require_expr = Expr::Member(MemberExpr {
+        obj: Box::new(Expr::Call(CallExpr {
+            callee: Callee::Expr(Box::new(Expr::Ident(Ident::new(
+                "require".into(),
+                DUMMY_SP,
+            )))),
+            args: vec![ExprOrSpread {
+                spread: None,
+                expr: Box::new(Expr::Lit(Lit::Str(Str {
+                    value: src.into(),
+                    ..Str::dummy()
+                }))),
+            }],
+            ..CallExpr::dummy()
+        })),
+        prop: MemberProp::Ident(Ident::new(imported.into(), DUMMY_SP)),
+        ..MemberExpr::dummy()
+    });
+    crate::create_assignment_stmt(local, require_expr)
 }
 
 impl<C: Clone + Comments, S: SourceMapper> VisitMut for CoverageVisitor<C, S> {
-    instrumentation_visitor!();
+    noop_visit_mut_type!();
 
-    #[instrument(skip_all, fields(node = %self.print_node()))]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.instrumentation.print_node())))]
     fn visit_mut_program(&mut self, program: &mut Program) {
-        self.nodes.push(crate::Node::Program);
-        if crate::hint_comments::should_ignore_file(&self.comments, program) {
+        self.instrumentation.nodes.push(crate::Node::Program);
+        if crate::hint_comments::should_ignore_file(&self.instrumentation.comments, program) {
             return;
         }
 
@@ -119,16 +838,32 @@ impl<C: Clone + Comments, S: SourceMapper> VisitMut for CoverageVisitor<C, S> {
         }
 
         program.visit_mut_children_with(self);
-        self.nodes.pop();
+        self.instrumentation.nodes.pop();
     }
 
-    #[instrument(skip_all, fields(node = %self.print_node()))]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.instrumentation.print_node())))]
+    fn visit_mut_module(&mut self, module: &mut Module) {
+        let is_empty = module.body.is_empty();
+        module.visit_mut_children_with(self);
+
+        if is_empty {
+            anchor_coverage_fn_decl(
+                module.body.iter_mut().filter_map(|item| match item {
+                    ModuleItem::Stmt(stmt) => Some(stmt),
+                    _ => None,
+                }),
+                module.span,
+            );
+        }
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.instrumentation.print_node())))]
     fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
         if self.is_instrumented_already() {
             return;
         }
 
-        let root_exists = match self.nodes.get(0) {
+        let root_exists = match self.instrumentation.nodes.first() {
             Some(node) => node == &crate::Node::Program,
             _ => false,
         };
@@ -138,142 +873,503 @@ impl<C: Clone + Comments, S: SourceMapper> VisitMut for CoverageVisitor<C, S> {
         // instead of manually injecting node here
         if !root_exists {
             let mut new_nodes = vec![crate::Node::Program];
-            new_nodes.extend(self.nodes.drain(..));
-            self.nodes = new_nodes;
+            new_nodes.append(&mut self.instrumentation.nodes);
+            self.instrumentation.nodes = new_nodes;
         }
 
-        // TODO: Should module_items need to be added in self.nodes?
-        let mut new_items = vec![];
-        for mut item in items.drain(..) {
-            let (old, _ignore_current) = match &mut item {
-                ModuleItem::ModuleDecl(decl) => self.on_enter(decl),
-                ModuleItem::Stmt(stmt) => self.on_enter(stmt),
-            };
-            item.visit_mut_children_with(self);
-
-            new_items.extend(self.before.drain(..).map(|v| ModuleItem::Stmt(v)));
-            new_items.push(item);
-            self.on_exit(old);
+        if self.is_unchanged_file() && !self.instrumentation.instrument_options.stub_unchanged_files
+        {
+            if !root_exists {
+                self.instrumentation.nodes.pop();
+            }
+            return;
+        }
+
+        let file_span = items.iter().map(|item| item.span()).next();
+        self.dump_debug_snapshot("before", items);
+        let started = self.start_stats();
+
+        // Module items can be nested (i.e ts namespace), resolve idents & modules
+        // once for the whole file.
+        let mut finder = IdentFinder::default();
+        items.visit_with(&mut finder);
+
+        let span = items.iter().find_map(|item| match item {
+            ModuleItem::ModuleDecl(decl) => {
+                crate::lookup_range::get_module_decl_span(decl).copied()
+            }
+            ModuleItem::Stmt(stmt) => crate::lookup_range::get_stmt_span(stmt).copied(),
+        });
+
+        self.resolve_cov_fn_ident_collision(&finder.0);
+        self.resolve_module_boundaries(span, &finder.0);
+        self.resolve_ignore_regions(file_span);
+        self.resolve_dead_code(items);
+
+        let original = self
+            .instrumentation
+            .instrument_options
+            .self_check
+            .then(|| items.clone());
+        let export_names = self.resolve_export_names(items);
+
+        // Directives of the prologue are not statements, those are kept as-is at the top.
+        let prologue_len = items
+            .iter()
+            .take_while(|item| matches!(item, ModuleItem::Stmt(stmt) if is_directive(stmt)))
+            .count();
+
+        let mut body = items.split_off(prologue_len);
+        body.visit_mut_with(&mut self.instrumentation);
+        items.extend(body);
+
+        if !export_names.is_empty() {
+            let record_names = self.instrumentation.instrument_options.fn_export_names;
+            self.instrumentation
+                .cov
+                .borrow_mut()
+                .set_export_names(&export_names, record_names);
+            for module in self.instrumentation.modules.iter() {
+                module
+                    .cov
+                    .borrow_mut()
+                    .set_export_names(&export_names, record_names);
+            }
         }
-        *items = new_items;
 
-        let (coverage_template, call_coverage_template_stmt) = self.get_coverage_templates();
+        self.self_check(original, items);
+        self.stub_unchanged_file(items);
+        self.skip_ignore_regions(items);
+        self.annotate_counters(items);
+        self.multiplex_counters(items);
+        let on_cover_ident = self.on_cover(items);
+        self.global_hit_counters(items);
+
+        let templates = self.create_coverage_templates(items);
+
+        // prepend template to the top of the code, after the prologue
+        items.splice(
+            prologue_len..prologue_len,
+            templates.into_iter().map(ModuleItem::Stmt),
+        );
+
+        // import { $export_name as $on_cover } from "$module";
+        if let (Some(on_cover_ident), Some(on_cover)) = (
+            on_cover_ident,
+            &self.instrumentation.instrument_options.on_cover,
+        ) {
+            items.insert(
+                prologue_len,
+                create_import(on_cover_ident, &on_cover.export_name, &on_cover.module),
+            );
+        }
 
-        // prepend template to the top of the code
-        items.insert(0, ModuleItem::Stmt(coverage_template));
-        items.insert(1, ModuleItem::Stmt(call_coverage_template_stmt));
+        // import { register as $register } from "$registry_module";
+        if let Some((register_ident, registry_module)) = self.get_registry_import() {
+            items.insert(
+                prologue_len,
+                create_import(
+                    register_ident,
+                    crate::COVERAGE_REGISTRY_EXPORT,
+                    registry_module,
+                ),
+            );
+        }
+
+        self.record_stats(started, file_span);
+        self.dump_debug_snapshot("after", items);
 
         if !root_exists {
-            self.nodes.pop();
+            self.instrumentation.nodes.pop();
         }
     }
 
-    #[instrument(skip_all, fields(node = %self.print_node()))]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.instrumentation.print_node())))]
     fn visit_mut_script(&mut self, items: &mut Script) {
         if self.is_instrumented_already() {
             return;
         }
 
+        if self.is_unchanged_file() && !self.instrumentation.instrument_options.stub_unchanged_files
+        {
+            return;
+        }
+
+        self.dump_debug_snapshot("before", &items.body);
+        let started = self.start_stats();
+
+        let mut finder = IdentFinder::default();
+        items.visit_with(&mut finder);
+
+        self.resolve_cov_fn_ident_collision(&finder.0);
+        self.resolve_module_boundaries(Some(items.span), &finder.0);
+        self.resolve_ignore_regions(Some(items.span));
+        self.resolve_dead_code(&items.body);
+
+        let original = self
+            .instrumentation
+            .instrument_options
+            .self_check
+            .then(|| items.body.clone());
+
+        // Directives of the prologue are not statements, those are kept as-is at the top.
+        let prologue_len = items
+            .body
+            .iter()
+            .take_while(|stmt| is_directive(stmt))
+            .count();
+
         let mut new_items = vec![];
-        for mut item in items.body.drain(..) {
-            item.visit_mut_children_with(self);
-            new_items.extend(self.before.drain(..));
+        for (idx, mut item) in items.body.drain(..).enumerate() {
+            if idx < prologue_len {
+                new_items.push(item);
+                continue;
+            }
+
+            item.visit_mut_children_with(&mut self.instrumentation);
+            new_items.append(&mut self.instrumentation.before);
             new_items.push(item);
         }
         items.body = new_items;
 
-        let (coverage_template, call_coverage_template_stmt) = self.get_coverage_templates();
+        self.self_check(original, &items.body);
+        self.stub_unchanged_file(&mut items.body);
+        self.skip_ignore_regions(&mut items.body);
+        self.annotate_counters(&mut items.body);
+        self.multiplex_counters(&mut items.body);
+        let on_cover_ident = self.on_cover(&mut items.body);
+        self.global_hit_counters(&mut items.body);
 
-        // prepend template to the top of the code
-        items.body.insert(0, coverage_template);
-        items.body.insert(1, call_coverage_template_stmt);
-    }
+        let is_empty = items.body.is_empty();
+        let templates = self.create_coverage_templates(&mut items.body);
 
-    // ExportDefaultDeclaration: entries(), // ignore processing only
-    #[instrument(skip_all, fields(node = %self.print_node()))]
-    fn visit_mut_export_default_decl(&mut self, export_default_decl: &mut ExportDefaultDecl) {
-        let (old, ignore_current) = self.on_enter(export_default_decl);
-        match ignore_current {
-            Some(crate::hint_comments::IgnoreScope::Next) => {}
-            _ => {
-                // noop
-                export_default_decl.visit_mut_children_with(self);
-            }
+        // prepend template to the top of the code, after the prologue
+        items.body.splice(prologue_len..prologue_len, templates);
+        if is_empty {
+            anchor_coverage_fn_decl(items.body.iter_mut(), items.span);
+        }
+
+        // Scripts cannot import, var $on_cover = require("$module").$export_name;
+        if let (Some(on_cover_ident), Some(on_cover)) = (
+            on_cover_ident,
+            &self.instrumentation.instrument_options.on_cover,
+        ) {
+            items.body.insert(
+                prologue_len,
+                create_require(&on_cover_ident, &on_cover.export_name, &on_cover.module),
+            );
         }
-        self.on_exit(old);
+
+        // var $register = require("$registry_module").register;
+        if let Some((register_ident, registry_module)) = self.get_registry_import() {
+            items.body.insert(
+                prologue_len,
+                create_require(
+                    &register_ident,
+                    crate::COVERAGE_REGISTRY_EXPORT,
+                    registry_module,
+                ),
+            );
+        }
+
+        self.record_stats(started, Some(items.span));
+        self.dump_debug_snapshot("after", &items.body);
     }
+}
 
-    // ExportNamedDeclaration: entries(), // ignore processing only
-    #[instrument(skip_all, fields(node = %self.print_node()))]
-    fn visit_mut_export_decl(&mut self, export_named_decl: &mut ExportDecl) {
-        let (old, ignore_current) = self.on_enter(export_named_decl);
-        match ignore_current {
-            Some(crate::hint_comments::IgnoreScope::Next) => {}
-            _ => {
-                // noop
-                export_named_decl.visit_mut_children_with(self);
-            }
+// Instrumented code is verified by the fixtures (spec/fixtures) through the host, tests here cover
+// the input the host can't produce (nodes altered by the earlier transforms) and the side effects.
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        sync::{Arc, Mutex},
+    };
+
+    use swc_common::{
+        comments::SingleThreadedComments,
+        errors::{Handler, HANDLER},
+        FileName, SourceMap, DUMMY_SP,
+    };
+    use swc_ecma_parser::{lexer::Lexer, Parser, StringInput, Syntax};
+    use swc_ecmascript::{ast::*, visit::VisitMutWith};
+
+    /// Collects diagnostics written by the handler.
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
         }
-        self.on_exit(old);
     }
 
-    // DebuggerStatement: entries(coverStatement),
-    #[instrument(skip_all, fields(node = %self.print_node()))]
-    fn visit_mut_debugger_stmt(&mut self, debugger_stmt: &mut DebuggerStmt) {
-        let (old, ignore_current) = self.on_enter(debugger_stmt);
-        match ignore_current {
-            Some(crate::hint_comments::IgnoreScope::Next) => {}
-            _ => {
-                debugger_stmt.visit_mut_children_with(self);
+    fn parse(source_map: &Arc<SourceMap>, comments: &SingleThreadedComments, code: &str) -> Module {
+        let file = source_map.new_source_file(FileName::Anon, code.to_string());
+        let lexer = Lexer::new(
+            Syntax::Es(Default::default()),
+            EsVersion::latest(),
+            StringInput::from(&*file),
+            Some(comments),
+        );
+        Parser::new_from(lexer).parse_module().unwrap()
+    }
+
+    #[test]
+    fn should_cover_stmts_in_synthesized_nested_blocks() {
+        /// Wraps the stmts of each block into 2 levels of blocks without a span,
+        /// same as the earlier transforms creating scopes.
+        struct BlockWrapper;
+
+        impl swc_ecmascript::visit::VisitMut for BlockWrapper {
+            fn visit_mut_block_stmt(&mut self, block_stmt: &mut BlockStmt) {
+                block_stmt.visit_mut_children_with(self);
+                let stmts = std::mem::take(&mut block_stmt.stmts);
+                block_stmt.stmts = vec![Stmt::Block(BlockStmt {
+                    span: DUMMY_SP,
+                    stmts: vec![Stmt::Block(BlockStmt {
+                        span: DUMMY_SP,
+                        stmts,
+                    })],
+                })];
             }
         }
-        self.on_exit(old);
+
+        let source_map: Arc<SourceMap> = Default::default();
+        let comments = SingleThreadedComments::default();
+        let mut module = parse(
+            &source_map,
+            &comments,
+            "function f() { if (a) { b(); } c(); }",
+        );
+        module.visit_mut_with(&mut BlockWrapper);
+
+        let mut visitor = super::create_coverage_instrumentation_visitor(
+            source_map,
+            comments,
+            Default::default(),
+            "blocks.js".to_string(),
+        );
+        module.visit_mut_with(&mut visitor);
+
+        let cov = visitor.instrumentation.cov.borrow();
+        assert_eq!(
+            cov.as_ref()
+                .statement_map
+                .values()
+                .map(|range| (range.start.line, range.start.column))
+                .collect::<Vec<_>>(),
+            vec![(1, 15), (1, 24), (1, 31)]
+        );
+    }
+
+    #[test]
+    fn should_skip_synthesized_nodes() {
+        let source_map: Arc<SourceMap> = Default::default();
+        let comments = SingleThreadedComments::default();
+        let mut module = parse(&source_map, &comments, "a = 1;\nb = 2;\n");
+        // Statement synthesized by the earlier transform, `helper();`
+        module.body.insert(
+            0,
+            ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Call(CallExpr {
+                    span: DUMMY_SP,
+                    callee: Callee::Expr(Box::new(Expr::Ident(Ident::new(
+                        "helper".into(),
+                        DUMMY_SP,
+                    )))),
+                    args: vec![],
+                    type_args: None,
+                })),
+            })),
+        );
+
+        let mut visitor = super::create_coverage_instrumentation_visitor(
+            source_map,
+            comments,
+            Default::default(),
+            "generated.js".to_string(),
+        );
+        module.visit_mut_with(&mut visitor);
+
+        let cov = visitor.instrumentation.cov.borrow();
+        assert_eq!(
+            cov.as_ref()
+                .statement_map
+                .values()
+                .map(|range| range.start.line)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
     }
 
-    // ConditionalExpression: entries(coverTernary),
-    #[instrument(skip_all, fields(node = %self.print_node()))]
-    fn visit_mut_cond_expr(&mut self, cond_expr: &mut CondExpr) {
-        let (old, ignore_current) = self.on_enter(cond_expr);
+    #[test]
+    fn should_dedupe_statements_of_same_span() {
+        let get_statements = |dedupe_statements: bool| {
+            let source_map: Arc<SourceMap> = Default::default();
+            let comments = SingleThreadedComments::default();
+            let mut module = parse(&source_map, &comments, "a();\nb();\n");
+            // Earlier transform duplicated `a();` keeping its span.
+            let duplicated = module.body[0].clone();
+            module.body.push(duplicated);
 
-        match ignore_current {
-            Some(crate::hint_comments::IgnoreScope::Next) => {}
-            _ => {
-                let range =
-                    crate::lookup_range::get_range_from_span(&self.source_map, &cond_expr.span);
-                let branch = self.cov.borrow_mut().new_branch(
-                    istanbul_oxide::BranchType::CondExpr,
-                    &range,
-                    false,
-                );
+            let mut visitor = super::create_coverage_instrumentation_visitor(
+                source_map,
+                comments,
+                crate::InstrumentOptions {
+                    dedupe_statements,
+                    ..Default::default()
+                },
+                "dedupe.js".to_string(),
+            );
+            module.visit_mut_with(&mut visitor);
 
-                let c_hint = crate::hint_comments::lookup_hint_comments(
-                    &self.comments,
-                    crate::lookup_range::get_expr_span(&*cond_expr.cons),
-                );
-                let a_hint = crate::hint_comments::lookup_hint_comments(
-                    &self.comments,
-                    crate::lookup_range::get_expr_span(&*cond_expr.alt),
-                );
+            let counter_ids = module
+                .body
+                .iter()
+                .filter_map(|item| match item {
+                    ModuleItem::Stmt(Stmt::Expr(ExprStmt { expr, .. })) => {
+                        crate::parse_increase_counter_expr(expr).map(|(_, _, id, _)| id)
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
 
-                if c_hint.as_deref() != Some("next") {
-                    // TODO: do we need this?
-                    // cond_expr.cons.visit_mut_children_with(self);
+            let cov = visitor.instrumentation.cov.borrow();
+            (cov.as_ref().statement_map.len(), counter_ids)
+        };
 
-                    // replace consequence to the paren for increase expr + expr itself
-                    self.replace_expr_with_branch_counter(&mut *cond_expr.cons, branch);
-                }
+        assert_eq!(get_statements(false), (3, vec![0, 1, 2]));
+        assert_eq!(get_statements(true), (2, vec![0, 1, 0]));
+    }
 
-                if a_hint.as_deref() != Some("next") {
-                    // TODO: do we need this?
-                    // cond_expr.alt.visit_mut_children_with(self);
+    #[test]
+    fn should_dump_debug_snapshots() {
+        let out_dir = std::env::temp_dir().join(format!(
+            "swc-coverage-instrument-debug-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&out_dir);
 
-                    // replace consequence to the paren for increase expr + expr itself
-                    self.replace_expr_with_branch_counter(&mut *cond_expr.alt, branch);
-                }
-            }
+        for filename in ["src/problem.js", "src/other.js"] {
+            let source_map: Arc<SourceMap> = Default::default();
+            let comments = SingleThreadedComments::default();
+            let mut module = parse(&source_map, &comments, "const a = 1;");
+            let mut visitor = super::create_coverage_instrumentation_visitor(
+                source_map,
+                comments,
+                crate::InstrumentOptions {
+                    debug: Some(crate::DebugOptions {
+                        files: vec!["**/problem.js".to_string()],
+                        emit: crate::DebugEmit::Ast,
+                        out_dir: Some(out_dir.to_string_lossy().to_string()),
+                    }),
+                    ..Default::default()
+                },
+                filename.to_string(),
+            );
+            module.visit_mut_with(&mut visitor);
+        }
+
+        let mut snapshots = std::fs::read_dir(&out_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        snapshots.sort();
+        assert_eq!(
+            snapshots,
+            vec!["src__problem.js.after.ast", "src__problem.js.before.ast"]
+        );
+
+        let before = std::fs::read_to_string(out_dir.join("src__problem.js.before.ast")).unwrap();
+        let after = std::fs::read_to_string(out_dir.join("src__problem.js.after.ast")).unwrap();
+        assert!(before.contains("VarDecl"));
+        assert!(!before.contains("cov_"));
+        assert!(after.contains("cov_"));
+
+        std::fs::remove_dir_all(&out_dir).unwrap();
+    }
+
+    #[test]
+    fn should_record_stats() {
+        let code = "function foo(a) {\n  return a ? 1 : 2;\n}\nfoo(1);\n";
+        let get_stats = |stats: bool| {
+            let source_map: Arc<SourceMap> = Default::default();
+            let comments = SingleThreadedComments::default();
+            let mut module = parse(&source_map, &comments, code);
+            let mut visitor = super::create_coverage_instrumentation_visitor(
+                source_map,
+                comments,
+                crate::InstrumentOptions {
+                    instrument_log: crate::InstrumentLogOptions {
+                        stats,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                "stats.js".to_string(),
+            );
+            module.visit_mut_with(&mut visitor);
+            visitor.stats().cloned()
         };
 
-        cond_expr.visit_mut_children_with(self);
-        self.on_exit(old);
+        assert_eq!(get_stats(false), None);
+
+        let stats = get_stats(true).expect("Should record stats");
+        assert!(stats.elapsed_ms >= 0.0);
+        assert_eq!(
+            stats,
+            crate::InstrumentStats {
+                path: "stats.js".to_string(),
+                elapsed_ms: stats.elapsed_ms,
+                statements: 2,
+                functions: 1,
+                branches: 1,
+                input_bytes: code.len() as u64,
+                output_bytes: None,
+                bytes_grown: None,
+            }
+        );
+    }
+
+    #[test]
+    fn should_report_error_if_self_check_fails() {
+        let source_map: Arc<SourceMap> = Default::default();
+        let comments = SingleThreadedComments::default();
+        let mut visitor = super::create_coverage_instrumentation_visitor(
+            source_map.clone(),
+            comments.clone(),
+            crate::InstrumentOptions {
+                lazy_init: true,
+                ..Default::default()
+            },
+            "self_check.js".to_string(),
+        );
+
+        let mut module = parse(&source_map, &comments, "var a = 1;\nvar b = a || 2;");
+        let original = parse(&source_map, &comments, "var a = 1;\nvar b = a && 2;");
+        module.visit_mut_with(&mut visitor);
+
+        // Compare with the code differs from the input, except the coverage fn.
+        let items: Vec<_> = module
+            .body
+            .into_iter()
+            .filter(|item| !matches!(item, ModuleItem::Stmt(Stmt::Decl(Decl::Fn(..)))))
+            .collect();
+
+        let output = Arc::new(Mutex::new(vec![]));
+        let handler = Handler::with_emitter_writer(Box::new(Output(output.clone())), None);
+        HANDLER.set(&handler, || {
+            visitor.self_check(Some(original.body), &items);
+        });
+
+        assert_eq!(handler.err_count(), 1);
+        assert!(String::from_utf8_lossy(&output.lock().unwrap()).contains(
+            "selfCheck failed: instrumentation altered the code of self_check.js other than inserting counters, at 2:0"
+        ));
     }
 }
@@ -1,3 +1,6 @@
+use std::collections::{HashMap, HashSet};
+
+use swc_common::{Span, Spanned};
 use swc_ecmascript::{
     ast::*,
     visit::{Visit, VisitWith},
@@ -74,22 +77,234 @@ impl Visit for ExprFinder {
     }
 }
 
-/// Traverse down given nodes to check if it's leaf of the logical expr,
-/// or have inner logical expr to recurse.
+/// Collects names of every identifier in the nodes, to check if an injected ident
+/// collides with one. Not limited to the bindings, as a reference to the injected ident
+/// can be shadowed in any scope.
+#[derive(Debug, Default)]
+pub struct IdentFinder(pub HashSet<String>);
+
+impl Visit for IdentFinder {
+    fn visit_ident(&mut self, ident: &Ident) {
+        self.0.insert(ident.sym.to_string());
+    }
+}
+
+/// Collects spans of the code behind statically false conditions, i.e consequent of
+/// `if (process.env.NODE_ENV !== "production")` when `NODE_ENV` is defined as `production`.
 #[derive(Debug)]
-pub struct LogicalExprLeafFinder(pub bool);
-
-impl Visit for LogicalExprLeafFinder {
-    fn visit_bin_expr(&mut self, bin_expr: &BinExpr) {
-        match &bin_expr.op {
-            BinaryOp::LogicalOr | BinaryOp::LogicalAnd | BinaryOp::NullishCoalescing => {
-                self.0 = true;
-                // short curcuit, we know it's not leaf
-                return;
+pub struct DeadCodeFinder<'a> {
+    defines: &'a HashMap<String, serde_json::Value>,
+    pub spans: Vec<Span>,
+}
+
+impl<'a> DeadCodeFinder<'a> {
+    pub fn new(defines: &'a HashMap<String, serde_json::Value>) -> DeadCodeFinder<'a> {
+        DeadCodeFinder {
+            defines,
+            spans: vec![],
+        }
+    }
+}
+
+impl Visit for DeadCodeFinder<'_> {
+    fn visit_if_stmt(&mut self, if_stmt: &IfStmt) {
+        match crate::static_conditions::eval_static_condition(&if_stmt.test, self.defines) {
+            Some(true) => {
+                if_stmt.cons.visit_with(self);
+                if let Some(alt) = &if_stmt.alt {
+                    self.spans.push(alt.span());
+                }
+            }
+            Some(false) => {
+                self.spans.push(if_stmt.cons.span());
+                if_stmt.alt.visit_with(self);
+            }
+            None => if_stmt.visit_children_with(self),
+        }
+    }
+
+    fn visit_cond_expr(&mut self, cond_expr: &CondExpr) {
+        match crate::static_conditions::eval_static_condition(&cond_expr.test, self.defines) {
+            Some(true) => {
+                cond_expr.cons.visit_with(self);
+                self.spans.push(cond_expr.alt.span());
+            }
+            Some(false) => {
+                self.spans.push(cond_expr.cons.span());
+                cond_expr.alt.visit_with(self);
+            }
+            None => cond_expr.visit_children_with(self),
+        }
+    }
+}
+
+/// Check if given expr is a logical expr, including one wrapped in parens
+/// (i.e `(a || b)`) which istanbul treats as same as non-parenthesized.
+pub fn is_logical_expr(expr: &Expr) -> bool {
+    match expr {
+        Expr::Paren(ParenExpr { expr, .. }) => is_logical_expr(expr),
+        Expr::Bin(BinExpr {
+            op: BinaryOp::LogicalOr | BinaryOp::LogicalAnd | BinaryOp::NullishCoalescing,
+            ..
+        }) => true,
+        _ => false,
+    }
+}
+
+/// Check if given superclass expression needs to be wrapped with parens to be parsed as
+/// `LeftHandSideExpression`, i.e once the counters are inserted into the expression.
+pub fn needs_paren_as_super_class(expr: &Expr) -> bool {
+    !matches!(
+        expr,
+        Expr::Paren(..)
+            | Expr::Ident(..)
+            | Expr::This(..)
+            | Expr::Member(..)
+            | Expr::SuperProp(..)
+            | Expr::Call(..)
+            | Expr::New(..)
+            | Expr::OptChain(..)
+            | Expr::Lit(..)
+            | Expr::Array(..)
+            | Expr::Object(..)
+            | Expr::Fn(..)
+            | Expr::Class(..)
+            | Expr::Tpl(..)
+            | Expr::TaggedTpl(..)
+            | Expr::MetaProp(..)
+    )
+}
+
+/// Span of the body of the fn / arrow fn, same as the `loc` of the function in the coverage.
+fn get_fn_body_span(expr: &Expr) -> Option<Span> {
+    match expr {
+        Expr::Paren(ParenExpr { expr, .. }) => get_fn_body_span(expr),
+        Expr::Fn(FnExpr { function, .. }) => function.body.as_ref().map(|body| body.span),
+        Expr::Arrow(ArrowExpr { body, .. }) => match body {
+            BlockStmtOrExpr::BlockStmt(block_stmt) => Some(block_stmt.span),
+            BlockStmtOrExpr::Expr(expr) => crate::lookup_range::get_expr_span(expr).copied(),
+        },
+        _ => None,
+    }
+}
+
+fn get_export_name(name: &ModuleExportName) -> String {
+    match name {
+        ModuleExportName::Ident(ident) => ident.sym.to_string(),
+        ModuleExportName::Str(str) => str.value.to_string(),
+    }
+}
+
+/// Find the fns exported from the top level module items, with the name each one is exported as.
+/// Fns are identified by the span of the body, which is not altered by the instrumentation.
+/// Exports of the locals (`export { a as b }`, `export default a`) are resolved to the top level
+/// fn decls / var decls of the fns; re-exports from other modules are not included.
+pub fn find_exported_fns(items: &[ModuleItem]) -> Vec<(Span, String)> {
+    let mut locals: HashMap<String, Span> = HashMap::new();
+    let add_var_decl = |locals: &mut HashMap<String, Span>, var_decl: &VarDecl| {
+        for decl in &var_decl.decls {
+            if let (Pat::Ident(BindingIdent { id, .. }), Some(init)) = (&decl.name, &decl.init) {
+                if let Some(span) = get_fn_body_span(init) {
+                    locals.insert(id.sym.to_string(), span);
+                }
             }
+        }
+    };
+
+    for item in items {
+        match item {
+            ModuleItem::Stmt(Stmt::Decl(Decl::Fn(fn_decl)))
+            | ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Fn(fn_decl),
+                ..
+            })) => {
+                if let Some(body) = &fn_decl.function.body {
+                    locals.insert(fn_decl.ident.sym.to_string(), body.span);
+                }
+            }
+            ModuleItem::Stmt(Stmt::Decl(Decl::Var(var_decl)))
+            | ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Var(var_decl),
+                ..
+            })) => add_var_decl(&mut locals, var_decl),
             _ => {}
         }
+    }
+
+    let mut exported = vec![];
+    for item in items {
+        let decl = match item {
+            ModuleItem::ModuleDecl(decl) => decl,
+            _ => continue,
+        };
 
-        bin_expr.visit_children_with(self);
+        match decl {
+            ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Fn(fn_decl),
+                ..
+            }) => {
+                if let Some(body) = &fn_decl.function.body {
+                    exported.push((body.span, fn_decl.ident.sym.to_string()));
+                }
+            }
+            ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Var(var_decl),
+                ..
+            }) => {
+                for decl in &var_decl.decls {
+                    if let (Pat::Ident(BindingIdent { id, .. }), Some(init)) =
+                        (&decl.name, &decl.init)
+                    {
+                        if let Some(span) = get_fn_body_span(init) {
+                            exported.push((span, id.sym.to_string()));
+                        }
+                    }
+                }
+            }
+            ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+                decl: DefaultDecl::Fn(FnExpr { function, .. }),
+                ..
+            }) => {
+                if let Some(body) = &function.body {
+                    exported.push((body.span, "default".to_string()));
+                }
+            }
+            ModuleDecl::ExportDefaultExpr(ExportDefaultExpr { expr, .. }) => {
+                let span = match &**expr {
+                    Expr::Ident(ident) => locals.get(&*ident.sym).copied(),
+                    expr => get_fn_body_span(expr),
+                };
+                if let Some(span) = span {
+                    exported.push((span, "default".to_string()));
+                }
+            }
+            ModuleDecl::ExportNamed(NamedExport {
+                src: None,
+                type_only: false,
+                specifiers,
+                ..
+            }) => {
+                for specifier in specifiers {
+                    if let ExportSpecifier::Named(ExportNamedSpecifier {
+                        orig: ModuleExportName::Ident(orig),
+                        exported: exported_name,
+                        is_type_only: false,
+                        ..
+                    }) = specifier
+                    {
+                        if let Some(span) = locals.get(&*orig.sym) {
+                            let name = exported_name
+                                .as_ref()
+                                .map(get_export_name)
+                                .unwrap_or_else(|| orig.sym.to_string());
+                            exported.push((*span, name));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
     }
+
+    exported
 }
@@ -0,0 +1,70 @@
+use swc_common::{util::take::Take, DUMMY_SP};
+use swc_ecmascript::{
+    ast::*,
+    visit::{noop_visit_mut_type, VisitMut, VisitMutWith},
+};
+
+/// A visitor to replace each counter (`cov_xyz().s[0]++`) with a call to the global fn,
+/// like `__swc_cov_hit("cov_xyz", "s", 0)`. Branch counters pass the index of the path as
+/// the last argument. The name of the coverage fn is the id its coverage is registered as.
+pub struct GlobalHitCountersVisitor {
+    hit_fn_ident: Ident,
+    cov_fn_idents: Vec<Ident>,
+}
+
+impl GlobalHitCountersVisitor {
+    pub fn new(hit_fn_name: &str, cov_fn_idents: Vec<Ident>) -> GlobalHitCountersVisitor {
+        GlobalHitCountersVisitor {
+            hit_fn_ident: Ident::new(hit_fn_name.into(), DUMMY_SP),
+            cov_fn_idents,
+        }
+    }
+}
+
+impl VisitMut for GlobalHitCountersVisitor {
+    noop_visit_mut_type!();
+
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        let call = crate::parse_increase_counter_expr(expr)
+            .filter(|(var_name, ..)| {
+                self.cov_fn_idents
+                    .iter()
+                    .any(|cov_fn_ident| cov_fn_ident.sym == var_name.sym)
+            })
+            .map(|(var_name, counter, id, idx)| {
+                let mut args = vec![
+                    Expr::Lit(Lit::Str(Str {
+                        value: var_name.sym.clone(),
+                        ..Str::dummy()
+                    })),
+                    Expr::Lit(Lit::Str(Str {
+                        value: counter.into(),
+                        ..Str::dummy()
+                    })),
+                    Expr::Lit(Lit::Num(Number::from(id as f64))),
+                ];
+                if let Some(idx) = idx {
+                    args.push(Expr::Lit(Lit::Num(Number::from(idx as f64))));
+                }
+
+                Expr::Call(CallExpr {
+                    callee: Callee::Expr(Box::new(Expr::Ident(self.hit_fn_ident.clone()))),
+                    args: args
+                        .into_iter()
+                        .map(|arg| ExprOrSpread {
+                            spread: None,
+                            expr: Box::new(arg),
+                        })
+                        .collect(),
+                    ..CallExpr::dummy()
+                })
+            });
+
+        if let Some(call) = call {
+            *expr = call;
+            return;
+        }
+
+        expr.visit_mut_children_with(self);
+    }
+}
@@ -0,0 +1,107 @@
+use swc_common::{util::take::Take, DUMMY_SP};
+use swc_ecmascript::{
+    ast::*,
+    visit::{noop_visit_mut_type, VisitMut, VisitMutWith},
+};
+
+use crate::constants::idents::{IDENT_B, IDENT_BT, IDENT_F, IDENT_S};
+
+/// A visitor to replace counter lookups via coverage fn (`cov_xyz().s[0]++`)
+/// into the references hoisted at the top of the file (`cov_xyz_s[0]++`),
+/// avoids a fn call and a property lookup per each hit.
+pub struct HoistCountersVisitor {
+    cov_fn_ident: Ident,
+    // counter object's ident (s, f, b, bT), and whether it is referenced
+    counters: Vec<(Ident, bool)>,
+}
+
+impl HoistCountersVisitor {
+    pub fn new(cov_fn_ident: &Ident) -> HoistCountersVisitor {
+        HoistCountersVisitor {
+            cov_fn_ident: cov_fn_ident.clone(),
+            counters: [&*IDENT_S, &*IDENT_F, &*IDENT_B, &*IDENT_BT]
+                .iter()
+                .map(|ident| ((*ident).clone(), false))
+                .collect(),
+        }
+    }
+
+    fn get_hoisted_ident(&self, counter: &Ident) -> Ident {
+        Ident::new(
+            format!("{}_{}", &*self.cov_fn_ident.sym, &*counter.sym).into(),
+            DUMMY_SP,
+        )
+    }
+
+    /// Creates `var cov_xyz_s = cov_xyz().s, ...` for the referenced counters,
+    /// replaces `cov_xyz();` ensures coverage is initialized.
+    /// Returns None if there are no counters referenced.
+    pub fn create_hoisted_decl(&self) -> Option<Stmt> {
+        let decls: Vec<VarDeclarator> = self
+            .counters
+            .iter()
+            .filter(|(_, referenced)| *referenced)
+            .map(|(counter, _)| VarDeclarator {
+                span: DUMMY_SP,
+                name: Pat::Ident(BindingIdent::from(self.get_hoisted_ident(counter))),
+                init: Some(Box::new(Expr::Member(MemberExpr {
+                    obj: Box::new(Expr::Call(CallExpr {
+                        callee: Callee::Expr(Box::new(Expr::Ident(self.cov_fn_ident.clone()))),
+                        ..CallExpr::dummy()
+                    })),
+                    prop: MemberProp::Ident(counter.clone()),
+                    ..MemberExpr::dummy()
+                }))),
+                definite: false,
+            })
+            .collect();
+
+        if decls.is_empty() {
+            return None;
+        }
+
+        Some(Stmt::Decl(Decl::Var(VarDecl {
+            span: DUMMY_SP,
+            kind: VarDeclKind::Var,
+            declare: false,
+            decls,
+        })))
+    }
+}
+
+impl VisitMut for HoistCountersVisitor {
+    noop_visit_mut_type!();
+
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if let Expr::Member(MemberExpr {
+            obj,
+            prop: MemberProp::Ident(prop),
+            ..
+        }) = expr
+        {
+            if let Expr::Call(CallExpr {
+                callee: Callee::Expr(callee),
+                args,
+                ..
+            }) = &**obj
+            {
+                let is_cov_fn_call = args.is_empty()
+                    && matches!(&**callee, Expr::Ident(ident) if ident.sym == self.cov_fn_ident.sym);
+
+                if is_cov_fn_call {
+                    if let Some(index) = self
+                        .counters
+                        .iter()
+                        .position(|(counter, _)| counter.sym == prop.sym)
+                    {
+                        self.counters[index].1 = true;
+                        *expr = Expr::Ident(self.get_hoisted_ident(&self.counters[index].0));
+                        return;
+                    }
+                }
+            }
+        }
+
+        expr.visit_mut_children_with(self);
+    }
+}
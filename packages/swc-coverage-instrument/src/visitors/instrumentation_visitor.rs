@@ -0,0 +1,1673 @@
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use swc_common::{comments::Comments, util::take::Take, SourceMapper, Span};
+use swc_ecmascript::{
+    ast::*,
+    visit::{noop_visit_mut_type, VisitMut, VisitMutWith},
+};
+
+use crate::{
+    constants::idents::IDENT_B,
+    hint_comments::IgnoreScope,
+    instrumenters::{
+        BranchInstrumenter, FunctionInstrumenter, InstrumentationContext, StatementInstrumenter,
+    },
+    InstrumentOptions, ModuleCoverage, Node, SourceCoverage,
+};
+
+pub(crate) const DIRECTIVES: &[&str] = &["use strict", "use asm", "use strong"];
+
+/// A visitor injects the counters to the nodes it visits, by composing the instrumenters
+/// (`StatementInstrumenter`, `FunctionInstrumenter`, `BranchInstrumenter`) it implements the context of.
+///
+/// Statements are visited by a child visitor of its own, which collects the statement counters
+/// to be prepended by the parent (`before`). `CoverageVisitor` drives this for the file.
+///
+/// Ids of the statements, fns and branches are allocated while the counters are injected, in the
+/// order of the visit. There is no separate read-only pass collecting the coverage maps upfront:
+/// it would have to repeat every rule deciding which nodes get counters (hint comments, options,
+/// unmapped spans), and both passes would have to agree exactly for the precomputed ids to match.
+pub struct InstrumentationVisitor<C: Clone + Comments, S: SourceMapper> {
+    // We may not need Arc in the plugin context - this is only to preserve isomorphic interface
+    // between plugin & custom transform pass.
+    pub(crate) source_map: Arc<S>,
+    pub(crate) comments: C,
+    pub(crate) cov: Rc<RefCell<SourceCoverage>>,
+    pub(crate) cov_fn_ident: Ident,
+    pub(crate) cov_fn_temp_ident: Ident,
+    // Coverage of each original module if input is a concatenation of modules,
+    // `cov` and `cov_fn_ident` are switched to the module current node belongs to.
+    pub(crate) modules: Rc<Vec<ModuleCoverage>>,
+    module_stack: Vec<(Rc<RefCell<SourceCoverage>>, Ident)>,
+    pub(crate) instrument_options: InstrumentOptions,
+    // Current visitor state to hold stmts to be prepended by parent node.
+    pub(crate) before: Vec<Stmt>,
+    pub(crate) nodes: Vec<Node>,
+    should_ignore: Option<IgnoreScope>,
+    // Branch of the switch stmt, if this visitor visits its cases.
+    switch_branch: Option<u32>,
+}
+
+impl<C: Clone + Comments, S: SourceMapper> InstrumentationVisitor<C, S> {
+    pub fn new(
+        source_map: Arc<S>,
+        comments: C,
+        cov: Rc<RefCell<SourceCoverage>>,
+        cov_fn_ident: Ident,
+        cov_fn_temp_ident: Ident,
+        instrument_options: InstrumentOptions,
+    ) -> InstrumentationVisitor<C, S> {
+        InstrumentationVisitor {
+            source_map,
+            comments,
+            cov,
+            cov_fn_ident,
+            cov_fn_temp_ident,
+            modules: Default::default(),
+            module_stack: vec![],
+            instrument_options,
+            before: vec![],
+            nodes: vec![],
+            should_ignore: None,
+            switch_branch: None,
+        }
+    }
+
+    /// Create a visitor for the children of the current node, shares the coverage and
+    /// the nodes from the root but collects its own stmts to be prepended.
+    fn create_child_visitor(
+        &self,
+        should_ignore: Option<IgnoreScope>,
+    ) -> InstrumentationVisitor<C, S> {
+        InstrumentationVisitor {
+            source_map: self.source_map.clone(),
+            comments: self.comments.clone(),
+            cov: self.cov.clone(),
+            cov_fn_ident: self.cov_fn_ident.clone(),
+            cov_fn_temp_ident: self.cov_fn_temp_ident.clone(),
+            modules: self.modules.clone(),
+            module_stack: vec![],
+            instrument_options: self.instrument_options.clone(),
+            before: vec![],
+            nodes: self.nodes.clone(),
+            should_ignore,
+            switch_branch: None,
+        }
+    }
+
+    // Display current nodes.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn print_node(&self) -> String {
+        self.nodes
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<String>>()
+            .join(":")
+    }
+
+    /// Switch coverage to the module of given span, previous one is restored on exit.
+    fn enter_module(&mut self, span: Option<&Span>) {
+        if self.modules.is_empty() {
+            return;
+        }
+
+        self.module_stack
+            .push((self.cov.clone(), self.cov_fn_ident.clone()));
+
+        let module = span
+            .filter(|span| !span.is_dummy())
+            .and_then(|span| crate::find_module_coverage(&self.modules, span.lo));
+        if let Some(module) = module {
+            self.cov = module.cov.clone();
+            self.cov_fn_ident = module.cov_fn_ident.clone();
+        }
+    }
+
+    fn exit_module(&mut self) {
+        if let Some((cov, cov_fn_ident)) = self.module_stack.pop() {
+            self.cov = cov;
+            self.cov_fn_ident = cov_fn_ident;
+        }
+    }
+
+    fn on_enter_with_span(
+        &mut self,
+        span: Option<&Span>,
+    ) -> (Option<IgnoreScope>, Option<IgnoreScope>) {
+        self.enter_module(span);
+
+        let old = self.should_ignore;
+        let ret = match old {
+            Some(IgnoreScope::Next) => old,
+            _ => {
+                self.should_ignore = crate::hint_comments::should_ignore(&self.comments, span);
+                self.should_ignore
+            }
+        };
+
+        (old, ret)
+    }
+
+    /// Push the node to the nodes from the root, and determine if it should be ignored
+    /// by the hint comments. Returns the previous scope to be restored by `on_exit`.
+    fn on_enter<N: EnterNode>(&mut self, n: &N) -> (Option<IgnoreScope>, Option<IgnoreScope>) {
+        self.nodes.push(n.node());
+        self.on_enter_with_span(n.enter_span())
+    }
+
+    fn on_exit(&mut self, old: Option<IgnoreScope>) {
+        self.should_ignore = old;
+        self.nodes.pop();
+        self.exit_module();
+    }
+
+    /// Visit individual statements with a child visitor, prepend the statement counters it collected.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn insert_stmts_counter(&mut self, stmts: &mut Vec<Stmt>) {
+        let mut new_stmts = vec![];
+
+        for mut stmt in stmts.drain(..) {
+            if !self.is_injected_counter_stmt(&stmt) {
+                let (old, ignore_current) = self.on_enter(&stmt);
+
+                match ignore_current {
+                    Some(IgnoreScope::Next) => {}
+                    _ => {
+                        let mut visitor = self.create_child_visitor(ignore_current);
+                        stmt.visit_mut_children_with(&mut visitor);
+
+                        new_stmts.append(&mut visitor.before);
+                    }
+                }
+                self.on_exit(old);
+            }
+
+            new_stmts.push(stmt);
+        }
+
+        *stmts = new_stmts;
+    }
+
+    /// Visit the for-variant loops (for, for-of, for-in) and the other loops (while, do-while)
+    /// which share same logic. Condition of the loop is covered as a branch if
+    /// `cover_loop_conditions` is set.
+    fn visit_mut_loop<L: LoopStmt + VisitMutWith<Self>>(&mut self, loop_stmt: &mut L) {
+        let (old, ignore_current) = self.on_enter(loop_stmt);
+
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                // cover_statement's is_stmt prepend logic for individual child stmt visitor
+                self.mark_prepend_stmt_counter(&loop_stmt.loop_span());
+
+                // Branch is created before visiting the children, to keep the order of
+                // the branches same as its source.
+                let branch = loop_stmt
+                    .test_mut()
+                    .and_then(|test| self.create_loop_condition_branch(test));
+
+                let body = *loop_stmt.body_mut().take();
+                // if for stmt body is not block, wrap it before insert statement counter
+                let body = if let Stmt::Block(body) = body {
+                    body
+                } else {
+                    let stmts = vec![body];
+                    BlockStmt {
+                        span: swc_common::DUMMY_SP,
+                        stmts,
+                    }
+                };
+
+                **loop_stmt.body_mut() = Stmt::Block(body);
+
+                // Condition is taken out while visiting the other children, otherwise
+                // the counters wrapping it are visited as a part of the children.
+                let test = branch.and_then(|_| loop_stmt.test_mut().map(|test| test.take()));
+
+                // Iterate children for inner stmt's counter insertion
+                loop_stmt.visit_mut_children_with(self);
+
+                if let (Some(branch), Some(mut test)) = (branch, test) {
+                    self.wrap_loop_condition_with_branch_counter(branch, &mut test);
+                    if let Some(slot) = loop_stmt.test_mut() {
+                        *slot = test;
+                    }
+                }
+            }
+        }
+
+        self.on_exit(old);
+    }
+}
+
+impl<C: Clone + Comments, S: SourceMapper> InstrumentationContext for InstrumentationVisitor<C, S> {
+    type Comments = C;
+    type SourceMap = S;
+
+    fn source_map(&self) -> &Arc<S> {
+        &self.source_map
+    }
+
+    fn comments(&self) -> &C {
+        &self.comments
+    }
+
+    fn cov(&self) -> &Rc<RefCell<SourceCoverage>> {
+        &self.cov
+    }
+
+    fn cov_fn_ident(&self) -> &Ident {
+        &self.cov_fn_ident
+    }
+
+    fn cov_fn_temp_ident(&self) -> &Ident {
+        &self.cov_fn_temp_ident
+    }
+
+    fn instrument_options(&self) -> &InstrumentOptions {
+        &self.instrument_options
+    }
+
+    fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    fn before_mut(&mut self) -> &mut Vec<Stmt> {
+        &mut self.before
+    }
+}
+
+/// Ast nodes the visitor enters: tracked in the nodes from the root, and its span is used to
+/// look up the hint comments to determine if certain section of the code should be ignored.
+pub(crate) trait EnterNode {
+    fn node(&self) -> Node;
+    fn enter_span(&self) -> Option<&Span>;
+}
+
+// Implement `EnterNode` for the types can access span directly.
+macro_rules! enter_node {
+    ($($N: ident),* $(,)?) => {
+        $(
+            impl EnterNode for $N {
+                fn node(&self) -> Node {
+                    Node::$N
+                }
+
+                fn enter_span(&self) -> Option<&Span> {
+                    Some(&self.span)
+                }
+            }
+        )*
+    };
+}
+
+enter_node!(
+    BinExpr,
+    VarDeclarator,
+    VarDecl,
+    CondExpr,
+    ParenExpr,
+    ExprStmt,
+    IfStmt,
+    LabeledStmt,
+    ContinueStmt,
+    ClassProp,
+    PrivateProp,
+    ClassMethod,
+    PrivateMethod,
+    ArrowExpr,
+    ForStmt,
+    ForOfStmt,
+    ForInStmt,
+    WhileStmt,
+    DoWhileStmt,
+    SwitchStmt,
+    SwitchCase,
+    BreakStmt,
+    ReturnStmt,
+    ExportAll,
+    NamedExport,
+    BlockStmt,
+    WithStmt,
+    TryStmt,
+    ThrowStmt,
+    ExportDecl,
+    ExportDefaultDecl,
+    TsExportAssignment,
+    TsImportEqualsDecl,
+    DebuggerStmt,
+    AssignPat,
+    AssignPatProp,
+    Decorator,
+    GetterProp,
+    SetterProp,
+);
+
+impl EnterNode for Expr {
+    fn node(&self) -> Node {
+        Node::Expr
+    }
+
+    fn enter_span(&self) -> Option<&Span> {
+        crate::lookup_range::get_expr_span(self)
+    }
+}
+
+impl EnterNode for Stmt {
+    fn node(&self) -> Node {
+        Node::Stmt
+    }
+
+    fn enter_span(&self) -> Option<&Span> {
+        crate::lookup_range::get_stmt_span(self)
+    }
+}
+
+impl EnterNode for ModuleDecl {
+    fn node(&self) -> Node {
+        Node::ModuleDecl
+    }
+
+    fn enter_span(&self) -> Option<&Span> {
+        crate::lookup_range::get_module_decl_span(self)
+    }
+}
+
+impl EnterNode for ClassDecl {
+    fn node(&self) -> Node {
+        Node::ClassDecl
+    }
+
+    fn enter_span(&self) -> Option<&Span> {
+        Some(&self.class.span)
+    }
+}
+
+impl EnterNode for FnExpr {
+    fn node(&self) -> Node {
+        Node::FnExpr
+    }
+
+    fn enter_span(&self) -> Option<&Span> {
+        Some(&self.function.span)
+    }
+}
+
+impl EnterNode for MethodProp {
+    fn node(&self) -> Node {
+        Node::MethodProp
+    }
+
+    fn enter_span(&self) -> Option<&Span> {
+        Some(&self.function.span)
+    }
+}
+
+impl EnterNode for FnDecl {
+    fn node(&self) -> Node {
+        Node::FnDecl
+    }
+
+    fn enter_span(&self) -> Option<&Span> {
+        Some(&self.function.span)
+    }
+}
+
+/// Loop statements, visited by `InstrumentationVisitor::visit_mut_loop`.
+pub(crate) trait LoopStmt: EnterNode {
+    fn loop_span(&self) -> Span;
+    fn body_mut(&mut self) -> &mut Box<Stmt>;
+    /// Condition of the loop, if it has one.
+    fn test_mut(&mut self) -> Option<&mut Box<Expr>> {
+        None
+    }
+}
+
+impl LoopStmt for ForStmt {
+    fn loop_span(&self) -> Span {
+        self.span
+    }
+
+    fn body_mut(&mut self) -> &mut Box<Stmt> {
+        &mut self.body
+    }
+
+    fn test_mut(&mut self) -> Option<&mut Box<Expr>> {
+        self.test.as_mut()
+    }
+}
+
+impl LoopStmt for ForInStmt {
+    fn loop_span(&self) -> Span {
+        self.span
+    }
+
+    fn body_mut(&mut self) -> &mut Box<Stmt> {
+        &mut self.body
+    }
+}
+
+impl LoopStmt for ForOfStmt {
+    fn loop_span(&self) -> Span {
+        self.span
+    }
+
+    fn body_mut(&mut self) -> &mut Box<Stmt> {
+        &mut self.body
+    }
+}
+
+impl LoopStmt for WhileStmt {
+    fn loop_span(&self) -> Span {
+        self.span
+    }
+
+    fn body_mut(&mut self) -> &mut Box<Stmt> {
+        &mut self.body
+    }
+
+    fn test_mut(&mut self) -> Option<&mut Box<Expr>> {
+        Some(&mut self.test)
+    }
+}
+
+impl LoopStmt for DoWhileStmt {
+    fn loop_span(&self) -> Span {
+        self.span
+    }
+
+    fn body_mut(&mut self) -> &mut Box<Stmt> {
+        &mut self.body
+    }
+
+    fn test_mut(&mut self) -> Option<&mut Box<Expr>> {
+        Some(&mut self.test)
+    }
+}
+
+impl<C: Clone + Comments, S: SourceMapper> VisitMut for InstrumentationVisitor<C, S> {
+    noop_visit_mut_type!();
+
+    // BlockStatement: entries(), // ignore processing only
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_block_stmt(&mut self, block_stmt: &mut BlockStmt) {
+        let (old, ignore_current) = self.on_enter(block_stmt);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                // Visit inner for the block stmt, stmts of the nested blocks (including the ones
+                // created by the earlier transforms without a span) get counters via visit_mut_stmts.
+                block_stmt.visit_mut_children_with(self);
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // FunctionDeclaration: entries(coverFunction),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_fn_decl(&mut self, fn_decl: &mut FnDecl) {
+        let (old, ignore_current) = self.on_enter(fn_decl);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                self.create_fn_instrumentation(
+                    &Some(&fn_decl.ident),
+                    crate::FunctionKind::Declaration,
+                    &mut fn_decl.function,
+                );
+                fn_decl.visit_mut_children_with(self);
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // ArrowFunctionExpression: entries(convertArrowExpression, coverFunction),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_arrow_expr(&mut self, arrow_expr: &mut ArrowExpr) {
+        let (old, ignore_current) = self.on_enter(arrow_expr);
+        let (is_async, is_generator) = (arrow_expr.is_async, arrow_expr.is_generator);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => match &mut arrow_expr.body {
+                BlockStmtOrExpr::BlockStmt(block_stmt) => {
+                    let mut new_stmts = vec![];
+                    if self.instrument_options.cover_functions
+                        && !self.is_unmapped_span(&arrow_expr.span)
+                    {
+                        let range = crate::lookup_range::get_anonymous_fn_decl_range(
+                            &self.source_map,
+                            &arrow_expr.span,
+                        );
+                        let body_range = crate::lookup_range::get_range_from_span(
+                            &self.source_map,
+                            &block_stmt.span,
+                        );
+                        let index = self
+                            .cov
+                            .borrow_mut()
+                            .new_function(&None, &range, &body_range);
+                        self.set_fn_metadata(
+                            index,
+                            crate::FunctionKind::Arrow,
+                            is_async,
+                            is_generator,
+                        );
+                        let b = crate::create_increase_counter_expr(
+                            &crate::constants::idents::IDENT_F,
+                            index,
+                            &self.cov_fn_ident,
+                            None,
+                        );
+
+                        // insert fn counter expression
+                        new_stmts.push(Stmt::Expr(ExprStmt {
+                            span: swc_common::DUMMY_SP,
+                            expr: Box::new(b),
+                        }));
+                    }
+                    // if arrow fn body is already blockstmt, insert stmt counter for each
+                    self.insert_stmts_counter(&mut block_stmt.stmts);
+                    new_stmts.append(&mut block_stmt.stmts);
+                    block_stmt.stmts = new_stmts;
+                }
+                BlockStmtOrExpr::Expr(expr) => {
+                    // TODO: refactor common logics creates a blockstmt from single expr
+                    let range = crate::lookup_range::get_anonymous_fn_decl_range(
+                        &self.source_map,
+                        &arrow_expr.span,
+                    );
+                    let span = crate::lookup_range::get_expr_span(expr);
+                    if let Some(span) = span {
+                        let mut stmts = vec![];
+                        if self.instrument_options.cover_functions
+                            && !self.is_unmapped_span(&arrow_expr.span)
+                        {
+                            let body_range =
+                                crate::lookup_range::get_range_from_span(&self.source_map, span);
+                            let index =
+                                self.cov
+                                    .borrow_mut()
+                                    .new_function(&None, &range, &body_range);
+                            self.set_fn_metadata(
+                                index,
+                                crate::FunctionKind::Arrow,
+                                is_async,
+                                is_generator,
+                            );
+                            let b = crate::create_increase_counter_expr(
+                                &crate::constants::idents::IDENT_F,
+                                index,
+                                &self.cov_fn_ident,
+                                None,
+                            );
+
+                            // insert fn counter expression
+                            stmts.push(Stmt::Expr(ExprStmt {
+                                span: swc_common::DUMMY_SP,
+                                expr: Box::new(b),
+                            }));
+                        }
+
+                        // single line expr in arrow fn need to be converted into return stmt
+                        // Note we should preserve original expr's span, otherwise statementmap will lose correct
+                        // code location
+                        let ret = Stmt::Return(ReturnStmt {
+                            span: *span,
+                            arg: Some(expr.take()),
+                        });
+                        stmts.push(ret);
+
+                        let mut new_stmts = vec![];
+                        // insert stmt counter for the returnstmt we made above
+                        self.insert_stmts_counter(&mut stmts);
+                        new_stmts.append(&mut stmts);
+
+                        arrow_expr.body = BlockStmtOrExpr::BlockStmt(BlockStmt {
+                            span: swc_common::DUMMY_SP,
+                            stmts: new_stmts,
+                        });
+                    }
+                }
+            },
+        }
+        self.on_exit(old);
+    }
+
+    /*
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_stmt(&mut self, stmt: &mut Stmt) {
+        if !self.is_injected_counter_stmt(stmt) {
+            let span = crate::lookup_range::get_stmt_span(&stmt);
+            if let Some(span) = span {
+                let increment_expr = self.create_stmt_increase_counter_expr(span, None);
+
+                self.before.push(Stmt::Expr(ExprStmt {
+                    span: DUMMY_SP,
+                    expr: Box::new(increment_expr),
+                }));
+            }
+        }
+    } */
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        // Each Stmt looks up own comments for the hint, we don't
+        // do self.on_enter() in here.
+        self.nodes.push(crate::Node::Stmts);
+        self.insert_stmts_counter(stmts);
+        self.nodes.pop();
+    }
+
+    // FunctionExpression: entries(coverFunction),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_fn_expr(&mut self, fn_expr: &mut FnExpr) {
+        let (old, ignore_current) = self.on_enter(fn_expr);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                let fn_ident = &fn_expr.ident.as_ref();
+
+                let should_ignore_via_options = if let Some(ident) = fn_ident {
+                    self.instrument_options
+                        .ignore_class_methods
+                        .iter()
+                        .any(|v| v.as_str() == &*ident.sym)
+                } else {
+                    false
+                };
+
+                if !should_ignore_via_options {
+                    // We do insert counter _first_, then iterate child:
+                    // Otherwise inner stmt / fn will get the first idx to the each counter.
+                    // `insert_stmts_counter` filters out injected counter internally.
+                    self.create_fn_instrumentation(
+                        fn_ident,
+                        crate::FunctionKind::Expression,
+                        &mut fn_expr.function,
+                    );
+                    fn_expr.visit_mut_children_with(self);
+                }
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // ExpressionStatement: entries(coverStatement),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_expr_stmt(&mut self, expr_stmt: &mut ExprStmt) {
+        let (old, ignore_current) = self.on_enter(expr_stmt);
+
+        if let Expr::Lit(Lit::Str(Str { value, .. })) = expr_stmt.expr.as_ref() {
+            let value: &str = value;
+
+            if DIRECTIVES.contains(&value) {
+                self.on_exit(old);
+                return;
+            }
+        }
+
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                if !self.is_injected_counter_expr(&expr_stmt.expr) {
+                    self.mark_prepend_stmt_counter(&expr_stmt.span);
+                }
+            }
+        }
+        expr_stmt.visit_mut_children_with(self);
+
+        self.on_exit(old);
+    }
+
+    // BreakStatement: entries(coverStatement),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_break_stmt(&mut self, break_stmt: &mut BreakStmt) {
+        let (old, ignore_current) = self.on_enter(break_stmt);
+
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                self.mark_prepend_stmt_counter(&break_stmt.span);
+            }
+        }
+        break_stmt.visit_mut_children_with(self);
+
+        self.on_exit(old);
+    }
+
+    // ReturnStatement: entries(coverStatement),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_return_stmt(&mut self, return_stmt: &mut ReturnStmt) {
+        let (old, ignore_current) = self.on_enter(return_stmt);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                self.mark_prepend_stmt_counter(&return_stmt.span);
+                return_stmt.visit_mut_children_with(self);
+            }
+        }
+
+        self.on_exit(old);
+    }
+
+    // VariableDeclaration: entries(), // ignore processing only
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_var_decl(&mut self, var_decl: &mut VarDecl) {
+        let (old, _ignore_current) = self.on_enter(var_decl);
+        //noop?
+        var_decl.visit_mut_children_with(self);
+        self.on_exit(old);
+    }
+
+    // ClassDeclaration: entries(parenthesizedExpressionProp('superClass')),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_class_decl(&mut self, class_decl: &mut ClassDecl) {
+        let (old, ignore_current) = self.on_enter(class_decl);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                class_decl.visit_mut_children_with(self);
+            }
+        }
+
+        self.on_exit(old);
+    }
+
+    // ClassExpression: entries(parenthesizedExpressionProp('superClass')),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_class(&mut self, class: &mut Class) {
+        // Superclass is not a statement by itself, only the branches inside of it
+        // are counted. Counters can turn it into an expression which can't be used as
+        // superclass without parens (`extends (cov.b[0][0]++, foo) || bar`),
+        // wrap it same as istanbul does for both of class declarations and expressions.
+        class.visit_mut_children_with(self);
+        if let Some(super_class) = &mut class.super_class {
+            if crate::visitors::finders::needs_paren_as_super_class(super_class) {
+                **super_class = Expr::Paren(ParenExpr {
+                    span: swc_common::DUMMY_SP,
+                    expr: super_class.take(),
+                });
+            }
+        }
+    }
+
+    // ClassProperty: entries(coverClassPropDeclarator),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_class_prop(&mut self, class_prop: &mut ClassProp) {
+        let (old, ignore_current) = self.on_enter(class_prop);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                class_prop.decorators.visit_mut_with(self);
+                if let Some(value) = &mut class_prop.value {
+                    // Initializer is always an expression, wrap it instead of prepending stmt.
+                    self.replace_expr_with_stmt_counter(&mut *value);
+                    value.visit_mut_with(self);
+                }
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // TODO: ClassAccessorProperty (`accessor x = 1`) is not supported by the current
+    // version of the parser, should be covered as same as class property once it's upgraded.
+
+    // ClassPrivateProperty: entries(coverClassPropDeclarator),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_private_prop(&mut self, private_prop: &mut PrivateProp) {
+        // TODO: this is same as visit_mut_class_prop
+        let (old, ignore_current) = self.on_enter(private_prop);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                private_prop.decorators.visit_mut_with(self);
+                if let Some(value) = &mut private_prop.value {
+                    // Initializer is always an expression, wrap it instead of prepending stmt.
+                    self.replace_expr_with_stmt_counter(&mut *value);
+                    value.visit_mut_with(self);
+                }
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // ClassMethod: entries(coverFunction),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_class_method(&mut self, class_method: &mut ClassMethod) {
+        let (old, ignore_current) = self.on_enter(class_method);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                // TODO: this does not cover all of PropName enum yet
+                // TODO: duplicated logic between fn_expr
+                if let PropName::Ident(ident) = &class_method.key {
+                    let should_ignore_via_options = self
+                        .instrument_options
+                        .ignore_class_methods
+                        .iter()
+                        .any(|v| v.as_str() == &*ident.sym);
+
+                    if !should_ignore_via_options {
+                        self.create_method_instrumentation(
+                            &Some(ident.sym.to_string()),
+                            &class_method.span,
+                            crate::instrumenters::function_instrumenter::get_method_kind(
+                                class_method.kind,
+                            ),
+                            &mut class_method.function,
+                        );
+                        class_method.visit_mut_children_with(self);
+                    }
+                }
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // ClassPrivateMethod: entries(coverFunction),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_private_method(&mut self, private_method: &mut PrivateMethod) {
+        let (old, ignore_current) = self.on_enter(private_method);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                let ident = &private_method.key.id;
+                let should_ignore_via_options = self
+                    .instrument_options
+                    .ignore_class_methods
+                    .iter()
+                    .any(|v| v.as_str() == &*ident.sym);
+
+                if !should_ignore_via_options {
+                    self.create_method_instrumentation(
+                        &Some(ident.sym.to_string()),
+                        &private_method.span,
+                        crate::instrumenters::function_instrumenter::get_method_kind(
+                            private_method.kind,
+                        ),
+                        &mut private_method.function,
+                    );
+                    private_method.visit_mut_children_with(self);
+                }
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // Decorator: istanbul has no entries for the decorator, but it is evaluated at the
+    // class definition time. Count each decorator expr as a statement, in place to
+    // preserve evaluation order among decorators and class members.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_decorator(&mut self, decorator: &mut Decorator) {
+        let (old, ignore_current) = self.on_enter(decorator);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ if !self.instrument_options.cover_statements
+                || self.is_unmapped_span(&decorator.span) =>
+            {
+                decorator.visit_mut_children_with(self);
+            }
+            _ => {
+                // Create statement counter before visiting inner exprs (i.e logical exprs in the arguments)
+                // to keep order of the counters same as its source.
+                let span =
+                    *crate::lookup_range::get_expr_span(&decorator.expr).unwrap_or(&decorator.span);
+                let increment_expr = self.create_stmt_increase_counter_expr(&span, None);
+
+                decorator.visit_mut_children_with(self);
+
+                let expr = decorator.expr.take();
+                *decorator.expr = crate::wrap_expr_with_counter(increment_expr, *expr);
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // ObjectMethod: entries(coverFunction),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_method_prop(&mut self, method_prop: &mut MethodProp) {
+        let (old, ignore_current) = self.on_enter(method_prop);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                let name =
+                    crate::instrumenters::function_instrumenter::get_prop_name(&method_prop.key);
+                if !self.should_ignore_method(&name) {
+                    // Function of the object method starts at the start of the prop.
+                    let span = method_prop.function.span;
+                    self.create_method_instrumentation(
+                        &name,
+                        &span,
+                        crate::FunctionKind::Method,
+                        &mut method_prop.function,
+                    );
+                    method_prop.visit_mut_children_with(self);
+                }
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // ObjectMethod: entries(coverFunction),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_getter_prop(&mut self, getter_prop: &mut GetterProp) {
+        let (old, ignore_current) = self.on_enter(getter_prop);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                let name =
+                    crate::instrumenters::function_instrumenter::get_prop_name(&getter_prop.key);
+                if !self.should_ignore_method(&name) {
+                    self.create_accessor_instrumentation(
+                        &name,
+                        &getter_prop.span,
+                        crate::FunctionKind::Getter,
+                        &mut getter_prop.body,
+                    );
+                    getter_prop.visit_mut_children_with(self);
+                }
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // ObjectMethod: entries(coverFunction),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_setter_prop(&mut self, setter_prop: &mut SetterProp) {
+        let (old, ignore_current) = self.on_enter(setter_prop);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                let name =
+                    crate::instrumenters::function_instrumenter::get_prop_name(&setter_prop.key);
+                if !self.should_ignore_method(&name) {
+                    self.create_accessor_instrumentation(
+                        &name,
+                        &setter_prop.span,
+                        crate::FunctionKind::Setter,
+                        &mut setter_prop.body,
+                    );
+                    setter_prop.visit_mut_children_with(self);
+                }
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // VariableDeclarator: entries(coverVariableDeclarator),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_var_declarator(&mut self, declarator: &mut VarDeclarator) {
+        let (old, ignore_current) = self.on_enter(declarator);
+
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                if let Some(init) = &mut declarator.init {
+                    let init = &mut **init;
+                    self.cover_statement(init);
+                }
+
+                declarator.visit_mut_children_with(self);
+            }
+        }
+
+        self.on_exit(old);
+    }
+
+    // ForStatement: entries(blockProp('body'), coverStatement),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_for_stmt(&mut self, for_stmt: &mut ForStmt) {
+        self.visit_mut_loop(for_stmt);
+    }
+
+    // ForInStatement: entries(blockProp('body'), coverStatement),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_for_in_stmt(&mut self, for_in_stmt: &mut ForInStmt) {
+        self.visit_mut_loop(for_in_stmt);
+    }
+
+    // ForOfStatement: entries(blockProp('body'), coverStatement),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_for_of_stmt(&mut self, for_of_stmt: &mut ForOfStmt) {
+        self.visit_mut_loop(for_of_stmt);
+    }
+
+    // WhileStatement: entries(blockProp('body'), coverStatement),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_while_stmt(&mut self, while_stmt: &mut WhileStmt) {
+        self.visit_mut_loop(while_stmt);
+    }
+
+    // DoWhileStatement: entries(blockProp('body'), coverStatement),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_do_while_stmt(&mut self, do_while_stmt: &mut DoWhileStmt) {
+        self.visit_mut_loop(do_while_stmt);
+    }
+
+    //LabeledStatement: entries(coverStatement),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_labeled_stmt(&mut self, labeled_stmt: &mut LabeledStmt) {
+        let (old, ignore_current) = self.on_enter(labeled_stmt);
+
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                // cover_statement's is_stmt prepend logic for individual child stmt visitor
+                self.mark_prepend_stmt_counter(&labeled_stmt.span);
+            }
+        }
+
+        labeled_stmt.visit_mut_children_with(self);
+
+        self.on_exit(old);
+    }
+
+    // ContinueStatement: entries(coverStatement),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_continue_stmt(&mut self, continue_stmt: &mut ContinueStmt) {
+        let (old, ignore_current) = self.on_enter(continue_stmt);
+
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                // cover_statement's is_stmt prepend logic for individual child stmt visitor
+                self.mark_prepend_stmt_counter(&continue_stmt.span);
+            }
+        }
+
+        continue_stmt.visit_mut_children_with(self);
+        self.on_exit(old);
+    }
+
+    // SwitchStatement: entries(createSwitchBranch, coverStatement),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_switch_stmt(&mut self, switch_stmt: &mut SwitchStmt) {
+        let (old, ignore_current) = self.on_enter(switch_stmt);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ if !self
+                .instrument_options
+                .should_cover_branch(&crate::BranchType::Switch)
+                || self.is_unmapped_span(&switch_stmt.span) =>
+            {
+                self.mark_prepend_stmt_counter(&switch_stmt.span);
+                switch_stmt.visit_mut_children_with(self);
+            }
+            _ => {
+                // Insert stmt counter for `switch` itself, then create a new branch
+                self.mark_prepend_stmt_counter(&switch_stmt.span);
+
+                let range =
+                    crate::lookup_range::get_range_from_span(&self.source_map, &switch_stmt.span);
+                let branch =
+                    self.cov
+                        .borrow_mut()
+                        .new_branch(crate::BranchType::Switch, &range, false);
+
+                // traverse `case` with a visitor contains branch idx, insert new
+                // branch increase counter accordingly
+                let mut visitor = self.create_child_visitor(ignore_current);
+                visitor.switch_branch = Some(branch);
+
+                switch_stmt.visit_mut_children_with(&mut visitor);
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // IfStatement: entries(blockProp('consequent'), blockProp('alternate'), coverStatement, coverIfBranches)
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_if_stmt(&mut self, if_stmt: &mut IfStmt) {
+        let (old, ignore_current) = self.on_enter(if_stmt);
+
+        match ignore_current {
+            Some(IgnoreScope::Next) => {
+                self.on_exit(old);
+            }
+            _ => {
+                // cover_statement's is_stmt prepend logic for individual child stmt visitor
+                self.mark_prepend_stmt_counter(&if_stmt.span);
+
+                let range =
+                    crate::lookup_range::get_range_from_span(&self.source_map, &if_stmt.span);
+                let branch = if self
+                    .instrument_options
+                    .should_cover_branch(&crate::BranchType::If)
+                    && !self.is_unmapped_span(&if_stmt.span)
+                {
+                    Some(
+                        self.cov
+                            .borrow_mut()
+                            .new_branch(crate::BranchType::If, &range, false),
+                    )
+                } else {
+                    None
+                };
+
+                // Visit the test before wrapping cons / alt, to keep the order of the counters
+                // same as upstream's traversal (test, consequent, alternate).
+                if_stmt.test.visit_mut_with(self);
+
+                // Wrap cons / alt into a block (blockProp) if it's not, then insert the branch counter
+                // as the first stmt of the block.
+                let mut wrap_with_counter = |stmt: &mut Box<Stmt>| {
+                    let mut stmt_body = *stmt.take();
+
+                    // create a branch path counter
+                    let expr = branch.map(|branch| {
+                        let idx = self.cov.borrow_mut().add_branch_path(branch, &range);
+                        let expr = crate::create_increase_counter_expr(
+                            &IDENT_B,
+                            branch,
+                            &self.cov_fn_ident,
+                            Some(idx),
+                        );
+
+                        Stmt::Expr(ExprStmt {
+                            span: swc_common::DUMMY_SP,
+                            expr: Box::new(expr),
+                        })
+                    });
+
+                    let body = if let Stmt::Block(mut block_stmt) = stmt_body {
+                        // if cons / alt is already blockstmt, insert stmt counter for each
+                        self.insert_stmts_counter(&mut block_stmt.stmts);
+
+                        let mut new_stmts: Vec<Stmt> = expr.into_iter().collect();
+                        new_stmts.append(&mut block_stmt.stmts);
+
+                        block_stmt.stmts = new_stmts;
+                        block_stmt
+                    } else {
+                        let mut stmts: Vec<Stmt> = expr.into_iter().collect();
+                        let mut visitor = self.create_child_visitor(ignore_current);
+                        stmt_body.visit_mut_with(&mut visitor);
+                        stmts.append(&mut visitor.before);
+
+                        stmts.push(stmt_body);
+
+                        BlockStmt {
+                            span: swc_common::DUMMY_SP,
+                            stmts,
+                        }
+                    };
+
+                    **stmt = Stmt::Block(body);
+                };
+
+                // Note: unlike upstream, we do not use setAttr-based approach as it is not easy to
+                // append arbitary dynamic metadata on the parents can be accessed in any childs.
+                if ignore_current != Some(IgnoreScope::If) {
+                    wrap_with_counter(&mut if_stmt.cons);
+                }
+
+                if ignore_current != Some(IgnoreScope::Else) {
+                    if let Some(alt) = &mut if_stmt.alt {
+                        wrap_with_counter(alt);
+                    } else if branch.is_some() {
+                        // alt can be none (`if some {}` without else).
+                        // Inject empty blockstmt then insert branch counters
+                        let mut alt = Box::new(Stmt::Block(BlockStmt::dummy()));
+                        wrap_with_counter(&mut alt);
+                        if_stmt.alt = Some(alt);
+                    }
+                }
+
+                self.on_exit(old);
+            }
+        };
+    }
+
+    // ConditionalExpression: entries(coverTernary),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_cond_expr(&mut self, cond_expr: &mut CondExpr) {
+        let (old, ignore_current) = self.on_enter(cond_expr);
+
+        // Branch is created before visiting the children, so the chained ternaries
+        // (`a ? x : b ? y : z`) have the branches in the order of the appearance.
+        let branch = match ignore_current {
+            Some(IgnoreScope::Next) => None,
+            _ if !self
+                .instrument_options
+                .should_cover_branch(&crate::BranchType::CondExpr) =>
+            {
+                None
+            }
+            _ if self.is_unmapped_span(&cond_expr.span) => None,
+            _ => {
+                let range =
+                    crate::lookup_range::get_range_from_span(&self.source_map, &cond_expr.span);
+                Some(
+                    self.cov
+                        .borrow_mut()
+                        .new_branch(crate::BranchType::CondExpr, &range, false),
+                )
+            }
+        };
+
+        // Visit the children before wrapping consequence / alternate with the counters,
+        // otherwise the counters are visited as a part of the children.
+        cond_expr.visit_mut_children_with(self);
+
+        if let Some(branch) = branch {
+            let c_hint = crate::hint_comments::lookup_hint_comments(
+                &self.comments,
+                crate::lookup_range::get_expr_span(&cond_expr.cons),
+            );
+            let a_hint = crate::hint_comments::lookup_hint_comments(
+                &self.comments,
+                crate::lookup_range::get_expr_span(&cond_expr.alt),
+            );
+
+            // `ignore if` / `ignore else` hints before the ternary only applies to its own
+            // consequence / alternate, not to the nested ternaries.
+            if ignore_current != Some(IgnoreScope::If) && c_hint.as_deref() != Some("next") {
+                // replace consequence to the paren for increase expr + expr itself
+                self.replace_expr_with_branch_counter(&mut cond_expr.cons, branch);
+            }
+
+            if ignore_current != Some(IgnoreScope::Else) && a_hint.as_deref() != Some("next") {
+                self.replace_expr_with_branch_counter(&mut cond_expr.alt, branch);
+            }
+        }
+
+        self.on_exit(old);
+    }
+
+    // Babel doesn't have a node for the parenthesized expression, hint comment before the paren
+    // is a leading comment of the inner expression: `return /* istanbul ignore next */ (a ? b : c)`
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_paren_expr(&mut self, paren_expr: &mut ParenExpr) {
+        let (old, _ignore_current) = self.on_enter(paren_expr);
+        paren_expr.visit_mut_children_with(self);
+        self.on_exit(old);
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_tpl(&mut self, tpl: &mut Tpl) {
+        for (quasi, expr) in tpl.quasis.iter_mut().zip(tpl.exprs.iter_mut()) {
+            quasi.visit_mut_with(self);
+
+            // Parser attaches the comment in `${ /* istanbul ignore next */ expr }` as a trailing
+            // comment of `${`, which ends 2 bytes after the quasi.
+            let hint = crate::hint_comments::lookup_trailing_hint_comments(
+                &self.comments,
+                quasi.span.hi + swc_common::BytePos(2),
+            );
+
+            if hint.as_deref() == Some("next") {
+                let old = self.should_ignore;
+                self.should_ignore = Some(IgnoreScope::Next);
+                expr.visit_mut_with(self);
+                self.should_ignore = old;
+            } else {
+                expr.visit_mut_with(self);
+            }
+        }
+
+        tpl.quasis
+            .iter_mut()
+            .skip(tpl.exprs.len())
+            .for_each(|quasi| quasi.visit_mut_with(self));
+    }
+
+    // LogicalExpression: entries(coverLogicalExpression)
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_bin_expr(&mut self, bin_expr: &mut BinExpr) {
+        // We don't use self.on_enter() here since Node::LogicalExpr is a dialect of BinExpr
+        // which we can't pass directly via on_enter()
+        let old = self.should_ignore;
+        let ignore_current = match old {
+            Some(IgnoreScope::Next) => old,
+            _ => {
+                self.should_ignore =
+                    crate::hint_comments::should_ignore(&self.comments, Some(&bin_expr.span));
+                self.should_ignore
+            }
+        };
+
+        match ignore_current {
+            Some(IgnoreScope::Next) => {
+                self.nodes.push(crate::Node::BinExpr);
+                bin_expr.visit_mut_children_with(self);
+                self.on_exit(old);
+            }
+            _ => {
+                match &bin_expr.op {
+                    BinaryOp::LogicalOr | BinaryOp::LogicalAnd | BinaryOp::NullishCoalescing
+                        if self
+                            .instrument_options
+                            .should_cover_branch(&crate::BranchType::BinaryExpr)
+                            && !self.is_unmapped_span(&bin_expr.span) =>
+                    {
+                        self.nodes.push(crate::Node::LogicalExpr);
+
+                        // Create a new branch. This id should be reused for any inner logical expr.
+                        let range = crate::lookup_range::get_range_from_span(
+                            &self.source_map,
+                            &bin_expr.span,
+                        );
+                        let branch = self.cov.borrow_mut().new_branch(
+                            crate::BranchType::BinaryExpr,
+                            &range,
+                            self.instrument_options.report_logic,
+                        );
+
+                        // Iterate over each expr, wrap it with branch counter.
+                        self.wrap_bin_expr_with_branch_counter(branch, &mut bin_expr.left);
+                        self.wrap_bin_expr_with_branch_counter(branch, &mut bin_expr.right);
+                        self.on_exit(old);
+                    }
+                    _ => {
+                        // iterate as normal for non loigical expr
+                        self.nodes.push(crate::Node::BinExpr);
+                        bin_expr.visit_mut_children_with(self);
+                        self.on_exit(old);
+                    }
+                }
+            }
+        }
+    }
+
+    // AssignmentPattern: entries(coverAssignmentPattern),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_assign_pat(&mut self, assign_pat: &mut AssignPat) {
+        let (old, ignore_current) = self.on_enter(assign_pat);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ if !self
+                .instrument_options
+                .should_cover_branch(&crate::BranchType::DefaultArg)
+                || self.is_unmapped_span(&assign_pat.span) =>
+            {
+                assign_pat.visit_mut_children_with(self);
+            }
+            _ => {
+                let range =
+                    crate::lookup_range::get_range_from_span(&self.source_map, &assign_pat.span);
+                let branch =
+                    self.cov
+                        .borrow_mut()
+                        .new_branch(crate::BranchType::DefaultArg, &range, false);
+
+                // Nested patterns can have defaults of their own (`{ a = 1 } = {}`).
+                assign_pat.left.visit_mut_with(self);
+                self.wrap_bin_expr_with_branch_counter(branch, &mut assign_pat.right);
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // ObjectProperty of AssignmentPattern value (`{ a = 1 }`): entries(coverAssignmentPattern),
+    // shorthand defaults of the object patterns, i.e in the params or the catch clause param.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_assign_pat_prop(&mut self, assign_pat_prop: &mut AssignPatProp) {
+        let (old, ignore_current) = self.on_enter(assign_pat_prop);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ if assign_pat_prop.value.is_none()
+                || !self
+                    .instrument_options
+                    .should_cover_branch(&crate::BranchType::DefaultArg)
+                || self.is_unmapped_span(&assign_pat_prop.span) =>
+            {
+                assign_pat_prop.visit_mut_children_with(self);
+            }
+            _ => {
+                let range = crate::lookup_range::get_range_from_span(
+                    &self.source_map,
+                    &assign_pat_prop.span,
+                );
+                let branch =
+                    self.cov
+                        .borrow_mut()
+                        .new_branch(crate::BranchType::DefaultArg, &range, false);
+
+                if let Some(value) = &mut assign_pat_prop.value {
+                    self.wrap_bin_expr_with_branch_counter(branch, value);
+                }
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // TryStatement: entries(coverStatement),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_try_stmt(&mut self, try_stmt: &mut TryStmt) {
+        let (old, ignore_current) = self.on_enter(try_stmt);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                self.mark_prepend_stmt_counter(&try_stmt.span);
+                try_stmt.visit_mut_children_with(self);
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // ThrowStatement: entries(coverStatement),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_throw_stmt(&mut self, throw_stmt: &mut ThrowStmt) {
+        let (old, ignore_current) = self.on_enter(throw_stmt);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                self.mark_prepend_stmt_counter(&throw_stmt.span);
+                throw_stmt.visit_mut_children_with(self);
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // WithStatement: entries(blockProp('body'), coverStatement),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_with_stmt(&mut self, with_stmt: &mut WithStmt) {
+        let (old, ignore_current) = self.on_enter(with_stmt);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                self.mark_prepend_stmt_counter(&with_stmt.span);
+
+                // Visit the object first, as it is evaluated before the body.
+                with_stmt.obj.visit_mut_with(self);
+
+                //TODO: duplicated codes for wrapping block
+                if let Stmt::Block(body_block) = &mut *with_stmt.body {
+                    self.insert_stmts_counter(&mut body_block.stmts);
+                } else {
+                    let mut visitor = self.create_child_visitor(ignore_current);
+                    with_stmt.body.visit_mut_with(&mut visitor);
+                    let mut new_stmts = vec![];
+                    new_stmts.append(&mut visitor.before);
+                    new_stmts.push(*with_stmt.body.take());
+
+                    *with_stmt.body = Stmt::Block(BlockStmt {
+                        span: swc_common::DUMMY_SP,
+                        stmts: new_stmts,
+                    });
+                }
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // SwitchCase: entries(coverSwitchCase),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_switch_case(&mut self, switch_case: &mut SwitchCase) {
+        let branch = match self.switch_branch {
+            Some(branch) => branch,
+            None => {
+                switch_case.visit_mut_children_with(self);
+                return;
+            }
+        };
+
+        let (old, ignore_current) = self.on_enter(switch_case);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                // TODO: conslidate brach expr creation, i.e ifstmt
+                let range =
+                    crate::lookup_range::get_range_from_span(&self.source_map, &switch_case.span);
+                let idx = self.cov.borrow_mut().add_branch_path(branch, &range);
+                let expr = crate::create_increase_counter_expr(
+                    &IDENT_B,
+                    branch,
+                    &self.cov_fn_ident,
+                    Some(idx),
+                );
+
+                switch_case.visit_mut_children_with(self);
+
+                let expr = Stmt::Expr(ExprStmt {
+                    span: swc_common::DUMMY_SP,
+                    expr: Box::new(expr),
+                });
+
+                let mut new_stmts = vec![expr];
+                new_stmts.append(&mut switch_case.cons);
+
+                switch_case.cons = new_stmts;
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // Module items of the file, or nested module items (ts namespaces, ambient modules).
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+        let mut new_items = vec![];
+        for mut item in items.drain(..) {
+            let (old, _ignore_current) = match &item {
+                ModuleItem::ModuleDecl(decl) => self.on_enter(decl),
+                ModuleItem::Stmt(stmt) => self.on_enter(stmt),
+            };
+            item.visit_mut_children_with(self);
+
+            new_items.extend(self.before.drain(..).map(ModuleItem::Stmt));
+            new_items.push(item);
+            self.on_exit(old);
+        }
+        *items = new_items;
+    }
+
+    // ExportDefaultDeclaration: entries(), // ignore processing only
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_export_default_decl(&mut self, export_default_decl: &mut ExportDefaultDecl) {
+        let (old, ignore_current) = self.on_enter(export_default_decl);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                // noop
+                export_default_decl.visit_mut_children_with(self);
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // ExportNamedDeclaration: entries(), // ignore processing only
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_export_decl(&mut self, export_named_decl: &mut ExportDecl) {
+        let (old, ignore_current) = self.on_enter(export_named_decl);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                // noop
+                export_named_decl.visit_mut_children_with(self);
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // ExportAllDeclaration: entries(), counted as a statement if `coverReExports` is set.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_export_all(&mut self, export_all: &mut ExportAll) {
+        let (old, ignore_current) = self.on_enter(export_all);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ if self.instrument_options.cover_re_exports => {
+                self.mark_prepend_stmt_counter(&export_all.span);
+            }
+            _ => {}
+        }
+        self.on_exit(old);
+    }
+
+    // ExportNamedDeclaration with source: entries(), counted as a statement if `coverReExports` is set.
+    // Local exports (`export { a }`) and type only re-exports are not counted.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_named_export(&mut self, named_export: &mut NamedExport) {
+        let (old, ignore_current) = self.on_enter(named_export);
+        let is_type_only = named_export.type_only
+            || (!named_export.specifiers.is_empty()
+                && named_export.specifiers.iter().all(|specifier| {
+                    matches!(
+                        specifier,
+                        ExportSpecifier::Named(ExportNamedSpecifier {
+                            is_type_only: true,
+                            ..
+                        })
+                    )
+                }));
+
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ if self.instrument_options.cover_re_exports
+                && named_export.src.is_some()
+                && !is_type_only =>
+            {
+                self.mark_prepend_stmt_counter(&named_export.span);
+            }
+            _ => {}
+        }
+        self.on_exit(old);
+    }
+
+    // TSExportAssignment: entries(coverStatement),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_ts_export_assignment(&mut self, export_assignment: &mut TsExportAssignment) {
+        let (old, ignore_current) = self.on_enter(export_assignment);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                self.mark_prepend_stmt_counter(&export_assignment.span);
+                export_assignment.visit_mut_children_with(self);
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // TSImportEqualsDeclaration: entries(coverStatement),
+    // Only `import x = require()` is covered, as an alias to the namespace may not be emitted.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_ts_import_equals_decl(&mut self, import_equals_decl: &mut TsImportEqualsDecl) {
+        let (old, ignore_current) = self.on_enter(import_equals_decl);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                let is_require = matches!(
+                    import_equals_decl.module_ref,
+                    TsModuleRef::TsExternalModuleRef(..)
+                );
+
+                if is_require && !import_equals_decl.declare && !import_equals_decl.is_type_only {
+                    self.mark_prepend_stmt_counter(&import_equals_decl.span);
+                }
+            }
+        }
+        self.on_exit(old);
+    }
+
+    // DebuggerStatement: entries(coverStatement),
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(node = %self.print_node())))]
+    fn visit_mut_debugger_stmt(&mut self, debugger_stmt: &mut DebuggerStmt) {
+        let (old, ignore_current) = self.on_enter(debugger_stmt);
+        match ignore_current {
+            Some(IgnoreScope::Next) => {}
+            _ => {
+                debugger_stmt.visit_mut_children_with(self);
+            }
+        }
+        self.on_exit(old);
+    }
+}
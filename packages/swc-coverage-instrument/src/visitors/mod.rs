@@ -1,5 +1,10 @@
+pub mod annotate_counters_visitor;
 pub mod coverage_visitor;
 pub mod finders;
-pub mod logical_expr_visitor;
-pub mod stmt_like_visitor;
-pub mod switch_case_visitor;
+pub mod global_hit_counters_visitor;
+pub mod hoist_counters_visitor;
+pub mod instrumentation_visitor;
+pub mod multiplex_counters_visitor;
+pub mod on_cover_visitor;
+pub mod skip_counters_visitor;
+pub mod strip_counters_visitor;
@@ -0,0 +1,64 @@
+use swc_common::{util::take::Take, DUMMY_SP};
+use swc_ecmascript::{
+    ast::*,
+    visit::{noop_visit_mut_type, VisitMut, VisitMutWith},
+};
+
+/// A visitor to make each counter (`cov_xyz().s[0]++`) increase the same counter of
+/// the coverage fns for the additional coverage variables as well,
+/// like `(cov_xyz().s[0]++, cov_xyz_1().s[0]++)`.
+pub struct MultiplexCountersVisitor {
+    // coverage fn's ident and the idents of the coverage fns to multiplex its counters into
+    coverages: Vec<(Ident, Vec<Ident>)>,
+}
+
+impl MultiplexCountersVisitor {
+    pub fn new(coverages: Vec<(Ident, Vec<Ident>)>) -> MultiplexCountersVisitor {
+        MultiplexCountersVisitor { coverages }
+    }
+}
+
+impl VisitMut for MultiplexCountersVisitor {
+    noop_visit_mut_type!();
+
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        let multiplexed =
+            crate::parse_increase_counter_expr(expr).and_then(|(var_name, counter, id, idx)| {
+                let (_, multiplexed_fn_idents) = self
+                    .coverages
+                    .iter()
+                    .find(|(cov_fn_ident, _)| cov_fn_ident.sym == var_name.sym)?;
+                let counter = Ident::new(counter.into(), DUMMY_SP);
+
+                Some(
+                    multiplexed_fn_idents
+                        .iter()
+                        .map(|cov_fn_ident| {
+                            Box::new(crate::create_increase_counter_expr(
+                                &counter,
+                                id,
+                                cov_fn_ident,
+                                idx,
+                            ))
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            });
+
+        if let Some(multiplexed) = multiplexed {
+            let mut exprs = vec![Box::new(expr.take())];
+            exprs.extend(multiplexed);
+
+            *expr = Expr::Paren(ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Seq(SeqExpr {
+                    span: DUMMY_SP,
+                    exprs,
+                })),
+            });
+            return;
+        }
+
+        expr.visit_mut_children_with(self);
+    }
+}
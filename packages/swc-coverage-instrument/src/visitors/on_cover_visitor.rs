@@ -0,0 +1,90 @@
+use swc_common::{util::take::Take, DUMMY_SP};
+use swc_ecmascript::{
+    ast::*,
+    visit::{noop_visit_mut_type, VisitMut, VisitMutWith},
+};
+
+/// A visitor to make each counter (`cov_xyz().s[0]++`) call the `onCover` fn as well,
+/// like `(cov_xyz().s[0]++, cov_xyz_onCover("path", "s", 0))`.
+/// Branch counters pass the index of the path as the last argument.
+pub struct OnCoverVisitor {
+    on_cover_ident: Ident,
+    // coverage fn's ident and the path of its coverage
+    coverages: Vec<(Ident, String)>,
+    pub referenced: bool,
+}
+
+impl OnCoverVisitor {
+    pub fn new(on_cover_ident: &Ident, coverages: Vec<(Ident, String)>) -> OnCoverVisitor {
+        OnCoverVisitor {
+            on_cover_ident: on_cover_ident.clone(),
+            coverages,
+            referenced: false,
+        }
+    }
+
+    /// Returns the path of the coverage, the type of the counter, the id of the counter
+    /// and the index of the branch path if given expr is a counter.
+    fn as_counter(&self, expr: &Expr) -> Option<(String, String, f64, Option<f64>)> {
+        let (var_name, counter, id, idx) = crate::parse_increase_counter_expr(expr)?;
+
+        self.coverages
+            .iter()
+            .find(|(cov_fn_ident, _)| cov_fn_ident.sym == var_name.sym)
+            .map(|(_, file_path)| {
+                (
+                    file_path.clone(),
+                    counter.to_string(),
+                    id as f64,
+                    idx.map(|idx| idx as f64),
+                )
+            })
+    }
+}
+
+impl VisitMut for OnCoverVisitor {
+    noop_visit_mut_type!();
+
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if let Some((file_path, counter, id, idx)) = self.as_counter(expr) {
+            let mut args = vec![
+                Expr::Lit(Lit::Str(Str {
+                    value: file_path.into(),
+                    ..Str::dummy()
+                })),
+                Expr::Lit(Lit::Str(Str {
+                    value: counter.into(),
+                    ..Str::dummy()
+                })),
+                Expr::Lit(Lit::Num(Number::from(id))),
+            ];
+            if let Some(idx) = idx {
+                args.push(Expr::Lit(Lit::Num(Number::from(idx))));
+            }
+
+            let call = Expr::Call(CallExpr {
+                callee: Callee::Expr(Box::new(Expr::Ident(self.on_cover_ident.clone()))),
+                args: args
+                    .into_iter()
+                    .map(|arg| ExprOrSpread {
+                        spread: None,
+                        expr: Box::new(arg),
+                    })
+                    .collect(),
+                ..CallExpr::dummy()
+            });
+
+            *expr = Expr::Paren(ParenExpr {
+                span: DUMMY_SP,
+                expr: Box::new(Expr::Seq(SeqExpr {
+                    span: DUMMY_SP,
+                    exprs: vec![Box::new(expr.take()), Box::new(call)],
+                })),
+            });
+            self.referenced = true;
+            return;
+        }
+
+        expr.visit_mut_children_with(self);
+    }
+}
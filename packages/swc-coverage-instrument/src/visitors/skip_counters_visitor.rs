@@ -0,0 +1,85 @@
+use swc_ecmascript::{
+    ast::*,
+    visit::{noop_visit_mut_type, VisitMut, VisitMutWith},
+};
+
+use crate::source_coverage::SkippedEntries;
+
+/// A visitor to remove the counters of the skipped entries (i.e in the region of
+/// `coverage-off` comments) from the instrumented code, leaving the code of the entries as-is.
+pub struct SkipCountersVisitor {
+    // coverage fn's ident and the entries skipped in its coverage
+    coverages: Vec<(Ident, SkippedEntries)>,
+}
+
+impl SkipCountersVisitor {
+    pub fn new(coverages: Vec<(Ident, SkippedEntries)>) -> SkipCountersVisitor {
+        SkipCountersVisitor { coverages }
+    }
+
+    fn is_skipped_counter(&self, expr: &Expr) -> bool {
+        let (var_name, counter, id, _) = match crate::parse_increase_counter_expr(expr) {
+            Some(counter) => counter,
+            None => return false,
+        };
+
+        self.coverages
+            .iter()
+            .filter(|(cov_fn_ident, _)| cov_fn_ident.sym == var_name.sym)
+            .any(|(_, skipped)| match counter {
+                "s" => skipped.s.contains(&id),
+                "f" => skipped.f.contains(&id),
+                "b" | "bT" => skipped.b.contains(&id),
+                _ => false,
+            })
+    }
+
+    fn is_skipped_counter_stmt(&self, stmt: &Stmt) -> bool {
+        match stmt {
+            Stmt::Expr(ExprStmt { expr, .. }) => self.is_skipped_counter(expr),
+            _ => false,
+        }
+    }
+}
+
+impl VisitMut for SkipCountersVisitor {
+    noop_visit_mut_type!();
+
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        // Logical truthiness of the skipped branch, `(cov().temp = expr, (... ? cov().bT[0][0]++ : null), cov().temp)`
+        // created by `create_increase_true_expr`, is replaced with the expr.
+        if let Some((_, value)) = crate::parse_increase_true_expr(expr)
+            .filter(|(counter, _)| self.is_skipped_counter(counter))
+        {
+            *expr = value.clone();
+            self.visit_mut_expr(expr);
+            return;
+        }
+
+        expr.visit_mut_children_with(self);
+
+        if let Expr::Seq(SeqExpr { exprs, .. }) = expr {
+            if exprs.iter().all(|expr| self.is_skipped_counter(expr)) {
+                return;
+            }
+
+            exprs.retain(|expr| !self.is_skipped_counter(expr));
+            if exprs.len() == 1 {
+                *expr = *exprs.remove(0);
+            }
+        }
+    }
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        stmts.visit_mut_children_with(self);
+        stmts.retain(|stmt| !self.is_skipped_counter_stmt(stmt));
+    }
+
+    fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+        items.visit_mut_children_with(self);
+        items.retain(|item| match item {
+            ModuleItem::Stmt(stmt) => !self.is_skipped_counter_stmt(stmt),
+            _ => true,
+        });
+    }
+}
@@ -0,0 +1,124 @@
+use swc_common::util::take::Take;
+use swc_ecmascript::{
+    ast::*,
+    visit::{noop_visit_mut_type, VisitMut, VisitMutWith},
+};
+
+/// A visitor to revert the instrumentation for `selfCheck`: removes all the counters, and
+/// the nodes created to hold them (parens, blocks of the single statement body, block body of the arrow fn).
+/// Nodes created by the instrumentation have a dummy span, unlike the nodes from the input.
+/// Result should be identical to the input except spans, unless the instrumentation altered the code.
+pub struct StripCountersVisitor {
+    // coverage fn's idents of the file, or each module of the file
+    cov_fn_idents: Vec<Ident>,
+}
+
+impl StripCountersVisitor {
+    pub fn new(cov_fn_idents: Vec<Ident>) -> StripCountersVisitor {
+        StripCountersVisitor { cov_fn_idents }
+    }
+
+    fn is_counter(&self, expr: &Expr) -> bool {
+        crate::parse_increase_counter_expr(expr).is_some_and(|(var_name, ..)| {
+            self.cov_fn_idents
+                .iter()
+                .any(|cov_fn_ident| cov_fn_ident.sym == var_name.sym)
+        })
+    }
+
+    fn is_counter_stmt(&self, stmt: &Stmt) -> bool {
+        match stmt {
+            Stmt::Expr(ExprStmt { expr, .. }) => self.is_counter(expr),
+            _ => false,
+        }
+    }
+}
+
+impl VisitMut for StripCountersVisitor {
+    noop_visit_mut_type!();
+
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        if let Some((_, value)) =
+            crate::parse_increase_true_expr(expr).filter(|(counter, _)| self.is_counter(counter))
+        {
+            *expr = value.clone();
+            self.visit_mut_expr(expr);
+            return;
+        }
+
+        expr.visit_mut_children_with(self);
+
+        match expr {
+            Expr::Seq(SeqExpr { exprs, .. }) => {
+                exprs.retain(|expr| !self.is_counter(expr));
+                if exprs.len() == 1 {
+                    *expr = *exprs.remove(0);
+                }
+            }
+            Expr::Paren(ParenExpr { span, expr: inner }) if span.is_dummy() => {
+                *expr = *inner.take();
+            }
+            // Loop condition is converted into `(test) ? true : false` to hold the branch counters.
+            Expr::Cond(CondExpr {
+                span,
+                test,
+                cons,
+                alt,
+            }) if span.is_dummy()
+                && matches!(&**cons, Expr::Lit(Lit::Bool(Bool { value: true, .. })))
+                && matches!(&**alt, Expr::Lit(Lit::Bool(Bool { value: false, .. }))) =>
+            {
+                *expr = *test.take();
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_mut_arrow_expr(&mut self, arrow_expr: &mut ArrowExpr) {
+        arrow_expr.visit_mut_children_with(self);
+
+        // Expression body is converted into the block body returns the expr.
+        if let BlockStmtOrExpr::BlockStmt(BlockStmt { span, stmts }) = &mut arrow_expr.body {
+            if span.is_dummy() {
+                if let [Stmt::Return(ReturnStmt { arg: Some(arg), .. })] = &mut stmts[..] {
+                    arrow_expr.body = BlockStmtOrExpr::Expr(arg.take());
+                }
+            }
+        }
+    }
+
+    fn visit_mut_stmt(&mut self, stmt: &mut Stmt) {
+        stmt.visit_mut_children_with(self);
+
+        // Single statement body (i.e `if (a) b;`) is wrapped into the block with the counters.
+        if let Stmt::Block(BlockStmt { span, stmts }) = stmt {
+            if span.is_dummy() && stmts.len() == 1 {
+                *stmt = stmts.remove(0);
+            }
+        }
+    }
+
+    fn visit_mut_if_stmt(&mut self, if_stmt: &mut IfStmt) {
+        if_stmt.visit_mut_children_with(self);
+
+        // Empty alt is injected to hold the branch counter if there's no `else`.
+        if let Some(Stmt::Block(BlockStmt { span, stmts })) = if_stmt.alt.as_deref() {
+            if span.is_dummy() && stmts.is_empty() {
+                if_stmt.alt = None;
+            }
+        }
+    }
+
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        stmts.visit_mut_children_with(self);
+        stmts.retain(|stmt| !self.is_counter_stmt(stmt));
+    }
+
+    fn visit_mut_module_items(&mut self, items: &mut Vec<ModuleItem>) {
+        items.visit_mut_children_with(self);
+        items.retain(|item| match item {
+            ModuleItem::Stmt(stmt) => !self.is_counter_stmt(stmt),
+            _ => true,
+        });
+    }
+}
@@ -0,0 +1,23 @@
+fn main() {
+    #[cfg(feature = "capi")]
+    generate_header();
+}
+
+/// Generates the header of the C ABI into `include/swc_coverage.h`.
+#[cfg(feature = "capi")]
+fn generate_header() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("Should be set by cargo");
+
+    println!("cargo:rerun-if-changed=src/capi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(
+            cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+                .expect("Should read cbindgen.toml"),
+        )
+        .generate()
+        .expect("Should generate the header")
+        .write_to_file(format!("{}/include/swc_coverage.h", crate_dir));
+}
@@ -0,0 +1,56 @@
+#ifndef SWC_COVERAGE_H
+#define SWC_COVERAGE_H
+
+/* Generated by cbindgen from src/capi.rs, do not edit manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Instruments `code` of the file `filename`. `options_json` is the JSON of the instrumentation
+// options same as the plugin's, or null for the default options. On success, instrumented code
+// and its source map (JSON) are written into `out_code` and `out_map`. Unknown keys of the options
+// are ignored, with the warnings available from [swc_coverage_last_warnings].
+//
+// # Safety
+// Strings should be nul terminated, out pointers should be valid to write.
+int swc_coverage_instrument(const char *code,
+                            const char *filename,
+                            const char *options_json,
+                            char **out_code,
+                            char **out_map);
+
+// Merges `len` coverage JSONs (`coverage-final.json`, or the coverage object collected from
+// the runtime) of `coverage_jsons` into one, written into `out_json`.
+//
+// # Safety
+// `coverage_jsons` should point `len` nul terminated strings, `out_json` should be valid to write.
+int swc_coverage_merge_coverage_json(const char *const *coverage_jsons,
+                                     size_t len,
+                                     char **out_json);
+
+// Summarizes the coverage JSON, writes the JSON of the summary of all files into `out_json`
+// (`lines`, `statements`, `functions`, `branches` with `total`, `covered`, `skipped`, `pct`),
+// same as the `total` of `coverage-summary.json`.
+//
+// # Safety
+// `coverage_json` should be nul terminated, `out_json` should be valid to write.
+int swc_coverage_summarize_json(const char *coverage_json, char **out_json);
+
+// Message of the last failure on the calling thread, or null if nothing failed yet.
+// Valid until the next failure on the thread, should not be freed.
+const char *swc_coverage_last_error(void);
+
+// Warnings of the last [swc_coverage_instrument] call on the calling thread (unknown keys of
+// the options), one per line, or null if there was none.
+// Valid until the next [swc_coverage_instrument] call on the thread, should not be freed.
+const char *swc_coverage_last_warnings(void);
+
+// Frees the string returned from this library. Null is ignored.
+//
+// # Safety
+// `value` should be the one returned from this library, not freed yet.
+void swc_coverage_string_free(char *value);
+
+#endif /* SWC_COVERAGE_H */
@@ -0,0 +1,339 @@
+//! C ABI of the instrumentation and the coverage utilities, for the build tools embedding
+//! this crate without Node.js or Rust (bazel rules, JVM build tools through JNA / Panama).
+//!
+//! Functions take nul terminated utf8 strings, and return `0` on success or `-1` on failure.
+//! Output strings are allocated by this library, free them with [swc_coverage_string_free].
+//! Message of the last failure on the calling thread is available from [swc_coverage_last_error],
+//! warnings of the last instrumentation from [swc_coverage_last_warnings].
+//! Header is generated by cbindgen into `include/swc_coverage.h` when building with `capi` feature.
+use std::{
+    cell::RefCell,
+    ffi::{CStr, CString},
+    os::raw::{c_char, c_int},
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr,
+};
+
+use istanbul_oxide::CoverageMap;
+
+use crate::pipeline::{get_syntax, instrument, parse_instrument_options};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+    static LAST_WARNINGS: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn to_c_string(message: String) -> CString {
+    CString::new(message.replace('\0', " ")).expect("Should not contain nul")
+}
+
+fn set_last_error(message: String) {
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(to_c_string(message)));
+}
+
+fn set_last_warnings(warnings: Vec<String>) {
+    let warnings = (!warnings.is_empty()).then(|| to_c_string(warnings.join("\n")));
+    LAST_WARNINGS.with(|last_warnings| *last_warnings.borrow_mut() = warnings);
+}
+
+/// Runs given fn, converting the error or the panic into `-1` with the last error set.
+fn run<F: FnOnce() -> Result<(), String>>(f: F) -> c_int {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => 0,
+        Ok(Err(message)) => {
+            set_last_error(message);
+            -1
+        }
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "Unknown panic".to_string());
+            set_last_error(format!("Panicked: {}", message));
+            -1
+        }
+    }
+}
+
+/// Reads a nul terminated utf8 string, `name` is the name of the argument for the error message.
+unsafe fn read_str<'a>(value: *const c_char, name: &str) -> Result<&'a str, String> {
+    if value.is_null() {
+        return Err(format!("{} should not be null", name));
+    }
+
+    CStr::from_ptr(value)
+        .to_str()
+        .map_err(|err| format!("{} is not a valid utf8: {}", name, err))
+}
+
+/// Writes the string into the out pointer, ownership is transferred to the caller.
+unsafe fn write_str(out: *mut *mut c_char, value: String, name: &str) -> Result<(), String> {
+    if out.is_null() {
+        return Err(format!("{} should not be null", name));
+    }
+
+    let value = CString::new(value).map_err(|err| format!("Invalid output: {}", err))?;
+    *out = value.into_raw();
+    Ok(())
+}
+
+/// Instruments `code` of the file `filename`. `options_json` is the JSON of the instrumentation
+/// options same as the plugin's, or null for the default options. On success, instrumented code
+/// and its source map (JSON) are written into `out_code` and `out_map`. Unknown keys of the options
+/// are ignored, with the warnings available from [swc_coverage_last_warnings].
+///
+/// # Safety
+/// Strings should be nul terminated, out pointers should be valid to write.
+#[no_mangle]
+pub unsafe extern "C" fn swc_coverage_instrument(
+    code: *const c_char,
+    filename: *const c_char,
+    options_json: *const c_char,
+    out_code: *mut *mut c_char,
+    out_map: *mut *mut c_char,
+) -> c_int {
+    set_last_warnings(vec![]);
+    run(|| {
+        let code = read_str(code, "code")?;
+        let filename = read_str(filename, "filename")?;
+        let options = if options_json.is_null() {
+            None
+        } else {
+            Some(
+                serde_json::from_str(read_str(options_json, "options_json")?)
+                    .map_err(|err| format!("options_json is not a valid JSON: {}", err))?,
+            )
+        };
+        let (instrument_options, warnings) = parse_instrument_options(options)?;
+        set_last_warnings(warnings);
+
+        let output = instrument(
+            code.to_string(),
+            filename,
+            instrument_options,
+            get_syntax(filename),
+        )?;
+        write_str(out_code, output.code, "out_code")?;
+        if let Err(err) = write_str(out_map, output.map, "out_map") {
+            swc_coverage_string_free(*out_code);
+            *out_code = ptr::null_mut();
+            return Err(err);
+        }
+        Ok(())
+    })
+}
+
+/// Merges `len` coverage JSONs (`coverage-final.json`, or the coverage object collected from
+/// the runtime) of `coverage_jsons` into one, written into `out_json`.
+///
+/// # Safety
+/// `coverage_jsons` should point `len` nul terminated strings, `out_json` should be valid to write.
+#[no_mangle]
+pub unsafe extern "C" fn swc_coverage_merge_coverage_json(
+    coverage_jsons: *const *const c_char,
+    len: usize,
+    out_json: *mut *mut c_char,
+) -> c_int {
+    run(|| {
+        if coverage_jsons.is_null() && len > 0 {
+            return Err("coverage_jsons should not be null".to_string());
+        }
+
+        let mut maps = vec![];
+        for index in 0..len {
+            let value = read_str(
+                *coverage_jsons.add(index),
+                &format!("coverage_jsons[{}]", index),
+            )?;
+            maps.push(CoverageMap::from_json(value).map_err(|err| {
+                format!("coverage_jsons[{}] is not a valid coverage: {}", index, err)
+            })?);
+        }
+
+        write_str(out_json, CoverageMap::merge_all(maps).to_json(), "out_json")
+    })
+}
+
+/// Summarizes the coverage JSON, writes the JSON of the summary of all files into `out_json`
+/// (`lines`, `statements`, `functions`, `branches` with `total`, `covered`, `skipped`, `pct`),
+/// same as the `total` of `coverage-summary.json`.
+///
+/// # Safety
+/// `coverage_json` should be nul terminated, `out_json` should be valid to write.
+#[no_mangle]
+pub unsafe extern "C" fn swc_coverage_summarize_json(
+    coverage_json: *const c_char,
+    out_json: *mut *mut c_char,
+) -> c_int {
+    run(|| {
+        let coverage = CoverageMap::from_json(read_str(coverage_json, "coverage_json")?)
+            .map_err(|err| format!("coverage_json is not a valid coverage: {}", err))?;
+        let summary = serde_json::to_string(&coverage.get_coverage_summary())
+            .map_err(|err| format!("Failed to serialize summary: {}", err))?;
+        write_str(out_json, summary, "out_json")
+    })
+}
+
+/// Message of the last failure on the calling thread, or null if nothing failed yet.
+/// Valid until the next failure on the thread, should not be freed.
+#[no_mangle]
+pub extern "C" fn swc_coverage_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| {
+        last_error
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Warnings of the last [swc_coverage_instrument] call on the calling thread (unknown keys of
+/// the options), one per line, or null if there was none.
+/// Valid until the next [swc_coverage_instrument] call on the thread, should not be freed.
+#[no_mangle]
+pub extern "C" fn swc_coverage_last_warnings() -> *const c_char {
+    LAST_WARNINGS.with(|last_warnings| {
+        last_warnings
+            .borrow()
+            .as_ref()
+            .map_or(ptr::null(), |warnings| warnings.as_ptr())
+    })
+}
+
+/// Frees the string returned from this library. Null is ignored.
+///
+/// # Safety
+/// `value` should be the one returned from this library, not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn swc_coverage_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::{CStr, CString},
+        os::raw::c_char,
+        ptr,
+    };
+
+    use super::*;
+
+    unsafe fn take_str(value: *mut c_char) -> String {
+        let result = CStr::from_ptr(value).to_str().unwrap().to_string();
+        swc_coverage_string_free(value);
+        result
+    }
+
+    #[test]
+    fn should_instrument() {
+        let code = CString::new("var a = 1;").unwrap();
+        let filename = CString::new("/a.js").unwrap();
+        let options = CString::new(r#"{"coverageVariable":"__cov__"}"#).unwrap();
+        let mut out_code = ptr::null_mut();
+        let mut out_map = ptr::null_mut();
+
+        unsafe {
+            assert_eq!(
+                swc_coverage_instrument(
+                    code.as_ptr(),
+                    filename.as_ptr(),
+                    options.as_ptr(),
+                    &mut out_code,
+                    &mut out_map,
+                ),
+                0
+            );
+            assert!(take_str(out_code).contains("__cov__"));
+            assert!(take_str(out_map).contains("\"mappings\""));
+        }
+    }
+
+    #[test]
+    fn should_set_last_warnings() {
+        let code = CString::new("var a = 1;").unwrap();
+        let filename = CString::new("/a.js").unwrap();
+        let options = CString::new(r#"{"coverageVarible":"__cov__"}"#).unwrap();
+        let mut out_code = ptr::null_mut();
+        let mut out_map = ptr::null_mut();
+
+        unsafe {
+            assert_eq!(
+                swc_coverage_instrument(
+                    code.as_ptr(),
+                    filename.as_ptr(),
+                    options.as_ptr(),
+                    &mut out_code,
+                    &mut out_map,
+                ),
+                0
+            );
+            take_str(out_code);
+            take_str(out_map);
+            let warnings = CStr::from_ptr(swc_coverage_last_warnings()).to_str().unwrap();
+            assert_eq!(warnings, "unknown option `coverageVarible` is ignored");
+
+            assert_eq!(
+                swc_coverage_instrument(
+                    code.as_ptr(),
+                    filename.as_ptr(),
+                    ptr::null(),
+                    &mut out_code,
+                    &mut out_map,
+                ),
+                0
+            );
+            take_str(out_code);
+            take_str(out_map);
+            assert!(swc_coverage_last_warnings().is_null());
+        }
+    }
+
+    #[test]
+    fn should_merge_and_summarize() {
+        let coverage = r#"{"/a.js":{"path":"/a.js","statementMap":{"0":{"start":{"line":1,"column":0},"end":{"line":1,"column":10}},"1":{"start":{"line":2,"column":0},"end":{"line":2,"column":10}}},"fnMap":{},"branchMap":{},"s":{"0":1,"1":0},"f":{},"b":{}}}"#;
+        let other = coverage.replace(r#""s":{"0":1,"1":0}"#, r#""s":{"0":0,"1":2}"#);
+        let coverages = [
+            CString::new(coverage).unwrap(),
+            CString::new(other).unwrap(),
+        ];
+        let pointers: Vec<*const c_char> = coverages.iter().map(|value| value.as_ptr()).collect();
+
+        unsafe {
+            let mut merged = ptr::null_mut();
+            assert_eq!(
+                swc_coverage_merge_coverage_json(pointers.as_ptr(), pointers.len(), &mut merged),
+                0
+            );
+            let merged = CString::new(take_str(merged)).unwrap();
+
+            let mut summary = ptr::null_mut();
+            assert_eq!(
+                swc_coverage_summarize_json(merged.as_ptr(), &mut summary),
+                0
+            );
+            let summary: serde_json::Value = serde_json::from_str(&take_str(summary)).unwrap();
+            assert_eq!(summary["statements"]["covered"], 2);
+            assert_eq!(summary["statements"]["total"], 2);
+        }
+    }
+
+    #[test]
+    fn should_set_last_error() {
+        let invalid = CString::new("{").unwrap();
+        let mut out = ptr::null_mut();
+
+        unsafe {
+            assert_eq!(swc_coverage_summarize_json(invalid.as_ptr(), &mut out), -1);
+            assert!(out.is_null());
+            let message = CStr::from_ptr(swc_coverage_last_error()).to_str().unwrap();
+            assert!(message.starts_with("coverage_json is not a valid coverage"));
+
+            assert_eq!(swc_coverage_summarize_json(ptr::null(), &mut out), -1);
+            let message = CStr::from_ptr(swc_coverage_last_error()).to_str().unwrap();
+            assert_eq!(message, "coverage_json should not be null");
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Single crate facade of `swc-coverage-instrument` and `istanbul-oxide`.
+//!
+//! Internal crates can have breaking changes without major semver bump, while this crate
+//! follows semver for the items re-exported here: removing or changing the signature of
+//! any of them, or upgrading the re-exported swc crates to an incompatible version,
+//! requires a major version bump. New options / items are added in minor versions.
+//!
+//! Visitor is typed against the swc crates re-exported as [swc_common] and [swc_ecmascript],
+//! use these instead of depending on the swc crates directly to avoid version mismatches.
+
+pub use swc_common;
+pub use swc_ecmascript;
+
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "pipeline")]
+pub mod pipeline;
+
+/// Instrumentation visitor and its options.
+pub mod instrument {
+    pub use swc_coverage_instrument::{
+        create_coverage_instrumentation_visitor, create_coverage_registry_module,
+        write_debug_snapshot, CachedInstrumentation, CoverageVisitor, DebugEmit, DebugOptions,
+        InstrumentBranchOptions, InstrumentCache, InstrumentLogOptions, InstrumentOptions,
+        InstrumentOptionsError, InstrumentStats, ModuleBoundary, OnCoverOptions,
+        COVERAGE_REGISTRY_EXPORT, DEBUG_SNAPSHOT_PREFIX, INSTRUMENT_STATS_PREFIX,
+    };
+}
+
+/// Reading coverage data, manifest attached to the instrumented output.
+pub mod comments {
+    pub use swc_coverage_instrument::{
+        extract_coverage_from_comments, extract_manifest_from_comments, strip_coverage_comments,
+        verify_coverage_manifest, CoverageManifestEntry, CoverageManifestMismatch,
+        COVERAGE_DATA_COMMENT_PREFIX, COVERAGE_MANIFEST_COMMENT_PREFIX,
+    };
+}
+
+/// Istanbul compatible coverage data.
+pub mod coverage {
+    pub use istanbul_oxide::{
+        canonicalize_path, parse_changed_lines, types::*, BranchDetail, CoverageMap,
+        CoveragePercentage, CoverageSession, CoverageSummary, EntryKind, FileCoverage,
+        FilePatchCoverage, FrozenFileCoverage, FunctionDetail, InvalidSession, LineDetail,
+        Location, MergeConflict, MergeKey, PatchCoverage, PathCanonicalizer, Range, SourceMap,
+        StatementDetail, Totals, UncoveredKind, UncoveredRange, UnusedExport, ValidationError,
+        INTEGRITY_KEY,
+    };
+}
+
+/// Reporters generating reports from the coverage data.
+pub mod report {
+    pub use istanbul_oxide::report::*;
+}
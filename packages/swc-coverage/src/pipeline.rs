@@ -0,0 +1,262 @@
+//! Instrumentation outside of swc's transform: parses the code, instruments it and emits the code
+//! with its source map. Shared by the bindings (C ABI, Node.js binding), code is emitted as-is other
+//! than the instrumentation, i.e types are not stripped from typescript.
+use std::{
+    io::{self, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use serde_json::Value;
+use swc_common::{
+    comments::SingleThreadedComments,
+    errors::{Handler, HANDLER},
+    FileName, FilePathMapping, SourceMap, GLOBALS,
+};
+use swc_coverage_instrument::{
+    create_coverage_instrumentation_visitor, write_debug_snapshot, DebugEmit, InstrumentOptions,
+};
+use swc_ecmascript::{
+    ast::{EsVersion, Program},
+    codegen::{text_writer::JsWriter, Emitter},
+    parser::{lexer::Lexer, EsConfig, Parser, StringInput, Syntax, TsConfig},
+    visit::VisitMutWith,
+};
+
+/// Instrumented code of the file.
+#[derive(Clone, Debug)]
+pub struct InstrumentOutput {
+    pub code: String,
+    /// Source map of the instrumented code, as a JSON string.
+    pub map: String,
+}
+
+/// Validates the JSON of the instrumentation options, same as the plugin's. `None` is the default options.
+/// Returns the options with the warnings for the ignored unknown keys, or the message of the invalid option.
+pub fn parse_instrument_options(
+    options: Option<Value>,
+) -> Result<(InstrumentOptions, Vec<String>), String> {
+    let options = match options {
+        Some(options) => options,
+        None => return Ok((Default::default(), vec![])),
+    };
+
+    let (options, unknown_keys) =
+        InstrumentOptions::from_json_value(options).map_err(|err| err.to_string())?;
+    let warnings = unknown_keys
+        .into_iter()
+        .map(|key| format!("unknown option `{}` is ignored", key))
+        .collect();
+
+    Ok((options, warnings))
+}
+
+/// Syntax of the name, one of `js`, `jsx`, `ts`, `tsx`.
+pub fn get_syntax_by_name(name: &str) -> Option<Syntax> {
+    match name {
+        "js" => Some(Syntax::Es(EsConfig {
+            decorators: true,
+            ..Default::default()
+        })),
+        "jsx" => Some(Syntax::Es(EsConfig {
+            jsx: true,
+            decorators: true,
+            ..Default::default()
+        })),
+        "ts" => Some(Syntax::Typescript(TsConfig {
+            decorators: true,
+            ..Default::default()
+        })),
+        "tsx" => Some(Syntax::Typescript(TsConfig {
+            tsx: true,
+            decorators: true,
+            ..Default::default()
+        })),
+        _ => None,
+    }
+}
+
+/// Determines the syntax to parse the code with, by the extension of the file.
+pub fn get_syntax(filename: &str) -> Syntax {
+    let syntax = match Path::new(filename)
+        .extension()
+        .and_then(|extension| extension.to_str())
+    {
+        Some("ts") | Some("mts") | Some("cts") => "ts",
+        Some("tsx") => "tsx",
+        _ => "jsx",
+    };
+
+    get_syntax_by_name(syntax).expect("Syntax should be known")
+}
+
+/// Collects diagnostics reported by the instrumentation.
+#[derive(Clone, Default)]
+struct Diagnostics(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Diagnostics {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().expect("Should not be poisoned").write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Instruments `code` of the file `filename`, parsed with the `syntax`. Errors reported by
+/// the instrumentation (i.e `selfCheck`) fail the instrumentation with the diagnostics.
+pub fn instrument(
+    code: String,
+    filename: &str,
+    instrument_options: InstrumentOptions,
+    syntax: Syntax,
+) -> Result<InstrumentOutput, String> {
+    // AST snapshots are dumped by the visitor.
+    let debug = instrument_options
+        .debug
+        .clone()
+        .filter(|debug| debug.emit == DebugEmit::Code && debug.should_dump(filename));
+    if let Some(debug) = &debug {
+        write_debug_snapshot(debug, filename, "before", "code", &code);
+    }
+
+    GLOBALS.set(&Default::default(), || {
+        let source_map = Arc::new(SourceMap::new(FilePathMapping::empty()));
+        let comments = SingleThreadedComments::default();
+
+        let file = source_map.new_source_file(FileName::Real(filename.into()), code);
+        let lexer = Lexer::new(
+            syntax,
+            EsVersion::latest(),
+            StringInput::from(&*file),
+            Some(&comments),
+        );
+        let mut parser = Parser::new_from(lexer);
+        let mut program = parser
+            .parse_program()
+            .map_err(|err| format!("Failed to parse {}: {:?}", filename, err))?;
+        if let Some(err) = parser.take_errors().into_iter().next() {
+            return Err(format!("Failed to parse {}: {:?}", filename, err));
+        }
+
+        let mut visitor = create_coverage_instrumentation_visitor(
+            source_map.clone(),
+            comments.clone(),
+            instrument_options,
+            filename.to_string(),
+        );
+
+        let diagnostics = Diagnostics::default();
+        let handler =
+            Handler::with_emitter_writer(Box::new(diagnostics.clone()), Some(source_map.clone()));
+        HANDLER.set(&handler, || program.visit_mut_with(&mut visitor));
+        if handler.has_errors() {
+            let diagnostics = diagnostics.0.lock().expect("Should not be poisoned");
+            return Err(format!(
+                "Failed to instrument {}:\n{}",
+                filename,
+                String::from_utf8_lossy(&diagnostics).trim_end()
+            ));
+        }
+
+        let mut buf = vec![];
+        let mut src_map_buf = vec![];
+        {
+            // Comments collected by the parser (license banners, magic comments of the bundlers)
+            // are emitted as-is, along with the comments attached by the instrumentation.
+            let mut emitter = Emitter {
+                cfg: Default::default(),
+                cm: source_map.clone(),
+                comments: Some(&comments),
+                wr: Box::new(JsWriter::new(
+                    source_map.clone(),
+                    "\n",
+                    &mut buf,
+                    Some(&mut src_map_buf),
+                )),
+            };
+
+            match &program {
+                Program::Module(module) => emitter.emit_module(module),
+                Program::Script(script) => emitter.emit_script(script),
+            }
+            .map_err(|err| format!("Failed to emit {}: {}", filename, err))?;
+        }
+
+        if let Some(debug) = &debug {
+            write_debug_snapshot(
+                debug,
+                filename,
+                "after",
+                "code",
+                &String::from_utf8_lossy(&buf),
+            );
+        }
+
+        if let Some(stats) = visitor.stats() {
+            let mut stats = stats.clone();
+            stats.set_output_bytes(buf.len() as u64);
+            eprintln!("{}", stats.to_log_line());
+        }
+
+        let mut map = vec![];
+        source_map
+            .build_source_map(&mut src_map_buf)
+            .to_writer(&mut map)
+            .map_err(|err| format!("Failed to write source map of {}: {}", filename, err))?;
+
+        Ok(InstrumentOutput {
+            code: String::from_utf8(buf).map_err(|err| format!("Emitted invalid utf8: {}", err))?,
+            map: String::from_utf8(map).map_err(|err| format!("Emitted invalid utf8: {}", err))?,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_warn_unknown_options() {
+        let (options, warnings) = parse_instrument_options(Some(serde_json::json!({
+            "coverageVariable": "__cov__",
+            "coverageVarible": "__typo__",
+        })))
+        .unwrap();
+
+        assert_eq!(options.coverage_variable, "__cov__");
+        assert_eq!(
+            warnings,
+            vec!["unknown option `coverageVarible` is ignored".to_string()]
+        );
+    }
+
+    #[test]
+    fn should_instrument_with_self_check() {
+        let output = instrument(
+            "var a = b || 1;".to_string(),
+            "/a.js",
+            InstrumentOptions {
+                self_check: true,
+                ..Default::default()
+            },
+            get_syntax("/a.js"),
+        )
+        .unwrap();
+        assert!(output.code.contains("var a = "));
+        assert!(output.map.contains("\"mappings\""));
+    }
+
+    #[test]
+    fn should_fail_on_parse_error() {
+        let err = instrument(
+            "var a = ;".to_string(),
+            "/a.js",
+            Default::default(),
+            get_syntax("/a.js"),
+        )
+        .unwrap_err();
+        assert!(err.starts_with("Failed to parse /a.js"));
+    }
+}
@@ -0,0 +1,13 @@
+//! Entrypoints of the plugin for each plugin ABI of the host (`@swc/core`).
+//!
+//! The ABI between the host and the plugin (i.e how source map lookups like
+//! `__lookup_char_pos_source_map_proxy` are called into the host) changes across
+//! `@swc/core` versions. Each module adapts one range of the ABI into
+//! `crate::instrument`, which only relies on `SourceMapper` and `Comments`, so
+//! supporting a new range is adding a module and a `plugin_abi_*` feature for it.
+
+#[cfg(not(any(feature = "plugin_abi_0_63")))]
+compile_error!("Enable one of the `plugin_abi_*` features to build the plugin");
+
+#[cfg(feature = "plugin_abi_0_63")]
+mod swc_plugin_0_63;
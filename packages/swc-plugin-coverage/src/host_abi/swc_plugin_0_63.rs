@@ -0,0 +1,25 @@
+//! `swc_plugin` 0.63.x.
+use std::sync::Arc;
+
+use swc_plugin::{
+    ast::Program,
+    errors::{Level, HANDLER},
+    plugin_transform, TransformPluginProgramMetadata,
+};
+
+#[plugin_transform]
+pub fn process(program: Program, metadata: TransformPluginProgramMetadata) -> Program {
+    crate::instrument(
+        program,
+        Arc::new(metadata.source_map),
+        metadata.comments.as_ref(),
+        &metadata.plugin_config,
+        &metadata.transform_context,
+        |level, message| {
+            HANDLER.with(|handler| match level {
+                Level::Error => handler.err(message),
+                _ => handler.warn(message),
+            })
+        },
+    )
+}
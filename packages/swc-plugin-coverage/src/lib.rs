@@ -1,15 +1,16 @@
+use std::sync::Arc;
+
 use serde_json::Value;
+use swc_common::{comments::Comments, errors::Level, SourceMapper};
 use swc_coverage_instrument::{
-    create_coverage_instrumentation_visitor, InstrumentLogOptions, InstrumentOptions,
-};
-use swc_plugin::{
-    ast::{as_folder, FoldWith, Program},
-    plugin_transform, TransformPluginProgramMetadata,
+    create_coverage_instrumentation_visitor, DebugEmit, InstrumentOptions,
 };
+use swc_ecmascript::{ast::Program, visit::VisitMutWith};
 
-use tracing_subscriber::fmt::format::FmtSpan;
+mod host_abi;
 
-fn initialize_instrumentation_log(log_options: &InstrumentLogOptions) {
+#[cfg(feature = "tracing")]
+fn initialize_instrumentation_log(log_options: &swc_coverage_instrument::InstrumentLogOptions) {
     let log_level = match log_options.level.as_deref() {
         Some("error") => Some(tracing::Level::ERROR),
         Some("debug") => Some(tracing::Level::DEBUG),
@@ -23,7 +24,10 @@ fn initialize_instrumentation_log(log_options: &InstrumentLogOptions) {
         let builder = tracing_subscriber::fmt().with_max_level(log_level);
 
         let builder = if log_options.enable_trace {
-            builder.with_span_events(FmtSpan::ENTER | FmtSpan::CLOSE)
+            builder.with_span_events(
+                tracing_subscriber::fmt::format::FmtSpan::ENTER
+                    | tracing_subscriber::fmt::format::FmtSpan::CLOSE,
+            )
         } else {
             builder
         };
@@ -35,31 +39,70 @@ fn initialize_instrumentation_log(log_options: &InstrumentLogOptions) {
     }
 }
 
-#[plugin_transform]
-pub fn process(program: Program, metadata: TransformPluginProgramMetadata) -> Program {
-    let context: Value = serde_json::from_str(&metadata.transform_context)
-        .expect("Should able to deserialize context");
+/// Instruments the program with the source map & comments of the host.
+/// Entrypoint of each plugin ABI in `host_abi` calls into this, so it does not
+/// depend on the ABI the plugin is built against. Diagnostics (i.e invalid options)
+/// are reported to the host via `emit_diagnostic`.
+pub(crate) fn instrument<C: Clone + Comments, S: SourceMapper>(
+    program: Program,
+    source_map: Arc<S>,
+    comments: C,
+    plugin_config: &str,
+    transform_context: &str,
+    emit_diagnostic: impl Fn(Level, &str),
+) -> Program {
+    let context: Value =
+        serde_json::from_str(transform_context).expect("Should able to deserialize context");
     let filename = if let Some(filename) = (&context["filename"]).as_str() {
         filename
     } else {
         "unknown.js"
     };
 
-    let instrument_options: InstrumentOptions = serde_json::from_str(&metadata.plugin_config)
-        .unwrap_or_else(|f| {
-            println!("Could not deserialize instrumentation option");
-            println!("{:#?}", f);
+    let mut instrument_options = match InstrumentOptions::from_json(plugin_config) {
+        Ok((instrument_options, unknown_keys)) => {
+            for key in unknown_keys {
+                emit_diagnostic(
+                    Level::Warning,
+                    &format!("swc-plugin-coverage: unknown option `{}` is ignored", key),
+                );
+            }
+            instrument_options
+        }
+        Err(err) => {
+            emit_diagnostic(
+                Level::Error,
+                &format!(
+                    "swc-plugin-coverage: {}, instrumenting with default options",
+                    err
+                ),
+            );
             Default::default()
-        });
+        }
+    };
 
+    #[cfg(feature = "tracing")]
     initialize_instrumentation_log(&instrument_options.instrument_log);
 
-    let visitor = create_coverage_instrumentation_visitor(
-        std::sync::Arc::new(metadata.source_map),
-        metadata.comments.as_ref(),
+    // Code is emitted by the host after the plugin returns, dump the AST instead.
+    if let Some(debug) = &mut instrument_options.debug {
+        debug.emit = DebugEmit::Ast;
+    }
+
+    let mut visitor = create_coverage_instrumentation_visitor(
+        source_map,
+        comments,
         instrument_options,
         filename.to_string(),
     );
 
-    program.fold_with(&mut as_folder(visitor))
+    let mut program = program;
+    program.visit_mut_with(&mut visitor);
+
+    // Output size is not known until the host emits the code.
+    if let Some(stats) = visitor.stats() {
+        eprintln!("{}", stats.to_log_line());
+    }
+
+    program
 }
@@ -0,0 +1,55 @@
+import { instrumentSync } from "../util/verifier";
+
+// Compares runtime of the instrumented code with / without `hoistCounters`.
+// Run with `npm run bench:counters`, ITERATIONS overrides the number of calls to the hot path.
+const iterations = Number(process.env.ITERATIONS ?? 1_000_000);
+const runs = 5;
+
+const code = `
+function fib(n) {
+  var a = 0, b = 1;
+  for (var i = 0; i < n; i++) {
+    var t = a + b;
+    a = b;
+    b = t > 1000 ? t % 1000 : t;
+  }
+  return a || b;
+}
+
+output = function (iterations) {
+  var sum = 0;
+  for (var i = 0; i < iterations; i++) {
+    sum += fib(i % 20);
+  }
+  return sum;
+};
+`;
+
+const measure = (name: string, instrumentOptions: Record<string, any>) => {
+  const { code: instrumented } = instrumentSync(
+    code,
+    `${name}.js`,
+    undefined,
+    { coverageVariable: "__bench_coverage__", ...instrumentOptions },
+    { isModule: false }
+  );
+
+  const hotPath = new Function(`var output;\n${instrumented}\nreturn output;`)();
+
+  const timings: Array<number> = [];
+  for (let i = 0; i < runs; i++) {
+    const start = process.hrtime.bigint();
+    hotPath(iterations);
+    timings.push(Number(process.hrtime.bigint() - start) / 1e6);
+  }
+
+  timings.sort((a, b) => a - b);
+  console.log(
+    `${name}: median ${timings[Math.floor(runs / 2)].toFixed(
+      2
+    )}ms, min ${timings[0].toFixed(2)}ms (${runs} runs, ${iterations} iterations)`
+  );
+};
+
+measure("default", {});
+measure("hoistCounters", { hoistCounters: true });
@@ -0,0 +1,67 @@
+import * as path from "path";
+import * as fs from "fs";
+import { assert } from "chai";
+import { instrumentSync, lastFileCoverage } from "./util/verifier";
+
+const { normalizeCoverage } = require("./compat/normalize");
+
+// Compares coverage map of the compat fixtures against babel-plugin-istanbul's,
+// committed under `compat/expected` by `npm run compat:update`.
+const fixturesDir = path.resolve(__dirname, "compat/fixtures");
+const expectedDir = path.resolve(__dirname, "compat/expected");
+
+const files = fs.readdirSync(fixturesDir).filter((f) => {
+  if (process.env.FILTER) {
+    return new RegExp(`.*${process.env.FILTER}.*`).test(f);
+  }
+  return true;
+});
+
+describe("babel-plugin-istanbul compatibility", () => {
+  files.forEach((file) => {
+    it(file, () => {
+      const filename = path.resolve(fixturesDir, file);
+      const code = fs.readFileSync(filename, "utf8");
+      const expected = JSON.parse(
+        fs.readFileSync(path.resolve(expectedDir, `${file}.json`), "utf8")
+      );
+
+      const output = instrumentSync(code, filename, undefined, undefined, {
+        isModule: true,
+        parser: file.endsWith(".ts") ? { syntax: "typescript" } : undefined,
+      } as any);
+      const actual = normalizeCoverage(lastFileCoverage(output.code));
+
+      assert.equal(
+        actual.statements.length,
+        expected.statements.length,
+        "Statement count mismatch"
+      );
+      assert.equal(
+        actual.functions.length,
+        expected.functions.length,
+        "Function count mismatch"
+      );
+      assert.equal(
+        actual.branches.length,
+        expected.branches.length,
+        "Branch count mismatch"
+      );
+      assert.deepEqual(
+        actual.statements,
+        expected.statements,
+        "Statement ranges mismatch"
+      );
+      assert.deepEqual(
+        actual.functions,
+        expected.functions,
+        "Function ranges mismatch"
+      );
+      assert.deepEqual(
+        actual.branches,
+        expected.branches,
+        "Branch mismatch"
+      );
+    });
+  });
+});
@@ -0,0 +1,9 @@
+var a = 1;
+function add(x, y) {
+  return x + y;
+}
+if (a > 0) {
+  add(a, 2);
+} else {
+  a = add(a, -1);
+}
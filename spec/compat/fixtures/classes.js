@@ -0,0 +1,18 @@
+class Counter {
+  count = 0;
+  static create(start = 1) {
+    return new Counter().add(start);
+  }
+  get value() {
+    return this.count;
+  }
+  add(n) {
+    this.count += n;
+    return this;
+  }
+}
+const double = (n) => n * 2;
+const items = [1, 2].map((v) => {
+  return double(v);
+});
+Counter.create().value;
@@ -0,0 +1,13 @@
+function describe(n) {
+  var sign = n > 0 ? "positive" : n < 0 ? "negative" : "zero";
+  switch (sign) {
+    case "positive":
+      return 1;
+    case "negative":
+      return -1;
+    default:
+      break;
+  }
+  return 0;
+}
+describe(1);
@@ -0,0 +1,12 @@
+var a = 0, b = "", c = null, d = 1;
+var x = a || b || c || d;
+var y = a && b && c;
+var z = a ?? b ?? c;
+var w = a || b && c || d;
+var v = (a || b) || (c || d);
+if (a || b || c) {
+  x = f(a || b || c) || d;
+}
+function f(e) {
+  return e ?? (a || b) ?? "none";
+}
@@ -0,0 +1,7 @@
+var a = 1, b = 0, c = null;
+var x = a && (b || c);
+var y = c ?? "fallback";
+function pick(d) {
+  return d || (b && c);
+}
+pick(x || y);
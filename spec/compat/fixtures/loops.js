@@ -0,0 +1,15 @@
+var total = 0;
+for (var i = 0; i < 3; i++) {
+  total += i;
+}
+for (var key in { a: 1 }) total++;
+for (const value of [1, 2]) {
+  if (value > 1) continue;
+}
+while (total > 10) total--;
+do {
+  total++;
+} while (total < 5);
+outer: for (;;) {
+  break outer;
+}
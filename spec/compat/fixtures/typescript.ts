@@ -0,0 +1,13 @@
+interface Options {
+  verbose?: boolean;
+}
+type Level = "info" | "warn";
+function log<T>(value: T, options?: Options): T {
+  const level: Level = options?.verbose ? "info" : "warn";
+  if (!options) {
+    return value as T;
+  }
+  console.log(level, value!);
+  return value;
+}
+export const result = log<number>(1, { verbose: true });
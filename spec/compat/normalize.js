@@ -0,0 +1,42 @@
+// Reduces a FileCoverage to the shape compared between babel-plugin-istanbul and this instrumenter.
+// Ids are allocated in traversal order which differs by AST shape, so entries are sorted by range.
+// Function names and declaration ranges depend on how each parser infers names, only body range is compared.
+const toRange = (loc) => ({
+  start: { line: loc.start.line, column: loc.start.column },
+  end: { line: loc.end.line, column: loc.end.column },
+});
+
+const compareRange = (a, b) =>
+  (a.start.line ?? 0) - (b.start.line ?? 0) ||
+  (a.start.column ?? 0) - (b.start.column ?? 0) ||
+  (a.end.line ?? 0) - (b.end.line ?? 0) ||
+  (a.end.column ?? 0) - (b.end.column ?? 0);
+
+function normalizeCoverage(coverage) {
+  const statements = Object.values(coverage.statementMap)
+    .map(toRange)
+    .sort(compareRange);
+
+  const functions = Object.values(coverage.fnMap)
+    .map((fn) => toRange(fn.loc))
+    .sort(compareRange);
+
+  const branches = Object.values(coverage.branchMap)
+    .map((branch) => ({
+      type: branch.type,
+      loc: toRange(branch.loc),
+      locations: branch.locations.map(toRange),
+    }))
+    .sort((a, b) => compareRange(a.loc, b.loc) || a.type.localeCompare(b.type));
+
+  return { statements, functions, branches };
+}
+
+// Keeps each position in a single line to make expected files reviewable.
+const stringifyCoverage = (normalized) =>
+  JSON.stringify(normalized, null, 2).replace(
+    /\{\s+"line": (\d+),\s+"column": (\d+)\s+\}/g,
+    '{ "line": $1, "column": $2 }'
+  ) + "\n";
+
+module.exports = { normalizeCoverage, stringifyCoverage };
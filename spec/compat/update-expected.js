@@ -0,0 +1,44 @@
+// Regenerates expected coverage of the compat fixtures using babel-plugin-istanbul.
+// Babel is not a dependency of the repo, install it temporarily before running:
+//
+//   npm install --no-save @babel/core @babel/preset-typescript babel-plugin-istanbul
+//   npm run compat:update
+const path = require("path");
+const fs = require("fs");
+const { transformSync } = require("@babel/core");
+const { normalizeCoverage, stringifyCoverage } = require("./normalize");
+
+const fixturesDir = path.resolve(__dirname, "fixtures");
+const expectedDir = path.resolve(__dirname, "expected");
+
+for (const file of fs.readdirSync(fixturesDir)) {
+  const filename = path.resolve(fixturesDir, file);
+  let coverage = null;
+
+  transformSync(fs.readFileSync(filename, "utf8"), {
+    filename,
+    babelrc: false,
+    configFile: false,
+    presets: file.endsWith(".ts") ? ["@babel/preset-typescript"] : [],
+    plugins: [
+      [
+        "babel-plugin-istanbul",
+        {
+          cwd: fixturesDir,
+          onCover: (_, fileCoverage) => {
+            coverage = fileCoverage;
+          },
+        },
+      ],
+    ],
+  });
+
+  if (!coverage) {
+    throw new Error(`babel-plugin-istanbul did not instrument ${file}`);
+  }
+
+  fs.writeFileSync(
+    path.resolve(expectedDir, `${file}.json`),
+    stringifyCoverage(normalizeCoverage(coverage))
+  );
+}
@@ -1,7 +1,12 @@
 import * as path from "path";
 import * as fs from "fs";
 import * as yaml from "js-yaml";
-import { create, instrumentSync } from "./util/verifier";
+import {
+  assertCoverageData,
+  create,
+  instrumentSync,
+  lastFileCoverage,
+} from "./util/verifier";
 import * as guards from "./util/guards";
 import { assert } from "chai";
 import { getCoverageMagicConstants } from "./swc-coverage-instrument-wasm/pkg/swc_coverage_instrument_wasm";
@@ -70,6 +75,8 @@ function generateTests(docs) {
             opts.filename = path.resolve(__dirname, doc.file);
             opts.transformOptions = {
               isModule: doc?.instrumentOpts?.esModules,
+              parser: doc?.parserOpts,
+              transform: doc?.transformOpts,
             };
             const v = create(
               doc.code,
@@ -80,8 +87,26 @@ function generateTests(docs) {
             const test = clone(t);
             const args = test.args;
             const out = test.out;
+            const codeContains = test.codeContains;
+            const codeNotContains = test.codeNotContains;
+            const coverageData = test.coverageData;
             delete test.args;
             delete test.out;
+            delete test.codeContains;
+            delete test.codeNotContains;
+            delete test.coverageData;
+            (codeContains || []).forEach((expected) => {
+              assert.include(v.getGeneratedCode(), expected);
+            });
+            (codeNotContains || []).forEach((expected) => {
+              assert.notInclude(v.getGeneratedCode(), expected);
+            });
+            if (coverageData) {
+              assertCoverageData(
+                lastFileCoverage(v.getGeneratedCode()),
+                coverageData
+              );
+            }
             if (!genOnly && !noCoverage) {
               await v.verify(args, out, test);
             }
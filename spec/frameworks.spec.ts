@@ -0,0 +1,68 @@
+import * as path from "path";
+import * as fs from "fs";
+import { assert } from "chai";
+import { instrumentSync, lastFileCoverage } from "./util/verifier";
+import { validateCoverage } from "./util/validate-coverage";
+
+// Realistic framework sources (Next.js, React, NestJS), mixing JSX, decorators and TS only syntax.
+// There's no expected coverage to compare, instead instrumented results are validated structurally.
+const fixturesDir = path.resolve(__dirname, "frameworks/fixtures");
+
+const files = fs.readdirSync(fixturesDir).filter((f) => {
+  if (process.env.FILTER) {
+    return new RegExp(`.*${process.env.FILTER}.*`).test(f);
+  }
+  return true;
+});
+
+// Parser / transform config each framework uses by default.
+const getTransformOptions = (file: string) => {
+  switch (path.extname(file)) {
+    case ".jsx":
+      return { isModule: true, parser: { syntax: "ecmascript", jsx: true } };
+    case ".tsx":
+      return { isModule: true, parser: { syntax: "typescript", tsx: true } };
+    default:
+      return {
+        isModule: true,
+        parser: { syntax: "typescript", decorators: true },
+        transform: { legacyDecorator: true, decoratorMetadata: true },
+      };
+  }
+};
+
+describe("framework fixtures", () => {
+  files.forEach((file) => {
+    describe(file, () => {
+      const filename = path.resolve(fixturesDir, file);
+      const code = fs.readFileSync(filename, "utf8");
+      const instrument = () =>
+        instrumentSync(
+          code,
+          filename,
+          undefined,
+          undefined,
+          getTransformOptions(file) as any
+        );
+
+      it("instruments into structurally valid coverage", () => {
+        const output = instrument();
+        const coverage = lastFileCoverage(output.code);
+
+        assert.isAbove(
+          Object.keys(coverage.statementMap ?? {}).length,
+          0,
+          "No statements found"
+        );
+        assert.deepEqual(validateCoverage(coverage, code, output.code), []);
+      });
+
+      it("instruments deterministically", () => {
+        assert.deepEqual(
+          lastFileCoverage(instrument().code),
+          lastFileCoverage(instrument().code)
+        );
+      });
+    });
+  });
+});
@@ -0,0 +1,100 @@
+import {
+  Body,
+  Controller,
+  Delete,
+  Get,
+  HttpCode,
+  HttpStatus,
+  Injectable,
+  NotFoundException,
+  Param,
+  ParseIntPipe,
+  Post,
+  Query,
+  UseGuards,
+} from "@nestjs/common";
+import { ApiOperation, ApiTags } from "@nestjs/swagger";
+import { IsInt, IsOptional, IsString, Min } from "class-validator";
+import { AuthGuard } from "./auth.guard";
+
+export class CreateCatDto {
+  @IsString()
+  readonly name!: string;
+
+  @IsInt()
+  @Min(0)
+  readonly age!: number;
+
+  @IsOptional()
+  @IsString()
+  readonly breed?: string;
+}
+
+export interface Cat extends CreateCatDto {
+  id: number;
+}
+
+export abstract class Repository<T extends { id: number }> {
+  protected readonly items = new Map<number, T>();
+
+  abstract create(input: Omit<T, "id">): T;
+
+  findOne(id: number): T | undefined {
+    return this.items.get(id);
+  }
+}
+
+@Injectable()
+export class CatsService extends Repository<Cat> {
+  private lastId = 0;
+
+  create(input: CreateCatDto): Cat {
+    const cat = { ...input, id: ++this.lastId };
+    this.items.set(cat.id, cat);
+    return cat;
+  }
+
+  findAll(breed?: string, limit = 10): Cat[] {
+    const cats = [...this.items.values()];
+    return (breed ? cats.filter((cat) => cat.breed === breed) : cats).slice(0, limit);
+  }
+
+  remove(id: number): boolean {
+    return this.items.delete(id);
+  }
+}
+
+@ApiTags("cats")
+@Controller("cats")
+@UseGuards(AuthGuard)
+export class CatsController {
+  constructor(private readonly catsService: CatsService) {}
+
+  @Post()
+  @ApiOperation({ summary: "Create cat" })
+  create(@Body() createCatDto: CreateCatDto): Cat {
+    return this.catsService.create(createCatDto);
+  }
+
+  @Get()
+  findAll(@Query("breed") breed?: string, @Query("limit") limit?: string): Cat[] {
+    return this.catsService.findAll(breed, limit ? parseInt(limit, 10) : undefined);
+  }
+
+  @Get(":id")
+  findOne(@Param("id", ParseIntPipe) id: number): Cat {
+    const cat = this.catsService.findOne(id);
+    if (!cat) {
+      throw new NotFoundException(`Cat #${id} not found`);
+    }
+    return cat;
+  }
+
+  @Delete(":id")
+  @HttpCode(HttpStatus.NO_CONTENT)
+  async remove(@Param("id", ParseIntPipe) id: number): Promise<void> {
+    if (!this.catsService.remove(id)) {
+      throw new NotFoundException();
+    }
+  }
+}
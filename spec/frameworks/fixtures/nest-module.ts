@@ -0,0 +1,71 @@
+import {
+  CanActivate,
+  ExecutionContext,
+  Global,
+  Inject,
+  Injectable,
+  MiddlewareConsumer,
+  Module,
+  NestModule,
+  Optional,
+} from "@nestjs/common";
+import { Reflector } from "@nestjs/core";
+import { CatsController, CatsService } from "./nest-controller";
+
+export const CONFIG = Symbol("CONFIG");
+
+export interface AppConfig {
+  apiKeys: string[];
+  logRequests?: boolean;
+}
+
+@Injectable()
+export class AuthGuard implements CanActivate {
+  constructor(
+    private readonly reflector: Reflector,
+    @Inject(CONFIG) private readonly config: AppConfig
+  ) {}
+
+  canActivate(context: ExecutionContext): boolean {
+    const isPublic = this.reflector.get<boolean>("public", context.getHandler());
+    if (isPublic) {
+      return true;
+    }
+    const key = context.switchToHttp().getRequest().headers["x-api-key"];
+    return typeof key === "string" && this.config.apiKeys.includes(key);
+  }
+}
+
+@Injectable()
+export class LoggerMiddleware {
+  constructor(@Optional() @Inject(CONFIG) private readonly config?: AppConfig) {}
+
+  use(req: { method: string; url: string }, _res: unknown, next: () => void) {
+    if (this.config?.logRequests) {
+      console.log(`${req.method} ${req.url}`);
+    }
+    next();
+  }
+}
+
+@Global()
+@Module({
+  controllers: [CatsController],
+  providers: [
+    CatsService,
+    AuthGuard,
+    {
+      provide: CONFIG,
+      useFactory: (): AppConfig => ({
+        apiKeys: (process.env.API_KEYS || "").split(",").filter(Boolean),
+        logRequests: process.env.NODE_ENV !== "production",
+      }),
+    },
+  ],
+  exports: [CatsService],
+})
+export class CatsModule implements NestModule {
+  configure(consumer: MiddlewareConsumer) {
+    consumer.apply(LoggerMiddleware).forRoutes(CatsController);
+  }
+}
@@ -0,0 +1,59 @@
+import type { NextApiRequest, NextApiResponse } from "next";
+import { getSession } from "next-auth/react";
+import { prisma } from "../../lib/prisma";
+
+type Data = { id: string; title: string } | { error: string };
+
+interface CreatePostBody {
+  title: string;
+  content?: string;
+}
+
+const isCreatePostBody = (body: unknown): body is CreatePostBody =>
+  typeof body === "object" && body !== null && "title" in body;
+
+export const config = {
+  api: { bodyParser: { sizeLimit: "1mb" } },
+};
+
+export default async function handler(
+  req: NextApiRequest,
+  res: NextApiResponse<Data>
+) {
+  const session = await getSession({ req });
+  if (!session?.user?.email) {
+    return res.status(401).json({ error: "Unauthorized" });
+  }
+
+  switch (req.method) {
+    case "GET": {
+      const posts = await prisma.post.findMany({
+        where: { author: { email: session.user.email } },
+      });
+      return res.status(200).json(posts as any);
+    }
+    case "POST": {
+      if (!isCreatePostBody(req.body)) {
+        return res.status(400).json({ error: "Invalid body" });
+      }
+
+      try {
+        const post = await prisma.post.create({
+          data: {
+            title: req.body.title,
+            content: req.body.content ?? "",
+            author: { connect: { email: session.user.email } },
+          },
+        });
+        return res.status(201).json(post);
+      } catch (err) {
+        return res
+          .status(500)
+          .json({ error: (err as Error).message || "Unknown error" });
+      }
+    }
+    default:
+      res.setHeader("Allow", ["GET", "POST"]);
+      res.status(405).end(`Method ${req.method} Not Allowed`);
+  }
+}
@@ -0,0 +1,79 @@
+import Head from "next/head";
+import Link from "next/link";
+import { useRouter } from "next/router";
+import { useEffect, useState } from "react";
+import styles from "../styles/Posts.module.css";
+
+export async function getServerSideProps({ query, res }) {
+  const page = Number(query.page ?? 1);
+  const response = await fetch(`https://api.example.com/posts?page=${page}`);
+
+  if (!response.ok) {
+    return { notFound: true };
+  }
+
+  res.setHeader("Cache-Control", "public, s-maxage=60");
+  const { posts, total } = await response.json();
+  return { props: { posts, page, total } };
+}
+
+function PostItem({ post, highlighted = false }) {
+  return (
+    <li className={highlighted ? styles.highlighted : styles.item}>
+      <Link href={`/posts/${post.slug}`}>{post.title}</Link>
+      {post.tags?.length > 0 && (
+        <ul>
+          {post.tags.map((tag) => (
+            <li key={tag}>{tag}</li>
+          ))}
+        </ul>
+      )}
+    </li>
+  );
+}
+
+export default function Posts({ posts, page, total }) {
+  const router = useRouter();
+  const [query, setQuery] = useState("");
+  const [visible, setVisible] = useState(posts);
+
+  useEffect(() => {
+    const timer = setTimeout(() => {
+      setVisible(
+        query
+          ? posts.filter((post) => post.title.toLowerCase().includes(query))
+          : posts
+      );
+    }, 200);
+    return () => clearTimeout(timer);
+  }, [query, posts]);
+
+  const onSubmit = (e) => {
+    e.preventDefault();
+    router.push({ pathname: "/posts", query: { page: 1 } });
+  };
+
+  return (
+    <>
+      <Head>
+        <title>{`Posts - page ${page}`}</title>
+      </Head>
+      <form onSubmit={onSubmit}>
+        <input
+          value={query}
+          onChange={(e) => setQuery(e.target.value.toLowerCase())}
+        />
+      </form>
+      {visible.length === 0 ? (
+        <p>No posts found</p>
+      ) : (
+        <ul>
+          {visible.map((post, idx) => (
+            <PostItem key={post.slug} post={post} highlighted={idx === 0} />
+          ))}
+        </ul>
+      )}
+      {page * 10 < total && <Link href={`/posts?page=${page + 1}`}>Next</Link>}
+    </>
+  );
+}
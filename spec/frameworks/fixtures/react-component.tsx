@@ -0,0 +1,131 @@
+import React, {
+  Component,
+  forwardRef,
+  useCallback,
+  useMemo,
+  useReducer,
+  type ReactNode,
+} from "react";
+
+export interface TodoItem {
+  id: number;
+  text: string;
+  done: boolean;
+}
+
+type Action =
+  | { type: "add"; text: string }
+  | { type: "toggle"; id: number }
+  | { type: "clear" };
+
+enum Filter {
+  All = "all",
+  Active = "active",
+  Done = "done",
+}
+
+function reducer(state: TodoItem[], action: Action): TodoItem[] {
+  switch (action.type) {
+    case "add":
+      return [...state, { id: state.length + 1, text: action.text, done: false }];
+    case "toggle":
+      return state.map((item) =>
+        item.id === action.id ? { ...item, done: !item.done } : item
+      );
+    case "clear":
+      return state.filter((item) => !item.done);
+    default:
+      return state;
+  }
+}
+
+interface ListProps<T> {
+  items: T[];
+  render: (item: T) => ReactNode;
+  empty?: ReactNode;
+}
+
+export function List<T extends { id: number }>({ items, render, empty }: ListProps<T>) {
+  if (!items.length) {
+    return <>{empty ?? <em>Nothing to show</em>}</>;
+  }
+  return <ul>{items.map((item) => <li key={item.id}>{render(item)}</li>)}</ul>;
+}
+
+export const TodoInput = forwardRef<HTMLInputElement, { onAdd: (text: string) => void }>(
+  function TodoInput({ onAdd }, ref) {
+    return (
+      <input
+        ref={ref}
+        onKeyDown={(e) => {
+          const value = e.currentTarget.value.trim();
+          if (e.key === "Enter" && value) {
+            onAdd(value);
+            e.currentTarget.value = "";
+          }
+        }}
+      />
+    );
+  }
+);
+
+export default function TodoApp({ initial = [] as TodoItem[], filter = Filter.All }) {
+  const [todos, dispatch] = useReducer(reducer, initial);
+  const onAdd = useCallback((text: string) => dispatch({ type: "add", text }), []);
+
+  const visible = useMemo(
+    () =>
+      todos.filter((item) =>
+        filter === Filter.All ? true : filter === Filter.Done ? item.done : !item.done
+      ),
+    [todos, filter]
+  );
+
+  return (
+    <section>
+      <TodoInput onAdd={onAdd} />
+      <List<TodoItem>
+        items={visible}
+        render={(item) => (
+          <label>
+            <input
+              type="checkbox"
+              checked={item.done}
+              onChange={() => dispatch({ type: "toggle", id: item.id })}
+            />
+            {item.done ? <s>{item.text}</s> : item.text}
+          </label>
+        )}
+      />
+      {todos.some((item) => item.done) && (
+        <button onClick={() => dispatch({ type: "clear" })}>Clear done</button>
+      )}
+    </section>
+  );
+}
+
+interface BoundaryState {
+  error?: Error;
+}
+
+export class ErrorBoundary extends Component<{ fallback?: ReactNode; children?: ReactNode }, BoundaryState> {
+  static defaultProps = { fallback: <p>Something went wrong</p> };
+  state: BoundaryState = {};
+
+  static getDerivedStateFromError(error: Error): BoundaryState {
+    return { error };
+  }
+
+  private reset = () => {
+    this.setState({ error: undefined });
+  };
+
+  render() {
+    const { fallback, children } = this.props;
+    return this.state.error ? (
+      <div onClick={this.reset}>{fallback}</div>
+    ) : (
+      children
+    );
+  }
+}
@@ -0,0 +1,91 @@
+import { assert } from "chai";
+import { instrumentSync } from "./util/verifier";
+
+// Instrumentation wraps sub expressions with counters. Wrapped expression should be evaluated
+// same as the original regardless of where it is placed, including the receiver (`this`) of the calls.
+// Generates the combinations of the operands and the positions, then compares the results
+// of the original code and the instrumented code.
+const prelude = `
+function C() {
+  return { kind: new.target ? "new" : "call", self: this && this.name, args: arguments.length };
+}
+var obj = { name: "obj", m: C };
+var other = { name: "other", m: C };
+`;
+
+const operands = [
+  "obj.m",
+  "args[0] || obj.m",
+  "args[0] && obj.m",
+  "args[0] ?? obj.m",
+  "args[0] ? obj.m : other.m",
+  "(args[0], obj.m)",
+  "args[0] || (args[1] && obj.m)",
+];
+
+const receivers = ["obj", "args[0] || obj", "args[0] ? obj : other", "args[0] ?? other"];
+
+const positions = [
+  (x: string) => `(${x})()`,
+  (x: string) => `new (${x})()`,
+  (x: string) => `(${x})\`tpl\``,
+  (x: string) => `(${x})?.()`,
+  (x: string) => `(${x}).call(other, 1)`,
+  (x: string) => `typeof (${x})`,
+  (x: string) => `[(${x})][0]()`,
+];
+
+const receiverPositions = [
+  (x: string) => `(${x}).m()`,
+  (x: string) => `(${x})["m"]()`,
+  (x: string) => `(${x})?.m()`,
+  (x: string) => `new (${x}).m()`,
+];
+
+const inputs = [
+  [undefined, undefined],
+  [null, true],
+  [0, false],
+  [1, true],
+  [{ name: "arg", m: undefined }, true],
+];
+
+const instrumentOptions = [{}, { reportLogic: true }, { hoistCounters: true }];
+
+const evaluate = (code: string, args: Array<any>) => {
+  const fn = new Function("args", `{ var output;\n${code}\nreturn output;\n}`);
+  try {
+    return JSON.stringify(fn(args));
+  } catch (e) {
+    return `throws ${e.constructor.name}`;
+  }
+};
+
+describe("precedence of instrumented expressions", () => {
+  const cases = [
+    ...operands.flatMap((operand) => positions.map((position) => position(operand))),
+    ...receivers.flatMap((receiver) =>
+      receiverPositions.map((position) => position(receiver))
+    ),
+  ];
+
+  cases.forEach((expr) => {
+    const code = `${prelude}\noutput = ${expr};`;
+
+    instrumentOptions.forEach((options) => {
+      it(`${expr} ${JSON.stringify(options)}`, () => {
+        const instrumented = instrumentSync(code, "precedence.js", undefined, options, {
+          isModule: false,
+        } as any).code;
+
+        inputs.forEach((args) => {
+          assert.equal(
+            evaluate(instrumented, args),
+            evaluate(code, args),
+            `Result mismatch with args ${JSON.stringify(args)}\n${instrumented}`
+          );
+        });
+      });
+    });
+  });
+});
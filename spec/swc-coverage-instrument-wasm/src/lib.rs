@@ -21,6 +21,30 @@ pub fn get_coverage_magic_constants() -> JsValue {
     .unwrap()
 }
 
+/// Extracts coverage data from the comments in the transformed output.
+#[wasm_bindgen(js_name = "extractCoverageFromComments")]
+pub fn extract_coverage_from_comments(code: &str) -> JsValue {
+    JsValue::from_serde(&swc_coverage_instrument::extract_coverage_from_comments(
+        code,
+    ))
+    .unwrap()
+}
+
+/// Extracts manifest entries from the comments in the transformed output.
+#[wasm_bindgen(js_name = "extractManifestFromComments")]
+pub fn extract_manifest_from_comments(code: &str) -> JsValue {
+    JsValue::from_serde(&swc_coverage_instrument::extract_manifest_from_comments(
+        code,
+    ))
+    .unwrap()
+}
+
+/// Removes coverage data comments and manifest comments from the transformed output.
+#[wasm_bindgen(js_name = "stripCoverageComments")]
+pub fn strip_coverage_comments(code: &str) -> String {
+    swc_coverage_instrument::strip_coverage_comments(code)
+}
+
 /// Wraps FileCoverage for the wasm-bindgen to allow to use coverage struct in JS context.
 #[wasm_bindgen]
 pub struct FileCoverageInterop {
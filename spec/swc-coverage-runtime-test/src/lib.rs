@@ -0,0 +1,230 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context as _, Error};
+use indexmap::IndexMap;
+use istanbul_oxide::FileCoverage;
+use rquickjs::{Context, Runtime};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use swc_common::{comments::SingleThreadedComments, FileName, Globals, SourceMap, GLOBALS};
+use swc_coverage_instrument::{create_coverage_instrumentation_visitor, InstrumentOptions};
+use swc_ecmascript::{
+    ast::{EsVersion, Program},
+    codegen::{text_writer::JsWriter, Config, Emitter},
+    parser::{lexer::Lexer, EsConfig, Parser, StringInput, Syntax},
+    visit::VisitMutWith,
+};
+
+/// Same coverage variable the mocha verifier uses.
+pub const COVERAGE_VARIABLE: &str = "__testing_coverage__";
+
+/// A document of the yaml fixtures under `spec/fixtures`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureDoc {
+    #[serde(default)]
+    pub name: String,
+    pub code: String,
+    #[serde(default)]
+    pub tests: Vec<FixtureTest>,
+    #[serde(default)]
+    pub opts: Map<String, Value>,
+    #[serde(default)]
+    pub instrument_opts: Map<String, Value>,
+    pub parser_opts: Option<Value>,
+    pub transform_opts: Option<Value>,
+    pub input_source_map: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixtureTest {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub args: Value,
+    pub out: Option<Value>,
+    #[serde(default)]
+    pub lines: Map<String, Value>,
+    #[serde(default)]
+    pub functions: Map<String, Value>,
+    #[serde(default)]
+    pub statements: Map<String, Value>,
+    #[serde(default)]
+    pub branches: Map<String, Value>,
+    #[serde(default)]
+    pub branches_true: Map<String, Value>,
+}
+
+impl FixtureDoc {
+    /// Returns the reason if the document can't be executed in the engine,
+    /// i.e it needs node.js specific setup or transforms other than instrumentation.
+    pub fn skip_reason(&self) -> Option<&'static str> {
+        let has_opt = |name: &str| self.opts.get(name) == Some(&Value::Bool(true));
+
+        if has_opt("generateOnly") || has_opt("noCoverage") {
+            Some("does not run generated code")
+        } else if has_opt("isAsync") {
+            Some("async scenario")
+        } else if self.parser_opts.is_some() || self.transform_opts.is_some() {
+            Some("requires additional transforms")
+        } else if self.input_source_map.is_some() {
+            Some("requires input source map")
+        } else {
+            None
+        }
+    }
+
+    fn is_module(&self) -> bool {
+        self.instrument_opts.get("esModules") != Some(&Value::Bool(false))
+    }
+
+    fn instrument_options(&self) -> Result<InstrumentOptions, Error> {
+        let mut options: InstrumentOptions =
+            serde_json::from_value(Value::Object(self.instrument_opts.clone()))
+                .context("Invalid instrumentOpts")?;
+        options.coverage_variable = COVERAGE_VARIABLE.to_string();
+        Ok(options)
+    }
+
+    /// Instrument the fixture code, then run given test scenario against it.
+    pub fn verify(&self, test: &FixtureTest) -> Result<(), Error> {
+        let code = instrument(&self.code, self.is_module(), self.instrument_options()?)?;
+        let Execution { output, coverage } = execute(&code, &test.args)?;
+
+        if output != test.out {
+            bail!(
+                "Output mismatch: expected {:?}, actual {:?}",
+                test.out,
+                output
+            );
+        }
+
+        assert_hits("Line", &test.lines, &coverage.get_line_coverage())?;
+        assert_hits("Function", &test.functions, &coverage.f)?;
+        assert_hits("Branch", &test.branches, &coverage.b)?;
+        assert_hits(
+            "Branch truthiness",
+            &test.branches_true,
+            &coverage.b_t.unwrap_or_default(),
+        )?;
+        assert_hits("Statement", &test.statements, &coverage.s)?;
+
+        Ok(())
+    }
+}
+
+fn assert_hits<T: serde::Serialize>(
+    kind: &str,
+    expected: &Map<String, Value>,
+    actual: &T,
+) -> Result<(), Error> {
+    let expected = Value::Object(expected.clone());
+    let actual = serde_json::to_value(actual)?;
+
+    if expected != actual {
+        bail!(
+            "{} coverage mismatch: expected {}, actual {}",
+            kind,
+            expected,
+            actual
+        );
+    }
+    Ok(())
+}
+
+/// Instrument given code and emit it back to the js.
+pub fn instrument(
+    code: &str,
+    is_module: bool,
+    options: InstrumentOptions,
+) -> Result<String, Error> {
+    let cm: Arc<SourceMap> = Default::default();
+    let fm = cm.new_source_file(FileName::Anon, code.to_string());
+    let comments = SingleThreadedComments::default();
+
+    let lexer = Lexer::new(
+        Syntax::Es(EsConfig {
+            jsx: true,
+            ..Default::default()
+        }),
+        EsVersion::Es2022,
+        StringInput::from(&*fm),
+        Some(&comments),
+    );
+    let mut parser = Parser::new_from(lexer);
+    let mut program = if is_module {
+        parser.parse_module().map(Program::Module)
+    } else {
+        parser.parse_script().map(Program::Script)
+    }
+    .map_err(|err| anyhow!("Failed to parse: {:?}", err.kind()))?;
+
+    let mut visitor = create_coverage_instrumentation_visitor(
+        cm.clone(),
+        comments,
+        options,
+        "fixture.js".to_string(),
+    );
+    GLOBALS.set(&Globals::new(), || program.visit_mut_with(&mut visitor));
+
+    let mut buf = vec![];
+    {
+        let mut emitter = Emitter {
+            cfg: Config::default(),
+            cm: cm.clone(),
+            comments: None,
+            wr: JsWriter::new(cm, "\n", &mut buf, None),
+        };
+
+        match &program {
+            Program::Module(module) => emitter.emit_module(module),
+            Program::Script(script) => emitter.emit_script(script),
+        }
+        .context("Failed to emit instrumented code")?;
+    }
+
+    String::from_utf8(buf).context("Instrumented code is not utf8")
+}
+
+pub struct Execution {
+    pub output: Option<Value>,
+    pub coverage: FileCoverage,
+}
+
+#[derive(Deserialize)]
+struct ExecutionResult {
+    output: Option<Value>,
+    coverage: IndexMap<String, FileCoverage>,
+}
+
+/// Runs instrumented code in a fresh engine the same way the mocha verifier does:
+/// code is wrapped into a function receives `args`, and returns `output` it assigns.
+pub fn execute(code: &str, args: &Value) -> Result<Execution, Error> {
+    let wrapped = format!(
+        "var __output = (function (args) {{ var output;\n{code}\nreturn output;\n}})({args});\n\
+         var __extract = globalThis.{cv}Extract;\n\
+         JSON.stringify({{ output: __output, coverage: __extract ? __extract() : globalThis.{cv} }});",
+        code = code,
+        args = serde_json::to_string(args)?,
+        cv = COVERAGE_VARIABLE
+    );
+
+    let runtime = Runtime::new()?;
+    let context = Context::full(&runtime)?;
+    let result: String = context
+        .with(|ctx| ctx.eval(wrapped))
+        .map_err(|err| anyhow!("Failed to execute: {}\n{}", err, code))?;
+
+    let result: ExecutionResult = serde_json::from_str(&result)?;
+    let coverage = result
+        .coverage
+        .into_values()
+        .next()
+        .ok_or_else(|| anyhow!("No coverage found"))?;
+
+    Ok(Execution {
+        output: result.output,
+        coverage,
+    })
+}
@@ -0,0 +1,45 @@
+use std::{env, fs, path::Path};
+
+use serde::Deserialize;
+use swc_coverage_runtime_test::FixtureDoc;
+
+#[test]
+fn should_match_fixture_hit_counts() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../fixtures");
+    let filter = env::var("FILTER").ok();
+
+    let mut files = fs::read_dir(&dir)
+        .expect("Should able to read fixtures")
+        .map(|entry| entry.expect("Should able to read fixture").path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "yaml"))
+        .filter(|path| {
+            filter.as_ref().map_or(true, |filter| {
+                path.to_string_lossy().contains(filter.as_str())
+            })
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+
+    let mut failures = vec![];
+    for path in files {
+        let file = path.file_name().unwrap().to_string_lossy().to_string();
+        let contents = fs::read_to_string(&path).expect("Should able to read fixture");
+
+        for document in serde_yaml::Deserializer::from_str(&contents) {
+            let doc = FixtureDoc::deserialize(document)
+                .unwrap_or_else(|err| panic!("Unable to load file [{}]: {}", file, err));
+
+            if doc.skip_reason().is_some() {
+                continue;
+            }
+
+            for test in &doc.tests {
+                if let Err(err) = doc.verify(test) {
+                    failures.push(format!("{}/{}/{}: {:#}", file, doc.name, test.name, err));
+                }
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+}
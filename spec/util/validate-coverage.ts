@@ -0,0 +1,162 @@
+import { parseSync } from "@swc/core";
+
+type Position = { line: number; column: number };
+type Range = { start: Position; end: Position };
+
+/**
+ * Structural checks of the coverage instrumented from given code, which do not depend on
+ * expected coverage values. Returns the list of problems, empty if the coverage is valid.
+ *
+ * - ids of each map are sequential from 0 and have initial counters of the same shape
+ * - every range is within the source, and its start is not after its end
+ * - every counter in the map is increased somewhere in the generated code
+ * - the generated code is parseable
+ */
+function validateCoverage(
+  coverage: Record<string, any>,
+  code: string,
+  generatedCode: string
+): Array<string> {
+  const problems: Array<string> = [];
+  const lines = code.split(/\r?\n/);
+
+  const checkPosition = (label: string, { line, column }: Position) => {
+    if (!Number.isInteger(line) || line < 1 || line > lines.length) {
+      problems.push(`${label}: line ${line} is out of the source`);
+    } else if (
+      !Number.isInteger(column) ||
+      column < 0 ||
+      column > lines[line - 1].length
+    ) {
+      problems.push(
+        `${label}: column ${column} is out of the line ${line} (${
+          lines[line - 1].length
+        })`
+      );
+    }
+  };
+
+  const checkRange = (label: string, range?: Range) => {
+    if (!range?.start || !range?.end) {
+      problems.push(`${label}: missing range`);
+      return;
+    }
+
+    checkPosition(`${label}.start`, range.start);
+    checkPosition(`${label}.end`, range.end);
+
+    if (
+      range.start.line > range.end.line ||
+      (range.start.line === range.end.line &&
+        range.start.column > range.end.column)
+    ) {
+      problems.push(`${label}: start is after end`);
+    }
+  };
+
+  const checkIds = (
+    label: string,
+    map: Record<string, unknown> = {},
+    counters: Record<string, unknown> = {}
+  ) => {
+    const ids = Object.keys(map);
+    ids.forEach((id, idx) => {
+      if (id !== `${idx}`) {
+        problems.push(`${label}: expected id ${idx}, found ${id}`);
+      }
+    });
+
+    const counterIds = Object.keys(counters);
+    if (ids.join() !== counterIds.join()) {
+      problems.push(
+        `${label}: ids of the map (${ids}) and the counters (${counterIds}) differ`
+      );
+    }
+  };
+
+  const collectCounters = (pattern: RegExp) =>
+    new Set(
+      Array.from(generatedCode.matchAll(pattern), (match) =>
+        match.slice(1).join(".")
+      )
+    );
+
+  const increasedStatements = collectCounters(/\.s\[(\d+)\]\+\+/g);
+  const increasedFunctions = collectCounters(/\.f\[(\d+)\]\+\+/g);
+  const increasedBranches = collectCounters(/\.b\[(\d+)\]\[(\d+)\]\+\+/g);
+
+  checkIds("statementMap", coverage.statementMap, coverage.s);
+  Object.entries<Range>(coverage.statementMap ?? {}).forEach(([id, range]) => {
+    checkRange(`statementMap[${id}]`, range);
+    if (coverage.s?.[id] !== 0) {
+      problems.push(`s[${id}]: initial counter is ${coverage.s?.[id]}`);
+    }
+    if (!increasedStatements.has(id)) {
+      problems.push(`s[${id}]: counter is never increased`);
+    }
+  });
+
+  checkIds("fnMap", coverage.fnMap, coverage.f);
+  Object.entries<any>(coverage.fnMap ?? {}).forEach(([id, fn]) => {
+    if (typeof fn.name !== "string" || !fn.name) {
+      problems.push(`fnMap[${id}]: missing name`);
+    }
+    checkRange(`fnMap[${id}].decl`, fn.decl);
+    checkRange(`fnMap[${id}].loc`, fn.loc);
+    if (fn.line !== fn.loc?.start?.line) {
+      problems.push(`fnMap[${id}]: line ${fn.line} differs from loc`);
+    }
+    if (coverage.f?.[id] !== 0) {
+      problems.push(`f[${id}]: initial counter is ${coverage.f?.[id]}`);
+    }
+    if (!increasedFunctions.has(id)) {
+      problems.push(`f[${id}]: counter is never increased`);
+    }
+  });
+
+  checkIds("branchMap", coverage.branchMap, coverage.b);
+  Object.entries<any>(coverage.branchMap ?? {}).forEach(([id, branch]) => {
+    if (typeof branch.type !== "string" || !branch.type) {
+      problems.push(`branchMap[${id}]: missing type`);
+    }
+    checkRange(`branchMap[${id}].loc`, branch.loc);
+
+    const locations: Array<Range> = branch.locations ?? [];
+    if (!locations.length) {
+      problems.push(`branchMap[${id}]: no locations`);
+    }
+    locations.forEach((location, idx) => {
+      checkRange(`branchMap[${id}].locations[${idx}]`, location);
+      if (!increasedBranches.has(`${id}.${idx}`)) {
+        problems.push(`b[${id}][${idx}]: counter is never increased`);
+      }
+    });
+
+    const counters = coverage.b?.[id];
+    if (
+      !Array.isArray(counters) ||
+      counters.length !== locations.length ||
+      counters.some((counter) => counter !== 0)
+    ) {
+      problems.push(
+        `b[${id}]: initial counters ${JSON.stringify(counters)} do not match ${
+          locations.length
+        } locations`
+      );
+    }
+  });
+
+  try {
+    parseSync(generatedCode, {
+      syntax: "ecmascript",
+      jsx: true,
+      decorators: true,
+    });
+  } catch (err) {
+    problems.push(`generated code is not parseable: ${err}`);
+  }
+
+  return problems;
+}
+
+export { validateCoverage };
@@ -2,8 +2,10 @@ import { Options, transformSync } from "@swc/core";
 import * as path from "path";
 import { assert } from "chai";
 import { readInitialCoverage } from "./read-coverage";
-import { EOL } from "os";
-import { FileCoverageInterop } from "../swc-coverage-instrument-wasm/pkg/swc_coverage_instrument_wasm";
+import {
+  FileCoverageInterop,
+  extractCoverageFromComments,
+} from "../swc-coverage-instrument-wasm/pkg/swc_coverage_instrument_wasm";
 
 const clone: typeof import("lodash.clone") = require("lodash.clone");
 
@@ -20,12 +22,11 @@ const instrumentSync = (
   instrumentOptions?: Record<string, any>,
   transformOptions?: Options
 ) => {
-  const pluginOptions = inputSourceMap
-    ? {
-        ...(instrumentOptions ?? {}),
-        inputSourceMap,
-      }
-    : instrumentOptions ?? {};
+  const pluginOptions = {
+    ...(instrumentOptions ?? {}),
+    ...(inputSourceMap ? { inputSourceMap } : {}),
+    ...(process.env.SELF_CHECK === "1" ? { selfCheck: true } : {}),
+  };
 
   const options = {
     filename: filename ?? "unknown",
@@ -33,7 +34,9 @@ const instrumentSync = (
       parser: {
         syntax: "ecmascript",
         jsx: true,
+        ...(transformOptions?.parser ?? {}),
       },
+      transform: transformOptions?.transform,
       target: "es2022",
       preserveAllComments: true,
     },
@@ -53,7 +56,7 @@ const instrumentSync = (
       Buffer.from(
         JSON.stringify({
           ...pluginOptions,
-          debugInitialCoverageComment: true,
+          emitCoverageComment: true,
         })
       )
     );
@@ -65,7 +68,7 @@ const instrumentSync = (
         pluginBinary,
         {
           ...pluginOptions,
-          debugInitialCoverageComment: true,
+          emitCoverageComment: true,
         },
       ],
     ],
@@ -80,13 +83,43 @@ const instrumentSync = (
  * to grab out data from plugin.
  */
 const lastFileCoverage = (code?: string) => {
-  const lines = (code ?? "").split(EOL);
-  const commentLine = lines
-    .find((v) => v.includes("__coverage_data_json_comment__::"))
-    ?.split("__coverage_data_json_comment__::")[1];
+  const [coverage] = extractCoverageFromComments(code ?? "");
+  return coverage ?? {};
+};
+
+/**
+ * Asserts the coverage data partially matches the expected: only the fields given are compared,
+ * except the maps keyed by ids (statementMap, fnMap, branchMap) should have the same ids,
+ * and `null` expects the field is not set.
+ */
+const assertCoverageData = (
+  actual: any,
+  expected: any,
+  fieldPath = "coverageData"
+) => {
+  if (expected === null) {
+    assert.notExists(actual, `${fieldPath} should not be set`);
+    return;
+  }
+
+  if (typeof expected !== "object") {
+    assert.deepEqual(actual, expected, `${fieldPath} mismatch`);
+    return;
+  }
 
-  const data = commentLine?.substring(0, commentLine.lastIndexOf("*/"));
-  return data ? JSON.parse(data) : {};
+  assert.ok(
+    actual !== null && typeof actual === "object",
+    `${fieldPath} should be an object`
+  );
+  const keys = Object.keys(expected);
+  if (Array.isArray(expected)) {
+    assert.lengthOf(actual, expected.length, `${fieldPath} length mismatch`);
+  } else if (keys.every((key) => /^\d+$/.test(key))) {
+    assert.deepEqual(Object.keys(actual), keys, `${fieldPath} ids mismatch`);
+  }
+  keys.forEach((key) =>
+    assertCoverageData(actual[key], expected[key], `${fieldPath}.${key}`)
+  );
 };
 
 type UnknownReserved = any;
@@ -159,6 +192,10 @@ class Verifier {
   }
 
   getCoverage() {
+    // Coverage is namespaced by realm id, use the emitted helper to get current realm's.
+    if (this.result.realmIsolation) {
+      return getGlobalObject()[`${this.result.coverageVariable}Extract`]();
+    }
     return getGlobalObject()[this.result.coverageVariable];
   }
 
@@ -296,10 +333,11 @@ const create = (code, options = {}, instrumentOptions = {}, inputSourceMap) => {
     code,
     generatedCode: instrumenterOutput,
     coverageVariable,
+    realmIsolation: !!instrumentOptions.realmIsolation,
     baseline: clone(g[coverageVariable]),
     emptyCoverage: lastFileCoverage(instrumenterOutput), //instrumenter.getLastFileCoverage()
     transformOptions: options.transformOptions,
   });
 };
 
-export { create, instrumentSync };
+export { assertCoverageData, create, instrumentSync, lastFileCoverage };